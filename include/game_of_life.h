@@ -0,0 +1,122 @@
+#ifndef GAME_OF_LIFE_H
+#define GAME_OF_LIFE_H
+
+/* Generated by cbindgen from src/ffi.rs, edit that instead. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct GolUniverse GolUniverse;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// A new universe of `width` by `height` dead cells.
+GolUniverse *gol_universe_new(uint32_t width, uint32_t height);
+
+// A new universe with each cell alive with probability `density`, drawn
+// from `seed` the same way `--seed` does on the command line.
+GolUniverse *gol_universe_new_random(uint32_t width,
+                                     uint32_t height,
+                                     double density,
+                                     uint64_t seed);
+
+// Destroy a universe.
+//
+// # Safety
+//
+// `universe` must be null or come from one of the constructors above, and
+// must not be used again.
+void gol_universe_free(GolUniverse *universe);
+
+// Advance the universe by `generations` generations.
+//
+// # Safety
+//
+// `universe` must be null or a live universe not in use elsewhere.
+void gol_universe_tick(GolUniverse *universe, uint32_t generations);
+
+// Width in cells, or 0 for a null universe.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+uint32_t gol_universe_width(const GolUniverse *universe);
+
+// Height in cells, or 0 for a null universe.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+uint32_t gol_universe_height(const GolUniverse *universe);
+
+// Generations ticked since the universe was made.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+uint64_t gol_universe_generation(const GolUniverse *universe);
+
+// Number of live cells.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+uint64_t gol_universe_population(const GolUniverse *universe);
+
+// Words of 64 cells holding each row in the buffer of
+// `gol_universe_cells`.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+size_t gol_universe_stride(const GolUniverse *universe);
+
+// The packed cells, `stride` words per row from the top, with bit
+// `c % 64` of word `c / 64` of a row holding column `c`. The number of
+// words is stored in `len` unless it is null.
+//
+// The buffer belongs to the universe and is only valid until it is next
+// ticked, changed or freed.
+//
+// # Safety
+//
+// `universe` must be null or a live universe, and `len` null or valid to
+// write.
+const uint64_t *gol_universe_cells(const GolUniverse *universe, size_t *len);
+
+// Whether the cell at `row` and `column` is alive, false outside the
+// universe.
+//
+// # Safety
+//
+// `universe` must be null or a live universe.
+bool gol_universe_get_cell(const GolUniverse *universe, uint32_t row, uint32_t column);
+
+// Bring the cell at `row` and `column` to life, or kill it. Returns false
+// and changes nothing if the cell is outside the universe.
+//
+// # Safety
+//
+// `universe` must be null or a live universe not in use elsewhere.
+bool gol_universe_set_cell(GolUniverse *universe, uint32_t row, uint32_t column, bool alive);
+
+// Switch to the rule written in `rule`, as `B3/S23` or any other form
+// `--rule` takes. Returns false and keeps the current rule if it cannot
+// be read.
+//
+// # Safety
+//
+// `universe` must be null or a live universe not in use elsewhere, and
+// `rule` null or a NUL-terminated string.
+bool gol_universe_set_rule(GolUniverse *universe, const char *rule);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GAME_OF_LIFE_H */
@@ -0,0 +1,213 @@
+//! Boards written as strings of bits, in hex or base64, compact enough to
+//! paste an exact soup into a bug report.
+//!
+//! The bits are the cells row by row from the top left corner, the highest
+//! bit of each hex digit or byte first, with 1 for a live cell. Only the
+//! width travels alongside the string: the board has as many rows as the
+//! bits fill, and the few bits past the last row that round the string up
+//! to whole digits or bytes are dead.
+
+use std::error::Error;
+use std::fmt;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How a string of bits is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitEncoding {
+    /// Four bits to a digit, in either case, with an optional `0x` prefix.
+    Hex,
+    /// Eight bits to a byte in standard base64, padded with `=`.
+    Base64,
+}
+
+impl BitEncoding {
+    pub const NAMES: [&'static str; 2] = ["hex", "base64"];
+
+    pub fn from_name(name: &str) -> Option<BitEncoding> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "hex" => Some(BitEncoding::Hex),
+            "base64" => Some(BitEncoding::Base64),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BitEncoding::Hex => "hex",
+            BitEncoding::Base64 => "base64",
+        }
+    }
+}
+
+/// A string of bits that could not be laid out as a board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitStringError {
+    message: String,
+}
+
+impl BitStringError {
+    pub(crate) fn new(message: impl Into<String>) -> BitStringError {
+        BitStringError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for BitStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for BitStringError {}
+
+/// The bits written in `text`, which may be broken up by whitespace.
+pub(crate) fn decode(text: &str, encoding: BitEncoding) -> Result<Vec<bool>, BitStringError> {
+    let text: String = text.chars().filter(|ch| !ch.is_whitespace()).collect();
+    let mut bits = Vec::new();
+    match encoding {
+        BitEncoding::Hex => {
+            let digits = text
+                .strip_prefix("0x")
+                .or_else(|| text.strip_prefix("0X"))
+                .unwrap_or(&text);
+            for ch in digits.chars() {
+                let digit = ch
+                    .to_digit(16)
+                    .ok_or_else(|| BitStringError::new(format!("'{}' is not a hex digit", ch)))?;
+                bits.extend((0..4).rev().map(|bit| digit >> bit & 1 == 1));
+            }
+        }
+        BitEncoding::Base64 => {
+            let (mut buffer, mut held) = (0u32, 0);
+            for ch in text.trim_end_matches('=').chars() {
+                let value = BASE64
+                    .iter()
+                    .position(|&symbol| symbol as char == ch)
+                    .ok_or_else(|| {
+                        BitStringError::new(format!("'{}' is not a base64 character", ch))
+                    })?;
+                buffer = buffer << 6 | value as u32;
+                held += 6;
+                if held >= 8 {
+                    held -= 8;
+                    let byte = buffer >> held & 0xff;
+                    bits.extend((0..8).rev().map(|bit| byte >> bit & 1 == 1));
+                }
+            }
+        }
+    }
+    Ok(bits)
+}
+
+/// How many bits a string is rounded up to a whole number of: a hex digit
+/// or a byte.
+pub(crate) fn unit(encoding: BitEncoding) -> usize {
+    match encoding {
+        BitEncoding::Hex => 4,
+        BitEncoding::Base64 => 8,
+    }
+}
+
+/// `bits` written out, rounded up to whole digits or bytes with dead cells.
+pub(crate) fn encode(bits: &[bool], encoding: BitEncoding) -> String {
+    let size = unit(encoding);
+    let values = bits.chunks(size).map(|chunk| {
+        let value = chunk
+            .iter()
+            .fold(0u32, |value, &bit| value << 1 | bit as u32);
+        value << (size - chunk.len())
+    });
+    match encoding {
+        BitEncoding::Hex => values
+            .map(|digit| char::from_digit(digit, 16).expect("a nibble is a hex digit"))
+            .collect(),
+        BitEncoding::Base64 => {
+            let bytes: Vec<u32> = values.collect();
+            let mut text = String::new();
+            for group in bytes.chunks(3) {
+                let buffer = group
+                    .iter()
+                    .chain([0, 0].iter())
+                    .take(3)
+                    .fold(0u32, |buffer, &byte| buffer << 8 | byte);
+                for index in 0..4 {
+                    if index <= group.len() {
+                        let value = buffer >> (18 - 6 * index) & 0x3f;
+                        text.push(BASE64[value as usize] as char);
+                    } else {
+                        text.push('=');
+                    }
+                }
+            }
+            text
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::SIZES;
+    use crate::Universe;
+
+    const ENCODINGS: [BitEncoding; 2] = [BitEncoding::Hex, BitEncoding::Base64];
+
+    #[test]
+    fn known_strings_decode() {
+        let bits = |text: &str| -> Vec<bool> { text.chars().map(|ch| ch == '1').collect() };
+        let hex = decode("0xA5 f", BitEncoding::Hex).unwrap();
+        assert_eq!(hex, bits("101001011111"));
+        let base64 = decode("TWE=", BitEncoding::Base64).unwrap();
+        assert_eq!(base64, bits("0100110101100001"));
+        assert_eq!(
+            encode(&bits("0100110101100001"), BitEncoding::Base64),
+            "TWE="
+        );
+        assert_eq!(encode(&bits("101001011"), BitEncoding::Hex), "a58");
+    }
+
+    #[test]
+    fn encoded_bits_decode_back() {
+        for encoding in ENCODINGS {
+            for length in 0..50 {
+                let bits: Vec<bool> = (0..length).map(|bit| bit * 7 % 3 == 1).collect();
+                let decoded = decode(&encode(&bits, encoding), encoding).unwrap();
+                // Padding only ever adds dead cells up to a whole digit or
+                // byte.
+                assert!(decoded.len() >= bits.len() && decoded.len() < bits.len() + 8);
+                assert_eq!(decoded[..bits.len()], bits[..], "{:?} {}", encoding, length);
+                assert!(!decoded[bits.len()..].contains(&true));
+            }
+        }
+    }
+
+    #[test]
+    fn universes_come_back_from_their_bit_strings() {
+        for encoding in ENCODINGS {
+            for (seed, &(width, height)) in SIZES.iter().enumerate() {
+                let universe = Universe::random(width, height, 0.5, seed as u64);
+                let text = universe.to_bit_string(encoding);
+                let back = Universe::from_bit_string(&text, encoding, width).unwrap();
+                assert_eq!((back.width(), back.height()), (width, height), "{}", text);
+                assert_eq!(back.get_cells(), universe.get_cells(), "{:?}", encoding);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_bit_strings_are_refused() {
+        let cases = [
+            ("0xfg", BitEncoding::Hex, 4),
+            ("12-3", BitEncoding::Hex, 4),
+            ("TW.u", BitEncoding::Base64, 4),
+            ("ff", BitEncoding::Hex, 3),
+            ("f", BitEncoding::Hex, 0),
+        ];
+        for (text, encoding, width) in cases {
+            let result = Universe::from_bit_string(text, encoding, width);
+            assert!(result.is_err(), "{:?} {:?} wide {}", text, encoding, width);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// What lies beyond one axis of the universe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// Opposite edges are joined, so patterns leaving one side come back on
+    /// the other.
+    #[default]
+    Torus,
+    /// Everything outside the universe is permanently dead.
+    Dead,
+    /// The row or column at the edge is reflected outwards.
+    Mirror,
+    /// The universe is enlarged whenever live cells reach the edge.
+    Grow,
+}
+
+impl Edge {
+    pub const NAMES: [&'static str; 4] = ["torus", "dead", "mirror", "grow"];
+
+    pub fn from_name(name: &str) -> Option<Edge> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "torus" | "wrap" => Some(Edge::Torus),
+            "dead" => Some(Edge::Dead),
+            "mirror" => Some(Edge::Mirror),
+            "grow" => Some(Edge::Grow),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Edge::Torus => "torus",
+            Edge::Dead => "dead",
+            Edge::Mirror => "mirror",
+            Edge::Grow => "grow",
+        }
+    }
+
+    /// The coordinate of the neighbour `delta` cells away from `coordinate`
+    /// on an axis of `size` cells, or `None` if that neighbour is always
+    /// dead.
+    pub fn resolve(self, coordinate: u32, delta: i32, size: u32) -> Option<u32> {
+        let target = coordinate as i64 + delta as i64;
+        if (0..size as i64).contains(&target) {
+            return Some(target as u32);
+        }
+
+        match self {
+            Edge::Torus => Some(target.rem_euclid(size as i64) as u32),
+            Edge::Mirror => Some(coordinate),
+            Edge::Dead | Edge::Grow => None,
+        }
+    }
+
+    /// How the edge behaves on a universe that keeps its size: growing
+    /// edges are dead.
+    pub(crate) fn fixed(self) -> Edge {
+        match self {
+            Edge::Grow => Edge::Dead,
+            edge => edge,
+        }
+    }
+}
+
+/// The boundary conditions of both axes of a universe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Boundary {
+    /// Behaviour at the left and right edges.
+    pub x: Edge,
+    /// Behaviour at the top and bottom edges.
+    pub y: Edge,
+}
+
+impl Boundary {
+    /// The same behaviour on both axes.
+    pub fn uniform(edge: Edge) -> Boundary {
+        Boundary { x: edge, y: edge }
+    }
+
+    /// Parse `torus` for both axes or `x=torus,y=dead` for each axis.
+    ///
+    /// An axis left out of the per-axis form keeps the default, torus.
+    pub fn parse(input: &str) -> Result<Boundary, BoundaryError> {
+        let error = || BoundaryError {
+            input: input.to_string(),
+        };
+        if let Some(edge) = Edge::from_name(input) {
+            return Ok(Boundary::uniform(edge));
+        }
+
+        let mut boundary = Boundary::default();
+        for part in input.split(',') {
+            let (axis, name) = part.split_once('=').ok_or_else(error)?;
+            let edge = Edge::from_name(name).ok_or_else(error)?;
+            match axis.trim() {
+                "x" => boundary.x = edge,
+                "y" => boundary.y = edge,
+                _ => return Err(error()),
+            }
+        }
+        Ok(boundary)
+    }
+}
+
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.x == self.y {
+            write!(f, "{}", self.x.name())
+        } else {
+            write!(f, "x={},y={}", self.x.name(), self.y.name())
+        }
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = BoundaryError;
+
+    fn from_str(input: &str) -> Result<Boundary, BoundaryError> {
+        Boundary::parse(input)
+    }
+}
+
+/// A boundary specification that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundaryError {
+    input: String,
+}
+
+impl fmt::Display for BoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a boundary: use one of {} or per-axis x=..,y=..",
+            self.input,
+            Edge::NAMES.join(", ")
+        )
+    }
+}
+
+impl Error for BoundaryError {}
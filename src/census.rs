@@ -0,0 +1,368 @@
+//! Counting the objects a board has settled into, the way soup searches
+//! tally the ash a random soup leaves behind.
+//!
+//! The live cells are split into groups touching each other, and each
+//! group is looked up, in any orientation and phase, among the objects
+//! most soups leave in Conway's Life. Under other rules those objects may
+//! not hold together, so nothing is looked up.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::pattern::{Format, Pattern};
+use crate::{Boundary, Cell, Edge, Rule, Universe};
+
+/// The objects recognized, with their period and one phase of each. Every
+/// phase has to hang together, which rules out oscillators such as the
+/// toad whose cells fall apart in some phases.
+const OBJECTS: &[(&str, u32, &str)] = &[
+    ("block", 1, "OO\nOO"),
+    ("beehive", 1, ".OO.\nO..O\n.OO."),
+    ("loaf", 1, ".OO.\nO..O\n.O.O\n..O."),
+    ("boat", 1, "OO.\nO.O\n.O."),
+    ("ship", 1, "OO.\nO.O\n.OO"),
+    ("tub", 1, ".O.\nO.O\n.O."),
+    ("pond", 1, ".OO.\nO..O\nO..O\n.OO."),
+    ("long boat", 1, "OO..\nO.O.\n.O.O\n..O."),
+    ("barge", 1, ".O..\nO.O.\n.O.O\n..O."),
+    ("aircraft carrier", 1, "OO..\nO..O\n..OO"),
+    ("blinker", 2, "OOO"),
+    ("glider", 4, ".O.\n..O\nOOO"),
+    ("lwss", 4, ".O..O\nO....\nO...O\nOOOO."),
+];
+
+/// Cells given relative to the top left corner of their bounds, sorted.
+type Shape = Vec<(i64, i64)>;
+
+/// A way of turning or flipping a cell around the origin.
+type Orientation = fn((i64, i64)) -> (i64, i64);
+
+/// How many objects of each kind a board holds.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Census {
+    /// The objects recognized and how many of each there are, most common
+    /// first.
+    pub objects: Vec<(&'static str, u64)>,
+    /// Groups of cells that are none of the objects recognized.
+    pub unrecognized: u64,
+}
+
+impl Census {
+    /// Count the objects on `universe`. Groups of cells that touch across
+    /// a wrapping edge count as one.
+    ///
+    /// Unless the universe follows Conway's Life, every group counts as
+    /// unrecognized, as `recognizes` tells.
+    pub fn from_universe(universe: &Universe) -> Census {
+        let mut counts: HashMap<&'static str, u64> = HashMap::new();
+        let mut census = Census::default();
+        let groups = groups(universe);
+        if !Census::recognizes(universe.rule()) {
+            census.unrecognized = groups.len() as u64;
+            return census;
+        }
+        let known = known_shapes();
+        for group in groups {
+            match known.get(&canonical(&group)) {
+                Some(&name) => *counts.entry(name).or_default() += 1,
+                None => census.unrecognized += 1,
+            }
+        }
+        census.set_counts(counts);
+        census
+    }
+
+    /// Whether objects are recognized on boards following `rule`: only
+    /// Conway's Life, whose objects they are.
+    pub fn recognizes(rule: Rule) -> bool {
+        rule == Rule::CONWAY
+    }
+
+    /// Add the objects counted in `other`, as when tallying the ash of many
+    /// soups.
+    pub fn merge(&mut self, other: &Census) {
+        let mut counts: HashMap<&'static str, u64> = self.objects.drain(..).collect();
+        for &(name, count) in &other.objects {
+            *counts.entry(name).or_default() += count;
+        }
+        self.set_counts(counts);
+        self.unrecognized += other.unrecognized;
+    }
+
+    fn set_counts(&mut self, counts: HashMap<&'static str, u64>) {
+        self.objects = counts.into_iter().collect();
+        self.objects
+            .sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    }
+
+    /// How many objects there are in all, recognized or not.
+    pub fn total(&self) -> u64 {
+        self.objects.iter().map(|&(_, count)| count).sum::<u64>() + self.unrecognized
+    }
+}
+
+impl fmt::Display for Census {
+    /// Such as `12 block, 8 blinker, 3 beehive, 2 unrecognized`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total() == 0 {
+            return f.write_str("no objects");
+        }
+        let mut parts: Vec<String> = self
+            .objects
+            .iter()
+            .map(|(name, count)| format!("{} {}", count, name))
+            .collect();
+        if self.unrecognized > 0 {
+            parts.push(format!("{} unrecognized", self.unrecognized));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// The canonical shape of every phase and orientation of every object
+/// recognized, with its name, worked out the first time it is needed.
+fn known_shapes() -> &'static HashMap<Shape, &'static str> {
+    static KNOWN: OnceLock<HashMap<Shape, &'static str>> = OnceLock::new();
+    KNOWN.get_or_init(work_out_known_shapes)
+}
+
+fn work_out_known_shapes() -> HashMap<Shape, &'static str> {
+    let mut known = HashMap::new();
+    for &(name, period, cells) in OBJECTS {
+        let pattern = Pattern::parse(cells, Format::Plaintext).expect("objects are valid");
+        // Room for the object to move or oscillate without meeting an edge.
+        let margin = 2 * period;
+        let size = |side: u32| side + 2 * margin;
+        let mut universe = Universe::empty(size(pattern.width()), size(pattern.height()));
+        universe.set_boundary(Boundary {
+            x: Edge::Dead,
+            y: Edge::Dead,
+        });
+        universe.insert_pattern(&pattern, margin, margin);
+        for _ in 0..period {
+            if let [group] = &groups(&universe)[..] {
+                known.insert(canonical(group), name);
+            }
+            universe.tick();
+        }
+    }
+    known
+}
+
+/// The groups of live cells that touch each other, through wrapping edges
+/// too, each given in coordinates that carry on past the wrapped edges so
+/// the group stays in one piece.
+fn groups(universe: &Universe) -> Vec<Shape> {
+    let (width, height) = (universe.width(), universe.height());
+    let boundary = universe.boundary();
+    let index = |row: u32, col: u32| row as usize * width as usize + col as usize;
+    let mut seen = vec![false; width as usize * height as usize];
+    let mut groups = Vec::new();
+    let mut stack = Vec::new();
+    for (row, col) in Pattern::from_universe(universe).cells().iter().copied() {
+        if seen[index(row, col)] {
+            continue;
+        }
+        seen[index(row, col)] = true;
+        stack.push((row as i64, col as i64));
+        let mut group = Vec::new();
+        while let Some((r, c)) = stack.pop() {
+            group.push((r, c));
+            for (dr, dc) in NEIGHBORS {
+                let (r, c) = (r + dr, c + dc);
+                let wrapped = (wrap(boundary.y, r, height), wrap(boundary.x, c, width));
+                if let (Some(row), Some(col)) = wrapped {
+                    if !seen[index(row, col)] && universe.get_cell(row, col) == Cell::Alive {
+                        seen[index(row, col)] = true;
+                        stack.push((r, c));
+                    }
+                }
+            }
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+const NEIGHBORS: [(i64, i64); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Where `coordinate` falls on an axis of `size` cells, or `None` if it is
+/// off a non-wrapping edge.
+fn wrap(edge: Edge, coordinate: i64, size: u32) -> Option<u32> {
+    match edge {
+        Edge::Torus => Some(coordinate.rem_euclid(size as i64) as u32),
+        _ if (0..size as i64).contains(&coordinate) => Some(coordinate as u32),
+        _ => None,
+    }
+}
+
+/// The same shape whichever way a group is turned or flipped: the least of
+/// its eight orientations, each moved to the top left corner and sorted.
+fn canonical(cells: &[(i64, i64)]) -> Shape {
+    let orientations: [Orientation; 8] = [
+        |(r, c)| (r, c),
+        |(r, c)| (r, -c),
+        |(r, c)| (-r, c),
+        |(r, c)| (-r, -c),
+        |(r, c)| (c, r),
+        |(r, c)| (c, -r),
+        |(r, c)| (-c, r),
+        |(r, c)| (-c, -r),
+    ];
+    orientations
+        .iter()
+        .map(|orient| {
+            let mut shape: Shape = cells.iter().map(|&cell| orient(cell)).collect();
+            let top = shape.iter().map(|&(r, _)| r).min().unwrap_or(0);
+            let left = shape.iter().map(|&(_, c)| c).min().unwrap_or(0);
+            shape
+                .iter_mut()
+                .for_each(|cell| *cell = (cell.0 - top, cell.1 - left));
+            shape.sort_unstable();
+            shape
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dead universe of `edge` edges with `objects` placed at their top
+    /// left corners.
+    fn board(edge: Edge, objects: &[(&str, u32, u32)]) -> Universe {
+        let mut universe = Universe::empty(40, 30);
+        universe.set_boundary(Boundary { x: edge, y: edge });
+        for &(cells, top, left) in objects {
+            let pattern = Pattern::parse(cells, Format::Plaintext).unwrap();
+            universe.insert_pattern(&pattern, top, left);
+        }
+        universe
+    }
+
+    const BLOCK: &str = "OO\nOO";
+    const BEEHIVE: &str = ".OO.\nO..O\n.OO.";
+    const BLINKER: &str = "OOO";
+    const GLIDER: &str = ".O.\n..O\nOOO";
+
+    fn count(census: &Census, name: &str) -> u64 {
+        census
+            .objects
+            .iter()
+            .find(|&&(object, _)| object == name)
+            .map_or(0, |&(_, count)| count)
+    }
+
+    #[test]
+    fn objects_are_counted_by_kind() {
+        let universe = board(
+            Edge::Dead,
+            &[
+                (BLOCK, 1, 1),
+                (BLOCK, 1, 10),
+                (BLOCK, 20, 30),
+                (BEEHIVE, 10, 1),
+                (BEEHIVE, 10, 10),
+                (BLINKER, 20, 1),
+                (GLIDER, 20, 10),
+            ],
+        );
+        let census = Census::from_universe(&universe);
+        assert_eq!(
+            census.objects,
+            [("block", 3), ("beehive", 2), ("blinker", 1), ("glider", 1)]
+        );
+        assert_eq!((census.unrecognized, census.total()), (0, 7));
+        assert_eq!(
+            census.to_string(),
+            "3 block, 2 beehive, 1 blinker, 1 glider"
+        );
+    }
+
+    #[test]
+    fn every_phase_and_orientation_is_recognized() {
+        let mut universe = board(
+            Edge::Dead,
+            &[(BEEHIVE, 2, 2), (BLINKER, 2, 20), (GLIDER, 12, 12)],
+        );
+        for _ in 0..8 {
+            let census = Census::from_universe(&universe);
+            assert_eq!(
+                (count(&census, "beehive"), count(&census, "blinker")),
+                (1, 1)
+            );
+            assert_eq!((count(&census, "glider"), census.unrecognized), (1, 0));
+            universe.tick();
+        }
+        // Turned upright, and mirrored.
+        let turned = board(
+            Edge::Dead,
+            &[(".O.\nO.O\nO.O\n.O.", 2, 2), ("OO.\nO.O\nO..", 10, 10)],
+        );
+        let census = Census::from_universe(&turned);
+        assert_eq!(
+            (count(&census, "beehive"), count(&census, "glider")),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn groups_across_a_wrapping_edge_count_once() {
+        let mut universe = board(Edge::Torus, &[]);
+        // A block in the four corners, and a blinker split by the sides.
+        universe.set_cells(&[(0, 0), (0, 39), (29, 0), (29, 39)]);
+        universe.set_cells(&[(10, 38), (10, 39), (10, 0)]);
+        let census = Census::from_universe(&universe);
+        assert_eq!(census.objects, [("blinker", 1), ("block", 1)]);
+    }
+
+    #[test]
+    fn other_shapes_are_unrecognized() {
+        let universe = board(
+            Edge::Dead,
+            &[(BLOCK, 1, 1), ("OOOO", 10, 10), ("O", 20, 20)],
+        );
+        let census = Census::from_universe(&universe);
+        assert_eq!(
+            (census.objects.clone(), census.unrecognized),
+            (vec![("block", 1)], 2)
+        );
+        assert_eq!(census.to_string(), "1 block, 2 unrecognized");
+        assert_eq!(
+            Census::from_universe(&Universe::empty(5, 5)).to_string(),
+            "no objects"
+        );
+    }
+
+    #[test]
+    fn nothing_is_recognized_under_other_rules() {
+        let mut universe = board(Edge::Dead, &[(BLOCK, 1, 1), (BLINKER, 10, 10)]);
+        universe.set_rule(Rule::parse("B36/S23").unwrap());
+        assert!(!Census::recognizes(universe.rule()));
+        let census = Census::from_universe(&universe);
+        assert_eq!((census.objects.len(), census.unrecognized), (0, 2));
+    }
+
+    #[test]
+    fn merging_adds_up_counts() {
+        let mut census = Census::from_universe(&board(Edge::Dead, &[(BLOCK, 1, 1), ("O", 9, 9)]));
+        census.merge(&Census::from_universe(&board(
+            Edge::Dead,
+            &[(BLINKER, 1, 1), (BLINKER, 5, 5), (BLOCK, 9, 9)],
+        )));
+        // Ties go by name.
+        assert_eq!(census.objects, [("blinker", 2), ("block", 2)]);
+        assert_eq!((census.unrecognized, census.total()), (1, 5));
+    }
+}
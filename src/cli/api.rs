@@ -0,0 +1,171 @@
+//! The HTTP API of `serve`, for dashboards and scripts that would rather
+//! speak HTTP than the line protocol of `control`:
+//!
+//! - `GET /state`: every row of the board, as `Delta::to_json` lays it out
+//! - `GET /state.rle`: the board as an RLE pattern
+//! - `GET /frame.png?scale=<pixels>`: the board as an image
+//! - `POST /tick?n=<count>`: tick that many generations, 1 by default
+//! - `POST /cells?state=alive|dead|toggle`: change the cells whose rows and
+//!   columns the body lists in pairs, as `[[row,col],...]` or plain numbers
+//! - `POST /pause` and `POST /resume`
+//! - `GET /metrics`: counters and timings for Prometheus
+//!
+//! The posts answer with the generation and population reached.
+
+use game_of_life::pattern::{Format, Pattern};
+use game_of_life::{Cell, Universe};
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::control::{call, Answer, Call, Command};
+use super::http::{respond, Request};
+use super::metrics::Metrics;
+use super::parse_value;
+
+/// Pixels per cell in `/frame.png` unless asked otherwise.
+const DEFAULT_SCALE: u32 = 4;
+
+/// Largest `scale` of `/frame.png`, to keep images of large boards sane.
+const MAX_SCALE: u32 = 32;
+
+/// Give up on clients that take longer than this to send their request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A response, as its status, content type and body.
+type Response = (&'static str, &'static str, Vec<u8>);
+
+/// Answer requests on `listener` for as long as the server runs, each on
+/// its own thread, by sending commands to `calls`.
+pub fn listen(listener: TcpListener, calls: Sender<Call>, metrics: Arc<Mutex<Metrics>>) {
+    for stream in listener.incoming().flatten() {
+        let calls = calls.clone();
+        let metrics = Arc::clone(&metrics);
+        thread::spawn(move || {
+            // Connections that break off or do not speak HTTP are dropped.
+            let _ = answer(stream, &calls, &metrics);
+        });
+    }
+}
+
+fn answer(mut stream: TcpStream, calls: &Sender<Call>, metrics: &Mutex<Metrics>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let request = Request::read(&stream)?;
+    if (request.method.as_str(), request.path.as_str()) == ("GET", "/metrics") {
+        // Read without asking the simulation, which answers no command
+        // while it runs a long step, so scrapes keep working meanwhile.
+        let metrics = metrics.lock().expect("no metrics writer panics").render();
+        return respond(
+            &mut stream,
+            "200 OK",
+            "text/plain; version=0.0.4",
+            metrics.as_bytes(),
+        );
+    }
+    let (status, content_type, body) = match route(&request, calls) {
+        Ok(response) => response,
+        Err((status, message)) => (status, "text/plain", format!("{}\n", message).into_bytes()),
+    };
+    respond(&mut stream, status, content_type, &body)
+}
+
+fn route(request: &Request, calls: &Sender<Call>) -> Result<Response, (&'static str, String)> {
+    let bad = |message: String| ("400 Bad Request", message);
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/state") => {
+            let universe = copy(calls)?;
+            Ok(json(universe.full_delta().to_json()))
+        }
+        ("GET", "/state.rle") => {
+            let universe = copy(calls)?;
+            let rle = Pattern::from_universe(&universe).write(Format::Rle);
+            Ok(("200 OK", "text/plain; charset=utf-8", rle.into_bytes()))
+        }
+        ("GET", "/frame.png") => {
+            let scale = match request.parameter("scale") {
+                Some(scale) => parse_value::<u32>("scale", scale).map_err(bad)?,
+                None => DEFAULT_SCALE,
+            };
+            if !(1..=MAX_SCALE).contains(&scale) {
+                return Err(bad(format!("scale must be from 1 to {}", MAX_SCALE)));
+            }
+            let universe = copy(calls)?;
+            let png = Pattern::from_universe(&universe).to_png(scale);
+            Ok(("200 OK", "image/png", png))
+        }
+        ("POST", "/tick") => {
+            let count = match request.parameter("n") {
+                Some(count) => parse_value::<u64>("n", count).map_err(bad)?,
+                None => 1,
+            };
+            command(calls, Command::Step(count))
+        }
+        ("POST", "/cells") => {
+            let cell = match request.parameter("state") {
+                None | Some("alive") => Some(Cell::Alive),
+                Some("dead") => Some(Cell::Dead),
+                Some("toggle") => None,
+                Some(other) => {
+                    return Err(bad(format!(
+                        "unknown state '{}', expected one of alive, dead, toggle",
+                        other
+                    )))
+                }
+            };
+            let cells = cell_list(&request.body).map_err(bad)?;
+            command(calls, Command::Cells(cells, cell))
+        }
+        ("POST", "/pause") => command(calls, Command::Pause),
+        ("POST", "/resume") => command(calls, Command::Resume),
+        (
+            _,
+            "/state" | "/state.rle" | "/frame.png" | "/tick" | "/cells" | "/pause" | "/resume"
+            | "/metrics",
+        ) => Err(("405 Method Not Allowed", String::from("method not allowed"))),
+        _ => Err(("404 Not Found", String::from("not found"))),
+    }
+}
+
+/// Carry out `command`, answering with where the universe got to.
+fn command(calls: &Sender<Call>, command: Command) -> Result<Response, (&'static str, String)> {
+    call(calls, command).map_err(|error| ("400 Bad Request", error))?;
+    let universe = copy(calls)?;
+    Ok(json(format!(
+        "{{\"generation\":{},\"population\":{}}}",
+        universe.generation(),
+        universe.population()
+    )))
+}
+
+fn copy(calls: &Sender<Call>) -> Result<Box<Universe>, (&'static str, String)> {
+    match call(calls, Command::Copy) {
+        Ok(Answer::Universe(universe)) => Ok(universe),
+        Ok(Answer::Line(line)) => Err(("500 Internal Server Error", line)),
+        Err(error) => Err(("503 Service Unavailable", error)),
+    }
+}
+
+fn json(body: String) -> Response {
+    ("200 OK", "application/json", body.into_bytes())
+}
+
+/// The numbers in `body` taken in pairs of row and column, whatever
+/// brackets, commas or spaces surround them.
+fn cell_list(body: &[u8]) -> Result<Vec<(u32, u32)>, String> {
+    let body = std::str::from_utf8(body).map_err(|_| String::from("the body is not text"))?;
+    if body.contains('-') {
+        return Err(String::from("rows and columns cannot be negative"));
+    }
+    let numbers = body
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .map(|number| parse_value::<u32>("cell", number))
+        .collect::<Result<Vec<u32>, String>>()?;
+    if !numbers.len().is_multiple_of(2) {
+        return Err(String::from("cells take a row and a column each"));
+    }
+    Ok(numbers.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
@@ -0,0 +1,150 @@
+use game_of_life::pattern::preset;
+use game_of_life::{Engine, Strips, Universe};
+use std::time::Instant;
+
+use super::Args;
+
+pub const USAGE: &str = "\
+Usage: game_of_life bench [options]
+
+Runs each standard workload and reports its wall time and throughput.
+
+Options:
+  --workload <name>           Run only this workload
+  --generations <count>       Generations to simulate (default 100)
+  --width <cells>             Benchmark a random soup of this width instead
+  --height <cells>            Benchmark a random soup of this height instead
+  --threads <count>           Split each universe across this many threads
+                              (default 1)
+  --engine <name>             Step with bitwise adders or a lookup table:
+                              adder or table (default adder)
+
+Workloads:
+  soup      random soup at 35% density on 1024x1024
+  gun       Gosper glider gun on 256x256
+  acorn     acorn methuselah on 512x512, mostly empty space
+  diehard   diehard on 512x512, dying out part way through";
+
+/// A named starting position with a fixed size, so results can be compared
+/// between versions and machines.
+struct Workload {
+    name: &'static str,
+    width: u32,
+    height: u32,
+    /// Preset placed in the center, or a random soup if `None`.
+    preset: Option<&'static str>,
+}
+
+const WORKLOADS: &[Workload] = &[
+    Workload {
+        name: "soup",
+        width: 1024,
+        height: 1024,
+        preset: None,
+    },
+    Workload {
+        name: "gun",
+        width: 256,
+        height: 256,
+        preset: Some("glider-gun"),
+    },
+    Workload {
+        name: "acorn",
+        width: 512,
+        height: 512,
+        preset: Some("acorn"),
+    },
+    Workload {
+        name: "diehard",
+        width: 512,
+        height: 512,
+        preset: Some("diehard"),
+    },
+];
+
+/// Density and seed of the random soups, fixed so every run is identical.
+const SOUP_DENSITY: f64 = 0.35;
+const SOUP_SEED: u64 = 1;
+
+impl Workload {
+    fn universe(&self) -> Universe {
+        match self.preset {
+            None => Universe::random(self.width, self.height, SOUP_DENSITY, SOUP_SEED),
+            Some(name) => {
+                let pattern = preset(name).expect("workloads use embedded presets");
+                let mut universe = Universe::empty(self.width, self.height);
+                let row = (self.height - pattern.height()) / 2;
+                let column = (self.width - pattern.width()) / 2;
+                universe.insert_pattern(&pattern, row, column);
+                universe
+            }
+        }
+    }
+}
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let workload = args.value("--workload")?;
+    let generations = args.parse::<u64>("--generations")?.unwrap_or(100);
+    let width = args.parse::<u32>("--width")?;
+    let height = args.parse::<u32>("--height")?;
+    let threads = args.parse::<usize>("--threads")?.unwrap_or(1).max(1);
+    let engine = match args.value("--engine")? {
+        Some(name) => super::run::parse_engine(&name)?,
+        None => Engine::default(),
+    };
+    args.finish()?;
+
+    let custom;
+    let workloads: Vec<&Workload> = match (width, height, workload) {
+        (None, None, None) => WORKLOADS.iter().collect(),
+        (None, None, Some(name)) => {
+            let found = WORKLOADS.iter().find(|workload| workload.name == name);
+            vec![found.ok_or_else(|| format!("unknown workload '{}'", name))?]
+        }
+        (_, _, Some(_)) => return Err("--workload cannot be combined with a custom size".into()),
+        (width, height, None) => {
+            custom = Workload {
+                name: "custom",
+                width: width.unwrap_or(512),
+                height: height.unwrap_or(512),
+                preset: None,
+            };
+            vec![&custom]
+        }
+    };
+
+    println!(
+        "{:<10} {:>11} {:>8} {:>10} {:>14}",
+        "workload", "size", "time", "gen/s", "cells/s"
+    );
+    for workload in workloads {
+        let mut universe = workload.universe();
+        universe.set_engine(engine);
+        let started = Instant::now();
+        if threads > 1 {
+            let mut strips = Strips::new(&universe, threads).expect("workloads wrap around");
+            strips.step(generations);
+        } else {
+            for _ in 0..generations {
+                universe.tick();
+            }
+        }
+        let seconds = started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        let cells = workload.width as f64 * workload.height as f64 * generations as f64;
+        println!(
+            "{:<10} {:>11} {:>7.3}s {:>10.1} {:>14.0}",
+            workload.name,
+            format!("{}x{}", workload.width, workload.height),
+            seconds,
+            generations as f64 / seconds,
+            cells / seconds
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use game_of_life::pattern::Pattern;
+use std::fmt::Write as _;
+
+/// Clear the terminal and move the cursor to the top left corner.
+pub const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+
+/// Patterns listed at once, around the selected one.
+const LIST_LENGTH: usize = 12;
+
+/// Largest part of a pattern shown in the preview.
+const PREVIEW_SIZE: u32 = 24;
+
+/// A full screen list of the stamps to pick from, narrowed down by typing.
+pub struct Browser {
+    query: String,
+    /// Indexes of the patterns matching the query, best match first.
+    matches: Vec<usize>,
+    /// Position of the highlighted pattern in `matches`.
+    selected: usize,
+}
+
+impl Browser {
+    pub fn new(patterns: &[Pattern]) -> Browser {
+        let mut browser = Browser {
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        browser.search(patterns);
+        browser
+    }
+
+    /// The index of the highlighted pattern, if any matches.
+    pub fn selected(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn type_char(&mut self, c: char, patterns: &[Pattern]) {
+        self.query.push(c);
+        self.search(patterns);
+    }
+
+    pub fn backspace(&mut self, patterns: &[Pattern]) {
+        self.query.pop();
+        self.search(patterns);
+    }
+
+    fn search(&mut self, patterns: &[Pattern]) {
+        let mut scored: Vec<(usize, usize)> = patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(index, pattern)| Some((fuzzy_score(&self.query, name(pattern))?, index)))
+            .collect();
+        scored.sort_unstable();
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+    }
+
+    /// The whole screen: the query, the matching patterns and a preview of
+    /// the highlighted one.
+    pub fn render(&self, patterns: &[Pattern]) -> String {
+        let mut screen = String::new();
+        writeln!(screen, "Find a pattern: {}_\n", self.query)
+            .expect("writing to a String cannot fail");
+
+        let first = self.selected.saturating_sub(LIST_LENGTH / 2);
+        for (position, &index) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(LIST_LENGTH)
+        {
+            let pattern = &patterns[index];
+            let marker = if position == self.selected { '>' } else { ' ' };
+            writeln!(
+                screen,
+                "{} {:<20} {}x{}, {} cells",
+                marker,
+                name(pattern),
+                pattern.width(),
+                pattern.height(),
+                pattern.population()
+            )
+            .expect("writing to a String cannot fail");
+        }
+        if self.matches.is_empty() {
+            screen.push_str("  nothing matches\n");
+        }
+
+        if let Some(index) = self.selected() {
+            screen.push('\n');
+            write_preview(&mut screen, &patterns[index]);
+        }
+        screen.push_str("\nup/down choose, enter stamps it, escape goes back\n");
+        screen
+    }
+}
+
+/// The name a pattern is listed and searched under.
+pub fn name(pattern: &Pattern) -> &str {
+    pattern.name.as_deref().unwrap_or("unnamed")
+}
+
+/// How well `query` matches `name`, lower being better, or `None` if the
+/// characters of the query don't all appear in order in the name.
+///
+/// The score is the number of characters skipped before the last match, so
+/// a name starting with the query beats one merely containing it.
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name = name.to_lowercase();
+    let mut chars = name.chars();
+    let mut skipped = 0;
+    for wanted in query.to_lowercase().chars() {
+        loop {
+            match chars.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => skipped += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(skipped)
+}
+
+/// Draw the top left corner of `pattern` with `O` for live cells.
+fn write_preview(screen: &mut String, pattern: &Pattern) {
+    let width = pattern.width().min(PREVIEW_SIZE);
+    let height = pattern.height().min(PREVIEW_SIZE);
+    let mut grid = vec![false; (width * height) as usize];
+    for &(row, col) in pattern.cells() {
+        if row < height && col < width {
+            grid[(row * width + col) as usize] = true;
+        }
+    }
+    for line in grid.chunks(width.max(1) as usize) {
+        screen.push_str("  ");
+        for &alive in line {
+            screen.push_str(if alive { "O " } else { ". " });
+        }
+        screen.push('\n');
+    }
+}
@@ -0,0 +1,187 @@
+use std::io;
+use std::str::FromStr;
+
+use clap::{Arg, ArgAction, ValueHint};
+use clap_complete::Shell;
+
+use super::{man, Args, COMMANDS};
+
+pub const USAGE: &str = "\
+Usage: game_of_life completions <shell>
+
+Shells: bash, zsh, fish, elvish, powershell
+
+Install the script where your shell loads completions from, for example
+`game_of_life completions bash > /etc/bash_completion.d/game_of_life`.";
+
+/// Name of the installed binary the scripts complete.
+const BIN: &str = "game_of_life";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let positional = args.finish()?;
+    let shell = match positional.as_slice() {
+        [shell] => Shell::from_str(shell)
+            .map_err(|_| format!("unsupported shell '{}'\n\n{}", shell, USAGE))?,
+        _ => return Err(format!("expected a shell\n\n{}", USAGE)),
+    };
+    clap_complete::generate(shell, &mut command_line(), BIN, &mut io::stdout());
+    Ok(())
+}
+
+/// The commands and options as `clap` describes them, built from the usage
+/// text of each command for `clap_complete` to write the scripts from.
+///
+/// Only the completions come from this, arguments are still parsed by
+/// `Args`.
+fn command_line() -> clap::Command {
+    let subcommands = COMMANDS.iter().map(|command| {
+        let options = command.options().into_iter().map(|(flag, description)| {
+            let arg = Arg::new(flag).long(&flag[2..]).help(description);
+            if !command.takes_value(flag) {
+                return arg.action(ArgAction::SetTrue);
+            }
+            // Paths complete as files, other values are free text.
+            let placeholder = man::option_line(command.usage, flag);
+            let hint = if placeholder.contains("<path")
+                || placeholder.contains("<file")
+                || placeholder.contains("<pattern")
+            {
+                ValueHint::FilePath
+            } else {
+                ValueHint::Other
+            };
+            arg.action(ArgAction::Set).value_hint(hint)
+        });
+        clap::Command::new(command.name)
+            .about(command.about)
+            .args(options)
+            .arg(
+                Arg::new("arguments")
+                    .num_args(0..)
+                    .value_hint(ValueHint::FilePath),
+            )
+    });
+    clap::Command::new(BIN)
+        .subcommands(subcommands)
+        .subcommand(clap::Command::new("help").about("Show this message"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Command;
+    use std::collections::BTreeSet;
+
+    /// The module reading the arguments of each command.
+    const SOURCES: &[(&str, &str)] = &[
+        ("run", include_str!("run.rs")),
+        ("convert", include_str!("convert.rs")),
+        ("bench", include_str!("bench.rs")),
+        ("diverge", include_str!("diverge.rs")),
+        ("soupsearch", include_str!("soupsearch.rs")),
+        ("serve", include_str!("serve.rs")),
+        ("info", include_str!("info.rs")),
+        ("completions", include_str!("completions.rs")),
+        ("man", include_str!("man.rs")),
+    ];
+
+    /// The methods of `Args` and the helpers reading options from the
+    /// environment.
+    const READERS: &[&str] = &[
+        "switch",
+        "value",
+        "parse",
+        "switch_or_env",
+        "value_or_env",
+        "parse_or_env",
+        "env_parse",
+        "env_value",
+    ];
+
+    /// The flags `source` reads, found as the string passed to one of
+    /// `READERS`.
+    fn flags_read(source: &str) -> BTreeSet<&str> {
+        let mut flags = BTreeSet::new();
+        for (at, _) in source.match_indices("(\"--") {
+            let mut before = &source[..at];
+            // Past the type of a call such as `parse::<u32>`.
+            if before.ends_with('>') {
+                before = &before[..before.rfind("::<").expect("a turbofish")];
+            }
+            let name_start = before
+                .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .map_or(0, |i| i + 1);
+            if !READERS.contains(&&before[name_start..]) {
+                continue;
+            }
+            let flag = &source[at + 2..];
+            flags.insert(&flag[..flag.find('"').expect("a closing quote")]);
+        }
+        flags.remove("--help");
+        flags
+    }
+
+    fn command(name: &str) -> &'static Command {
+        COMMANDS
+            .iter()
+            .find(|command| command.name == name)
+            .expect("a command")
+    }
+
+    #[test]
+    fn every_command_has_its_source_checked() {
+        let checked: Vec<&str> = SOURCES.iter().map(|&(name, _)| name).collect();
+        let commands: Vec<&str> = COMMANDS.iter().map(|command| command.name).collect();
+        assert_eq!(checked, commands);
+    }
+
+    #[test]
+    fn every_option_read_is_documented_and_back() {
+        for &(name, source) in SOURCES {
+            let documented: BTreeSet<&str> = command(name)
+                .options()
+                .into_iter()
+                .map(|(flag, _)| flag)
+                .collect();
+            let read = flags_read(source);
+            let undocumented: Vec<_> = read.difference(&documented).collect();
+            let unread: Vec<_> = documented.difference(&read).collect();
+            assert!(undocumented.is_empty(), "{} reads {:?}", name, undocumented);
+            assert!(unread.is_empty(), "{} documents {:?}", name, unread);
+        }
+    }
+
+    #[test]
+    fn completions_offer_every_documented_option_and_no_other() {
+        let line = command_line();
+        for command in COMMANDS {
+            let subcommand = line
+                .find_subcommand(command.name)
+                .unwrap_or_else(|| panic!("no {} subcommand", command.name));
+            let offered: BTreeSet<String> = subcommand
+                .get_arguments()
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{}", long))
+                .collect();
+            let documented: BTreeSet<String> = command
+                .options()
+                .into_iter()
+                .map(|(flag, _)| flag.to_string())
+                .collect();
+            assert_eq!(offered, documented, "{}", command.name);
+
+            for arg in subcommand.get_arguments() {
+                if let Some(long) = arg.get_long() {
+                    let flag = format!("--{}", long);
+                    let takes_value = arg.get_action().takes_values();
+                    assert_eq!(takes_value, command.takes_value(&flag), "{}", flag);
+                }
+            }
+        }
+    }
+}
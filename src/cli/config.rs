@@ -0,0 +1,92 @@
+//! Defaults for the options of `run` kept in a config file, for settings
+//! someone always wants without typing them or exporting `GOL_*` variables.
+//!
+//! Each line of the file sets an option by its name without the dashes,
+//! such as `fps = 30`, `theme = amber` or `no-hud = true`, and lines
+//! starting with `#` are comments. The command line takes precedence over
+//! the environment, which in turn takes precedence over the file.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Where the file was found and what it sets, once `load` read it.
+static CONFIG: OnceLock<(PathBuf, Vec<(String, String)>)> = OnceLock::new();
+
+/// Where the config file is looked for: `GOL_CONFIG` if it is set, and
+/// otherwise `game_of_life/config` in `XDG_CONFIG_HOME` or `~/.config`.
+pub fn path() -> Option<PathBuf> {
+    let var = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(path) = var("GOL_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = match var("XDG_CONFIG_HOME") {
+        Some(base) => PathBuf::from(base),
+        None => PathBuf::from(var("HOME")?).join(".config"),
+    };
+    Some(base.join("game_of_life").join("config"))
+}
+
+/// Read the config file for `value` to look options up in. A missing file
+/// sets nothing, unless `GOL_CONFIG` names it.
+pub fn load() -> Result<(), String> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error)
+            if error.kind() == io::ErrorKind::NotFound && env::var_os("GOL_CONFIG").is_none() =>
+        {
+            return Ok(())
+        }
+        Err(error) => return Err(format!("{}: {}", path.display(), error)),
+    };
+    let values = parse(&text).map_err(|message| format!("{}: {}", path.display(), message))?;
+    let _ = CONFIG.set((path, values));
+    Ok(())
+}
+
+/// The value the config file gives `flag`, and which file gave it, for
+/// error messages.
+pub fn value(flag: &str) -> Option<(String, String)> {
+    let (path, values) = CONFIG.get()?;
+    let name = flag.trim_start_matches('-');
+    values
+        .iter()
+        .rev()
+        .find(|(key, _)| key == name)
+        .map(|(key, value)| (format!("{} in {}", key, path.display()), value.clone()))
+}
+
+/// The `name = value` pairs of a config file, in the order they appear.
+fn parse(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut values = Vec::new();
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected 'name = value'", number))?;
+        let name = name.trim().trim_start_matches('-');
+        if name.is_empty() {
+            return Err(format!("line {}: missing option name", number));
+        }
+        let value = value.trim();
+        // Values may be quoted, to keep the spaces around them.
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        values.push((name.to_string(), value.to_string()));
+    }
+    Ok(values)
+}
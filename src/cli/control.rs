@@ -0,0 +1,192 @@
+//! A line-based protocol for driving a running `serve` from scripts, on a
+//! TCP port or a Unix socket. Each line is a command and gets a line back,
+//! `ok` and maybe a value, or `error:` and why:
+//!
+//! ```text
+//! > step 10
+//! < ok generation 10
+//! > toggle 3 4
+//! < ok
+//! > hash
+//! < ok 6288a8f46afc086a
+//! ```
+
+use game_of_life::pattern::{preset, Pattern};
+use game_of_life::{Cell, Universe};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use super::parse_value;
+
+pub const HELP: &str = "commands: pause, resume, step [<count>], toggle <row> <col>, \
+load <pattern> [<row> <col>], clear, hash, status, help, quit";
+
+/// What a client asked the simulation to do.
+pub enum Command {
+    Pause,
+    Resume,
+    /// Tick this many generations, running or not.
+    Step(u64),
+    /// Add a pattern at a row and column, or in the middle.
+    Load(Pattern, Option<(u32, u32)>),
+    /// Kill every cell and count generations from 0.
+    Clear,
+    Hash,
+    Status,
+    /// Bring cells to life, kill them, or for `None` toggle them.
+    Cells(Vec<(u32, u32)>, Option<Cell>),
+    /// Hand back a copy of the universe.
+    Copy,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = match words.as_slice() {
+            ["pause"] => Command::Pause,
+            ["resume"] => Command::Resume,
+            ["step"] => Command::Step(1),
+            ["step", count] => Command::Step(parse_value("step count", count)?),
+            ["toggle", row, column] => Command::Cells(
+                vec![(parse_value("row", row)?, parse_value("column", column)?)],
+                None,
+            ),
+            ["load", name, position @ ..] => {
+                let pattern = match preset(name) {
+                    Some(pattern) => pattern,
+                    None => Pattern::load(name).map_err(|error| format!("{}: {}", name, error))?,
+                };
+                let position = match position {
+                    [] => None,
+                    [row, column] => {
+                        Some((parse_value("row", row)?, parse_value("column", column)?))
+                    }
+                    _ => {
+                        return Err(String::from(
+                            "load takes a pattern and maybe a row and column",
+                        ))
+                    }
+                };
+                Command::Load(pattern, position)
+            }
+            ["clear"] => Command::Clear,
+            ["hash"] => Command::Hash,
+            ["status"] => Command::Status,
+            [] => return Err(String::from("empty command")),
+            [other, ..] => return Err(format!("unknown command '{}', {}", other, HELP)),
+        };
+        Ok(command)
+    }
+}
+
+/// What the simulation answers a command with.
+pub enum Answer {
+    /// A line to send back, `ok` and maybe a value.
+    Line(String),
+    /// The copy asked for with `Command::Copy`.
+    Universe(Box<Universe>),
+}
+
+/// A command with where to send its answer.
+pub struct Call {
+    pub command: Command,
+    pub reply: Sender<Result<Answer, String>>,
+}
+
+/// Send `command` to the simulation and wait for the answer.
+pub fn call(calls: &Sender<Call>, command: Command) -> Result<Answer, String> {
+    let (reply, answer) = mpsc::channel();
+    calls
+        .send(Call { command, reply })
+        .map_err(|_| String::from("the simulation has stopped"))?;
+    answer
+        .recv()
+        .map_err(|_| String::from("the simulation has stopped"))?
+}
+
+/// Where to listen for control connections.
+pub enum Address {
+    /// A port on the address served on, or a `host:port`.
+    Tcp(String),
+    /// The path of a Unix socket.
+    Unix(PathBuf),
+}
+
+impl Address {
+    /// Read `value` as a port, a `host:port` or a Unix socket path, which
+    /// is anything with a slash in it.
+    pub fn parse(value: &str, bind: &str) -> Result<Address, String> {
+        if value.contains('/') {
+            Ok(Address::Unix(PathBuf::from(value)))
+        } else if value.contains(':') {
+            Ok(Address::Tcp(value.to_string()))
+        } else {
+            let port = parse_value::<u16>("--control", value)?;
+            Ok(Address::Tcp(format!("{}:{}", bind, port)))
+        }
+    }
+}
+
+/// Start taking control connections on `address`, passing their commands
+/// to `calls`. Fails only if the address cannot be listened on.
+pub fn listen(address: &Address, calls: Sender<Call>) -> Result<(), String> {
+    match address {
+        Address::Tcp(address) => {
+            let listener = TcpListener::bind(address)
+                .map_err(|error| format!("cannot listen on {}: {}", address, error))?;
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let calls = calls.clone();
+                    thread::spawn(move || session(stream, calls));
+                }
+            });
+        }
+        #[cfg(unix)]
+        Address::Unix(path) => {
+            let listener = UnixListener::bind(path)
+                .map_err(|error| format!("cannot listen on {}: {}", path.display(), error))?;
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let calls = calls.clone();
+                    thread::spawn(move || session(stream, calls));
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        Address::Unix(_) => {
+            return Err(String::from(
+                "Unix sockets are not available here, give a port",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Answer the commands of one connection until it closes or quits.
+fn session<S: Read + Write>(stream: S, calls: Sender<Call>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let answer = match line.trim() {
+            "quit" => return Ok(()),
+            "help" => Ok(String::from(HELP)),
+            command => Command::parse(command).and_then(|command| match call(&calls, command)? {
+                Answer::Line(line) => Ok(line),
+                Answer::Universe(_) => Ok(String::from("ok")),
+            }),
+        };
+        let stream = reader.get_mut();
+        match answer {
+            Ok(answer) => writeln!(stream, "{}", answer)?,
+            Err(error) => writeln!(stream, "error: {}", error)?,
+        }
+        stream.flush()?;
+        line.clear();
+    }
+    Ok(())
+}
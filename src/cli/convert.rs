@@ -0,0 +1,51 @@
+use game_of_life::pattern::{Format, Pattern, PNG_SCALE};
+use std::fs;
+use std::io::{self, Write};
+
+use super::Args;
+
+pub const USAGE: &str = "\
+Usage: game_of_life convert <input> --to <format> [--output <path>]
+
+Formats: rle, cells, life106, json, and png (output only)
+
+Options:
+  --to <format>               Format to convert to
+  --output <path>             Write to this file instead of stdout
+  --scale <pixels>            Size of a cell in png images (default 4)
+
+The converted pattern is printed to stdout unless --output is given.";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let to = args.value("--to")?.ok_or("--to <format> is required")?;
+    let png = to.eq_ignore_ascii_case("png");
+    let format = match Format::from_name(&to) {
+        Some(format) => Some(format),
+        None if png => None,
+        None => return Err(format!("unknown format '{}'", to)),
+    };
+    let scale = args.parse::<u32>("--scale")?.unwrap_or(PNG_SCALE);
+    let output = args.value("--output")?;
+    let positional = args.finish()?;
+    let input = match positional.as_slice() {
+        [input] => input,
+        _ => return Err(format!("expected one input file\n\n{}", USAGE)),
+    };
+
+    let pattern = Pattern::load(input).map_err(|error| format!("{}: {}", input, error))?;
+    let converted = match format {
+        Some(format) => pattern.write(format).into_bytes(),
+        None => pattern.to_png(scale),
+    };
+    match output {
+        Some(path) => fs::write(&path, converted).map_err(|error| format!("{}: {}", path, error)),
+        None => io::stdout()
+            .write_all(&converted)
+            .map_err(|error| error.to_string()),
+    }
+}
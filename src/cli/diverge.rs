@@ -0,0 +1,158 @@
+use game_of_life::pattern::{preset, Pattern};
+use game_of_life::{Boundary, Rng, Rule, Universe};
+
+use super::run::{time_seed, DEFAULT_DENSITY};
+use super::Args;
+
+pub const USAGE: &str = "\
+Usage: game_of_life diverge [options]
+
+Flips a few random cells of a copy of a universe, runs both side by side
+and reports how many cells they differ by every generation, to watch the
+damage spread or heal.
+
+Options:
+  --width <cells>             Width of the universe (default 128)
+  --height <cells>            Height of the universe (default 128)
+  --pattern <file>            Start from this pattern, centered, instead of a
+                              random soup
+  --preset <name>             Start from a built-in pattern such as acorn
+  --density <fraction>        Share of cells alive in a random soup (default
+                              0.35)
+  --seed <number>             Seed for the soup and the cells flipped, for
+                              reproducible runs
+  --rule <rule>               Rulestring such as B36/S23, or a preset name
+  --wrap <edges>              Boundary: torus, dead, mirror or grow, or per axis
+                              as x=torus,y=dead (default torus)
+  --flips <count>             Cells to flip in the copy (default 1)
+  --generations <count>       Generations to run (default 200)
+  --every <generations>       How often to report (default 1)";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let width = args.parse::<u32>("--width")?.unwrap_or(128);
+    let height = args.parse::<u32>("--height")?.unwrap_or(128);
+    let pattern = match (args.value("--pattern")?, args.value("--preset")?) {
+        (Some(_), Some(_)) => return Err(String::from("use either --pattern or --preset")),
+        (Some(path), None) => {
+            Some(Pattern::load(&path).map_err(|error| format!("{}: {}", path, error))?)
+        }
+        (None, Some(name)) => Some(
+            preset(&name)
+                .ok_or_else(|| format!("unknown preset '{}', see run --list-presets", name))?,
+        ),
+        (None, None) => None,
+    };
+    let density = args.parse::<f64>("--density")?.unwrap_or(DEFAULT_DENSITY);
+    if !(0.0..=1.0).contains(&density) {
+        return Err(String::from("--density must be between 0 and 1"));
+    }
+    let seed = args.parse::<u64>("--seed")?;
+    let rule = match args.value("--rule")? {
+        Some(rule) => Some(Rule::parse(&rule).map_err(|error| error.to_string())?),
+        None => None,
+    };
+    let boundary = match args.value("--wrap")? {
+        Some(wrap) => Boundary::parse(&wrap).map_err(|error| error.to_string())?,
+        None => Boundary::default(),
+    };
+    let flips = args.parse::<u64>("--flips")?.unwrap_or(1);
+    let generations = args.parse::<u64>("--generations")?.unwrap_or(200);
+    let every = args.parse::<u64>("--every")?.unwrap_or(1).max(1);
+    let positional = args.finish()?;
+    if !positional.is_empty() {
+        return Err(format!("unexpected argument '{}'", positional[0]));
+    }
+    if width == 0 || height == 0 {
+        return Err(String::from("the universe must be at least 1x1"));
+    }
+    let cells = width as u64 * height as u64;
+    if flips > cells {
+        return Err(format!("cannot flip {} of {} cells", flips, cells));
+    }
+
+    let seed = seed.unwrap_or_else(time_seed);
+    let mut original = match &pattern {
+        Some(pattern) => {
+            let mut universe = Universe::empty(width, height);
+            universe
+                .place_centered(pattern)
+                .map_err(|error| error.to_string())?;
+            universe
+        }
+        None => Universe::random(width, height, density, seed),
+    };
+    // A rule given on the command line wins over the one stored in the file.
+    let file_rule = pattern.and_then(|pattern| pattern.rule);
+    let rule = match (rule, file_rule) {
+        (Some(rule), _) => rule,
+        (None, Some(rule)) => Rule::parse(&rule).map_err(|error| error.to_string())?,
+        (None, None) => Rule::default(),
+    };
+    original.set_rule(rule);
+    original.set_boundary(boundary);
+
+    let mut copy = original.clone();
+    flip_cells(&mut copy, flips, seed);
+    println!(
+        "{}x{}, {}, seed {}, {} flipped",
+        width,
+        height,
+        rule,
+        seed,
+        plural(flips, "cell")
+    );
+
+    // The largest distance reached and the generation it was first reached.
+    let mut peak = (0, 0);
+    for generation in 0..=generations {
+        let distance = original.diff(&copy).hamming();
+        if distance > peak.0 {
+            peak = (distance, generation);
+        }
+        if generation % every == 0 || distance == 0 {
+            println!("generation {:>6}  distance {:>8}", generation, distance);
+        }
+        if distance == 0 {
+            println!("the damage healed at generation {}", generation);
+            return Ok(());
+        }
+        if generation < generations {
+            original.tick();
+            copy.tick();
+        }
+    }
+    println!(
+        "largest distance {} ({:.2}% of the cells) at generation {}",
+        peak.0,
+        peak.0 as f64 * 100.0 / cells as f64,
+        peak.1
+    );
+    Ok(())
+}
+
+/// Flip `count` distinct cells of `universe`, picked at random from `seed`.
+fn flip_cells(universe: &mut Universe, count: u64, seed: u64) {
+    // Kept apart from the soup, which is drawn from the same seed.
+    let mut rng = Rng::new(!seed);
+    let (width, height) = (universe.width() as u64, universe.height() as u64);
+    let mut flipped = Vec::new();
+    while (flipped.len() as u64) < count {
+        let cell = rng.below(width * height);
+        if !flipped.contains(&cell) {
+            flipped.push(cell);
+            universe.toggle_cell((cell / width) as u32, (cell % width) as u32);
+        }
+    }
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        count => format!("{} {}s", count, noun),
+    }
+}
@@ -0,0 +1,53 @@
+use game_of_life::render::Status;
+use game_of_life::Universe;
+use std::fmt::Write as _;
+
+use super::run::USAGE;
+use super::session::Settings;
+use super::simulation::Batch;
+
+/// The help screen: the keys and commands from the usage text, then the
+/// settings the session is running with.
+pub fn screen(universe: &Universe, status: &Status, settings: &Settings) -> String {
+    let keys = USAGE
+        .find("Keys:")
+        .map(|start| &USAGE[start..])
+        .unwrap_or_default();
+    let keys = keys
+        .find("\nProfiles:")
+        .map(|end| &keys[..end])
+        .unwrap_or(keys);
+
+    let mut screen = String::from(keys.trim_end());
+    screen.push_str("\n\nSettings:\n");
+    let speed = match status.interval {
+        _ if status.max_speed => "max speed".to_string(),
+        Some(interval) => format!("{:.0} fps", 1.0 / interval.as_secs_f64()),
+        None => "no delay".to_string(),
+    };
+    let batch = match settings.batch {
+        Batch::Fixed(batch) => format!("{} generations per frame", batch),
+        Batch::Adaptive => "as many generations per frame as fit".to_string(),
+    };
+    let limit = match settings.generations {
+        Some(limit) => format!("stopping at generation {}", limit),
+        None => "running until quit".to_string(),
+    };
+    writeln!(
+        screen,
+        "  {}x{} cells, rule {}, edges {}\n  {}, {}, {}\n  \
+         {} generations of history, {} patterns to stamp",
+        universe.width(),
+        universe.height(),
+        universe.rule(),
+        universe.boundary(),
+        speed,
+        batch,
+        limit,
+        settings.history,
+        settings.stamps.len()
+    )
+    .expect("writing to a String cannot fail");
+    screen.push_str("\nPress any key to go back.\n");
+    screen
+}
@@ -0,0 +1,241 @@
+//! Just enough HTTP/1.1 for the `serve` command: reading a request and
+//! writing a whole response back.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+/// Longest request head accepted, to keep a misbehaving client from
+/// growing it forever.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Most headers accepted in a request.
+const MAX_HEADERS: usize = 64;
+
+/// Longest request body accepted.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+/// A request, read whole.
+pub struct Request {
+    pub method: String,
+    /// The path, without the query.
+    pub path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read a request from `stream`, failing on anything that is not HTTP.
+    pub fn read(stream: impl Read) -> io::Result<Request> {
+        let mut reader = BufReader::new(stream);
+        let mut lines = Vec::new();
+        let mut size = 0;
+        loop {
+            let mut line = String::new();
+            // Reading one byte past what is left is enough to tell the
+            // head is too long, however long the line would go on.
+            let left = (MAX_HEAD - size + 1) as u64;
+            size += reader.by_ref().take(left).read_line(&mut line)?;
+            if size > MAX_HEAD {
+                return Err(invalid("request head too long"));
+            }
+            // A line cut short by the end of the stream has no ending.
+            let line = match line.strip_suffix("\r\n") {
+                Some(line) => line,
+                None => return Err(invalid("request line not ended by CRLF")),
+            };
+            if line.is_empty() {
+                break;
+            }
+            if lines.len() > MAX_HEADERS {
+                return Err(invalid("too many request headers"));
+            }
+            lines.push(line.to_string());
+        }
+        let mut start = lines
+            .first()
+            .ok_or_else(|| invalid("empty request"))?
+            .split(' ');
+        let (method, target) = match (start.next(), start.next(), start.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+                (method.to_string(), target)
+            }
+            _ => return Err(invalid("not an HTTP request")),
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut headers = Vec::with_capacity(lines.len() - 1);
+        for line in &lines[1..] {
+            match line.split_once(':') {
+                Some((name, value)) if !name.is_empty() && !name.contains(' ') => {
+                    headers.push((name.to_ascii_lowercase(), value.trim().to_string()))
+                }
+                _ => return Err(invalid("malformed request header")),
+            }
+        }
+        let mut request = Request {
+            method,
+            path: path.to_string(),
+            query: query.to_string(),
+            headers,
+            body: Vec::new(),
+        };
+        let length = match request.header("content-length") {
+            Some(length) => length
+                .parse::<usize>()
+                .map_err(|_| invalid("invalid content length"))?,
+            None => 0,
+        };
+        if length > MAX_BODY {
+            return Err(invalid("request body too long"));
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+        Ok(request)
+    }
+
+    /// The value of the header `name`, given in lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of `name` in the query, as in `?name=value`.
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Write a complete response and leave the connection to be closed.
+pub fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(request: &[u8]) -> io::Result<Request> {
+        Request::read(request)
+    }
+
+    #[test]
+    fn request_lines_are_split_up() {
+        let request = read(b"GET /universe?generation=3&pretty HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/universe");
+        assert_eq!(request.parameter("generation"), Some("3"));
+        assert_eq!(request.parameter("pretty"), Some(""));
+        assert_eq!(request.parameter("missing"), None);
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn headers_are_found_by_lowercase_name() {
+        let request = read(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade:  websocket \r\n\
+              X-Empty:\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.header("host"), Some("localhost"));
+        assert_eq!(request.header("upgrade"), Some("websocket"));
+        assert_eq!(request.header("x-empty"), Some(""));
+        assert_eq!(request.header("accept"), None);
+    }
+
+    #[test]
+    fn bodies_are_read_to_their_content_length() {
+        let request =
+            read(b"POST /cells HTTP/1.1\r\nContent-Length: 5\r\n\r\n1,2\n3extra").unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.body, b"1,2\n3");
+
+        // A body cut short is an error, not a shorter body.
+        assert!(read(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nabc").is_err());
+        assert!(read(b"POST / HTTP/1.1\r\nContent-Length: five\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn oversized_requests_are_refused() {
+        let mut long_head = b"GET / HTTP/1.1\r\nX-Long: ".to_vec();
+        long_head.resize(MAX_HEAD + 1, b'a');
+        long_head.extend_from_slice(b"\r\n\r\n");
+        assert!(read(&long_head).is_err());
+
+        let mut many_headers = b"GET / HTTP/1.1\r\n".to_vec();
+        for i in 0..=MAX_HEADERS {
+            many_headers.extend_from_slice(format!("X-{}: 1\r\n", i).as_bytes());
+        }
+        many_headers.extend_from_slice(b"\r\n");
+        assert!(read(&many_headers).is_err());
+
+        let long_body = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert!(read(long_body.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn the_most_headers_are_kept() {
+        let mut request = b"GET / HTTP/1.1\r\n".to_vec();
+        for i in 0..MAX_HEADERS {
+            request.extend_from_slice(format!("X-{}: {}\r\n", i, i).as_bytes());
+        }
+        request.extend_from_slice(b"\r\n");
+        let request = read(&request).unwrap();
+        let last = format!("x-{}", MAX_HEADERS - 1);
+        assert_eq!(request.header(&last), Some(&*(MAX_HEADERS - 1).to_string()));
+    }
+
+    #[test]
+    fn malformed_requests_are_refused() {
+        for request in [
+            &b""[..],
+            b"\r\n",
+            b"GET /\r\n\r\n",
+            b"GET / SPDY/3\r\n\r\n",
+            b"GET / HTTP/1.1\r\nno colon\r\n\r\n",
+            b"GET / HTTP/1.1\r\n: no name\r\n\r\n",
+            b"GET / HTTP/1.1\r\nBad Name: value\r\n\r\n",
+        ] {
+            assert!(
+                read(request).is_err(),
+                "{:?}",
+                String::from_utf8_lossy(request)
+            );
+        }
+    }
+
+    #[test]
+    fn lines_must_end_with_crlf() {
+        // A head the stream ends in the middle of.
+        assert!(read(b"GET / HTTP/1.1\r\nHost: localhost\r\n").is_err());
+        assert!(read(b"GET / HTTP/1.1").is_err());
+        // Lines ended by a bare line feed.
+        assert!(read(b"GET / HTTP/1.1\n\n").is_err());
+        assert!(read(b"GET / HTTP/1.1\r\nHost: localhost\n\r\n").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use game_of_life::pattern::{Format, Pattern};
+use game_of_life::Universe;
+use std::fs;
+use std::path::Path;
+
+use super::Args;
+
+pub const USAGE: &str = "\
+Usage: game_of_life info <pattern file>
+
+Prints the name, size, population and comments of a pattern.";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let positional = args.finish()?;
+    let path = match positional.as_slice() {
+        [path] => path,
+        _ => return Err(format!("expected one pattern file\n\n{}", USAGE)),
+    };
+
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let format = Format::from_path(Path::new(path)).unwrap_or_else(|| Format::sniff(&text));
+    let pattern = Pattern::parse(&text, format).map_err(|error| format!("{}: {}", path, error))?;
+
+    println!("file:       {}", path);
+    println!("format:     {}", format.name());
+    if let Some(name) = &pattern.name {
+        println!("name:       {}", name);
+    }
+    if let Some(rule) = &pattern.rule {
+        println!("rule:       {}", rule);
+    }
+    println!("size:       {}x{}", pattern.width(), pattern.height());
+    println!("population: {}", pattern.population());
+    let mut universe = Universe::empty(pattern.width(), pattern.height());
+    universe.insert_pattern(&pattern, 0, 0);
+    println!("symmetry:   {}", universe.symmetry());
+    for comment in &pattern.comments {
+        println!("comment:    {}", comment);
+    }
+    Ok(())
+}
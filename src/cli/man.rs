@@ -0,0 +1,97 @@
+use std::fmt::Write;
+
+use super::{Args, COMMANDS};
+
+pub const USAGE: &str = "\
+Usage: game_of_life man
+
+Prints the manual page in roff format, for example
+`game_of_life man > /usr/share/man/man1/game_of_life.1`.";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    args.finish()?;
+
+    print!("{}", page());
+    Ok(())
+}
+
+fn page() -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        ".TH GAME_OF_LIFE 1 \"\" \"{}\"",
+        env!("CARGO_PKG_VERSION")
+    )
+    .unwrap();
+    writeln!(out, ".SH NAME").unwrap();
+    writeln!(
+        out,
+        "game_of_life \\- Conway's Game of Life in the terminal"
+    )
+    .unwrap();
+    writeln!(out, ".SH SYNOPSIS").unwrap();
+    writeln!(out, ".B game_of_life").unwrap();
+    writeln!(out, "\\fIcommand\\fR [\\fIoptions\\fR]").unwrap();
+    writeln!(out, ".SH DESCRIPTION").unwrap();
+    writeln!(
+        out,
+        "Simulates cellular automata such as Conway's Game of Life, converts and \
+         describes pattern files and benchmarks the simulation. Started without \
+         arguments it asks for the basic settings interactively."
+    )
+    .unwrap();
+    writeln!(out, ".SH COMMANDS").unwrap();
+    for command in COMMANDS {
+        writeln!(out, ".TP").unwrap();
+        writeln!(out, ".B {}", command.name).unwrap();
+        writeln!(out, "{}", roff_escape(command.about)).unwrap();
+    }
+    for command in COMMANDS {
+        let options = command.options();
+        if options.is_empty() {
+            continue;
+        }
+        writeln!(out, ".SH {} OPTIONS", command.name.to_uppercase()).unwrap();
+        for (flag, description) in options {
+            writeln!(out, ".TP").unwrap();
+            writeln!(out, ".B {}", roff_escape(option_line(command.usage, flag))).unwrap();
+            writeln!(out, "{}", roff_escape(&description)).unwrap();
+        }
+    }
+    writeln!(out, ".SH ENVIRONMENT").unwrap();
+    writeln!(
+        out,
+        "Most run options can be defaulted with a GOL_ variable named after the \
+         option, such as GOL_RULE for \\-\\-rule, or with a 'name = value' line \
+         in the config file, game_of_life/config under XDG_CONFIG_HOME or \
+         ~/.config unless GOL_CONFIG names another. Options on the command \
+         line take precedence over the environment, which takes precedence \
+         over the file."
+    )
+    .unwrap();
+    out
+}
+
+/// The flag and its value placeholder as written in the usage text.
+pub(super) fn option_line<'a>(usage: &'a str, flag: &'a str) -> &'a str {
+    usage
+        .lines()
+        .map(str::trim_start)
+        .find(|line| line.starts_with(flag))
+        .and_then(|line| line.split("  ").next())
+        .unwrap_or(flag)
+        .trim()
+}
+
+/// Escape text for use in a roff document.
+fn roff_escape(text: &str) -> String {
+    let text = text.replace('\\', "\\\\").replace('-', "\\-");
+    match text.chars().next() {
+        Some('.') | Some('\'') => format!("\\&{}", text),
+        _ => text,
+    }
+}
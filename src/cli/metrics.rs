@@ -0,0 +1,281 @@
+//! What `serve` reports on `/metrics`, in the text format Prometheus
+//! scrapes.
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+/// Upper bounds in seconds of the buckets of the duration histograms.
+const BUCKETS: [f64; 11] = [
+    0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0,
+];
+
+/// Shortest span births and deaths per second are measured over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// The numbers `serve` keeps about itself, updated by the simulation and
+/// read by the HTTP API.
+pub struct Metrics {
+    generation: u64,
+    population: u64,
+    births: u64,
+    deaths: u64,
+    /// Births and deaths per second over the last window that ended.
+    rates: (f64, f64),
+    /// When the current window started, and the births and deaths then.
+    window: (Instant, u64, u64),
+    clients: usize,
+    paused: bool,
+    tick: Histogram,
+    render: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics {
+            generation: 0,
+            population: 0,
+            births: 0,
+            deaths: 0,
+            rates: (0.0, 0.0),
+            window: (Instant::now(), 0, 0),
+            clients: 0,
+            paused: false,
+            tick: Histogram::default(),
+            render: Histogram::default(),
+        }
+    }
+
+    /// Count a tick that took `duration` and its births and deaths.
+    pub fn record_tick(&mut self, duration: Duration, births: u64, deaths: u64) {
+        self.tick.observe(duration);
+        self.births += births;
+        self.deaths += deaths;
+        let (start, births, deaths) = self.window;
+        let elapsed = start.elapsed();
+        if elapsed >= RATE_WINDOW {
+            let seconds = elapsed.as_secs_f64();
+            self.rates = (
+                (self.births - births) as f64 / seconds,
+                (self.deaths - deaths) as f64 / seconds,
+            );
+            self.window = (Instant::now(), self.births, self.deaths);
+        }
+    }
+
+    /// Count sending out a generation to the clients, which took
+    /// `duration`.
+    pub fn record_render(&mut self, duration: Duration) {
+        self.render.observe(duration);
+    }
+
+    pub fn set_state(&mut self, generation: u64, population: u64, clients: usize, paused: bool) {
+        (self.generation, self.population) = (generation, population);
+        (self.clients, self.paused) = (clients, paused);
+    }
+
+    /// Everything in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            writeln!(out, "# HELP game_of_life_{} {}", name, escape_help(help)).unwrap();
+            writeln!(out, "# TYPE game_of_life_{} {}", name, kind).unwrap();
+            writeln!(out, "game_of_life_{} {}", name, value).unwrap();
+        };
+        metric(
+            "generation",
+            "gauge",
+            "Generation the universe is at.",
+            self.generation.to_string(),
+        );
+        metric(
+            "population",
+            "gauge",
+            "Live cells.",
+            self.population.to_string(),
+        );
+        metric(
+            "births_total",
+            "counter",
+            "Cells born since the server started.",
+            self.births.to_string(),
+        );
+        metric(
+            "deaths_total",
+            "counter",
+            "Cells that died since the server started.",
+            self.deaths.to_string(),
+        );
+        metric(
+            "births_per_second",
+            "gauge",
+            "Cells born per second lately.",
+            self.rates.0.to_string(),
+        );
+        metric(
+            "deaths_per_second",
+            "gauge",
+            "Cells that died per second lately.",
+            self.rates.1.to_string(),
+        );
+        metric(
+            "websocket_clients",
+            "gauge",
+            "WebSocket clients being streamed to.",
+            self.clients.to_string(),
+        );
+        metric(
+            "paused",
+            "gauge",
+            "1 while the simulation is paused.",
+            (self.paused as u8).to_string(),
+        );
+        self.tick.render(
+            &mut out,
+            "tick_duration_seconds",
+            "Time taken by each tick.",
+        );
+        self.render.render(
+            &mut out,
+            "render_duration_seconds",
+            "Time taken to encode and send each generation to the clients.",
+        );
+        out
+    }
+}
+
+/// How many durations fell at or under each of `BUCKETS`.
+#[derive(Default)]
+struct Histogram {
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (count, &bound) in self.counts.iter_mut().zip(&BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        writeln!(out, "# HELP game_of_life_{} {}", name, escape_help(help)).unwrap();
+        writeln!(out, "# TYPE game_of_life_{} histogram", name).unwrap();
+        let bounds = BUCKETS.iter().map(|bound| bound.to_string());
+        let counts = self.counts.iter().chain([&self.count]);
+        for (bound, count) in bounds.chain([String::from("+Inf")]).zip(counts) {
+            writeln!(
+                out,
+                "game_of_life_{}_bucket{{le=\"{}\"}} {}",
+                name,
+                escape_label(&bound),
+                count
+            )
+            .unwrap();
+        }
+        writeln!(out, "game_of_life_{}_sum {}", name, self.sum).unwrap();
+        writeln!(out, "game_of_life_{}_count {}", name, self.count).unwrap();
+    }
+}
+
+/// `help` with backslashes and line feeds escaped, as HELP lines need.
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// `value` escaped to go between the quotes of a label.
+fn escape_label(value: &str) -> String {
+    escape_help(value).replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_metric_has_help_and_a_type() {
+        let mut metrics = Metrics::new();
+        metrics.set_state(7, 42, 2, true);
+        metrics.record_tick(Duration::from_micros(20), 5, 3);
+        let text = metrics.render();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(text.ends_with('\n'));
+
+        assert_eq!(
+            lines[..3],
+            [
+                "# HELP game_of_life_generation Generation the universe is at.",
+                "# TYPE game_of_life_generation gauge",
+                "game_of_life_generation 7",
+            ]
+        );
+        for line in [
+            "game_of_life_population 42",
+            "game_of_life_births_total 5",
+            "game_of_life_deaths_total 3",
+            "game_of_life_websocket_clients 2",
+            "game_of_life_paused 1",
+            "# TYPE game_of_life_births_total counter",
+            "# TYPE game_of_life_tick_duration_seconds histogram",
+        ] {
+            assert!(lines.contains(&line), "{}", line);
+        }
+
+        // Each sample follows the HELP and TYPE of its metric.
+        let mut family = None;
+        for line in &lines {
+            if let Some(help) = line.strip_prefix("# HELP ") {
+                family = help.split(' ').next();
+            } else if let Some(kind) = line.strip_prefix("# TYPE ") {
+                assert_eq!(kind.split(' ').next(), family, "{}", line);
+            } else {
+                let name = line.split([' ', '{']).next().unwrap();
+                let family = family.unwrap();
+                assert!(name.starts_with(family), "{} outside {}", line, family);
+            }
+        }
+    }
+
+    #[test]
+    fn histograms_count_up_to_each_bound() {
+        let mut histogram = Histogram::default();
+        histogram.observe(Duration::from_micros(70));
+        histogram.observe(Duration::from_secs(2));
+        let mut out = String::new();
+        histogram.render(&mut out, "tick_duration_seconds", "Time taken.");
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[2],
+            "game_of_life_tick_duration_seconds_bucket{le=\"0.00001\"} 0"
+        );
+        assert_eq!(
+            lines[4],
+            "game_of_life_tick_duration_seconds_bucket{le=\"0.0001\"} 1"
+        );
+        assert_eq!(
+            lines[12],
+            "game_of_life_tick_duration_seconds_bucket{le=\"1\"} 1"
+        );
+        assert_eq!(
+            lines[13],
+            "game_of_life_tick_duration_seconds_bucket{le=\"+Inf\"} 2"
+        );
+        assert_eq!(lines[14], "game_of_life_tick_duration_seconds_sum 2.00007");
+        assert_eq!(lines[15], "game_of_life_tick_duration_seconds_count 2");
+    }
+
+    #[test]
+    fn help_and_labels_are_escaped() {
+        assert_eq!(escape_help("a\\b\nc \"d\""), "a\\\\b\\nc \"d\"");
+        assert_eq!(escape_label("a\\b\nc \"d\""), "a\\\\b\\nc \\\"d\\\"");
+
+        let mut out = String::new();
+        Histogram::default().render(&mut out, "x", "One\nor \\ two.");
+        assert!(out.starts_with("# HELP game_of_life_x One\\nor \\\\ two.\n"));
+    }
+}
@@ -0,0 +1,286 @@
+use std::env;
+use std::str::FromStr;
+
+use game_of_life::{Census, Universe};
+
+mod api;
+pub mod bench;
+mod browser;
+pub mod completions;
+pub mod config;
+mod control;
+pub mod convert;
+pub mod diverge;
+mod help;
+mod http;
+pub mod info;
+pub mod man;
+mod metrics;
+mod mqtt;
+pub mod run;
+pub mod serve;
+mod session;
+mod simulation;
+pub mod soupsearch;
+mod websocket;
+pub mod wizard;
+
+pub const USAGE: &str = "\
+Usage: game_of_life <command> [options]
+
+Commands:
+  run          Simulate a universe in the terminal
+  convert      Convert a pattern file between formats
+  bench        Measure simulation performance
+  diverge      Watch a few flipped cells spread through a copy
+  soupsearch   Tally the objects random soups settle into
+  serve        Serve a universe to browsers and scripts over the network
+  info         Describe a pattern file
+  completions  Print a shell completion script
+  man          Print the manual page
+  help         Show this message
+
+Run `game_of_life <command> --help` for the options of each command, or
+start without arguments to be asked for the basic settings.";
+
+/// A subcommand, as listed in completions and the manual page.
+pub struct Command {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub usage: &'static str,
+}
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "run",
+        about: "Simulate a universe in the terminal",
+        usage: run::USAGE,
+    },
+    Command {
+        name: "convert",
+        about: "Convert a pattern file between formats",
+        usage: convert::USAGE,
+    },
+    Command {
+        name: "bench",
+        about: "Measure simulation performance",
+        usage: bench::USAGE,
+    },
+    Command {
+        name: "diverge",
+        about: "Watch a few flipped cells spread through a copy",
+        usage: diverge::USAGE,
+    },
+    Command {
+        name: "soupsearch",
+        about: "Tally the objects random soups settle into",
+        usage: soupsearch::USAGE,
+    },
+    Command {
+        name: "serve",
+        about: "Serve a universe to browsers and scripts over the network",
+        usage: serve::USAGE,
+    },
+    Command {
+        name: "info",
+        about: "Describe a pattern file",
+        usage: info::USAGE,
+    },
+    Command {
+        name: "completions",
+        about: "Print a shell completion script",
+        usage: completions::USAGE,
+    },
+    Command {
+        name: "man",
+        about: "Print the manual page",
+        usage: man::USAGE,
+    },
+];
+
+impl Command {
+    /// The options documented in the usage text, as `(flag, description)`.
+    ///
+    /// Option lines start with two spaces and `--`, and descriptions may
+    /// continue on the following, more deeply indented lines.
+    pub fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options: Vec<(&'static str, String)> = Vec::new();
+        let mut in_option = false;
+        for line in self.usage.lines() {
+            if let Some(option) = line.strip_prefix("  --") {
+                let (flag, description) = option.split_once("  ").unwrap_or((option, ""));
+                let flag = flag.split_whitespace().next().unwrap_or(flag);
+                options.push((&line[2..4 + flag.len()], description.trim().to_string()));
+                in_option = true;
+            } else if in_option && line.starts_with("    ") {
+                let (_, description) = options.last_mut().expect("an option was seen");
+                description.push(' ');
+                description.push_str(line.trim());
+            } else {
+                in_option = false;
+            }
+        }
+        options
+    }
+
+    /// Whether the option takes a value, judging from its usage line.
+    pub fn takes_value(&self, flag: &str) -> bool {
+        self.usage.lines().any(|line| {
+            line.trim_start()
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with(" <") || rest.starts_with('='))
+        })
+    }
+}
+
+/// The command line arguments left to consume, with helpers to pull flags
+/// out of them in any order.
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: Vec<String>) -> Args {
+        Args { args }
+    }
+
+    /// Remove `--name`, returning whether it was present.
+    pub fn switch(&mut self, name: &str) -> bool {
+        match self.args.iter().position(|arg| arg == name) {
+            Some(position) => {
+                self.args.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove `--name value` or `--name=value`, returning the value.
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, String> {
+        let prefix = format!("{}=", name);
+        if let Some(position) = self.args.iter().position(|arg| arg.starts_with(&prefix)) {
+            let arg = self.args.remove(position);
+            return Ok(Some(arg[prefix.len()..].to_string()));
+        }
+
+        let position = match self.args.iter().position(|arg| arg == name) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        self.args.remove(position);
+        if position < self.args.len() {
+            Ok(Some(self.args.remove(position)))
+        } else {
+            Err(format!("{} requires a value", name))
+        }
+    }
+
+    /// Like `value`, but fall back to the matching `GOL_*` environment
+    /// variable when the flag is not given, and then to the config file.
+    pub fn value_or_env(&mut self, name: &str) -> Result<Option<String>, String> {
+        match self.value(name)? {
+            Some(value) => Ok(Some(value)),
+            None => Ok(env_value(name)),
+        }
+    }
+
+    /// Like `switch`, but also true when the matching `GOL_*` environment
+    /// variable, or else the config file, sets it to a true value such as
+    /// `1` or `yes`.
+    pub fn switch_or_env(&mut self, name: &str) -> bool {
+        self.switch(name)
+            || env_value(name).is_some_and(|value| {
+                matches!(
+                    value.to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+    }
+
+    /// Like `parse`, but fall back to the matching environment variable and
+    /// then to the config file.
+    pub fn parse_or_env<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        match self.parse(name)? {
+            Some(value) => Ok(Some(value)),
+            None => env_parse(name),
+        }
+    }
+
+    /// Remove `--name value` and parse the value.
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        match self.value(name)? {
+            Some(value) => parse_value(name, &value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the remaining positional arguments, failing on unknown flags.
+    pub fn finish(self) -> Result<Vec<String>, String> {
+        match self.args.iter().find(|arg| arg.starts_with("--")) {
+            Some(flag) => Err(format!("unknown option '{}'", flag)),
+            None => Ok(self.args),
+        }
+    }
+}
+
+/// The environment variable overriding the default of a flag: `--fps` is
+/// `GOL_FPS`, `--no-hud` is `GOL_NO_HUD`.
+pub fn env_name(flag: &str) -> String {
+    format!(
+        "GOL_{}",
+        flag.trim_start_matches('-')
+            .replace('-', "_")
+            .to_ascii_uppercase()
+    )
+}
+
+/// The non-empty value of the environment variable for `flag`, or else the
+/// value the config file gives it.
+pub fn env_value(flag: &str) -> Option<String> {
+    setting(flag).map(|(_, value)| value)
+}
+
+/// Parse the environment variable or config file setting for `flag`, if
+/// either is set.
+pub fn env_parse<T: FromStr>(flag: &str) -> Result<Option<T>, String> {
+    match setting(flag) {
+        Some((what, value)) => parse_value(&what, &value).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The value set for `flag` outside the command line, with the variable or
+/// file that set it.
+fn setting(flag: &str) -> Option<(String, String)> {
+    let name = env_name(flag);
+    match env::var(&name).ok().filter(|value| !value.is_empty()) {
+        Some(value) => Some((name, value)),
+        None => config::value(flag),
+    }
+}
+
+/// Parse `value`, naming `what` in the error if it is malformed.
+pub fn parse_value<T: FromStr>(what: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("invalid value '{}' for {}", value, what))
+}
+
+/// Parse two values separated by `separator`, such as `80x40` or `3,7`.
+pub fn parse_pair<T: FromStr>(what: &str, value: &str, separator: char) -> Result<(T, T), String> {
+    let (first, second) = value
+        .split_once(separator)
+        .ok_or_else(|| format!("{} expects two values separated by '{}'", what, separator))?;
+    Ok((parse_value(what, first)?, parse_value(what, second)?))
+}
+
+/// The census of `universe`, saying so when its rule leaves every group of
+/// cells unrecognized.
+fn census(universe: &Universe) -> String {
+    let census = Census::from_universe(universe);
+    if Census::recognizes(universe.rule()) {
+        census.to_string()
+    } else {
+        format!("{} (objects are only recognized under B3/S23)", census)
+    }
+}
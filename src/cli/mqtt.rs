@@ -0,0 +1,212 @@
+//! A small MQTT 3.1.1 client for `serve` that only ever publishes, at most
+//! once and without waiting for the broker to acknowledge anything past
+//! the connection.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+/// The port brokers listen on unless told otherwise.
+const DEFAULT_PORT: u16 = 1883;
+
+/// How long the broker waits without hearing from the client before it
+/// gives up on it.
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+pub struct Mqtt {
+    stream: TcpStream,
+    /// When the last packet went out, to ping the broker before the keep
+    /// alive runs out.
+    sent: Instant,
+}
+
+impl Mqtt {
+    /// Connect to the broker at `host` or `host:port` as `client_id`.
+    pub fn connect(address: &str, client_id: &str) -> io::Result<Mqtt> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        let mut stream = TcpStream::connect(&address)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+
+        stream.write_all(&connect_packet(client_id))?;
+
+        let mut ack = [0; 4];
+        stream.read_exact(&mut ack)?;
+        match ack {
+            [0x20, 2, _, 0] => Ok(Mqtt {
+                stream,
+                sent: Instant::now(),
+            }),
+            [0x20, 2, _, code] => Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("the broker refused the connection: {}", refusal(code)),
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the broker did not answer like an MQTT broker",
+            )),
+        }
+    }
+
+    /// Publish `payload` to `topic`. Retained messages are kept by the
+    /// broker and handed to whoever subscribes later.
+    pub fn publish(&mut self, topic: &str, payload: &[u8], retain: bool) -> io::Result<()> {
+        self.send(&publish_packet(topic, payload, retain))
+    }
+
+    /// Ping the broker if nothing was sent for half the keep alive, so a
+    /// paused simulation is not taken for a dead one.
+    pub fn keep_alive(&mut self) -> io::Result<()> {
+        if self.sent.elapsed() >= KEEP_ALIVE / 2 {
+            self.send(&[0xc0, 0])?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.write_all(packet)?;
+        self.sent = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        // Disconnecting cleanly keeps the broker from reporting it lost us.
+        let _ = self.stream.write_all(&[0xe0, 0]);
+    }
+}
+
+/// The CONNECT packet opening a clean session as `client_id`.
+fn connect_packet(client_id: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    string(&mut body, "MQTT");
+    // Protocol level 4 is MQTT 3.1.1, asking for a clean session.
+    body.extend_from_slice(&[4, 0x02]);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    string(&mut body, client_id);
+    packet(0x10, &body)
+}
+
+/// The PUBLISH packet of `payload` to `topic`, at most once.
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    string(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x30 | retain as u8, &body)
+}
+
+/// A packet of the given type and flags around `body`.
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    // The remaining length takes seven bits a byte, lowest first.
+    let mut length = body.len();
+    loop {
+        let byte = (length % 128) as u8;
+        length /= 128;
+        if length == 0 {
+            packet.push(byte);
+            break;
+        }
+        packet.push(byte | 0x80);
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Append `text` the way MQTT writes strings, behind its length in two
+/// bytes.
+fn string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Why a broker refused a connection, by the return code it gave.
+fn refusal(code: u8) -> String {
+    match code {
+        1 => String::from("unacceptable protocol version"),
+        2 => String::from("client identifier rejected"),
+        3 => String::from("server unavailable"),
+        4 => String::from("bad user name or password"),
+        5 => String::from("not authorized"),
+        code => format!("return code {}", code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// The header and remaining length of a packet around `length` bytes.
+    fn head(length: usize) -> Vec<u8> {
+        let mut packet = packet(0x30, &vec![0; length]);
+        packet.truncate(packet.len() - length);
+        packet
+    }
+
+    #[test]
+    fn remaining_lengths_take_seven_bits_a_byte() {
+        assert_eq!(head(0), [0x30, 0x00]);
+        assert_eq!(head(127), [0x30, 0x7f]);
+        assert_eq!(head(128), [0x30, 0x80, 0x01]);
+        assert_eq!(head(16_383), [0x30, 0xff, 0x7f]);
+        assert_eq!(head(16_384), [0x30, 0x80, 0x80, 0x01]);
+        assert_eq!(head(2_097_152), [0x30, 0x80, 0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn connect_packets_match_the_spec() {
+        assert_eq!(
+            connect_packet("life"),
+            [
+                0x10, 16, // CONNECT and the remaining length
+                0, 4, b'M', b'Q', b'T', b'T', // protocol name
+                4,    // protocol level, 3.1.1
+                0x02, // clean session
+                0, 60, // keep alive in seconds
+                0, 4, b'l', b'i', b'f', b'e', // client identifier
+            ]
+        );
+    }
+
+    #[test]
+    fn publish_packets_match_the_spec() {
+        assert_eq!(
+            publish_packet("a/b", b"hi", false),
+            [0x30, 7, 0, 3, b'a', b'/', b'b', b'h', b'i']
+        );
+        assert_eq!(publish_packet("t", b"", true), [0x31, 3, 0, 1, b't']);
+    }
+
+    /// Connect to a broker that reads the CONNECT packet and answers `ack`.
+    fn connect_to(ack: [u8; 4]) -> io::Result<Mqtt> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let broker = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut connect = vec![0; connect_packet("life").len()];
+            stream.read_exact(&mut connect).unwrap();
+            stream.write_all(&ack).unwrap();
+            connect
+        });
+        let mqtt = Mqtt::connect(&address, "life");
+        assert_eq!(broker.join().unwrap(), connect_packet("life"));
+        mqtt
+    }
+
+    #[test]
+    fn connections_wait_for_the_broker() {
+        assert!(connect_to([0x20, 2, 0, 0]).is_ok());
+        let refused = connect_to([0x20, 2, 0, 5]).err().unwrap();
+        assert_eq!(refused.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(refused.to_string().contains("not authorized"));
+        let garbled = connect_to([0x30, 2, 0, 0]).err().unwrap();
+        assert_eq!(garbled.kind(), io::ErrorKind::InvalidData);
+    }
+}
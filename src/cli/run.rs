@@ -0,0 +1,1756 @@
+use futures::executor::block_on;
+use game_of_life::input::{catch_interrupts, interrupted, load_keys};
+use game_of_life::pattern::{preset, Format, Pattern, Preset, PRESETS};
+use game_of_life::render::{
+    display_width, ending, throughput, Camera, Charset, ColorMode, DiffRenderer, FollowRenderer,
+    HeatmapRenderer, Recorder, RecordingRenderer, Renderer, SeriesRenderer, SplitRenderer,
+    StatsRenderer, TerminalRenderer, Theme, TrailsRenderer,
+};
+#[cfg(feature = "gpu")]
+use game_of_life::GpuUniverse;
+use game_of_life::{
+    BitEncoding, Boundary, CycleDetector, Dither, Engine, Font, Image, Initializer, Records, Rule,
+    SparseUniverse, Strips, Universe,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::session::{play, play_split, Settings, WhenStill};
+use super::simulation::{settled, Batch};
+use super::{env_parse, env_value, parse_pair, parse_value, Args};
+
+/// Delay between frames when no pacing option is given.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Generations a headless run on several threads or the GPU simulates
+/// between checks for Ctrl+C.
+const INTERRUPT_CHECK: u64 = 64;
+
+/// Share of live cells in a random soup when no density is given.
+pub(super) const DEFAULT_DENSITY: f64 = 0.35;
+
+/// Radius of each of the --clusters when none is given.
+const DEFAULT_RADIUS: u32 = 8;
+
+/// Generations kept for stepping back when `--history` isn't given.
+const DEFAULT_HISTORY: usize = 100;
+
+pub const USAGE: &str = "\
+Usage: game_of_life run <width> <height> [<first divider> <second divider>] [options]
+
+Without dividers the universe starts as a random soup.
+
+Options:
+  --pattern <file>[@row,col]  Start from a .rle or .cells file, centered unless
+                              a position is given
+  --preset <name>[@row,col]   Start from a built-in pattern such as glider-gun
+  --text <text>[@row,col]     Start from text written in live cells, one line
+                              of glyphs for each line of text
+  --font <name>               Font for --text: small or standard (default
+                              standard)
+  --hex <digits>[@row,col]    Start from cells given as bits in hex, row by
+                              row from the top left with the highest bit of
+                              each digit first
+  --base64 <text>[@row,col]   The same with the bits in base64
+  --row-width <cells>         Cells in each row of --hex or --base64 (default
+                              the width of the board)
+  --image <file>[@row,col]    Start from a PNG, PGM or PPM image as large as
+                              fits the board, its dark pixels alive
+  --dither <method>           Shades of gray in --image: threshold, ordered or
+                              floyd-steinberg (default floyd-steinberg)
+  --image-width <cells>       Draw --image this many cells wide instead
+  --invert                    Make the light pixels of --image alive instead
+  --stamp <file>              Offer this pattern first when stamping in edit
+                              mode
+  --catalog <dir>             Offer the pattern files in this directory too
+  --list-presets              List the built-in patterns
+  --empty                     Start from a dead board, to draw on in edit mode
+  --random                    Start from a random soup (the default without
+                              dividers)
+  --density <fraction>        Share of cells alive in a random soup (default
+                              0.35)
+  --noise <scale>             Start from blobs of gradient noise around this
+                              many cells across instead of a random soup
+  --threshold <level>         Noise level from -1 to 1 above which cells start
+                              alive (default 0, about half of them)
+  --clusters <count>          Start from this many separate soups at random
+                              places instead of one across the board
+  --radius <cells>            Radius of each of the --clusters (default 8)
+  --seed <number>             Seed for the random soup, the noise or the
+                              clusters, for reproducible runs
+  --daily                     Seed with today's date instead, the same soup
+                              of the day for everyone
+  --rule <rule>               Rulestring such as B36/S23, or a preset name
+  --fps <frames|max>          Frames per second, or max for no delay (default
+                              10)
+  --interval <duration>       Delay between frames such as 16ms or 0.5s
+  --profile <name>            Defaults tuned for smooth (the default), fast or
+                              turbo playback, see below
+  --batch <generations|auto>  Simulate this many generations per frame, or
+                              with auto as many as fit in the frame interval
+  --wrap <edges>              Boundary: torus, dead, mirror or grow, or per axis
+                              as x=torus,y=dead (default torus)
+  --heatmap <generations|all> Color cells by activity over the last
+                              generations, or over all of them
+  --heatmap-image <path>      Also save the --heatmap as a PNG image when
+                              stopping
+  --trails <generations>      Fade out recently dead cells
+  --diff                      Only redraw rows that changed
+  --follow <width>x<height>   Follow the live cells with a camera of this size
+  --split <a>,<b>             Run a second universe with these dividers side by
+                              side
+  --quiet                     Print a statistics line instead of the grid
+  --every <generations>       How often --quiet reports (default 10)
+  --sparkline                 Show the recent population as a sparkline on the
+                              HUD
+  --series <path>             Also save the generation, population, births and
+                              deaths of every frame as CSV
+  --record <path>             Also record the session as .gif, .cast or .jsonl
+  --start-paused              Show generation 0 and wait for space to start
+  --record-keys <path>        Save the keys pressed and when, for replaying
+  --replay-keys <path>        Press the keys saved with --record-keys again at
+                              the same times
+  --save-format <format>      Format of the files saved with s: rle, cells,
+                              life106 or json (default rle)
+  --history <generations>     Generations kept for stepping back (default
+                              100)
+  --headless                  Simulate without rendering (requires
+                              --generations)
+  --threads <count>           Split a headless simulation across this many
+                              threads (default 1)
+  --gpu                       Step a headless simulation on the GPU (needs a
+                              build with the gpu feature)
+  --engine <name>             Step with bitwise adders or a lookup table:
+                              adder or table (default adder)
+  --fold-symmetric            Simulate only the top half of a board that is the
+                              same flipped top to bottom or turned half way
+                              round, filling in the bottom half from it
+  --mapped <path>             Keep a headless universe in this file instead of
+                              in memory, for boards larger than RAM (needs a
+                              --pattern or --preset)
+  --sparse                    Keep a headless universe as runs of live cells,
+                              so a tick costs time in proportion to what is
+                              alive rather than to the board (needs a
+                              --pattern or --preset, ignores --engine)
+  --generations <count>       Stop after this many generations
+  --when-still <policy>       Once the board dies out or stops changing: keep
+                              going, stop, loop back to the start or reseed
+                              with a random soup (default keep)
+  --stop-on-cycle             Stop once the board comes back to an earlier
+                              state, which it repeats from then on
+  --spaceships                Also look for the live cells coming back
+                              shifted, as a lone spaceship does, and report
+                              its speed and direction
+  --records                   Keep track of the most cells alive, the largest
+                              bounds, the most births and the longest steady
+                              population, reported when stopping and by
+                              --quiet
+  --output <path>             Save the final state as .rle or .cells when
+                              stopping
+  --census                    Count the blocks, blinkers, gliders and other
+                              common objects left on the board when stopping
+  --state-hash                Print a hash of the final state that stays the
+                              same across versions and machines
+  --print-bits <encoding>     Print the final state as bits in hex or base64,
+                              to pass back with --hex or --base64
+  --ascii                     Draw with plain ASCII characters
+  --charset <alive>,<dead>    Glyphs for live and dead cells, such as '█ ,· '
+  --color <when>              Color --heatmap, --trails and --theme: auto,
+                              always or never (default auto, which colors
+                              only on a terminal and when NO_COLOR is unset)
+  --theme <name>              Colors to draw the board in: plain, green,
+                              amber, ocean or paper (default plain, the
+                              terminal's own)
+  --no-hud                    Start with the status line hidden
+
+Keys:
+  space     pause or resume
+  n or .    while paused, advance one generation
+  N         while paused, advance ten generations
+  ,         while paused, go back one generation, as does the left arrow
+  g         go to a generation, simulating ahead or going back through the
+            history
+  :         type a command, see below
+  + or ]    speed up
+  - or [    slow down
+  M         toggle max speed, drawing only some of the generations
+  h         show or hide the status line
+  e         edit the board, pausing the simulation
+  p         browse the patterns to stamp, typing to search them
+  u         undo the last cells changed by hand
+  ctrl+r    redo what was undone
+  s         save the board to a new file in the working directory
+  c         clear the board
+  r         replace the board with a new random soup
+  R         switch to the next well known rule, keeping the board
+  m1 to m9  bookmark the board, to come back to it with '1 to '9
+  ?         show the keys and the settings in use
+  q         quit
+
+Clicking a cell toggles it and dragging paints more cells the same way. In
+edit mode the arrow keys or h, j, k and l move the cursor, enter or space
+toggles the cell under it, and e or escape goes back. Pressing t there
+picks a pattern to stamp instead: the --stamp file, the starting pattern,
+each preset and the --catalog files in turn, before going back to single
+cells. r turns the stamp clockwise, f mirrors it, and enter or space places
+it with its top left corner on the cursor.
+
+To copy part of the board, press v in edit mode and move the cursor to the
+opposite corner, then y to copy the selection or x to cut it. The copy
+becomes the stamp, and P picks it again later. F fills the selection with a
+random soup instead, asking for its density and optionally a seed.
+
+The number keys pick the brush used for toggling and painting: 1 for single
+cells, 2 for a 3x3 square, and 3 to 9 for circles of radius 2 to 8.
+
+Commands:
+  :rule <rule>              switch to another rule, keeping the board
+  :resize <width> <height>  change the board size, keeping what still fits
+  :save <path>              save the board, in the format the extension names
+  :census                   count the common objects on the board
+  :symmetry                 tell how the board is symmetric
+  :goto <generation>        same as g
+  :fps <rate>               change the speed, or max to remove the delay
+  :clear, :random           same as c and r
+  :quit                     same as q
+
+Profiles:
+  smooth    full redraws, one generation per frame at 10 frames per second
+  fast      --diff, one generation per frame at about 60 frames per second
+  turbo     --diff, 16 generations per frame, as fast as possible
+
+Environment:
+  GOL_RULE, GOL_PROFILE, GOL_WRAP, GOL_FPS, GOL_INTERVAL, GOL_GENERATIONS,
+  GOL_WHEN_STILL, GOL_ENGINE, GOL_DENSITY, GOL_SEED, GOL_ASCII, GOL_CHARSET,
+  GOL_COLOR, GOL_THEME, GOL_QUIET, GOL_NO_HUD and GOL_CATALOG set the
+  default of the matching option. Options given on the command line take
+  precedence. NO_COLOR disables colors unless --color always is given.
+
+Config file:
+  The same options can be set in game_of_life/config under XDG_CONFIG_HOME
+  or ~/.config, or in the file GOL_CONFIG names, one 'name = value' line
+  each, such as 'fps = 30' or 'no-hud = true'. The environment and the
+  command line take precedence over the file.";
+
+/// A named bundle of defaults for `--profile`, trading smooth animation for
+/// simulation speed. Options given explicitly still take precedence.
+struct Profile {
+    name: &'static str,
+    /// Redraw only the rows that changed.
+    diff: bool,
+    batch: Batch,
+    interval: Option<Duration>,
+}
+
+const PROFILES: [Profile; 3] = [
+    Profile {
+        name: "smooth",
+        diff: false,
+        batch: Batch::Fixed(1),
+        interval: Some(DEFAULT_INTERVAL),
+    },
+    Profile {
+        name: "fast",
+        diff: true,
+        batch: Batch::Fixed(1),
+        interval: Some(Duration::from_millis(16)),
+    },
+    Profile {
+        name: "turbo",
+        diff: true,
+        batch: Batch::Fixed(16),
+        interval: None,
+    },
+];
+
+impl Profile {
+    fn named(name: &str) -> Result<&'static Profile, String> {
+        PROFILES
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = PROFILES.iter().map(|profile| profile.name).collect();
+                format!(
+                    "unknown profile '{}', expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    catch_interrupts();
+
+    let backend = Backend::parse(&mut args)?;
+    let profile = match args.value_or_env("--profile")? {
+        Some(name) => Profile::named(&name)?,
+        None => &PROFILES[0],
+    };
+    let mut settings = parse_settings(&mut args, profile, backend.generations)?;
+    let mut view = View::parse(&mut args, profile)?;
+    if args.switch("--list-presets") {
+        for preset in PRESETS {
+            println!("{:<16} {}", preset.name, preset.description);
+        }
+        return Ok(());
+    }
+    let mut start = Start::parse(&mut args)?;
+    let positional = args.finish()?;
+    let (width, height, dividers) = parse_board(&positional)?;
+    check(&backend, &view, &settings, &start, dividers.is_some())?;
+    // Reports and recordings are read later, so none of them may be lost.
+    settings.every_batch = view.quiet || view.record.is_some() || view.series.is_some();
+    if start.daily {
+        let (seed, announcement) = soup_of_the_day(today());
+        start.seed = Some(seed);
+        settings.announcement = Some(announcement);
+    }
+
+    let pattern = start.pattern((width, height))?;
+    let output = view.output.take();
+    if backend.storage().is_some() {
+        let pattern = pattern.expect("checked along with the other options");
+        let generations = backend
+            .generations
+            .expect("checked along with the other options");
+        return run_stored(
+            &backend,
+            (width, height),
+            pattern,
+            generations,
+            output.as_deref(),
+        );
+    }
+    // Reseeding during the session uses the density a random start would.
+    settings.density = match start.density {
+        Some(density) => density,
+        None => env_parse::<f64>("--density")?.unwrap_or(DEFAULT_DENSITY),
+    };
+    let universe = build_universe(
+        (width, height),
+        dividers,
+        start,
+        pattern,
+        &backend,
+        &mut settings,
+    )?;
+
+    let report = view.report;
+    let universe = if backend.headless {
+        let generations = backend
+            .generations
+            .expect("checked along with the other options");
+        let work = (backend.threads(), backend.gpu);
+        run_headless(universe, generations, work, &settings, &view.charset)?
+    } else if let Some(split) = view.split {
+        run_split(universe, split, &backend, &view, &settings)?
+    } else {
+        run_live(universe, view, &settings)?
+    };
+    finish(&universe, output.as_deref(), report)
+}
+
+/// How the universe is stepped, and for how long.
+struct Backend {
+    rule: Option<Rule>,
+    boundary: Boundary,
+    engine: Engine,
+    generations: Option<u64>,
+    headless: bool,
+    threads: Option<usize>,
+    gpu: bool,
+    mapped: Option<String>,
+    sparse: bool,
+    fold_symmetric: bool,
+}
+
+impl Backend {
+    fn parse(args: &mut Args) -> Result<Backend, String> {
+        Ok(Backend {
+            rule: match args.value_or_env("--rule")? {
+                Some(rule) => Some(Rule::parse(&rule).map_err(|error| error.to_string())?),
+                None => None,
+            },
+            boundary: match args.value_or_env("--wrap")? {
+                Some(wrap) => Boundary::parse(&wrap).map_err(|error| error.to_string())?,
+                None => Boundary::default(),
+            },
+            engine: match args.value_or_env("--engine")? {
+                Some(name) => parse_engine(&name)?,
+                None => Engine::default(),
+            },
+            generations: args.parse_or_env::<u64>("--generations")?,
+            headless: args.switch("--headless"),
+            threads: args.parse::<usize>("--threads")?,
+            gpu: args.switch("--gpu"),
+            mapped: args.value("--mapped")?,
+            sparse: args.switch("--sparse"),
+            fold_symmetric: args.switch("--fold-symmetric"),
+        })
+    }
+
+    fn threads(&self) -> usize {
+        self.threads.unwrap_or(1)
+    }
+
+    /// The flag keeping the universe somewhere other than a `Universe`, if
+    /// one was given.
+    fn storage(&self) -> Option<&'static str> {
+        match (&self.mapped, self.sparse) {
+            (Some(_), _) => Some("--mapped"),
+            (None, true) => Some("--sparse"),
+            (None, false) => None,
+        }
+    }
+
+    /// Set up `universe` to be stepped with `rule` as asked.
+    fn configure(&self, universe: &mut Universe, rule: Rule) {
+        universe.set_rule(rule);
+        universe.set_engine(self.engine);
+        universe.set_boundary(self.boundary);
+        universe.set_fold_symmetric(self.fold_symmetric);
+    }
+}
+
+/// How a live session is drawn, and what is reported once it ends.
+struct View {
+    heatmap: Option<Option<usize>>,
+    heatmap_image: Option<String>,
+    trails: Option<u32>,
+    follow: Option<Camera>,
+    split: Option<(u32, u32)>,
+    diff: bool,
+    quiet: bool,
+    every: u64,
+    record: Option<String>,
+    series: Option<String>,
+    charset: Charset,
+    color: bool,
+    theme: Theme,
+    output: Option<String>,
+    /// Whether to print the census and the state hash, and the encoding to
+    /// print the cells in.
+    report: (bool, bool, Option<BitEncoding>),
+}
+
+impl View {
+    fn parse(args: &mut Args, profile: &Profile) -> Result<View, String> {
+        let heatmap = match args.value("--heatmap")? {
+            Some(window) => Some(parse_window(&window)?),
+            None => None,
+        };
+        let heatmap_image = args.value("--heatmap-image")?;
+        let trails = args.parse::<u32>("--trails")?;
+        let output = args.value("--output")?;
+        let census = args.switch("--census");
+        let state_hash = args.switch("--state-hash");
+        let print_bits = match args.value("--print-bits")? {
+            Some(name) => Some(BitEncoding::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown encoding '{}', expected one of {}",
+                    name,
+                    BitEncoding::NAMES.join(", ")
+                )
+            })?),
+            None => None,
+        };
+        let record = args.value("--record")?;
+        let series = args.value("--series")?;
+        let diff = args.switch("--diff") || profile.diff;
+        let quiet = args.switch_or_env("--quiet");
+        let every = args.parse::<u64>("--every")?.unwrap_or(10);
+        let follow = match args.value("--follow")? {
+            Some(view) => {
+                let (width, height) = parse_pair::<u32>("--follow", &view, 'x')?;
+                Some(Camera::new(width, height))
+            }
+            None => None,
+        };
+        let split = match args.value("--split")? {
+            Some(dividers) => match parse_pair::<u32>("--split", &dividers, ',')? {
+                (0, _) | (_, 0) => return Err(String::from("--split dividers must be at least 1")),
+                dividers => Some(dividers),
+            },
+            None => None,
+        };
+        let ascii = args.switch("--ascii");
+        let charset = match args.value("--charset")? {
+            Some(_) if ascii => return Err(String::from("use either --ascii or --charset")),
+            Some(glyphs) => parse_charset(&glyphs)?,
+            None if ascii => Charset::ASCII,
+            None => match env_value("--charset") {
+                Some(glyphs) => parse_charset(&glyphs)?,
+                None if args.switch_or_env("--ascii") => Charset::ASCII,
+                None => Charset::detect(),
+            },
+        };
+        let color = match args.value_or_env("--color")? {
+            Some(name) => ColorMode::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown color mode '{}', expected one of {}",
+                    name,
+                    ColorMode::NAMES.join(", ")
+                )
+            })?,
+            None => ColorMode::default(),
+        }
+        .enabled();
+        let theme = match args.value_or_env("--theme")? {
+            Some(name) => Theme::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown theme '{}', expected one of {}",
+                    name,
+                    Theme::NAMES.join(", ")
+                )
+            })?,
+            None => Theme::default(),
+        };
+        Ok(View {
+            heatmap,
+            heatmap_image,
+            trails,
+            follow,
+            split,
+            diff,
+            quiet,
+            every,
+            record,
+            series,
+            charset,
+            color,
+            theme,
+            output,
+            report: (census, state_hash, print_bits),
+        })
+    }
+
+    /// The charset to draw the board in. Heatmaps and trails pick colors of
+    /// their own, the other views draw the board in those of the theme.
+    fn themed(&self) -> Charset {
+        if self.color {
+            self.charset.clone().themed(self.theme)
+        } else {
+            self.charset.clone()
+        }
+    }
+}
+
+/// The options of a live session that `Settings` holds.
+fn parse_settings(
+    args: &mut Args,
+    profile: &Profile,
+    generations: Option<u64>,
+) -> Result<Settings, String> {
+    let mut pacing = (args.value("--fps")?, args.value("--interval")?);
+    if pacing == (None, None) {
+        // Only consult the environment when neither flag was given, so the
+        // command line can override a GOL_FPS with --interval and vice versa.
+        pacing = (
+            args.value_or_env("--fps")?,
+            args.value_or_env("--interval")?,
+        );
+    }
+    let interval = match pacing {
+        (Some(_), Some(_)) => return Err(String::from("use either --fps or --interval")),
+        (Some(fps), None) => parse_fps(&fps)?,
+        (None, Some(interval)) => Some(parse_interval(&interval)?),
+        (None, None) => profile.interval,
+    };
+    Ok(Settings {
+        show_hud: !args.switch_or_env("--no-hud"),
+        sparkline: args.switch("--sparkline"),
+        start_paused: args.switch("--start-paused"),
+        interval,
+        batch: match args.value("--batch")? {
+            Some(batch) => parse_batch(&batch)?,
+            None => profile.batch,
+        },
+        generations,
+        history: args.parse::<usize>("--history")?.unwrap_or(DEFAULT_HISTORY),
+        stamps: Vec::new(),
+        record_keys: args.value("--record-keys")?,
+        replay_keys: match args.value("--replay-keys")? {
+            Some(path) => load_keys(&path).map_err(|error| format!("{}: {}", path, error))?,
+            None => Vec::new(),
+        },
+        density: DEFAULT_DENSITY,
+        every_batch: false,
+        when_still: match args.value_or_env("--when-still")? {
+            Some(name) => WhenStill::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown policy '{}', expected one of {}",
+                    name,
+                    WhenStill::NAMES.join(", ")
+                )
+            })?,
+            None => WhenStill::default(),
+        },
+        stop_on_cycle: args.switch("--stop-on-cycle"),
+        track_motion: args.switch("--spaceships"),
+        records: args.switch("--records"),
+        announcement: None,
+        save_format: match args.value("--save-format")? {
+            Some(name) => Format::from_name(&name)
+                .ok_or_else(|| format!("unknown pattern format '{}'", name))?,
+            None => Format::Rle,
+        },
+    })
+}
+
+/// A pattern and where its top left corner goes, centered if not given.
+type Placed = (Pattern, Option<(u32, u32)>);
+
+/// What the board starts from: a pattern in one of several forms, or a
+/// soup, along with the patterns offered for stamping.
+struct Start {
+    /// A pattern from `--pattern`, `--preset` or `--text`.
+    pattern: Option<Placed>,
+    /// How many of the ways of giving a pattern were used.
+    sources: usize,
+    font: Option<Font>,
+    text: bool,
+    bits: Option<(BitEncoding, String)>,
+    row_width: Option<u32>,
+    image: Option<(Image, Option<(u32, u32)>)>,
+    dither: Option<Dither>,
+    image_width: Option<u32>,
+    invert: bool,
+    stamp: Option<Pattern>,
+    catalog: Vec<Pattern>,
+    random: bool,
+    density: Option<f64>,
+    seed: Option<u64>,
+    daily: bool,
+    noise: Option<f64>,
+    threshold: Option<f64>,
+    clusters: Option<u32>,
+    radius: Option<u32>,
+    empty: bool,
+}
+
+impl Start {
+    fn parse(args: &mut Args) -> Result<Start, String> {
+        let font = match args.value("--font")? {
+            Some(name) => Some(Font::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown font '{}', expected one of {}",
+                    name,
+                    Font::NAMES.join(", ")
+                )
+            })?),
+            None => None,
+        };
+        let text = args.value("--text")?;
+        let bits = match (args.value("--hex")?, args.value("--base64")?) {
+            (Some(_), Some(_)) => return Err(String::from("use either --hex or --base64")),
+            (Some(value), None) => Some((BitEncoding::Hex, value)),
+            (None, Some(value)) => Some((BitEncoding::Base64, value)),
+            (None, None) => None,
+        };
+        let row_width = args.parse::<u32>("--row-width")?;
+        let image = match args.value("--image")? {
+            Some(value) => {
+                let (path, position) = parse_placement(&value)?;
+                let image = Image::load(&path).map_err(|error| format!("{}: {}", path, error))?;
+                Some((image, position))
+            }
+            None => None,
+        };
+        let dither = match args.value("--dither")? {
+            Some(name) => Some(Dither::from_name(&name).ok_or_else(|| {
+                format!(
+                    "unknown dithering '{}', expected one of {}",
+                    name,
+                    Dither::NAMES.join(", ")
+                )
+            })?),
+            None => None,
+        };
+        let image_width = args.parse::<u32>("--image-width")?;
+        let invert = args.switch("--invert");
+        let (path, name) = (args.value("--pattern")?, args.value("--preset")?);
+        let sources = [
+            path.is_some(),
+            name.is_some(),
+            text.is_some(),
+            bits.is_some(),
+            image.is_some(),
+        ];
+        let sources = sources.iter().filter(|&&given| given).count();
+        let given_text = text.is_some();
+        let pattern = match (path, name, text) {
+            _ if sources > 1 => None,
+            (Some(value), _, _) => {
+                let (path, position) = parse_placement(&value)?;
+                let pattern =
+                    Pattern::load(&path).map_err(|error| format!("{}: {}", path, error))?;
+                Some((pattern, position))
+            }
+            (_, Some(value), _) => {
+                let (name, position) = parse_placement(&value)?;
+                let pattern = preset(&name)
+                    .ok_or_else(|| format!("unknown preset '{}', see --list-presets", name))?;
+                Some((pattern, position))
+            }
+            (_, _, Some(value)) => {
+                let (text, position) = parse_placement(&value)?;
+                let banner = Universe::from_text_banner(&text, font.unwrap_or_default());
+                Some((Pattern::from_universe(&banner), position))
+            }
+            _ => None,
+        };
+        Ok(Start {
+            pattern,
+            sources,
+            font,
+            text: given_text,
+            bits,
+            row_width,
+            image,
+            dither,
+            image_width,
+            invert,
+            stamp: match args.value("--stamp")? {
+                Some(path) => {
+                    Some(Pattern::load(&path).map_err(|error| format!("{}: {}", path, error))?)
+                }
+                None => None,
+            },
+            catalog: match args.value_or_env("--catalog")? {
+                Some(dir) => load_catalog(&dir)?,
+                None => Vec::new(),
+            },
+            random: args.switch("--random"),
+            density: args.parse::<f64>("--density")?,
+            seed: args.parse::<u64>("--seed")?,
+            // The soup of the day is seeded with the date, so everyone
+            // trying it the same day starts from the same board.
+            daily: args.switch("--daily"),
+            noise: args.parse::<f64>("--noise")?,
+            threshold: args.parse::<f64>("--threshold")?,
+            clusters: args.parse::<u32>("--clusters")?,
+            radius: args.parse::<u32>("--radius")?,
+            empty: args.switch("--empty"),
+        })
+    }
+
+    /// Which of the ways of generating a soup were asked for: randomly,
+    /// from noise and in clusters.
+    fn soups(&self) -> [bool; 3] {
+        [
+            self.random || self.density.is_some(),
+            self.noise.is_some(),
+            self.clusters.is_some(),
+        ]
+    }
+
+    /// Whether the board starts from a random soup, noise or clusters rather
+    /// than dividers or an empty one.
+    fn generated(&self) -> bool {
+        self.soups().contains(&true) || self.seed.is_some() || self.daily
+    }
+
+    /// The pattern to start from on a board of `size`, in whichever form it
+    /// was given. The bits are laid out in rows as wide as the board unless
+    /// told otherwise, and images are scaled to fit it.
+    fn pattern(&mut self, (width, height): (u32, u32)) -> Result<Option<Placed>, String> {
+        if let Some((encoding, value)) = &self.bits {
+            let (bits, position) = parse_placement(value)?;
+            let laid_out =
+                Universe::from_bit_string(&bits, *encoding, self.row_width.unwrap_or(width))
+                    .map_err(|error| format!("--{}: {}", encoding.name(), error))?;
+            return Ok(Some((Pattern::from_universe(&laid_out), position)));
+        }
+        if let Some((mut image, position)) = self.image.take() {
+            if self.invert {
+                image.invert();
+            }
+            let (columns, rows) = image_size(&image, (width, height), self.image_width);
+            let dither = self.dither.unwrap_or_default();
+            let drawn = Universe::from_image(&image, columns, rows, dither);
+            return Ok(Some((Pattern::from_universe(&drawn), position)));
+        }
+        Ok(self.pattern.take())
+    }
+
+    /// How to fill the board when not from a pattern or dividers. The
+    /// environment only supplies defaults for a random soup, it never
+    /// conflicts with dividers or a pattern.
+    fn soup(&self, density: f64) -> Result<Initializer, String> {
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => env_parse::<u64>("--seed")?.unwrap_or_else(time_seed),
+        };
+        Ok(match (self.noise, self.clusters) {
+            (Some(scale), _) => Initializer::Noise {
+                scale,
+                threshold: self.threshold.unwrap_or(0.0),
+                seed,
+            },
+            (_, Some(count)) => Initializer::Clusters {
+                count,
+                radius: self.radius.unwrap_or(DEFAULT_RADIUS),
+                seed,
+            },
+            (None, None) => Initializer::Random { density, seed },
+        })
+    }
+}
+
+/// The width and height of the board and the dividers, if given.
+type Board = (u32, u32, Option<(u32, u32)>);
+
+/// Read the board from the positional arguments.
+fn parse_board(positional: &[String]) -> Result<Board, String> {
+    if positional.len() != 2 && positional.len() != 4 {
+        return Err(format!("expected 2 or 4 arguments\n\n{}", USAGE));
+    }
+    let width = parse_value::<u32>("width", &positional[0])?;
+    let height = parse_value::<u32>("height", &positional[1])?;
+    if width == 0 || height == 0 {
+        return Err(String::from("the universe must be at least 1x1"));
+    }
+    let dividers = match &positional[2..] {
+        [a, b] => match (
+            parse_value::<u32>("first divider", a)?,
+            parse_value::<u32>("second divider", b)?,
+        ) {
+            (0, _) | (_, 0) => return Err(String::from("dividers must be at least 1")),
+            dividers => Some(dividers),
+        },
+        _ => None,
+    };
+    Ok((width, height, dividers))
+}
+
+/// Refuse options that make no sense together, or without the others they
+/// need.
+fn check(
+    backend: &Backend,
+    view: &View,
+    settings: &Settings,
+    start: &Start,
+    dividers: bool,
+) -> Result<(), String> {
+    let threads = backend.threads();
+    let mapped = backend.mapped.is_some();
+    if view.heatmap_image.is_some() && view.heatmap.is_none() {
+        return Err("--heatmap-image only works with --heatmap".to_string());
+    }
+    match backend.threads {
+        Some(_) if !backend.headless => {
+            return Err("--threads only works with --headless".to_string())
+        }
+        Some(0) => return Err("--threads must be at least 1".to_string()),
+        _ => {}
+    }
+    if mapped && !backend.headless {
+        return Err("--mapped only works with --headless".to_string());
+    }
+    if mapped && threads > 1 {
+        return Err("--mapped and --threads cannot be combined".to_string());
+    }
+    if backend.sparse && !backend.headless {
+        return Err("--sparse only works with --headless".to_string());
+    }
+    if backend.sparse && (threads > 1 || mapped) {
+        return Err("--sparse cannot be combined with --threads or --mapped".to_string());
+    }
+    if backend.fold_symmetric && (threads > 1 || mapped || backend.sparse) {
+        return Err(
+            "--fold-symmetric cannot be combined with --threads, --mapped or --sparse".to_string(),
+        );
+    }
+    if backend.gpu && !backend.headless {
+        return Err("--gpu only works with --headless".to_string());
+    }
+    if backend.gpu && (threads > 1 || mapped || backend.sparse || backend.fold_symmetric) {
+        return Err(
+            "--gpu cannot be combined with --threads, --mapped, --sparse or --fold-symmetric"
+                .to_string(),
+        );
+    }
+    if view.quiet && view.split.is_some() {
+        return Err("--quiet and --split cannot be combined".to_string());
+    }
+    if view.record.is_some() && (backend.headless || view.split.is_some()) {
+        return Err("--record only works with a single live session".to_string());
+    }
+    if view.series.is_some() && (backend.headless || view.split.is_some()) {
+        return Err("--series only works with a single live session".to_string());
+    }
+    let elsewhere = threads > 1 || backend.gpu || mapped || backend.sparse;
+    let others = elsewhere || view.split.is_some();
+    if settings.when_still != WhenStill::Keep && others {
+        return Err(
+            "--when-still cannot be combined with --threads, --gpu, --mapped, --sparse or --split"
+                .to_string(),
+        );
+    }
+    if settings.records && elsewhere {
+        return Err(
+            "--records cannot be combined with --threads, --gpu, --mapped or --sparse".to_string(),
+        );
+    }
+    if (settings.stop_on_cycle || settings.track_motion) && others {
+        return Err(
+            "--stop-on-cycle and --spaceships cannot be combined with --threads, \
+                    --gpu, --mapped, --sparse or --split"
+                .to_string(),
+        );
+    }
+    check_start(start)?;
+
+    let generated = start.generated();
+    if let Some(flag) = backend.storage() {
+        let (census, state_hash, print_bits) = view.report;
+        if census || state_hash || print_bits.is_some() {
+            return Err(format!(
+                "{} cannot be combined with --census, --state-hash or --print-bits",
+                flag
+            ));
+        }
+        if dividers || generated {
+            return Err(format!(
+                "{} cannot be combined with dividers, --random, --density, --noise, --clusters or --seed",
+                flag
+            ));
+        }
+        if start.sources == 0 {
+            return Err(format!(
+                "{} requires --pattern, --preset, --text, --hex, --base64 or --image",
+                flag
+            ));
+        }
+    }
+    if backend.headless && backend.generations.is_none() {
+        return Err(String::from("--headless requires --generations <count>"));
+    }
+    if dividers && (generated || start.empty || start.sources > 0) {
+        return Err(String::from(
+            "dividers cannot be combined with a pattern, --empty, --random, --density, --noise, --clusters or --seed",
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse ways of starting the board that make no sense together.
+fn check_start(start: &Start) -> Result<(), String> {
+    if start.font.is_some() && !start.text {
+        return Err(String::from("--font requires --text"));
+    }
+    if start.row_width.is_some() && start.bits.is_none() {
+        return Err(String::from("--row-width requires --hex or --base64"));
+    }
+    let image = start.image.is_some();
+    if (start.dither.is_some() || start.image_width.is_some() || start.invert) && !image {
+        return Err(String::from(
+            "--dither, --image-width and --invert require --image",
+        ));
+    }
+    if start.image_width == Some(0) {
+        return Err(String::from("--image-width must be at least 1"));
+    }
+    if start.sources > 1 {
+        return Err(String::from(
+            "use only one of --pattern, --preset, --text, --hex, --base64 or --image",
+        ));
+    }
+    if start.daily && start.seed.is_some() {
+        return Err(String::from("use either --seed or --daily"));
+    }
+    if start
+        .density
+        .is_some_and(|density| !(0.0..=1.0).contains(&density))
+    {
+        return Err(String::from("--density must be between 0 and 1"));
+    }
+    if start
+        .noise
+        .is_some_and(|scale| !(scale > 0.0 && scale.is_finite()))
+    {
+        return Err(String::from("--noise must be a positive number of cells"));
+    }
+    if start
+        .threshold
+        .is_some_and(|threshold| !(-1.0..=1.0).contains(&threshold))
+    {
+        return Err(String::from("--threshold must be between -1 and 1"));
+    }
+    if start.threshold.is_some() && start.noise.is_none() {
+        return Err(String::from("--threshold requires --noise"));
+    }
+    if start.radius.is_some() && start.clusters.is_none() {
+        return Err(String::from("--radius requires --clusters"));
+    }
+    if start.soups().iter().filter(|&&given| given).count() > 1 {
+        return Err(String::from(
+            "use only one of --random or --density, --noise or --clusters",
+        ));
+    }
+    if start.empty && start.generated() {
+        return Err(String::from(
+            "--empty cannot be combined with --random, --density, --noise, --clusters or --seed",
+        ));
+    }
+    Ok(())
+}
+
+/// The seed of the soup of the day on `date`, made of the date itself, and
+/// the line announcing it.
+fn soup_of_the_day((year, month, day): (i64, u32, u32)) -> (u64, String) {
+    let announcement = format!(
+        "soup of the day {:04}-{:02}-{:02}, --seed {:04}{:02}{:02}",
+        year, month, day, year, month, day
+    );
+    (
+        year as u64 * 10000 + month as u64 * 100 + day as u64,
+        announcement,
+    )
+}
+
+/// Fill a board of `size` from the dividers, the pattern or a soup, set up
+/// to be stepped as asked, and offer the patterns to be stamped.
+fn build_universe(
+    (width, height): (u32, u32),
+    dividers: Option<(u32, u32)>,
+    mut start: Start,
+    pattern: Option<Placed>,
+    backend: &Backend,
+    settings: &mut Settings,
+) -> Result<Universe, String> {
+    let initializer = match dividers {
+        Some((a, b)) => Initializer::Divisors { a, b },
+        None if start.empty || (pattern.is_some() && !start.generated()) => Initializer::Empty,
+        None => start.soup(settings.density)?,
+    };
+    let mut universe = Universe::from_initializer(width, height, &initializer)
+        .map_err(|error| error.to_string())?;
+
+    // Stamps offer the patterns given on the command line first, then the
+    // presets and the catalog.
+    settings.stamps = start.stamp.take().into_iter().collect();
+    if let Some((pattern, _)) = &pattern {
+        settings.stamps.push(pattern.clone());
+    }
+    settings.stamps.extend(PRESETS.iter().map(Preset::pattern));
+    settings.stamps.append(&mut start.catalog);
+
+    // A rule given on the command line wins over the one stored in the file.
+    let mut rule = backend.rule;
+    if let Some((pattern, position)) = pattern {
+        match position {
+            Some((row, col)) => universe.insert_pattern(&pattern, row, col),
+            None => {
+                universe
+                    .place_centered(&pattern)
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+        if rule.is_none() {
+            rule = pattern
+                .rule
+                .as_deref()
+                .and_then(|rule| Rule::parse(rule).ok());
+        }
+    }
+    backend.configure(&mut universe, rule.unwrap_or_default());
+    Ok(universe)
+}
+
+/// Run `generations` ticks of a universe kept in a mapped file or as runs
+/// of live cells, as `backend` asks, starting from `pattern`.
+fn run_stored(
+    backend: &Backend,
+    (width, height): (u32, u32),
+    (pattern, position): Placed,
+    generations: u64,
+    output: Option<&str>,
+) -> Result<(), String> {
+    let (row, col) = position.unwrap_or((
+        height.saturating_sub(pattern.height()) / 2,
+        width.saturating_sub(pattern.width()) / 2,
+    ));
+    let rule = backend
+        .rule
+        .or_else(|| {
+            pattern
+                .rule
+                .as_deref()
+                .and_then(|rule| Rule::parse(rule).ok())
+        })
+        .unwrap_or_default();
+    let placed = (&pattern, row, col);
+    match &backend.mapped {
+        Some(path) => run_mapped(
+            path,
+            (width, height),
+            placed,
+            (rule, backend.engine),
+            backend.boundary,
+            generations,
+            output,
+        ),
+        None => run_sparse(
+            (width, height),
+            placed,
+            rule,
+            backend.boundary,
+            generations,
+            output,
+        ),
+    }
+}
+
+/// Play `universe` side by side with one filled in from the dividers
+/// `split`.
+fn run_split(
+    universe: Universe,
+    (a, b): (u32, u32),
+    backend: &Backend,
+    view: &View,
+    settings: &Settings,
+) -> Result<Universe, String> {
+    let divisors = Initializer::Divisors { a, b };
+    let mut right = Universe::from_initializer(universe.width(), universe.height(), &divisors)
+        .map_err(|error| error.to_string())?;
+    backend.configure(&mut right, universe.rule());
+    let mut renderer = SplitRenderer::new(view.themed());
+    let (universe, _) = block_on(play_split(universe, right, &mut renderer, settings))
+        .map_err(|error| error.to_string())?;
+    Ok(universe)
+}
+
+/// Play `universe` in the terminal, drawn the way `view` asks.
+fn run_live(universe: Universe, view: View, settings: &Settings) -> Result<Universe, String> {
+    let themed = view.themed();
+    let (charset, color) = (view.charset, view.color);
+    let recording_charset = charset.clone();
+    let mut renderer: Box<dyn Renderer> = match (view.heatmap, view.trails, view.follow) {
+        _ if view.quiet => Box::new(StatsRenderer::new(view.every)),
+        (Some(window), _, _) => {
+            let mut heatmap = HeatmapRenderer::new(window, charset, color);
+            if let Some(path) = view.heatmap_image {
+                heatmap.save_image(path);
+            }
+            Box::new(heatmap)
+        }
+        (None, Some(length), _) => Box::new(TrailsRenderer::new(length, charset, color)),
+        (None, None, Some(camera)) => Box::new(FollowRenderer::new(themed, camera)),
+        (None, None, None) if view.diff => Box::new(DiffRenderer::new(themed)),
+        (None, None, None) => Box::new(TerminalRenderer::new(themed)),
+    };
+    if let Some(path) = view.record {
+        let recorder = Recorder::create(&path, recording_charset)
+            .map_err(|error| format!("{}: {}", path, error))?;
+        renderer = Box::new(RecordingRenderer::new(renderer, recorder));
+    }
+    if let Some(path) = view.series {
+        renderer = Box::new(
+            SeriesRenderer::create(renderer, &path)
+                .map_err(|error| format!("{}: {}", path, error))?,
+        );
+    }
+    block_on(play(universe, renderer.as_mut(), settings)).map_err(|error| error.to_string())
+}
+
+/// Parse `--charset alive,dead`. Glyphs have to take up some room on the
+/// screen, which control characters and lone accents do not.
+fn parse_charset(value: &str) -> Result<Charset, String> {
+    match value.split_once(',') {
+        Some((alive, dead)) if !alive.is_empty() && !dead.is_empty() => {
+            let printable = |glyph: &str| !glyph.chars().any(char::is_control);
+            for glyph in [alive, dead] {
+                if !printable(glyph) || display_width(glyph) == 0 {
+                    return Err(format!(
+                        "--charset glyph '{}' takes no columns on the screen",
+                        glyph.escape_debug()
+                    ));
+                }
+            }
+            Ok(Charset::custom(alive, dead))
+        }
+        _ => Err(format!(
+            "--charset expects '<alive>,<dead>' glyphs, got '{}'",
+            value
+        )),
+    }
+}
+
+/// Parse `--engine`.
+pub(super) fn parse_engine(name: &str) -> Result<Engine, String> {
+    Engine::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown engine '{}', expected one of {}",
+            name,
+            Engine::NAMES.join(", ")
+        )
+    })
+}
+
+/// How many cells an image is drawn across: `image_width` wide if given,
+/// otherwise as large as fits the board, keeping the shape of the image
+/// either way.
+fn image_size(image: &Image, (width, height): (u32, u32), image_width: Option<u32>) -> (u32, u32) {
+    match image_width {
+        // As wide as asked, and as tall as that makes it.
+        Some(image_width) => image.fit(image_width, u32::MAX),
+        None => image.fit(width, height),
+    }
+}
+
+/// Split `name[@row,col]` into the path or preset name and the optional
+/// position.
+fn parse_placement(value: &str) -> Result<(String, Option<(u32, u32)>), String> {
+    match value.rsplit_once('@') {
+        Some((path, position)) if position.contains(',') => {
+            let position = parse_pair::<u32>("--pattern position", position, ',')?;
+            Ok((path.to_string(), Some(position)))
+        }
+        _ => Ok((value.to_string(), None)),
+    }
+}
+
+/// Load every pattern file in `dir`, named after the file unless it names
+/// itself, in file name order.
+fn load_catalog(dir: &str) -> Result<Vec<Pattern>, String> {
+    let entries = fs::read_dir(dir).map_err(|error| format!("{}: {}", dir, error))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| Format::from_path(path).is_some())
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let mut pattern =
+                Pattern::load(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+            if pattern.name.is_none() {
+                pattern.name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned());
+            }
+            Ok(pattern)
+        })
+        .collect()
+}
+
+/// The date in UTC as year, month and day.
+fn today() -> (i64, u32, u32) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    civil_from_days((seconds / 86_400) as i64)
+}
+
+/// The date `days` after 1970-01-01 in the proleptic Gregorian calendar,
+/// after Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months counted from March, so leap days fall at the end of the year.
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// A seed that differs between runs, for when none was given.
+pub(super) fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// Wrap up a run, counting the objects left if `census`, printing the
+/// state hash if `state_hash` and the cells as bits if `print_bits`, and
+/// saving the final state if an output path was requested.
+fn finish(
+    universe: &Universe,
+    path: Option<&str>,
+    (census, state_hash, print_bits): (bool, bool, Option<BitEncoding>),
+) -> Result<(), String> {
+    if census {
+        println!("census: {}", super::census(universe));
+    }
+    if state_hash {
+        println!("state hash: {:016x}", universe.state_hash());
+    }
+    if let Some(encoding) = print_bits {
+        println!(
+            "{} ({} cells wide): {}",
+            encoding.name(),
+            universe.width(),
+            universe.to_bit_string(encoding)
+        );
+    }
+    save_output(universe, path)
+}
+
+/// Save the final state of a run if an output path was requested.
+fn save_output(universe: &Universe, path: Option<&str>) -> Result<(), String> {
+    match path {
+        Some(path) => Pattern::from_universe(universe)
+            .save(path)
+            .map_err(|error| format!("{}: {}", path, error)),
+        None => Ok(()),
+    }
+}
+
+/// Simulate `generations` ticks as fast as possible without rendering, or
+/// fewer if interrupted, then print the final state and how long the
+/// simulation took, on `threads` threads or on the GPU.
+fn run_headless(
+    mut universe: Universe,
+    generations: u64,
+    (threads, gpu): (usize, bool),
+    settings: &Settings,
+    charset: &Charset,
+) -> Result<Universe, String> {
+    if let Some(announcement) = &settings.announcement {
+        println!("{}", announcement);
+    }
+    let started = Instant::now();
+    let mut simulated = generations;
+    let mut cycles = CycleDetector::new();
+    cycles.set_track_motion(settings.track_motion);
+    let mut records = settings.records.then(Records::new);
+    if gpu {
+        simulated = run_gpu(&mut universe, generations)?;
+    } else if threads > 1 {
+        let mut strips = Strips::new(&universe, threads)
+            .ok_or("--threads cannot be combined with a growing universe")?;
+        simulated = 0;
+        while simulated < generations && !interrupted() {
+            let batch = INTERRUPT_CHECK.min(generations - simulated);
+            strips.step(batch);
+            simulated += batch;
+        }
+        strips.gather(&mut universe);
+    } else {
+        let first = universe.snapshot();
+        cycles.observe(&universe);
+        if let Some(records) = &mut records {
+            records.observe(&universe);
+        }
+        for done in 1..=generations {
+            if interrupted() {
+                simulated = done - 1;
+                break;
+            }
+            universe.tick();
+            let cycle = cycles.observe(&universe);
+            if let Some(records) = &mut records {
+                records.observe(&universe);
+            }
+            if cycle.is_some() && settings.stop_on_cycle {
+                simulated = done;
+                break;
+            }
+            if !settled(&universe) {
+                continue;
+            }
+            match settings.when_still {
+                WhenStill::Keep => {}
+                WhenStill::Stop => {
+                    simulated = done;
+                    break;
+                }
+                WhenStill::Loop => universe.restore(&first),
+                WhenStill::Reseed => {
+                    universe.reset();
+                    universe.scatter(settings.density, time_seed());
+                }
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    let mut state = String::new();
+    charset
+        .write_grid(&mut state, &universe)
+        .expect("writing to a String cannot fail");
+    println!("{}", state);
+    println!("{}", ending(&universe, cycles.found()));
+    println!(
+        "{}",
+        throughput((universe.width(), universe.height()), simulated, elapsed)
+    );
+    if let Some(records) = records {
+        println!("records: {}", records);
+    }
+    Ok(universe)
+}
+
+/// Step `universe` on the GPU for `generations` ticks, or fewer if
+/// interrupted, returning how many it simulated.
+#[cfg(feature = "gpu")]
+fn run_gpu(universe: &mut Universe, generations: u64) -> Result<u64, String> {
+    let mut gpu = GpuUniverse::new(universe).map_err(|error| error.to_string())?;
+    println!("stepping on {}", gpu.adapter());
+    let mut simulated = 0;
+    while simulated < generations && !interrupted() {
+        let batch = INTERRUPT_CHECK.min(generations - simulated);
+        gpu.step(batch);
+        simulated += batch;
+    }
+    gpu.gather(universe);
+    Ok(simulated)
+}
+
+#[cfg(not(feature = "gpu"))]
+fn run_gpu(_: &mut Universe, _: u64) -> Result<u64, String> {
+    Err(String::from("--gpu needs a build with the gpu feature"))
+}
+
+/// Like `run_headless` for a universe kept in the file at `path`, starting
+/// from `pattern` placed at a row and column, and printing only the
+/// generation and population since the board may be far too large to draw.
+#[cfg(all(unix, target_pointer_width = "64"))]
+fn run_mapped(
+    path: &str,
+    (width, height): (u32, u32),
+    (pattern, row, col): (&Pattern, u32, u32),
+    (rule, engine): (Rule, Engine),
+    boundary: Boundary,
+    generations: u64,
+    output: Option<&str>,
+) -> Result<(), String> {
+    let mut universe = game_of_life::MappedUniverse::create(path, width, height)
+        .map_err(|error| format!("{}: {}", path, error))?;
+    universe.set_rule(rule);
+    universe.set_engine(engine);
+    universe.set_boundary(boundary);
+    universe.insert_pattern(pattern, row, col);
+
+    let started = Instant::now();
+    let mut simulated = 0;
+    while simulated < generations && !interrupted() {
+        universe.tick();
+        simulated += 1;
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "generation {}, population {}",
+        simulated,
+        universe.population()
+    );
+    println!(
+        "{}",
+        throughput((universe.width(), universe.height()), simulated, elapsed)
+    );
+    match output {
+        Some(path) => universe
+            .to_pattern()
+            .save(path)
+            .map_err(|error| format!("{}: {}", path, error)),
+        None => Ok(()),
+    }
+}
+
+/// Like `run_mapped` for a universe kept as runs of live cells.
+fn run_sparse(
+    (width, height): (u32, u32),
+    (pattern, row, col): (&Pattern, u32, u32),
+    rule: Rule,
+    boundary: Boundary,
+    generations: u64,
+    output: Option<&str>,
+) -> Result<(), String> {
+    let mut universe = SparseUniverse::new(width, height);
+    universe.set_rule(rule);
+    universe.set_boundary(boundary);
+    universe.insert_pattern(pattern, row, col);
+
+    let started = Instant::now();
+    let mut simulated = 0;
+    while simulated < generations && !interrupted() {
+        universe.tick();
+        simulated += 1;
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "generation {}, population {}",
+        simulated,
+        universe.population()
+    );
+    println!(
+        "{}",
+        throughput((universe.width(), universe.height()), simulated, elapsed)
+    );
+    match output {
+        Some(path) => universe
+            .to_pattern()
+            .save(path)
+            .map_err(|error| format!("{}: {}", path, error)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(all(unix, target_pointer_width = "64")))]
+fn run_mapped(
+    _: &str,
+    _: (u32, u32),
+    _: (&Pattern, u32, u32),
+    _: (Rule, Engine),
+    _: Boundary,
+    _: u64,
+    _: Option<&str>,
+) -> Result<(), String> {
+    Err(String::from("--mapped needs a 64-bit Unix system"))
+}
+
+/// Parse `--fps`, where `max` means no delay at all.
+pub(super) fn parse_fps(value: &str) -> Result<Option<Duration>, String> {
+    if value == "max" {
+        return Ok(None);
+    }
+    let fps = parse_value::<f64>("--fps", value)?;
+    if !(fps > 0.0 && fps.is_finite()) {
+        return Err(format!("--fps must be positive or max, got '{}'", value));
+    }
+    Ok(Some(Duration::from_secs_f64(1.0 / fps)))
+}
+
+/// Parse `--heatmap generations|all`, `None` standing for all of them.
+fn parse_window(value: &str) -> Result<Option<usize>, String> {
+    match value {
+        "all" => Ok(None),
+        _ => Ok(Some(parse_value::<usize>("--heatmap", value)?)),
+    }
+}
+
+/// Parse `--batch`, where `auto` fits the batch to the frame interval.
+fn parse_batch(value: &str) -> Result<Batch, String> {
+    if value == "auto" {
+        return Ok(Batch::Adaptive);
+    }
+    match parse_value::<u64>("--batch", value)? {
+        0 => Err(format!("--batch must be positive or auto, got '{}'", value)),
+        batch => Ok(Batch::Fixed(batch)),
+    }
+}
+
+/// Parse a duration such as `16ms`, `0.5s` or a bare number of milliseconds.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let (number, scale) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 0.001)
+    } else if let Some(s) = value.strip_suffix('s') {
+        (s, 1.0)
+    } else {
+        (value, 0.001)
+    };
+    let number = parse_value::<f64>("--interval", number)?;
+    if !(number >= 0.0 && number.is_finite()) {
+        return Err(format!("--interval must not be negative, got '{}'", value));
+    }
+    Ok(Duration::from_secs_f64(number * scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Edge;
+
+    #[test]
+    fn charsets_need_glyphs_that_show() {
+        let charset = parse_charset("🟩,.").unwrap();
+        assert_eq!((&*charset.alive, &*charset.dead), ("🟩", ". "));
+        for value in ["#", "#,", ",.", "\u{301},.", "#,\t", "\x1B,."] {
+            assert!(parse_charset(value).is_err(), "{:?}", value);
+        }
+    }
+
+    fn view(args: &[&str]) -> Result<View, String> {
+        let mut args = Args::new(args.iter().map(|arg| arg.to_string()).collect());
+        View::parse(&mut args, &PROFILES[0])
+    }
+
+    #[test]
+    fn ascii_replaces_the_detected_charset() {
+        assert_eq!(view(&["--ascii"]).unwrap().charset, Charset::ASCII);
+        assert!(view(&["--ascii", "--charset", "#,."]).is_err());
+    }
+
+    #[test]
+    fn batches_hold_at_least_one_generation() {
+        assert_eq!(parse_batch("auto"), Ok(Batch::Adaptive));
+        assert_eq!(parse_batch("1"), Ok(Batch::Fixed(1)));
+        assert_eq!(parse_batch("250"), Ok(Batch::Fixed(250)));
+        for value in ["0", "-1", "lots"] {
+            let error = parse_batch(value).unwrap_err();
+            assert!(error.contains("--batch"), "{}", error);
+        }
+    }
+
+    fn start(args: &[&str]) -> Result<Start, String> {
+        let mut args = Args::new(args.iter().map(|arg| arg.to_string()).collect());
+        let start = Start::parse(&mut args)?;
+        check_start(&start)?;
+        Ok(start)
+    }
+
+    #[test]
+    fn noise_soups_take_a_scale_and_threshold() {
+        let noise = start(&["--noise", "6", "--threshold", "0.25", "--seed", "3"]).unwrap();
+        assert_eq!(
+            noise.soup(0.5),
+            Ok(Initializer::Noise {
+                scale: 6.0,
+                threshold: 0.25,
+                seed: 3
+            })
+        );
+        let plain = start(&["--noise", "6", "--seed", "3"]).unwrap();
+        assert!(matches!(
+            plain.soup(0.5),
+            Ok(Initializer::Noise { threshold, .. }) if threshold == 0.0
+        ));
+        for args in [
+            &["--noise", "0"][..],
+            &["--noise", "inf"],
+            &["--noise", "6", "--threshold", "1.5"],
+            &["--threshold", "0.5"],
+            &["--noise", "6", "--density", "0.5"],
+        ] {
+            assert!(start(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn cluster_soups_take_a_count_and_radius() {
+        let clusters = start(&["--clusters", "5", "--seed", "3"]).unwrap();
+        assert_eq!(
+            clusters.soup(0.5),
+            Ok(Initializer::Clusters {
+                count: 5,
+                radius: DEFAULT_RADIUS,
+                seed: 3
+            })
+        );
+        let wide = start(&["--clusters", "5", "--radius", "12", "--seed", "3"]).unwrap();
+        assert!(matches!(
+            wide.soup(0.5),
+            Ok(Initializer::Clusters { radius: 12, .. })
+        ));
+        for args in [
+            &["--radius", "4"][..],
+            &["--clusters", "5", "--noise", "6"],
+            &["--clusters", "-1"],
+        ] {
+            assert!(start(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn days_are_counted_from_1970() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(31), (1970, 2, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(10_957), (2000, 1, 1));
+        // Leap days, also in a year divisible by 400 but not in one only
+        // divisible by 100.
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        assert_eq!(civil_from_days(20_740), (2026, 10, 14));
+    }
+
+    #[test]
+    fn today_falls_after_the_epoch() {
+        let (year, month, day) = today();
+        assert!(year >= 2024, "{}", year);
+        assert!((1..=12).contains(&month) && (1..=31).contains(&day));
+    }
+
+    #[test]
+    fn the_soup_of_the_day_is_seeded_with_the_date() {
+        let (seed, announcement) = soup_of_the_day((2026, 3, 7));
+        assert_eq!(seed, 20260307);
+        assert_eq!(announcement, "soup of the day 2026-03-07, --seed 20260307");
+        assert!(start(&["--daily"]).is_ok());
+        assert!(start(&["--daily", "--seed", "1"]).is_err());
+    }
+
+    fn settings(args: &[&str]) -> Settings {
+        let mut args = Args::new(args.iter().map(|arg| arg.to_string()).collect());
+        parse_settings(&mut args, &PROFILES[0], None).unwrap()
+    }
+
+    fn headless(universe: Universe, generations: u64, threads: usize, args: &[&str]) -> Universe {
+        let settings = settings(args);
+        run_headless(
+            universe,
+            generations,
+            (threads, false),
+            &settings,
+            &Charset::ASCII,
+        )
+        .unwrap()
+    }
+
+    fn blinker() -> Universe {
+        let mut universe = Universe::empty(5, 5);
+        universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+        universe
+    }
+
+    #[test]
+    fn headless_runs_simulate_every_generation() {
+        let universe = headless(blinker(), 5, 1, &[]);
+        assert_eq!(universe.generation(), 5);
+        assert_eq!(universe.live_bounds(), Some((1, 2, 3, 2)));
+
+        // Nothing stops a run early unless asked to.
+        let mut block = Universe::empty(4, 4);
+        block.set_cells(&[(1, 1), (1, 2), (2, 1), (2, 2)]);
+        assert_eq!(headless(block.clone(), 10, 1, &[]).generation(), 10);
+        let stopped = headless(block, 10, 1, &["--when-still", "stop"]);
+        assert_eq!(stopped.generation(), 1);
+        let cycled = headless(blinker(), 10, 1, &["--stop-on-cycle"]);
+        assert!(cycled.generation() < 10);
+    }
+
+    #[test]
+    fn headless_runs_start_over_once_settled() {
+        let mut lonely = Universe::empty(5, 5);
+        lonely.set_cells(&[(2, 2)]);
+        let looped = headless(lonely.clone(), 5, 1, &["--when-still", "loop"]);
+        assert_eq!(looped.population(), 1);
+        assert_eq!(headless(lonely, 5, 1, &[]).population(), 0);
+    }
+
+    #[test]
+    fn headless_runs_on_threads_agree_with_one() {
+        let soup = Universe::random(64, 48, 0.4, 7);
+        let alone = headless(soup.clone(), 30, 1, &[]);
+        let shared = headless(soup, 30, 3, &[]);
+        assert_eq!(shared.get_cells(), alone.get_cells());
+
+        let mut growing = blinker();
+        growing.set_boundary(Boundary {
+            x: Edge::Grow,
+            y: Edge::Grow,
+        });
+        let settings = settings(&[]);
+        let refused = run_headless(growing, 5, (2, false), &settings, &Charset::ASCII);
+        assert!(refused.is_err());
+    }
+}
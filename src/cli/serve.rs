@@ -0,0 +1,527 @@
+use game_of_life::input::{catch_interrupts, interrupted};
+use game_of_life::pattern::{preset, Format, Pattern};
+use game_of_life::{Boundary, Delta, Initializer, Rule, Universe};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::mem;
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::api;
+use super::control::{self, Address, Answer, Call, Command};
+use super::http::{respond, Request};
+use super::metrics::Metrics;
+use super::mqtt::Mqtt;
+use super::run::{parse_fps, time_seed, DEFAULT_DENSITY};
+use super::websocket::{self, Client, Opcode};
+use super::Args;
+
+pub const USAGE: &str = "\
+Usage: game_of_life serve [--ws <port>] [--http <port>] [--control <address>]
+                          [--mqtt <broker>] [options]
+
+Runs a universe without a terminal and serves it over the network. Each
+WebSocket client is sent the whole board when it connects and then the
+rows that changed every generation. Opening the same port in a browser
+shows a page that draws the frames it is sent.
+
+The HTTP port answers GET /state, /state.rle and /frame.png, and POST
+/tick?n=<count>, /cells?state=alive|dead|toggle with the cells listed as
+[[row,col],...] in the body, /pause and /resume. GET /metrics reports the
+population, births, deaths and how long ticks take for Prometheus.
+
+The control address takes one command per line and answers each with a
+line: pause, resume, step [<count>], toggle <row> <col>, load <pattern>
+[<row> <col>], clear, hash, status, help and quit.
+
+An MQTT broker is sent the generation, population, births and deaths as
+JSON on <topic>/stats, and with --mqtt-frames the board as RLE on
+<topic>/frame, both retained for dashboards that subscribe later.
+
+Options:
+  --ws <port>                 Stream the generations to WebSocket clients on
+                              this port
+  --http <port>               Serve the HTTP API on this port
+  --control <address>         Take commands on a port, host:port, or the path
+                              of a Unix socket
+  --mqtt <host[:port]>        Publish stats to this MQTT broker
+  --mqtt-topic <prefix>       Topic to publish under (default game_of_life)
+  --mqtt-every <generations>  How often to publish (default 10)
+  --mqtt-frames               Also publish the board as RLE
+  --paused                    Start paused, to be stepped or resumed with a
+                              command
+  --frames <format>           Frames as json text or packed binary (default
+                              json)
+  --bind <address>            Address to listen on (default 127.0.0.1)
+  --fps <rate>                Generations per second, or max (default 10)
+  --generations <count>       Stop after this many generations
+  --width <cells>             Width of the universe (default 128)
+  --height <cells>            Height of the universe (default 128)
+  --pattern <file>            Start from this pattern, centered, instead of a
+                              random soup
+  --preset <name>             Start from a built-in pattern such as acorn
+  --density <fraction>        Share of cells alive in a random soup (default
+                              0.35)
+  --seed <number>             Seed for the soup, for reproducible runs
+  --rule <rule>               Rulestring such as B36/S23, or a preset name
+  --wrap <edges>              Boundary: torus, dead, mirror or grow, or per axis
+                              as x=torus,y=dead (default torus)";
+
+/// The page handed to browsers, which connects back and draws the frames.
+const VIEWER: &str = include_str!("viewer.html");
+
+/// Topic published under unless told otherwise.
+const DEFAULT_TOPIC: &str = "game_of_life";
+
+/// Ten generations a second.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest the simulation spends on a step between looking at other
+/// commands and the clients, so long steps hold up nobody.
+const STEP_SLICE: Duration = Duration::from_millis(20);
+
+/// How long a client may hold up a frame before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How each generation is sent to WebSocket clients.
+#[derive(Clone, Copy)]
+enum Frames {
+    /// `Delta::to_json` in a text frame.
+    Json,
+    /// `Delta::to_bytes` in a binary frame.
+    Binary,
+}
+
+impl Frames {
+    fn send(self, client: &mut Client, delta: &Delta) -> io::Result<()> {
+        match self {
+            Frames::Json => client.send(Opcode::Text, delta.to_json().as_bytes()),
+            Frames::Binary => client.send(Opcode::Binary, &delta.to_bytes()),
+        }
+    }
+}
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let port = args.parse::<u16>("--ws")?;
+    let http = args.parse::<u16>("--http")?;
+    let control = args.value("--control")?;
+    let mqtt = args.value("--mqtt")?;
+    let topic = args
+        .value("--mqtt-topic")?
+        .unwrap_or_else(|| String::from(DEFAULT_TOPIC));
+    let every = args.parse::<u64>("--mqtt-every")?.unwrap_or(10).max(1);
+    let publish_frames = args.switch("--mqtt-frames");
+    let mut paused = args.switch("--paused");
+    let frames = match args.value("--frames")?.as_deref() {
+        None | Some("json") => Frames::Json,
+        Some("binary") => Frames::Binary,
+        Some(other) => {
+            return Err(format!(
+                "unknown frame format '{}', expected one of json, binary",
+                other
+            ))
+        }
+    };
+    let bind = args
+        .value("--bind")?
+        .unwrap_or_else(|| String::from("127.0.0.1"));
+    let interval = match args.value("--fps")? {
+        Some(fps) => parse_fps(&fps)?,
+        None => Some(DEFAULT_INTERVAL),
+    };
+    let generations = args.parse::<u64>("--generations")?;
+    let width = args.parse::<u32>("--width")?.unwrap_or(128);
+    let height = args.parse::<u32>("--height")?.unwrap_or(128);
+    let pattern = match (args.value("--pattern")?, args.value("--preset")?) {
+        (Some(_), Some(_)) => return Err(String::from("use either --pattern or --preset")),
+        (Some(path), None) => {
+            Some(Pattern::load(&path).map_err(|error| format!("{}: {}", path, error))?)
+        }
+        (None, Some(name)) => Some(
+            preset(&name)
+                .ok_or_else(|| format!("unknown preset '{}', see run --list-presets", name))?,
+        ),
+        (None, None) => None,
+    };
+    let density = args.parse::<f64>("--density")?.unwrap_or(DEFAULT_DENSITY);
+    if !(0.0..=1.0).contains(&density) {
+        return Err(String::from("--density must be between 0 and 1"));
+    }
+    let seed = args.parse::<u64>("--seed")?;
+    let rule = match args.value("--rule")? {
+        Some(rule) => Some(Rule::parse(&rule).map_err(|error| error.to_string())?),
+        None => None,
+    };
+    let boundary = match args.value("--wrap")? {
+        Some(wrap) => Boundary::parse(&wrap).map_err(|error| error.to_string())?,
+        None => Boundary::default(),
+    };
+    let positional = args.finish()?;
+    if !positional.is_empty() {
+        return Err(format!("unexpected argument '{}'", positional[0]));
+    }
+    if port.is_none() && http.is_none() && control.is_none() && mqtt.is_none() {
+        return Err(String::from(
+            "serve needs --ws <port>, --http <port>, --control <address> or --mqtt <broker>",
+        ));
+    }
+    let control = match control {
+        Some(control) => Some(Address::parse(&control, &bind)?),
+        None => None,
+    };
+    if width == 0 || height == 0 {
+        return Err(String::from("the universe must be at least 1x1"));
+    }
+
+    // A rule given on the command line wins over the one stored in the file.
+    let file_rule = pattern.as_ref().and_then(|pattern| pattern.rule.clone());
+    let initializer = match pattern {
+        Some(pattern) => Initializer::Pattern(pattern),
+        None => Initializer::Random {
+            density,
+            seed: seed.unwrap_or_else(time_seed),
+        },
+    };
+    let mut universe = Universe::from_initializer(width, height, &initializer)
+        .map_err(|error| error.to_string())?;
+    let rule = match (rule, file_rule) {
+        (Some(rule), _) => rule,
+        (None, Some(rule)) => Rule::parse(&rule).map_err(|error| error.to_string())?,
+        (None, None) => Rule::default(),
+    };
+    universe.set_rule(rule);
+    universe.set_boundary(boundary);
+
+    let arrivals = Arc::new(Mutex::new(Vec::new()));
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+    if let Some(port) = port {
+        let listener = TcpListener::bind((bind.as_str(), port))
+            .map_err(|error| format!("cannot listen on {}:{}: {}", bind, port, error))?;
+        let arrivals = Arc::clone(&arrivals);
+        thread::spawn(move || listen(listener, arrivals));
+        eprintln!(
+            "streaming on ws://{}:{}/, open http://{}:{}/ to watch",
+            bind, port, bind, port
+        );
+    }
+    let (calls, commands) = mpsc::channel();
+    if let Some(http) = http {
+        let listener = TcpListener::bind((bind.as_str(), http))
+            .map_err(|error| format!("cannot listen on {}:{}: {}", bind, http, error))?;
+        let calls = calls.clone();
+        let metrics = Arc::clone(&metrics);
+        thread::spawn(move || api::listen(listener, calls, metrics));
+        eprintln!("serving the HTTP API on http://{}:{}/", bind, http);
+    }
+    if let Some(address) = &control {
+        control::listen(address, calls)?;
+        match address {
+            Address::Tcp(address) => eprintln!("taking commands on {}", address),
+            Address::Unix(path) => eprintln!("taking commands on {}", path.display()),
+        }
+    }
+
+    let mut publisher = match mqtt {
+        Some(broker) => {
+            let client_id = format!("game_of_life-{}", process::id());
+            let mqtt = Mqtt::connect(&broker, &client_id)
+                .map_err(|error| format!("cannot connect to {}: {}", broker, error))?;
+            eprintln!("publishing to {} under {}/", broker, topic);
+            Some(Publisher {
+                mqtt,
+                topic,
+                every,
+                frames: publish_frames,
+                published: None,
+            })
+        }
+        None => None,
+    };
+
+    catch_interrupts();
+    let mut clients: Vec<Client> = Vec::new();
+    // Newcomers are sent the whole board, so the rows changed so far are
+    // of no use to anyone.
+    universe.take_delta();
+    let mut steps = VecDeque::new();
+    let mut next = Instant::now();
+    while !interrupted() && generations.is_none_or(|count| universe.generation() < count) {
+        answer_calls(&commands, &mut universe, &mut paused, &mut steps);
+        let joined = mem::take(&mut *arrivals.lock().expect("no listener panics"));
+        if !joined.is_empty() {
+            let full = universe.full_delta();
+            clients.extend(
+                joined
+                    .into_iter()
+                    .filter_map(|mut client| frames.send(&mut client, &full).ok().map(|_| client)),
+            );
+        }
+
+        if let Some(Err(error)) = publisher
+            .as_mut()
+            .map(|publisher| publisher.update(&universe))
+        {
+            eprintln!("mqtt: {}, no longer publishing", error);
+            publisher = None;
+        }
+
+        if let Some(step) = steps.front_mut() {
+            // Steps run as fast as they can, a slice at a time.
+            let start = Instant::now();
+            while step.left > 0 && start.elapsed() < STEP_SLICE {
+                tick(&mut universe, &metrics);
+                step.left -= 1;
+            }
+            if step.left == 0 {
+                let line = format!("ok generation {}", universe.generation());
+                // A client that hung up without waiting for the answer is
+                // no error.
+                let _ = step.reply.send(Ok(Answer::Line(line)));
+                steps.pop_front();
+            }
+        } else if !paused {
+            tick(&mut universe, &metrics);
+        }
+        // Paused boards are only sent out when a command changed them.
+        let delta = universe.take_delta();
+        if !paused || !delta.is_empty() {
+            let start = Instant::now();
+            clients.retain_mut(|client| frames.send(client, &delta).is_ok());
+            locked(&metrics).record_render(start.elapsed());
+        }
+        locked(&metrics).set_state(
+            universe.generation(),
+            universe.population(),
+            clients.len(),
+            paused,
+        );
+
+        match interval {
+            Some(interval) if !paused && steps.is_empty() => {
+                next += interval;
+                match next.checked_duration_since(Instant::now()) {
+                    Some(wait) => thread::sleep(wait),
+                    // Running behind: start counting again from now rather
+                    // than rushing to catch up.
+                    None => next = Instant::now(),
+                }
+            }
+            _ => next = Instant::now(),
+        }
+    }
+    if let Some(Address::Unix(path)) = &control {
+        // Left behind, the socket would keep the next server from binding.
+        let _ = fs::remove_file(path);
+    }
+    eprintln!(
+        "stopped at generation {}, population {}",
+        universe.generation(),
+        universe.population()
+    );
+    Ok(())
+}
+
+/// Sends the state of the universe to an MQTT broker now and then.
+struct Publisher {
+    mqtt: Mqtt,
+    topic: String,
+    every: u64,
+    /// Whether to send the board too.
+    frames: bool,
+    /// The generation last published.
+    published: Option<u64>,
+}
+
+impl Publisher {
+    /// Publish if the universe moved `every` generations since it was last
+    /// published, either way, as clearing it counts from 0 again.
+    fn update(&mut self, universe: &Universe) -> io::Result<()> {
+        let generation = universe.generation();
+        if self
+            .published
+            .is_some_and(|published| generation.abs_diff(published) < self.every)
+        {
+            return self.mqtt.keep_alive();
+        }
+        self.published = Some(generation);
+        let stats = format!(
+            "{{\"generation\":{},\"population\":{},\"births\":{},\"deaths\":{}}}",
+            generation,
+            universe.population(),
+            universe.births(),
+            universe.deaths()
+        );
+        let topic = &self.topic;
+        self.mqtt
+            .publish(&format!("{}/stats", topic), stats.as_bytes(), true)?;
+        if self.frames {
+            let rle = Pattern::from_universe(universe).write(Format::Rle);
+            self.mqtt
+                .publish(&format!("{}/frame", topic), rle.as_bytes(), true)?;
+        }
+        Ok(())
+    }
+}
+
+/// A step still being taken, answered once it is done.
+struct Step {
+    /// Generations still to tick.
+    left: u64,
+    reply: Sender<Result<Answer, String>>,
+}
+
+/// Carry out the commands waiting, or while paused the ones arriving in
+/// the next little while. Steps are only queued in `steps`, for the
+/// simulation to take a slice at a time.
+fn answer_calls(
+    commands: &Receiver<Call>,
+    universe: &mut Universe,
+    paused: &mut bool,
+    steps: &mut VecDeque<Step>,
+) {
+    let mut call = if *paused && steps.is_empty() {
+        match commands.recv_timeout(DEFAULT_INTERVAL) {
+            Ok(call) => Some(call),
+            Err(RecvTimeoutError::Timeout) => None,
+            // Nothing can resume the run any more.
+            Err(RecvTimeoutError::Disconnected) => {
+                *paused = false;
+                None
+            }
+        }
+    } else {
+        commands.try_recv().ok()
+    };
+    while let Some(Call { command, reply }) = call {
+        match command {
+            Command::Step(left) => steps.push_back(Step { left, reply }),
+            // A client that hung up without waiting for the answer is no
+            // error.
+            command => {
+                let _ = reply.send(carry_out(command, universe, paused));
+            }
+        }
+        call = commands.try_recv().ok();
+    }
+}
+
+fn carry_out(
+    command: Command,
+    universe: &mut Universe,
+    paused: &mut bool,
+) -> Result<Answer, String> {
+    let (width, height) = (universe.width(), universe.height());
+    let line = match command {
+        Command::Pause => {
+            *paused = true;
+            String::from("ok")
+        }
+        Command::Resume => {
+            *paused = false;
+            String::from("ok")
+        }
+        Command::Step(_) => unreachable!("steps are taken by the simulation loop"),
+        Command::Cells(cells, cell) => {
+            if let Some(&(row, column)) = cells
+                .iter()
+                .find(|&&(row, column)| row >= height || column >= width)
+            {
+                return Err(format!(
+                    "cell {},{} is outside the {}x{} universe",
+                    row, column, width, height
+                ));
+            }
+            for &(row, column) in &cells {
+                match cell {
+                    Some(cell) => universe.set_cell(row, column, cell),
+                    None => universe.toggle_cell(row, column),
+                }
+            }
+            String::from("ok")
+        }
+        Command::Load(pattern, Some((row, column))) => {
+            if row >= height || column >= width {
+                return Err(format!(
+                    "{},{} is outside the {}x{} universe",
+                    row, column, width, height
+                ));
+            }
+            universe.insert_pattern(&pattern, row, column);
+            String::from("ok")
+        }
+        Command::Load(pattern, None) => {
+            let (row, column) = universe
+                .place_centered(&pattern)
+                .map_err(|error| error.to_string())?;
+            format!("ok at {},{}", row, column)
+        }
+        Command::Clear => {
+            universe.reset();
+            String::from("ok")
+        }
+        Command::Hash => format!("ok {:016x}", universe.state_hash()),
+        Command::Status => format!(
+            "ok generation {} population {} {}",
+            universe.generation(),
+            universe.population(),
+            if *paused { "paused" } else { "running" }
+        ),
+        Command::Copy => return Ok(Answer::Universe(Box::new(universe.clone()))),
+    };
+    Ok(Answer::Line(line))
+}
+
+/// Advance a generation, timing it.
+fn tick(universe: &mut Universe, metrics: &Mutex<Metrics>) {
+    let start = Instant::now();
+    universe.tick();
+    let duration = start.elapsed();
+    locked(metrics).record_tick(duration, universe.births(), universe.deaths());
+}
+
+fn locked(metrics: &Mutex<Metrics>) -> MutexGuard<'_, Metrics> {
+    metrics.lock().expect("no metrics reader panics")
+}
+
+/// Take connections for as long as the server runs, each answered on its
+/// own thread so a slow one holds up no other. WebSocket clients are left
+/// in `arrivals` for the simulation to pick up.
+fn listen(listener: TcpListener, arrivals: Arc<Mutex<Vec<Client>>>) {
+    for stream in listener.incoming().flatten() {
+        let arrivals = Arc::clone(&arrivals);
+        thread::spawn(move || {
+            // Connections that break off or do not speak HTTP are dropped.
+            let _ = answer(stream, &arrivals);
+        });
+    }
+}
+
+fn answer(mut stream: TcpStream, arrivals: &Mutex<Vec<Client>>) -> io::Result<()> {
+    stream.set_read_timeout(Some(WRITE_TIMEOUT))?;
+    let request = Request::read(&stream)?;
+    if websocket::is_upgrade(&request) {
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let client = websocket::accept(stream, &request)?;
+        arrivals.lock().expect("no listener panics").push(client);
+        return Ok(());
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => respond(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            VIEWER.as_bytes(),
+        ),
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"not found\n"),
+    }
+}
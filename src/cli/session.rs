@@ -0,0 +1,1271 @@
+use game_of_life::input::{self, Key, KeyRecorder, Mouse, MouseAction, RawMode};
+use game_of_life::pattern::{Format, Pattern};
+use game_of_life::render::{write_frame, Renderer, SplitRenderer, Status};
+use game_of_life::rule::PRESETS as RULES;
+use game_of_life::{
+    Cell, Cycle, CycleDetector, EditLog, History, Records, Rng, Rule, Snapshot, Universe,
+};
+use settimeout::set_timeout;
+use std::fs;
+use std::io;
+use std::mem;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::browser::{Browser, CLEAR_SCREEN};
+use super::help;
+use super::parse_value;
+use super::run::{parse_fps, time_seed};
+use super::simulation::{settled, Batch, Pace, Progress, Simulation};
+
+/// Range the speed keys adjust the frame interval within. Going faster than
+/// the minimum removes the delay altogether.
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+const MAX_INTERVAL: Duration = Duration::from_secs(4);
+
+/// How often frames are still drawn at max speed.
+const MAX_SPEED_FRAME: Duration = Duration::from_millis(33);
+
+/// What to do once the universe dies out or stops changing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WhenStill {
+    /// Carry on simulating the frozen board.
+    #[default]
+    Keep,
+    Stop,
+    /// Go back to the board the run started from.
+    Loop,
+    /// Start over from a fresh random soup.
+    Reseed,
+}
+
+impl WhenStill {
+    pub const NAMES: [&'static str; 4] = ["keep", "stop", "loop", "reseed"];
+
+    pub fn from_name(name: &str) -> Option<WhenStill> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "keep" => Some(WhenStill::Keep),
+            "stop" => Some(WhenStill::Stop),
+            "loop" => Some(WhenStill::Loop),
+            "reseed" => Some(WhenStill::Reseed),
+            _ => None,
+        }
+    }
+}
+
+/// How a rendered session is paced and presented.
+pub struct Settings {
+    pub show_hud: bool,
+    /// Draw the recent population as a sparkline on the HUD.
+    pub sparkline: bool,
+    /// Wait for the space bar before simulating the first generation.
+    pub start_paused: bool,
+    /// Time between frames, or `None` to run as fast as possible.
+    pub interval: Option<Duration>,
+    /// Generations simulated for every frame drawn.
+    pub batch: Batch,
+    /// Stop once this many generations have been simulated.
+    pub generations: Option<u64>,
+    /// Past generations kept so a paused session can step back through them.
+    pub history: usize,
+    /// Patterns that can be stamped in edit mode, in the order `t` picks them.
+    pub stamps: Vec<Pattern>,
+    /// Share of live cells in the soups `r` starts over with.
+    pub density: f64,
+    /// Draw every batch, holding the simulation back if need be, rather
+    /// than only the latest one when drawing falls behind.
+    pub every_batch: bool,
+    pub when_still: WhenStill,
+    /// Stop once the universe is found to repeat itself.
+    pub stop_on_cycle: bool,
+    /// Look for the live cells repeating somewhere else too, as a spaceship
+    /// does.
+    pub track_motion: bool,
+    /// Keep track of the records set over the run.
+    pub records: bool,
+    /// Shown on the HUD as the session starts, such as the date of the soup
+    /// of the day.
+    pub announcement: Option<String>,
+    /// Where to record the keys pressed, for replaying them later.
+    pub record_keys: Option<String>,
+    /// Keys recorded earlier to press again, at the times they were pressed.
+    pub replay_keys: Vec<(Duration, Key)>,
+    /// Format of the files written by `s`.
+    pub save_format: Format,
+}
+
+impl Settings {
+    /// How many of `count` generations can be simulated from `generation`
+    /// without overshooting `generations`.
+    fn limit(&self, count: u64, generation: u64) -> u64 {
+        match self.generations {
+            Some(limit) => count.min(limit.saturating_sub(generation)),
+            None => count,
+        }
+    }
+
+    fn status(&self, universe: &Universe) -> Status {
+        let mut status = Status::new(universe, self.show_hud);
+        status.paused = self.start_paused;
+        status.interval = self.interval;
+        status.show_sparkline = self.sparkline;
+        status.message = self.announcement.clone();
+        status.records = self.records.then(|| {
+            let mut records = Records::new();
+            records.observe(universe);
+            records
+        });
+        status
+    }
+}
+
+/// An interactive session on a single universe.
+struct Session<'a> {
+    universe: Universe,
+    /// The board the session started from, for `--when-still loop`.
+    first: Snapshot,
+    /// Set once the universe settled for good, ending the session after
+    /// one last frame.
+    finished: bool,
+    renderer: &'a mut dyn Renderer,
+    settings: &'a Settings,
+    status: Status,
+    /// Where the edit cursor was last, kept while not editing.
+    cursor: (u32, u32),
+    /// Generations to step through before the next frame while paused.
+    steps: u64,
+    /// What dragging the mouse draws, set by the click that started it.
+    paint: Option<Cell>,
+    history: History,
+    /// Watches the generations simulated for the universe repeating itself.
+    cycles: CycleDetector,
+    /// Cells changed by hand, for undo and redo.
+    edits: EditLog,
+    /// The picked stamp, as an index into `Settings::stamps` or `None` for
+    /// the clipboard, and the pattern turned and flipped as requested.
+    stamp: Option<(Option<usize>, Pattern)>,
+    brush: Brush,
+    /// The corner a selection started from, opposite the edit cursor.
+    anchor: Option<(u32, u32)>,
+    /// Cells last copied or cut from a selection.
+    clipboard: Option<Pattern>,
+    /// The pattern browser, shown instead of the board while open.
+    browser: Option<Browser>,
+    /// Whether the help screen is shown instead of the board.
+    help: bool,
+    /// The question being answered, with the answer typed so far.
+    prompt: Option<(Prompt, String)>,
+    recorder: Option<KeyRecorder>,
+    /// `m` or `'` when the next digit names a bookmark to save or go to.
+    mark: Option<char>,
+    /// States saved with `m1` to `m9`: the cells, which know their
+    /// generation, and the rule.
+    bookmarks: [Option<(Snapshot, Rule)>; 9],
+    /// The thread simulating the universe while the session runs, which
+    /// `universe` is kept in step with frame by frame.
+    simulation: Option<Simulation>,
+}
+
+/// The cells set around the cursor or mouse when drawing by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Brush {
+    Cell,
+    /// A 3x3 square.
+    Square,
+    /// A disc with the given radius in cells.
+    Circle(u32),
+}
+
+impl Brush {
+    /// The brush picked with a number key: 1 for single cells, 2 for the
+    /// square and 3 to 9 for ever larger circles.
+    fn from_digit(digit: u32) -> Option<Brush> {
+        match digit {
+            1 => Some(Brush::Cell),
+            2 => Some(Brush::Square),
+            3..=9 => Some(Brush::Circle(digit - 1)),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> Option<String> {
+        match self {
+            Brush::Cell => None,
+            Brush::Square => Some("brush 3x3".to_string()),
+            Brush::Circle(radius) => Some(format!("brush radius {}", radius)),
+        }
+    }
+
+    /// The cells covered when the brush is centered on `row` and `col` of
+    /// `universe`.
+    fn cells(self, universe: &Universe, row: u32, col: u32) -> Vec<(u32, u32)> {
+        let (reach, radius) = match self {
+            Brush::Cell => return vec![(row, col)],
+            Brush::Square => (1, None),
+            Brush::Circle(radius) => (radius, Some(radius)),
+        };
+        let rows = row.saturating_sub(reach)..=(row + reach).min(universe.height() - 1);
+        let cols = col.saturating_sub(reach)..=(col + reach).min(universe.width() - 1);
+        rows.flat_map(|r| cols.clone().map(move |c| (r, c)))
+            .filter(|&(r, c)| {
+                radius.is_none_or(|radius| {
+                    let (dr, dc) = (r.abs_diff(row), c.abs_diff(col));
+                    dr * dr + dc * dc <= radius * radius
+                })
+            })
+            .collect()
+    }
+}
+
+/// A question asked on the HUD line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Prompt {
+    /// Which generation to jump to.
+    Goto,
+    /// A command such as `rule B36/S23`, typed after `:`.
+    Command,
+    /// The density and optional seed to fill the selection with.
+    Fill,
+}
+
+impl Prompt {
+    fn question(self) -> &'static str {
+        match self {
+            Prompt::Goto => "go to generation: ",
+            Prompt::Command => ":",
+            Prompt::Fill => "fill with density and seed: ",
+        }
+    }
+}
+
+/// Run `universe` until the user quits or the generation limit is reached,
+/// drawing every frame with `renderer`.
+pub async fn play(
+    universe: Universe,
+    renderer: &mut dyn Renderer,
+    settings: &Settings,
+) -> io::Result<Universe> {
+    let mut raw_mode = RawMode::enable();
+    if let Some(raw_mode) = &mut raw_mode {
+        raw_mode.capture_mouse();
+    }
+    let keys = match settings.replay_keys[..] {
+        [] => input::spawn_reader(),
+        _ => input::spawn_replay(settings.replay_keys.clone()),
+    };
+    let recorder = match &settings.record_keys {
+        Some(path) => Some(KeyRecorder::create(path)?),
+        None => None,
+    };
+    let mut cycles = CycleDetector::new();
+    cycles.set_track_motion(settings.track_motion);
+    let session = Session {
+        status: settings.status(&universe),
+        first: universe.snapshot(),
+        finished: false,
+        cursor: (universe.height() / 2, universe.width() / 2),
+        universe,
+        renderer,
+        settings,
+        steps: 0,
+        paint: None,
+        history: History::new(settings.history),
+        cycles,
+        edits: EditLog::new(),
+        stamp: None,
+        brush: Brush::Cell,
+        anchor: None,
+        clipboard: None,
+        browser: None,
+        help: false,
+        prompt: None,
+        recorder,
+        mark: None,
+        bookmarks: Default::default(),
+        simulation: None,
+    };
+    let (universe, summary) = session.run(&keys).await?;
+    drop(raw_mode);
+    if let Some(summary) = summary {
+        println!("{}", summary);
+    }
+    Ok(universe)
+}
+
+impl Session<'_> {
+    /// Run until the session ends, returning the universe and the summary to
+    /// print once the terminal is back to normal.
+    async fn run(mut self, keys: &Receiver<Key>) -> io::Result<(Universe, Option<String>)> {
+        self.renderer.init(&self.universe)?;
+        if self.status.paused {
+            self.renderer.draw(&self.universe, &self.status)?;
+        }
+
+        'session: while !self.finished && self.settings.generations != Some(self.status.generation)
+        {
+            let pressed = next_keys(keys, self.status.paused);
+            if !pressed.is_empty() {
+                // Keys act on the universe, so it has to hold still.
+                self.stop_simulating();
+            }
+            for key in pressed {
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.record(key)?;
+                }
+                self.status.message = None;
+                let mark = self.mark.take();
+                let running = match (key, self.status.cursor) {
+                    (Key::Mouse(mouse), _) => {
+                        self.click(mouse);
+                        true
+                    }
+                    _ if self.help => {
+                        self.help = false;
+                        // The help screen drew over the board.
+                        self.renderer.invalidate();
+                        true
+                    }
+                    _ if self.browser.is_some() => self.browse(key),
+                    _ if self.prompt.is_some() => self.answer(key),
+                    (Key::Char(digit @ '1'..='9'), _) if mark.is_some() => {
+                        self.bookmark(mark == Some('m'), digit);
+                        true
+                    }
+                    (Key::Char(prefix @ ('m' | '\'')), _) => {
+                        self.mark = Some(prefix);
+                        true
+                    }
+                    (_, Some(cursor)) => self.edit(key, cursor),
+                    (_, None) => self.control(key),
+                };
+                if !running {
+                    break 'session;
+                }
+            }
+
+            if self.status.paused {
+                let steps = std::mem::take(&mut self.steps);
+                self.advance(steps);
+            } else if !self.keep_simulating() {
+                continue;
+            }
+
+            let screen = match &self.browser {
+                _ if self.help => Some(help::screen(&self.universe, &self.status, self.settings)),
+                Some(browser) => Some(browser.render(&self.settings.stamps)),
+                None => None,
+            };
+            if let Some(screen) = screen {
+                write_frame(&format!("{}{}", CLEAR_SCREEN, screen))?;
+                continue;
+            }
+            self.show_tool();
+            self.renderer.draw(&self.universe, &self.status)?;
+            self.universe.clear_changed_rows();
+        }
+
+        self.stop_simulating();
+        self.renderer.shutdown()?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.finish()?;
+        }
+        let summary = self.status.summary(&self.universe);
+        Ok((self.universe, summary))
+    }
+
+    /// React to a key outside edit mode, returning whether to keep going.
+    fn control(&mut self, key: Key) -> bool {
+        match key {
+            Key::Char('h') => self.status.show_hud = !self.status.show_hud,
+            Key::Char(' ') => self.toggle_pause(),
+            Key::Char('n' | '.') if self.status.paused => self.steps += 1,
+            Key::Char('N') if self.status.paused => self.steps += 10,
+            Key::Char(',') | Key::Left if self.status.paused => self.rewind(),
+            Key::Char('+' | '=' | ']') => faster(&mut self.status),
+            Key::Char('-' | '[') => slower(&mut self.status),
+            Key::Char('M') => self.status.max_speed = !self.status.max_speed,
+            Key::Char('u') => self.undo(),
+            Key::Ctrl('r') => self.redo(),
+            Key::Char('s') => self.save(),
+            Key::Char('g') => self.ask(Prompt::Goto),
+            Key::Char(':') => self.ask(Prompt::Command),
+            Key::Char('c') => self.start_over(None),
+            Key::Char('r') => self.start_over(Some(time_seed())),
+            Key::Char('R') => self.next_rule(),
+            Key::Char('e') => self.start_editing(),
+            Key::Char('p') => self.open_browser(),
+            Key::Char('?') => self.open_help(),
+            Key::Char('q') | Key::Interrupt => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn start_editing(&mut self) {
+        // Editing a moving board would be frustrating, so pause.
+        self.status.paused = true;
+        let (row, col) = self.cursor;
+        self.status.cursor = Some((
+            row.min(self.universe.height() - 1),
+            col.min(self.universe.width() - 1),
+        ));
+    }
+
+    /// React to a key in edit mode, returning whether to keep going.
+    fn edit(&mut self, key: Key, (row, col): (u32, u32)) -> bool {
+        let (height, width) = (self.universe.height(), self.universe.width());
+        let moved = match key {
+            Key::Up | Key::Char('k') => (row.saturating_sub(1), col),
+            Key::Down | Key::Char('j') => ((row + 1).min(height - 1), col),
+            Key::Left | Key::Char('h') => (row, col.saturating_sub(1)),
+            Key::Right | Key::Char('l') => (row, (col + 1).min(width - 1)),
+            Key::Enter | Key::Char(' ') => {
+                self.edits.begin();
+                match &self.stamp {
+                    Some((_, pattern)) => {
+                        self.edits
+                            .insert_pattern(&mut self.universe, pattern, row, col)
+                    }
+                    None => {
+                        let cell = self.universe.get_cell(row, col).toggled();
+                        self.draw_brush(row, col, cell);
+                    }
+                }
+                // Refresh the population shown in the HUD.
+                self.status.step(&self.universe);
+                (row, col)
+            }
+            Key::Char('n' | '.') => {
+                self.steps += 1;
+                (row, col)
+            }
+            Key::Char('N') => {
+                self.steps += 10;
+                (row, col)
+            }
+            Key::Char('t') => {
+                self.next_stamp();
+                (row, col)
+            }
+            Key::Char('p') => {
+                self.cursor = (row, col);
+                self.open_browser();
+                (row, col)
+            }
+            Key::Char('?') => {
+                self.open_help();
+                (row, col)
+            }
+            Key::Char(digit @ '1'..='9') => {
+                self.brush = digit
+                    .to_digit(10)
+                    .and_then(Brush::from_digit)
+                    .unwrap_or(self.brush);
+                // Drawing with a brush means stamping is over.
+                self.stamp = None;
+                (row, col)
+            }
+            Key::Char('v') => {
+                self.anchor = match self.anchor {
+                    Some(_) => None,
+                    None => Some((row, col)),
+                };
+                (row, col)
+            }
+            Key::Char('y') if self.anchor.is_some() => {
+                self.copy((row, col), false);
+                (row, col)
+            }
+            Key::Char('x') if self.anchor.is_some() => {
+                self.copy((row, col), true);
+                (row, col)
+            }
+            Key::Char('F') if self.anchor.is_some() => {
+                self.ask(Prompt::Fill);
+                (row, col)
+            }
+            Key::Char('P') => {
+                self.stamp = self.clipboard.clone().map(|pattern| (None, pattern));
+                (row, col)
+            }
+            Key::Escape if self.anchor.is_some() => {
+                self.anchor = None;
+                (row, col)
+            }
+            Key::Char('r') => {
+                if let Some((_, pattern)) = &mut self.stamp {
+                    *pattern = pattern.rotated();
+                }
+                (row, col)
+            }
+            Key::Char('f') => {
+                if let Some((_, pattern)) = &mut self.stamp {
+                    *pattern = pattern.flipped();
+                }
+                (row, col)
+            }
+            Key::Char('u') => {
+                self.undo();
+                (row, col)
+            }
+            Key::Char('s') => {
+                self.save();
+                (row, col)
+            }
+            Key::Ctrl('r') => {
+                self.redo();
+                (row, col)
+            }
+            Key::Char(',') => {
+                self.rewind();
+                (
+                    row.min(self.universe.height() - 1),
+                    col.min(self.universe.width() - 1),
+                )
+            }
+            Key::Char('e') | Key::Escape => {
+                self.anchor = None;
+                self.cursor = (row, col);
+                self.status.cursor = None;
+                return true;
+            }
+            Key::Char('q') | Key::Interrupt => return false,
+            _ => (row, col),
+        };
+        self.status.cursor = Some(moved);
+        true
+    }
+
+    /// Toggle the clicked cell, then paint cells the same way while the
+    /// mouse is dragged.
+    fn click(&mut self, mouse: Mouse) {
+        if mouse.action == MouseAction::Release {
+            self.paint = None;
+            return;
+        }
+        let (row, col) = match self
+            .renderer
+            .cell_at(&self.universe, mouse.row, mouse.column)
+        {
+            Some(cell) => cell,
+            None => return,
+        };
+
+        let cell = match (mouse.action, self.paint) {
+            (MouseAction::Drag, Some(cell)) => cell,
+            _ => {
+                // A whole stroke is undone at once.
+                self.edits.begin();
+                self.universe.get_cell(row, col).toggled()
+            }
+        };
+        self.draw_brush(row, col, cell);
+        self.paint = Some(cell);
+        if self.status.cursor.is_some() {
+            self.status.cursor = Some((row, col));
+        }
+        self.status.step(&self.universe);
+    }
+
+    /// Set the cells under the brush centered on `row` and `col`.
+    fn draw_brush(&mut self, row: u32, col: u32, cell: Cell) {
+        for (r, c) in self.brush.cells(&self.universe, row, col) {
+            self.edits.set_cell(&mut self.universe, r, c, cell);
+        }
+    }
+
+    /// Let the simulation thread run the universe, starting it if needed,
+    /// and take in what it simulated since the last frame. Returns whether
+    /// there is anything new to draw.
+    fn keep_simulating(&mut self) -> bool {
+        let simulation = self.simulation.get_or_insert_with(|| {
+            let pace = Pace {
+                interval: self.status.interval,
+                max_speed: self.status.max_speed,
+                batch: self.settings.batch,
+                limit: self.settings.generations,
+                every_batch: self.settings.every_batch,
+                until_settled: self.settings.when_still != WhenStill::Keep,
+                until_cycle: self.settings.stop_on_cycle,
+            };
+            let history = mem::replace(&mut self.history, History::new(0));
+            let cycles = mem::take(&mut self.cycles);
+            Simulation::start(
+                self.universe.clone(),
+                (history, cycles),
+                self.status.records,
+                pace,
+                MAX_SPEED_FRAME,
+            )
+        });
+        match simulation.receive(MAX_SPEED_FRAME) {
+            Ok(Some(progress)) => {
+                self.take_progress(progress);
+                true
+            }
+            Ok(None) => false,
+            // The generation limit was reached or the universe settled or
+            // cycled.
+            Err(_) => {
+                self.stop_simulating();
+                self.settle();
+                true
+            }
+        }
+    }
+
+    /// Stop the simulation thread, if it runs, catching up with the
+    /// generations it simulated and taking the history and cycle detector
+    /// back.
+    fn stop_simulating(&mut self) {
+        if let Some(simulation) = self.simulation.take() {
+            let (history, cycles, progress) = simulation.stop();
+            self.history = history;
+            self.cycles = cycles;
+            if let Some(progress) = progress {
+                self.note_cycle(progress.cycle);
+                self.status.records = progress.records;
+                // Cut short by the stop, so it says nothing about the pace.
+                self.universe.apply_delta(&progress.delta);
+                self.status.step(&self.universe);
+                self.status
+                    .simulated(&self.universe, progress.generations, progress.busy);
+            }
+        }
+    }
+
+    /// Act on the universe having died out, stopped changing or started
+    /// repeating itself, as the settings say.
+    fn settle(&mut self) {
+        let at_limit = self.settings.generations == Some(self.status.generation);
+        if at_limit {
+            return;
+        }
+        if !settled(&self.universe) || self.settings.when_still == WhenStill::Keep {
+            if let Some(cycle) = self.status.cycle.filter(|_| self.settings.stop_on_cycle) {
+                self.finished = true;
+                self.status.message = Some(format!("{}, stopped", cycle));
+            }
+            return;
+        }
+        let what = if self.universe.is_extinct() {
+            "died out"
+        } else {
+            "still"
+        };
+        let generation = self.status.generation;
+        let then = match self.settings.when_still {
+            WhenStill::Keep => return,
+            WhenStill::Stop => {
+                self.finished = true;
+                "stopped"
+            }
+            WhenStill::Loop => {
+                self.universe.restore(&self.first);
+                self.history.clear();
+                self.edits.clear();
+                self.status.step(&self.universe);
+                "starting over"
+            }
+            WhenStill::Reseed => {
+                self.start_over(Some(time_seed()));
+                "reseeded"
+            }
+        };
+        self.status.message = Some(format!("{} at generation {}, {}", what, generation, then));
+    }
+
+    /// Bring the universe up to date with generations simulated elsewhere.
+    fn take_progress(&mut self, progress: Progress) {
+        self.note_cycle(progress.cycle);
+        self.status.records = progress.records;
+        self.universe.apply_delta(&progress.delta);
+        let elapsed = progress.elapsed.as_secs_f64();
+        self.status
+            .advance_by(&self.universe, progress.generations, elapsed);
+        self.status
+            .simulated(&self.universe, progress.generations, progress.busy);
+    }
+
+    /// Say so the first time the universe is found to be in a cycle, and
+    /// keep track of it until the universe is changed by hand.
+    fn note_cycle(&mut self, cycle: Option<Cycle>) {
+        if let (None, Some(cycle)) = (self.status.cycle, cycle) {
+            self.status.message = Some(cycle.to_string());
+        }
+        self.status.cycle = cycle;
+    }
+
+    /// Simulate up to `steps` generations without drawing them.
+    fn advance(&mut self, steps: u64) {
+        let steps = self.settings.limit(steps, self.status.generation);
+        self.cycles.observe(&self.universe);
+        for _ in 0..steps {
+            self.history.push(&self.universe);
+            self.universe.tick();
+            self.cycles.observe(&self.universe);
+            if let Some(records) = &mut self.status.records {
+                records.observe(&self.universe);
+            }
+        }
+        self.note_cycle(self.cycles.found());
+        self.status.step(&self.universe);
+    }
+
+    /// Start asking `prompt` on the HUD line.
+    fn ask(&mut self, prompt: Prompt) {
+        self.status.prompt = Some(prompt.question().to_string());
+        self.prompt = Some((prompt, String::new()));
+    }
+
+    /// React to a key typed while answering a prompt, returning whether to
+    /// keep going.
+    fn answer(&mut self, key: Key) -> bool {
+        let (prompt, mut answer) = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return true,
+        };
+        match key {
+            Key::Enter => {
+                self.status.prompt = None;
+                return self.submit(prompt, answer.trim());
+            }
+            Key::Escape => {
+                self.status.prompt = None;
+                return true;
+            }
+            Key::Interrupt => return false,
+            Key::Backspace => {
+                answer.pop();
+            }
+            Key::Char(c) => answer.push(c),
+            _ => {}
+        }
+        self.status.prompt = Some(format!("{}{}", prompt.question(), answer));
+        self.prompt = Some((prompt, answer));
+        true
+    }
+
+    /// Act on the answer to a prompt, returning whether to keep going.
+    fn submit(&mut self, prompt: Prompt, answer: &str) -> bool {
+        let result = match prompt {
+            Prompt::Goto => parse_value::<u64>("generation", answer).map(|generation| {
+                self.goto(generation);
+                true
+            }),
+            Prompt::Command => self.command(answer),
+            Prompt::Fill => self.fill(answer).map(|()| true),
+        };
+        result.unwrap_or_else(|error| {
+            self.status.message = Some(error);
+            true
+        })
+    }
+
+    /// Run a command typed after `:`, returning whether to keep going.
+    fn command(&mut self, line: &str) -> Result<bool, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            [] => {}
+            ["rule", rule] => {
+                let rule = Rule::parse(rule).map_err(|error| error.to_string())?;
+                self.universe.set_rule(rule);
+                self.status.step(&self.universe);
+            }
+            ["resize", width, height] => {
+                let width = parse_value::<u32>("width", width)?;
+                let height = parse_value::<u32>("height", height)?;
+                if width == 0 || height == 0 {
+                    return Err("the board needs at least one cell".to_string());
+                }
+                self.universe.resize(width, height);
+                if let Some((row, col)) = self.status.cursor {
+                    self.status.cursor = Some((row.min(height - 1), col.min(width - 1)));
+                }
+                self.status.step(&self.universe);
+            }
+            ["save", path] => {
+                Pattern::from_universe(&self.universe)
+                    .save(path)
+                    .map_err(|error| format!("could not save {}: {}", path, error))?;
+                self.status.message = Some(format!("saved {}", path));
+            }
+            ["census"] => {
+                self.status.message = Some(super::census(&self.universe));
+            }
+            ["symmetry"] => {
+                self.status.message = Some(self.universe.symmetry().to_string());
+            }
+            ["goto" | "g", generation] => {
+                self.goto(parse_value::<u64>("generation", generation)?);
+            }
+            ["fps", fps] => {
+                self.status.interval = parse_fps(fps)?;
+            }
+            ["clear"] => self.start_over(None),
+            ["random"] => self.start_over(Some(time_seed())),
+            ["q" | "quit"] => return Ok(false),
+            _ => return Err(format!("unknown command '{}'", line)),
+        }
+        Ok(true)
+    }
+
+    /// Simulate ahead to `generation`, or go back to it if the history still
+    /// holds it.
+    fn goto(&mut self, generation: u64) {
+        if generation >= self.status.generation {
+            self.advance(generation - self.status.generation);
+            return;
+        }
+        match self.history.pop_to(generation) {
+            Some(snapshot) => {
+                self.universe.restore(&snapshot);
+                self.status.step(&self.universe);
+                let kept = snapshot.generation();
+                if kept > generation {
+                    self.status.message = Some(format!("history only goes back to {}", kept));
+                }
+            }
+            None => self.status.message = Some("no history to go back through".into()),
+        }
+    }
+
+    /// Go back to the previous generation kept in the history, if any.
+    fn rewind(&mut self) {
+        // Steps still pending would otherwise undo the rewind right away.
+        if self.steps > 0 {
+            self.steps -= 1;
+            return;
+        }
+        if let Some(snapshot) = self.history.pop() {
+            self.universe.restore(&snapshot);
+            self.status.step(&self.universe);
+        }
+    }
+
+    /// Show the help screen instead of the board, pausing meanwhile.
+    fn open_help(&mut self) {
+        self.status.paused = true;
+        self.help = true;
+    }
+
+    /// Show the pattern browser instead of the board, pausing meanwhile.
+    fn open_browser(&mut self) {
+        self.status.paused = true;
+        self.browser = Some(Browser::new(&self.settings.stamps));
+    }
+
+    /// React to a key in the pattern browser, returning whether to keep
+    /// going.
+    fn browse(&mut self, key: Key) -> bool {
+        let stamps = &self.settings.stamps;
+        let browser = match &mut self.browser {
+            Some(browser) => browser,
+            None => return true,
+        };
+        match key {
+            Key::Up => browser.up(),
+            Key::Down => browser.down(),
+            Key::Backspace => browser.backspace(stamps),
+            Key::Char(c) => browser.type_char(c, stamps),
+            Key::Enter => {
+                if let Some(index) = browser.selected() {
+                    self.stamp = Some((Some(index), stamps[index].clone()));
+                    self.start_editing();
+                }
+                self.close_browser();
+            }
+            Key::Escape => self.close_browser(),
+            Key::Interrupt => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn close_browser(&mut self) {
+        self.browser = None;
+        // The browser drew over the board.
+        self.renderer.invalidate();
+    }
+
+    /// Pick the next stamp, going back to toggling single cells after the
+    /// last one.
+    fn next_stamp(&mut self) {
+        let next = match &self.stamp {
+            Some((Some(index), _)) => index + 1,
+            _ => 0,
+        };
+        self.stamp = self
+            .settings
+            .stamps
+            .get(next)
+            .map(|pattern| (Some(next), pattern.clone()));
+    }
+
+    /// Put the live cells between the selection anchor and `cursor` on the
+    /// clipboard, killing them if `cut`, and pick the clipboard as stamp.
+    fn copy(&mut self, cursor: (u32, u32), cut: bool) {
+        let (top, left, bottom, right) = match self.selection(cursor) {
+            Some(selection) => selection,
+            None => return,
+        };
+        self.anchor = None;
+        let mut cells = Vec::new();
+        if cut {
+            self.edits.begin();
+        }
+        for row in top..=bottom {
+            for col in left..=right {
+                if self.universe.get_cell(row, col) == Cell::Alive {
+                    cells.push((row - top, col - left));
+                    if cut {
+                        self.edits
+                            .set_cell(&mut self.universe, row, col, Cell::Dead);
+                    }
+                }
+            }
+        }
+        let mut pattern = Pattern::new(right - left + 1, bottom - top + 1, cells);
+        pattern.name = Some("clipboard".to_string());
+        self.status.step(&self.universe);
+        self.clipboard = Some(pattern.clone());
+        self.stamp = Some((None, pattern));
+    }
+
+    /// Give every cell of the selection a random state, from an answer
+    /// such as `0.3` or `0.3 42` naming the density and seed.
+    fn fill(&mut self, answer: &str) -> Result<(), String> {
+        let mut words = answer.split_whitespace();
+        let density = parse_value::<f64>("density", words.next().unwrap_or_default())?;
+        if !(0.0..=1.0).contains(&density) {
+            return Err("the density must be between 0 and 1".to_string());
+        }
+        let seed = match words.next() {
+            Some(seed) => parse_value::<u64>("seed", seed)?,
+            None => time_seed(),
+        };
+        let cursor = self.status.cursor.unwrap_or(self.cursor);
+        let (top, left, bottom, right) = match self.selection(cursor) {
+            Some(selection) => selection,
+            None => return Ok(()),
+        };
+        self.anchor = None;
+
+        let mut rng = Rng::new(seed);
+        self.edits.begin();
+        for row in top..=bottom {
+            for col in left..=right {
+                let cell = if rng.chance(density) {
+                    Cell::Alive
+                } else {
+                    Cell::Dead
+                };
+                self.edits.set_cell(&mut self.universe, row, col, cell);
+            }
+        }
+        self.status.step(&self.universe);
+        self.status.message = Some(format!("filled with seed {}", seed));
+        Ok(())
+    }
+
+    /// The top, left, bottom and right edges of the selection ending at
+    /// `cursor`, all included, if one was started.
+    fn selection(&self, (row, col): (u32, u32)) -> Option<(u32, u32, u32, u32)> {
+        // The board may have shrunk since the selection was started.
+        let (anchor_row, anchor_col) = self.anchor?;
+        let anchor_row = anchor_row.min(self.universe.height() - 1);
+        let anchor_col = anchor_col.min(self.universe.width() - 1);
+        Some((
+            row.min(anchor_row),
+            col.min(anchor_col),
+            row.max(anchor_row),
+            col.max(anchor_col),
+        ))
+    }
+
+    /// Show the selection or the picked stamp and where it would land while
+    /// editing.
+    fn show_tool(&mut self) {
+        self.status.tool = None;
+        self.status.preview.clear();
+        let (row, col) = match self.status.cursor {
+            Some(cursor) => cursor,
+            None => return,
+        };
+
+        if let Some((top, left, bottom, right)) = self.selection((row, col)) {
+            self.status.tool = Some(format!("select {}x{}", right - left + 1, bottom - top + 1));
+            // Outline the selection, leaving its inside visible.
+            self.status.preview = (top..=bottom)
+                .flat_map(|r| (left..=right).map(move |c| (r, c)))
+                .filter(|&(r, c)| r == top || r == bottom || c == left || c == right)
+                .collect();
+        } else if let Some((_, stamp)) = &self.stamp {
+            let name = stamp.name.as_deref().unwrap_or("pattern");
+            self.status.tool = Some(format!("stamp {}", name));
+            self.status.preview = stamp
+                .cells()
+                .iter()
+                .map(|&(r, c)| (r + row, c + col))
+                .filter(|&(r, c)| r < self.universe.height() && c < self.universe.width())
+                .collect();
+        } else {
+            self.status.tool = self.brush.name();
+        }
+    }
+
+    /// Revert the last cells changed by hand.
+    fn undo(&mut self) {
+        if self.edits.undo(&mut self.universe) {
+            self.status.step(&self.universe);
+        }
+    }
+
+    /// Change back the cells last reverted by `undo`.
+    fn redo(&mut self) {
+        if self.edits.redo(&mut self.universe) {
+            self.status.step(&self.universe);
+        }
+    }
+
+    /// Start again from generation 0 on an empty board, scattered with live
+    /// cells when a seed is given.
+    fn start_over(&mut self, seed: Option<u64>) {
+        self.universe.reset();
+        if let Some(seed) = seed {
+            self.universe.scatter(self.settings.density, seed);
+        }
+        // Neither the past generations nor the edits apply to the new board.
+        self.history.clear();
+        self.edits.clear();
+        self.status.step(&self.universe);
+    }
+
+    /// Save the current state under `digit` if `save`, or otherwise go back
+    /// to the state saved there.
+    fn bookmark(&mut self, save: bool, digit: char) {
+        let slot = match digit.to_digit(10) {
+            Some(number @ 1..=9) => &mut self.bookmarks[number as usize - 1],
+            _ => return,
+        };
+        if save {
+            *slot = Some((self.universe.snapshot(), self.universe.rule()));
+            self.status.message = Some(format!("saved bookmark {}", digit));
+            return;
+        }
+
+        match slot {
+            Some((snapshot, rule)) => {
+                self.universe.restore(snapshot);
+                self.universe.set_rule(*rule);
+                self.status.message = Some(format!("back to bookmark {}", digit));
+                // The history and edits belong to the branch being left.
+                self.history.clear();
+                self.edits.clear();
+                if let Some((row, col)) = self.status.cursor {
+                    self.status.cursor = Some((
+                        row.min(self.universe.height() - 1),
+                        col.min(self.universe.width() - 1),
+                    ));
+                }
+                self.status.step(&self.universe);
+            }
+            None => self.status.message = Some(format!("no bookmark {}", digit)),
+        }
+    }
+
+    /// Switch to the rule after the current one in the list of well known
+    /// rules, keeping the board as it is.
+    fn next_rule(&mut self) {
+        let rule = self.universe.rule();
+        let current = RULES
+            .iter()
+            .position(|&(_, rulestring)| Rule::parse(rulestring).ok() == Some(rule));
+        let (name, rulestring) = match current {
+            Some(index) => RULES[(index + 1) % RULES.len()],
+            None => RULES[0],
+        };
+        let rule = Rule::parse(rulestring).expect("rule presets are valid");
+        self.universe.set_rule(rule);
+        self.status.step(&self.universe);
+        self.status.message = Some(format!("rule {}", name));
+    }
+
+    /// Write the current generation to a new file in the working directory
+    /// and say where in the HUD.
+    fn save(&mut self) {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let path = format!(
+            "life-{}-gen{}.{}",
+            seconds,
+            self.status.generation,
+            self.settings.save_format.name()
+        );
+        let mut pattern = Pattern::from_universe(&self.universe);
+        pattern.name = Some(format!("generation {}", self.status.generation));
+        self.status.message = Some(
+            match fs::write(&path, pattern.write(self.settings.save_format)) {
+                Ok(()) => format!("saved {}", path),
+                Err(error) => format!("could not save {}: {}", path, error),
+            },
+        );
+    }
+
+    fn toggle_pause(&mut self) {
+        self.status.paused = !self.status.paused;
+    }
+}
+
+/// Run two universes on a shared clock and show them side by side.
+pub async fn play_split(
+    mut left: Universe,
+    mut right: Universe,
+    renderer: &mut SplitRenderer,
+    settings: &Settings,
+) -> io::Result<(Universe, Universe)> {
+    let raw_mode = RawMode::enable();
+    let keys = input::spawn_reader();
+    let mut statuses = [settings.status(&left), settings.status(&right)];
+
+    renderer.init()?;
+    if settings.start_paused {
+        renderer.draw(&left, &right, &statuses)?;
+    }
+    let mut last_tick = Instant::now();
+    'session: while settings.generations != Some(statuses[0].generation) {
+        let mut steps = 0;
+        let paused = statuses[0].paused;
+        for key in next_keys(&keys, paused) {
+            match key {
+                Key::Char('h') => statuses.iter_mut().for_each(|s| s.show_hud = !s.show_hud),
+                Key::Char(' ') => {
+                    statuses.iter_mut().for_each(|s| s.paused = !s.paused);
+                    last_tick = Instant::now();
+                }
+                Key::Char('n' | '.') if paused => steps += 1,
+                Key::Char('N') if paused => steps += 10,
+                Key::Char('+' | '=' | ']') => statuses.iter_mut().for_each(faster),
+                Key::Char('-' | '[') => statuses.iter_mut().for_each(slower),
+                Key::Char('M') => statuses.iter_mut().for_each(|s| s.max_speed = !s.max_speed),
+                Key::Char('q') | Key::Interrupt => break 'session,
+                _ => {}
+            }
+        }
+        if statuses[0].paused {
+            let steps = settings.limit(steps, statuses[0].generation);
+            for _ in 0..steps {
+                left.tick();
+                right.tick();
+            }
+            statuses[0].step(&left);
+            statuses[1].step(&right);
+            renderer.draw(&left, &right, &statuses)?;
+            continue;
+        }
+
+        let (batch, busy) = run_frame(settings, &statuses[0], last_tick, || {
+            left.tick();
+            right.tick();
+        })
+        .await;
+        let elapsed = last_tick.elapsed().as_secs_f64();
+        last_tick = Instant::now();
+        statuses[0].advance_by(&left, batch, elapsed);
+        statuses[1].advance_by(&right, batch, elapsed);
+        // Both universes were stepped in the same time, so each gets half.
+        statuses[0].simulated(&left, batch, busy / 2);
+        statuses[1].simulated(&right, batch, busy / 2);
+
+        renderer.draw(&left, &right, &statuses)?;
+    }
+
+    renderer.shutdown()?;
+    drop(raw_mode);
+    for (universe, status) in [(&left, &statuses[0]), (&right, &statuses[1])] {
+        if let Some(summary) = status.summary(universe) {
+            println!("{}", summary);
+        }
+    }
+    Ok((left, right))
+}
+
+/// The keys pressed since the last frame. While paused nothing else can
+/// change, so this blocks until a key arrives. A paused session whose input
+/// is closed could never resume, so it ends as if interrupted.
+fn next_keys(keys: &Receiver<Key>, paused: bool) -> Vec<Key> {
+    let mut pressed: Vec<Key> = keys.try_iter().collect();
+    if paused && pressed.is_empty() {
+        match keys.recv() {
+            Ok(key) => pressed.push(key),
+            Err(_) => pressed.push(Key::Interrupt),
+        }
+    }
+    pressed
+}
+
+/// Simulate the generations of the next frame with `tick`, returning how
+/// many were simulated and the time spent simulating them.
+///
+/// Normally that is one batch, followed by a wait for the frame interval. At
+/// max speed generations are simulated until a frame's worth of time has
+/// passed, skipping the frames in between, and adaptive batches do the same
+/// for the frame interval.
+async fn run_frame(
+    settings: &Settings,
+    status: &Status,
+    last_tick: Instant,
+    mut tick: impl FnMut(),
+) -> (u64, Duration) {
+    let started = Instant::now();
+    let frame = match (status.max_speed, settings.batch) {
+        (false, Batch::Fixed(batch)) => {
+            let batch = settings.limit(batch, status.generation);
+            for _ in 0..batch {
+                tick();
+            }
+            let busy = started.elapsed();
+            wait_for_frame(status.interval, last_tick).await;
+            return (batch, busy);
+        }
+        (false, Batch::Adaptive) => status.interval.unwrap_or(MAX_SPEED_FRAME),
+        (true, _) => MAX_SPEED_FRAME,
+    };
+
+    let limit = settings.limit(u64::MAX, status.generation);
+    let mut simulated = 0;
+    while simulated < limit {
+        tick();
+        simulated += 1;
+        if last_tick.elapsed() >= frame {
+            break;
+        }
+    }
+    (simulated, started.elapsed())
+}
+
+/// Halve the time between frames, down to running without delay.
+fn faster(status: &mut Status) {
+    status.interval = status
+        .interval
+        .map(|interval| interval / 2)
+        .filter(|&interval| interval >= MIN_INTERVAL);
+}
+
+/// Double the time between frames, up to `MAX_INTERVAL`.
+fn slower(status: &mut Status) {
+    status.interval = Some(match status.interval {
+        Some(interval) => (interval * 2).min(MAX_INTERVAL),
+        None => MIN_INTERVAL,
+    });
+}
+
+/// Sleep for whatever is left of the frame interval since `last_tick`.
+async fn wait_for_frame(interval: Option<Duration>, last_tick: Instant) {
+    if let Some(remaining) = interval.and_then(|interval| interval.checked_sub(last_tick.elapsed()))
+    {
+        set_timeout(remaining).await;
+    }
+}
@@ -0,0 +1,263 @@
+use game_of_life::{Cycle, CycleDetector, Delta, History, Records, Universe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often a simulation handing over every batch checks whether the last
+/// one was received.
+const HANDOVER_POLL: Duration = Duration::from_millis(1);
+
+/// Whether the universe just ticked will never change again, having died
+/// out or stopped changing.
+pub fn settled(universe: &Universe) -> bool {
+    universe.is_extinct() || universe.is_still()
+}
+
+/// How many generations are simulated for every frame drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Batch {
+    Fixed(u64),
+    /// As many as fit in the frame interval, simulated while the frame
+    /// before is drawn.
+    Adaptive,
+}
+
+/// How a simulation in the background is paced, fixed while it runs.
+#[derive(Clone, Copy, Debug)]
+pub struct Pace {
+    /// Time between batches, or `None` to run without delay.
+    pub interval: Option<Duration>,
+    /// Simulate for a frame's worth of time per batch instead of `batch`
+    /// generations, with no delay in between.
+    pub max_speed: bool,
+    pub batch: Batch,
+    /// The generation to stop at.
+    pub limit: Option<u64>,
+    /// Wait for each batch to be received instead of folding it into the
+    /// next one.
+    pub every_batch: bool,
+    /// Stop once the universe has settled.
+    pub until_settled: bool,
+    /// Stop once the universe is found to repeat itself.
+    pub until_cycle: bool,
+}
+
+/// Generations simulated since the last progress was received.
+pub struct Progress {
+    pub delta: Delta,
+    pub generations: u64,
+    /// Time they took, waits included.
+    pub elapsed: Duration,
+    /// Time spent simulating them, leaving out the waits between batches.
+    pub busy: Duration,
+    /// The cycle the universe is in, once found.
+    pub cycle: Option<Cycle>,
+    /// The records set so far, when they are kept.
+    pub records: Option<Records>,
+}
+
+impl Progress {
+    fn merge(&mut self, later: Progress) {
+        self.delta.merge(later.delta);
+        self.generations += later.generations;
+        self.elapsed += later.elapsed;
+        self.busy += later.busy;
+        self.cycle = later.cycle;
+        self.records = later.records;
+    }
+}
+
+/// A universe simulated on its own thread, which sends the changes of each
+/// batch to whoever draws it.
+///
+/// Only one batch waits to be received at a time. While it does the thread
+/// keeps simulating and folds the later batches into it, so a slow terminal
+/// means fewer frames rather than a slower simulation, unless it is paced to
+/// hand over every batch.
+pub struct Simulation {
+    every_batch: bool,
+    stop: Arc<AtomicBool>,
+    progress: Receiver<Progress>,
+    thread: Option<JoinHandle<Finished>>,
+}
+
+/// What the simulation thread hands back: the history and cycle detector
+/// it was given, along with the last batch if it could not be sent.
+type Finished = (History, CycleDetector, Option<Progress>);
+
+impl Simulation {
+    /// Simulate `universe`, remembering each generation in `history`,
+    /// watching for cycles with `cycles` and keeping up any `records`, until
+    /// stopped, the limit is reached or, if the pace says so, the universe
+    /// settles or cycles.
+    pub fn start(
+        universe: Universe,
+        (history, cycles): (History, CycleDetector),
+        records: Option<Records>,
+        pace: Pace,
+        frame: Duration,
+    ) -> Simulation {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, progress) = mpsc::sync_channel(1);
+        let running = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            simulate(
+                universe,
+                (history, cycles),
+                records,
+                pace,
+                frame,
+                &running,
+                &sender,
+            )
+        });
+        Simulation {
+            every_batch: pace.every_batch,
+            stop,
+            progress,
+            thread: Some(thread),
+        }
+    }
+
+    /// Every batch received within `timeout`, merged into one unless each
+    /// is to be drawn, or `None` if nothing came. Fails once the simulation
+    /// has reached its limit or the universe settled or cycled.
+    pub fn receive(&self, timeout: Duration) -> Result<Option<Progress>, RecvTimeoutError> {
+        let mut progress = match self.progress.recv_timeout(timeout) {
+            Ok(progress) => progress,
+            Err(RecvTimeoutError::Timeout) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if !self.every_batch {
+            for later in self.progress.try_iter() {
+                progress.merge(later);
+            }
+        }
+        Ok(Some(progress))
+    }
+
+    /// Stop simulating and hand back the history and cycle detector along
+    /// with the progress not received yet.
+    pub fn stop(mut self) -> Finished {
+        let (history, cycles, mut unsent) = self.join();
+        let mut progress = self.progress.try_iter().chain(unsent.take());
+        let first = progress.next();
+        let merged = first.map(|mut first| {
+            progress.for_each(|later| first.merge(later));
+            first
+        });
+        (history, cycles, merged)
+    }
+
+    fn join(&mut self) -> Finished {
+        self.stop.store(true, Ordering::Relaxed);
+        let thread = self
+            .thread
+            .take()
+            .expect("a simulation is only stopped once");
+        thread.thread().unpark();
+        thread.join().expect("the simulation thread does not panic")
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.join();
+        }
+    }
+}
+
+/// The body of the simulation thread.
+fn simulate(
+    mut universe: Universe,
+    (mut history, mut cycles): (History, CycleDetector),
+    mut records: Option<Records>,
+    pace: Pace,
+    frame: Duration,
+    stop: &AtomicBool,
+    sender: &SyncSender<Progress>,
+) -> Finished {
+    let at_limit = |universe: &Universe| pace.limit == Some(universe.generation());
+    // Whoever draws the universe already has its current state.
+    universe.clear_changed_rows();
+    cycles.observe(&universe);
+    if let Some(records) = &mut records {
+        records.observe(&universe);
+    }
+    let mut unsent: Option<Progress> = None;
+    let mut settled_down = false;
+    while !stop.load(Ordering::Relaxed) && !at_limit(&universe) && !settled_down {
+        let started = Instant::now();
+        let mut generations = 0;
+        while !at_limit(&universe) {
+            history.push(&universe);
+            universe.tick();
+            generations += 1;
+            let cycle = cycles.observe(&universe);
+            if let Some(records) = &mut records {
+                records.observe(&universe);
+            }
+            if (pace.until_settled && settled(&universe)) || (pace.until_cycle && cycle.is_some()) {
+                settled_down = true;
+                break;
+            }
+            let done = match (pace.max_speed, pace.batch) {
+                (true, _) => started.elapsed() >= frame,
+                (false, Batch::Fixed(batch)) => generations == batch,
+                (false, Batch::Adaptive) => started.elapsed() >= pace.interval.unwrap_or(frame),
+            };
+            if done || stop.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+
+        let busy = started.elapsed();
+
+        // Batches are only shown once their time is up, like frames.
+        if let Some(interval) = pace.interval.filter(|_| !pace.max_speed) {
+            let deadline = started + interval;
+            while !stop.load(Ordering::Relaxed) {
+                match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => thread::park_timeout(remaining),
+                    _ => break,
+                }
+            }
+        }
+
+        let progress = Progress {
+            delta: universe.take_delta(),
+            generations,
+            elapsed: started.elapsed(),
+            busy,
+            cycle: cycles.found(),
+            records,
+        };
+        let mut progress = match unsent.take() {
+            Some(mut earlier) => {
+                earlier.merge(progress);
+                earlier
+            }
+            None => progress,
+        };
+        loop {
+            match sender.try_send(progress) {
+                Ok(()) => break,
+                Err(TrySendError::Full(held))
+                    if pace.every_batch && !stop.load(Ordering::Relaxed) =>
+                {
+                    progress = held;
+                    thread::park_timeout(HANDOVER_POLL);
+                }
+                Err(TrySendError::Full(held)) => {
+                    unsent = Some(held);
+                    break;
+                }
+                Err(TrySendError::Disconnected(_)) => return (history, cycles, None),
+            }
+        }
+    }
+    (history, cycles, unsent)
+}
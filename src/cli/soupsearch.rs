@@ -0,0 +1,178 @@
+use game_of_life::input::{catch_interrupts, interrupted};
+use game_of_life::pattern::Pattern;
+use game_of_life::{Boundary, Census, CycleDetector, Rule, Universe};
+use std::fmt::Write as _;
+use std::fs;
+use std::time::Instant;
+
+use super::run::time_seed;
+use super::{parse_pair, Args};
+
+pub const USAGE: &str = "\
+Usage: game_of_life soupsearch [options]
+
+Runs random soups one after another until each settles into ash, counts the
+objects left by every one and tallies them over all the soups.
+
+Options:
+  --soups <count>             Soups to run (default 1000)
+  --soup <width>x<height>     Size of each soup (default 16x16)
+  --board <width>x<height>    Size of the wrapping board each soup is run on
+                              (default 128x128)
+  --density <fraction>        Share of cells alive in each soup (default 0.5)
+  --seed <number>             Seed of the first soup, each next one taking
+                              the next seed
+  --rule <rule>               Rulestring such as B36/S23, or a preset name
+  --max-generations <count>   Give up on a soup that has not settled by then
+                              (default 20000)
+  --progress <soups>          How often to report the tally so far (default
+                              100)
+  --results <path>            Also save the tally as text";
+
+pub fn main(mut args: Args) -> Result<(), String> {
+    if args.switch("--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let soups = args.parse::<u64>("--soups")?.unwrap_or(1000);
+    let soup = match args.value("--soup")? {
+        Some(size) => parse_pair::<u32>("--soup", &size, 'x')?,
+        None => (16, 16),
+    };
+    let board = match args.value("--board")? {
+        Some(size) => parse_pair::<u32>("--board", &size, 'x')?,
+        None => (128, 128),
+    };
+    let density = args.parse::<f64>("--density")?.unwrap_or(0.5);
+    if !(0.0..=1.0).contains(&density) {
+        return Err(String::from("--density must be between 0 and 1"));
+    }
+    let first_seed = args.parse::<u64>("--seed")?;
+    let rule = match args.value("--rule")? {
+        Some(rule) => Rule::parse(&rule).map_err(|error| error.to_string())?,
+        None => Rule::default(),
+    };
+    let max_generations = args.parse::<u64>("--max-generations")?.unwrap_or(20_000);
+    let progress = args.parse::<u64>("--progress")?.unwrap_or(100).max(1);
+    let results = args.value("--results")?;
+    let positional = args.finish()?;
+    if !positional.is_empty() {
+        return Err(format!("unexpected argument '{}'", positional[0]));
+    }
+    if soup.0 == 0 || soup.1 == 0 || soup.0 > board.0 || soup.1 > board.1 {
+        return Err(String::from(
+            "soups must be at least 1x1 and fit on the board",
+        ));
+    }
+
+    catch_interrupts();
+    let first_seed = first_seed.unwrap_or_else(time_seed);
+    let started = Instant::now();
+    let mut tally = Tally::default();
+    for seed in first_seed..first_seed.saturating_add(soups) {
+        if interrupted() {
+            break;
+        }
+        let mut universe = Universe::empty(board.0, board.1);
+        universe.set_rule(rule);
+        universe.set_boundary(Boundary::default());
+        let cells = Pattern::from_universe(&Universe::random(soup.0, soup.1, density, seed));
+        universe.insert_pattern(&cells, (board.1 - soup.1) / 2, (board.0 - soup.0) / 2);
+        tally.add(&mut universe, max_generations);
+        if tally.soups % progress == 0 {
+            println!(
+                "{} soups in {:.1}s: {}",
+                tally.soups,
+                started.elapsed().as_secs_f64(),
+                tally.census
+            );
+        }
+    }
+
+    let last_seed = first_seed + tally.soups.saturating_sub(1);
+    let report = tally.report(
+        (soup, board, density, rule),
+        (first_seed, last_seed),
+        max_generations,
+    );
+    print!("{}", report);
+    match results {
+        Some(path) => fs::write(&path, report).map_err(|error| format!("{}: {}", path, error)),
+        None => Ok(()),
+    }
+}
+
+/// The ash of every soup run so far.
+#[derive(Default)]
+struct Tally {
+    soups: u64,
+    /// Soups that had not settled by the generation limit, whose objects
+    /// are left out of the census.
+    unsettled: u64,
+    census: Census,
+    /// Generations simulated over all soups.
+    generations: u64,
+}
+
+impl Tally {
+    /// Run `universe` until it repeats itself, then count what is left.
+    fn add(&mut self, universe: &mut Universe, max_generations: u64) {
+        self.soups += 1;
+        let mut cycles = CycleDetector::new();
+        cycles.observe(universe);
+        while universe.generation() < max_generations {
+            universe.tick();
+            if cycles.observe(universe).is_some() {
+                self.generations += universe.generation();
+                self.census.merge(&Census::from_universe(universe));
+                return;
+            }
+        }
+        self.generations += universe.generation();
+        self.unsettled += 1;
+    }
+
+    /// The tally as text, one kind of object per line, most common first.
+    fn report(
+        &self,
+        (soup, board, density, rule): ((u32, u32), (u32, u32), f64, Rule),
+        (first_seed, last_seed): (u64, u64),
+        max_generations: u64,
+    ) -> String {
+        let mut report = String::new();
+        writeln!(
+            report,
+            "# {} soups of {}x{} at {:.0}% on {}x{}, {}, seeds {} to {}",
+            self.soups,
+            soup.0,
+            soup.1,
+            density * 100.0,
+            board.0,
+            board.1,
+            rule,
+            first_seed,
+            last_seed
+        )
+        .and_then(|()| {
+            writeln!(
+                report,
+                "# {} generations, {} soups unsettled after {}",
+                self.generations, self.unsettled, max_generations
+            )
+        })
+        .expect("writing to a String cannot fail");
+        if !Census::recognizes(rule) {
+            writeln!(report, "# objects are only recognized under B3/S23")
+                .expect("writing to a String cannot fail");
+        }
+        for (name, count) in &self.census.objects {
+            writeln!(report, "{} {}", count, name).expect("writing to a String cannot fail");
+        }
+        if self.census.unrecognized > 0 {
+            writeln!(report, "{} unrecognized", self.census.unrecognized)
+                .expect("writing to a String cannot fail");
+        }
+        report
+    }
+}
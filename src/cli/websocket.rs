@@ -0,0 +1,295 @@
+//! The server side of the WebSocket protocol (RFC 6455), as far as `serve`
+//! needs it: accepting the upgrade of an HTTP request, sending frames, and
+//! answering the pings and closes that clients send back.
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha1::{Digest, Sha1};
+
+use super::http::Request;
+
+/// Appended to the key of the client before hashing it, as the protocol
+/// says.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The longest frame taken from a client. `serve` expects nothing larger
+/// than a close or ping, whose payloads fit in 125 bytes.
+const MAX_CLIENT_FRAME: u64 = 64 * 1024;
+
+/// The kinds of frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation = 0,
+    Text = 1,
+    Binary = 2,
+    Close = 8,
+    Ping = 9,
+    Pong = 10,
+}
+
+impl Opcode {
+    fn from_bits(bits: u8) -> io::Result<Opcode> {
+        match bits {
+            0 => Ok(Opcode::Continuation),
+            1 => Ok(Opcode::Text),
+            2 => Ok(Opcode::Binary),
+            8 => Ok(Opcode::Close),
+            9 => Ok(Opcode::Ping),
+            10 => Ok(Opcode::Pong),
+            _ => Err(invalid(format!("unknown WebSocket opcode {}", bits))),
+        }
+    }
+}
+
+/// A client that upgraded to a WebSocket. A thread reads what it sends,
+/// answering pings and closes, while frames are sent to it from elsewhere.
+pub struct Client {
+    stream: Arc<Mutex<TcpStream>>,
+    /// Set once the client closed the connection or it broke off.
+    closed: Arc<AtomicBool>,
+}
+
+impl Client {
+    /// Send `payload` as one frame, failing once the client has gone.
+    pub fn send(&mut self, opcode: Opcode, payload: &[u8]) -> io::Result<()> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the WebSocket client closed the connection",
+            ));
+        }
+        let mut stream = self.stream.lock().expect("no WebSocket writer panics");
+        write_frame(&mut *stream, opcode, payload)
+    }
+}
+
+/// Whether `request` asks to become a WebSocket.
+pub fn is_upgrade(request: &Request) -> bool {
+    request
+        .header("upgrade")
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+/// Answer an upgrade request, after which frames can be sent to the client.
+pub fn accept(mut stream: TcpStream, request: &Request) -> io::Result<Client> {
+    let key = request
+        .header("sec-websocket-key")
+        .ok_or_else(|| invalid("WebSocket upgrade without a key"))?;
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )?;
+    stream.flush()?;
+    // Clients may stay quiet for as long as they like.
+    stream.set_read_timeout(None)?;
+
+    let reader = stream.try_clone()?;
+    let stream = Arc::new(Mutex::new(stream));
+    let closed = Arc::new(AtomicBool::new(false));
+    let (writer, done) = (Arc::clone(&stream), Arc::clone(&closed));
+    thread::spawn(move || {
+        // However the connection ends, nothing more is sent on it.
+        let _ = answer_frames(reader, &writer);
+        done.store(true, Ordering::Relaxed);
+    });
+    Ok(Client { stream, closed })
+}
+
+/// Read the frames of a client until it closes the connection, answering
+/// each ping with a pong and a close with a close, and ignoring the rest.
+fn answer_frames(mut reader: TcpStream, writer: &Mutex<TcpStream>) -> io::Result<()> {
+    loop {
+        let (opcode, payload) = read_frame(&mut reader)?;
+        let mut stream = writer.lock().expect("no WebSocket writer panics");
+        match opcode {
+            Opcode::Ping => write_frame(&mut *stream, Opcode::Pong, &payload)?,
+            Opcode::Close => {
+                // The close is answered with the status code it came with.
+                let code = payload.get(..2).unwrap_or_default();
+                write_frame(&mut *stream, Opcode::Close, code)?;
+                return stream.shutdown(Shutdown::Both);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write `payload` as one unmasked frame, as servers send them.
+fn write_frame(out: &mut impl Write, opcode: Opcode, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode as u8);
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    out.write_all(&frame)
+}
+
+/// Read one frame sent by a client, which the protocol has it mask, and
+/// return its opcode and unmasked payload.
+fn read_frame(input: &mut impl Read) -> io::Result<(Opcode, Vec<u8>)> {
+    let mut head = [0; 2];
+    input.read_exact(&mut head)?;
+    let opcode = Opcode::from_bits(head[0] & 0x0f)?;
+    if head[1] & 0x80 == 0 {
+        return Err(invalid("unmasked WebSocket frame from a client"));
+    }
+    let length = match head[1] & 0x7f {
+        126 => {
+            let mut bytes = [0; 2];
+            input.read_exact(&mut bytes)?;
+            u16::from_be_bytes(bytes) as u64
+        }
+        127 => {
+            let mut bytes = [0; 8];
+            input.read_exact(&mut bytes)?;
+            u64::from_be_bytes(bytes)
+        }
+        length => length as u64,
+    };
+    if length > MAX_CLIENT_FRAME {
+        return Err(invalid(format!("WebSocket frame of {} bytes", length)));
+    }
+    let mut mask = [0; 4];
+    input.read_exact(&mut mask)?;
+    let mut payload = vec![0; length as usize];
+    input.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+/// The `Sec-WebSocket-Accept` answering a `Sec-WebSocket-Key`: the base64
+/// of the SHA-1 digest of the key and `GUID`.
+fn accept_key(key: &str) -> String {
+    STANDARD.encode(Sha1::digest(format!("{}{}", key, GUID)))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn accept_key_matches_the_rfc() {
+        // The example of section 1.3 of RFC 6455.
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_match_the_rfc() {
+        // The examples of section 5.7 of RFC 6455.
+        let mut frame = Vec::new();
+        write_frame(&mut frame, Opcode::Text, b"Hello").unwrap();
+        assert_eq!(frame, [0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]);
+
+        let masked = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        let (opcode, payload) = read_frame(&mut &masked[..]).unwrap();
+        assert_eq!((opcode, &payload[..]), (Opcode::Text, &b"Hello"[..]));
+
+        let mut long = Vec::new();
+        write_frame(&mut long, Opcode::Binary, &[0; 256]).unwrap();
+        assert_eq!(long[..4], [0x82, 126, 0x01, 0x00]);
+        let mut longer = Vec::new();
+        write_frame(&mut longer, Opcode::Binary, &[0; 65536]).unwrap();
+        assert_eq!(longer[..10], [0x82, 127, 0, 0, 0, 0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn bad_client_frames_are_refused() {
+        let unmasked = [0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f];
+        assert!(read_frame(&mut &unmasked[..]).is_err());
+        let unknown = [0x83, 0x80, 0, 0, 0, 0];
+        assert!(read_frame(&mut &unknown[..]).is_err());
+        let huge = [0x82, 0xff, 0, 0, 0, 0, 0x10, 0, 0, 0];
+        assert!(read_frame(&mut &huge[..]).is_err());
+        let cut_short = [0x81, 0x85, 0x37, 0xfa];
+        assert!(read_frame(&mut &cut_short[..]).is_err());
+    }
+
+    /// A frame as a client sends it, masked with a fixed key.
+    fn client_frame(opcode: Opcode, payload: &[u8]) -> Vec<u8> {
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x80 | opcode as u8, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(
+            payload
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ mask[i % 4]),
+        );
+        frame
+    }
+
+    #[test]
+    fn pings_and_closes_are_answered() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut browser = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let request = b"GET / HTTP/1.1\r\nUpgrade: websocket\r\n\
+                        Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        let request = Request::read(&mut &request[..]).unwrap();
+        let mut client = accept(server, &request).unwrap();
+
+        let mut handshake = [0; 129];
+        browser.read_exact(&mut handshake).unwrap();
+        assert!(handshake.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+
+        let unframe = |browser: &mut TcpStream| read_server_frame(browser);
+        browser
+            .write_all(&client_frame(Opcode::Ping, b"hi"))
+            .unwrap();
+        assert_eq!(unframe(&mut browser), (Opcode::Pong, b"hi".to_vec()));
+        client.send(Opcode::Text, b"frame").unwrap();
+        assert_eq!(unframe(&mut browser), (Opcode::Text, b"frame".to_vec()));
+
+        browser
+            .write_all(&client_frame(
+                Opcode::Close,
+                &[0x03, 0xe8, b'b', b'y', b'e'],
+            ))
+            .unwrap();
+        assert_eq!(unframe(&mut browser), (Opcode::Close, vec![0x03, 0xe8]));
+        // The server hangs up after answering, and sends nothing more.
+        assert_eq!(browser.read(&mut [0; 1]).unwrap(), 0);
+        while !client.closed.load(Ordering::Relaxed) {
+            thread::yield_now();
+        }
+        assert!(client.send(Opcode::Text, b"late").is_err());
+    }
+
+    /// Read a short unmasked frame, as the server sends them.
+    fn read_server_frame(input: &mut impl Read) -> (Opcode, Vec<u8>) {
+        let mut head = [0; 2];
+        input.read_exact(&mut head).unwrap();
+        let mut payload = vec![0; head[1] as usize];
+        input.read_exact(&mut payload).unwrap();
+        (Opcode::from_bits(head[0] & 0x0f).unwrap(), payload)
+    }
+}
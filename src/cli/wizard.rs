@@ -0,0 +1,80 @@
+use game_of_life::input::terminal_size;
+use game_of_life::pattern::PRESETS;
+use game_of_life::rule::PRESETS as RULES;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use super::{run, Args, USAGE};
+
+/// Ask a few questions and start `run` with the answers, for when the
+/// program is launched without any arguments.
+pub fn main() -> Result<(), String> {
+    if !io::stdin().is_terminal() {
+        return Err(format!("missing command\n\n{}", USAGE));
+    }
+
+    println!("Conway's Game of Life");
+    println!("Press enter to accept the default shown in brackets.\n");
+
+    // Leave room for the HUD and the prompt line below the grid.
+    let (columns, rows) = terminal_size().unwrap_or((80, 24));
+    let default_size = format!("{}x{}", (columns / 2).max(1), rows.saturating_sub(2).max(1));
+    let size = ask("Grid size (WIDTHxHEIGHT)", &default_size)?;
+    let (width, height) = size
+        .split_once('x')
+        .ok_or_else(|| format!("'{}' is not a size like 80x24", size))?;
+
+    println!("\nStarting pattern:");
+    println!("  {:>2}) random soup", 0);
+    for (i, preset) in PRESETS.iter().enumerate() {
+        println!("  {:>2}) {:<16} {}", i + 1, preset.name, preset.description);
+    }
+    println!("  or type the path of a .rle or .cells file");
+    let pattern = ask("Pattern", "0")?;
+
+    println!("\nRule:");
+    for (i, (name, rulestring)) in RULES.iter().enumerate() {
+        println!("  {:>2}) {:<20} {}", i + 1, name, rulestring);
+    }
+    println!("  or type a rulestring such as B36/S23");
+    let rule = ask("Rule", "1")?;
+
+    let mut args = vec![width.to_string(), height.to_string()];
+    match pattern.parse::<usize>() {
+        Ok(0) => args.push(String::from("--random")),
+        Ok(choice) => {
+            let preset = PRESETS
+                .get(choice - 1)
+                .ok_or_else(|| format!("there is no pattern number {}", choice))?;
+            args.extend([String::from("--preset"), preset.name.to_string()]);
+        }
+        Err(_) => args.extend([String::from("--pattern"), pattern]),
+    }
+    let rule = match rule.parse::<usize>() {
+        Ok(choice) => RULES
+            .get(choice.wrapping_sub(1))
+            .map(|(name, _)| name.to_string())
+            .ok_or_else(|| format!("there is no rule number {}", choice))?,
+        Err(_) => rule,
+    };
+    args.extend([String::from("--rule"), rule]);
+
+    run::main(Args::new(args))
+}
+
+/// Print `question` and read a line, returning `default` for an empty answer.
+fn ask(question: &str, default: &str) -> Result<String, String> {
+    print!("{} [{}]: ", question, default);
+    io::stdout().flush().map_err(|error| error.to_string())?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|error| error.to_string())?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::{Rule, Universe};
+
+/// Generations remembered by a `CycleDetector`, which is also the longest
+/// period it can find.
+const WINDOW: usize = 1 << 16;
+
+/// Generations that repeat forever, each `period` generations after the
+/// last, from `start` on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+    /// How far the live cells move every period, in rows down and columns
+    /// right, as a spaceship does. Zero for a board that repeats in place.
+    pub shift: (i64, i64),
+}
+
+impl Cycle {
+    /// Whether the live cells travel rather than repeat in place.
+    pub fn moves(&self) -> bool {
+        self.shift != (0, 0)
+    }
+
+    /// How fast the live cells travel in cells per generation, as a
+    /// fraction of the speed of light such as `c/4` or `2c/5`.
+    pub fn speed(&self) -> String {
+        let (rows, columns) = self.shift;
+        let cells = rows.unsigned_abs().max(columns.unsigned_abs());
+        let divisor = gcd(cells, self.period).max(1);
+        match (cells / divisor, self.period / divisor) {
+            (0, _) => "0".to_string(),
+            (1, 1) => "c".to_string(),
+            (1, period) => format!("c/{}", period),
+            (cells, 1) => format!("{}c", cells),
+            (cells, period) => format!("{}c/{}", cells, period),
+        }
+    }
+
+    /// Which way the live cells travel: a point of the compass when they
+    /// move straight or diagonally, or `oblique` otherwise.
+    pub fn direction(&self) -> &'static str {
+        let (rows, columns) = self.shift;
+        if rows != 0 && columns != 0 && rows.abs() != columns.abs() {
+            return "oblique";
+        }
+        match (rows.signum(), columns.signum()) {
+            (-1, 0) => "north",
+            (1, 0) => "south",
+            (0, 1) => "east",
+            (0, -1) => "west",
+            (-1, 1) => "northeast",
+            (-1, -1) => "northwest",
+            (1, 1) => "southeast",
+            (1, -1) => "southwest",
+            _ => "nowhere",
+        }
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.moves() {
+            write!(
+                f,
+                "spaceship of period {} moving {} {} since generation {}",
+                self.period,
+                self.speed(),
+                self.direction(),
+                self.start
+            )
+        } else {
+            write!(
+                f,
+                "cycle of period {} since generation {}",
+                self.period, self.start
+            )
+        }
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// Spots a universe coming back to a state it was in before, which it has
+/// to repeat from then on, and if asked to, coming back to the same
+/// pattern of live cells somewhere else, as a lone spaceship does.
+///
+/// States are told apart by `Universe::fingerprint` and patterns by
+/// `Universe::shape`, so two different ones could in principle be taken
+/// for the same, but the odds are negligible. A spaceship is only taken to
+/// keep going as long as nothing gets in its way.
+#[derive(Debug, Default)]
+pub struct CycleDetector {
+    /// Also compare the patterns of live cells wherever they are.
+    track_motion: bool,
+    /// Generations by fingerprint, for the last `WINDOW` of them.
+    seen: HashMap<u64, u64>,
+    /// Generations and where their live cells started by shape, when
+    /// tracking motion, counted from the top left corner the universe had
+    /// before it grew.
+    shapes: HashMap<u64, (u64, (i64, i64))>,
+    /// The fingerprints and shapes in `seen` and `shapes`, oldest first, to
+    /// forget them in order.
+    order: VecDeque<(u64, Option<u64>)>,
+    /// The generation, fingerprint and rule observed last.
+    last: Option<(u64, u64, Rule)>,
+    found: Option<Cycle>,
+}
+
+impl CycleDetector {
+    pub fn new() -> CycleDetector {
+        CycleDetector::default()
+    }
+
+    /// Look for spaceships too, which costs a pass over the cells in the
+    /// bounds of the live ones every generation. Starts the search over.
+    pub fn set_track_motion(&mut self, track_motion: bool) {
+        self.track_motion = track_motion;
+        self.clear();
+    }
+
+    /// Take note of the current generation of `universe`, returning the
+    /// cycle it is in once one is found.
+    ///
+    /// The generations observed are expected to follow one another. Anything
+    /// else, such as a rewind, an edit or a change of rule, starts the
+    /// search over from the current generation.
+    pub fn observe(&mut self, universe: &Universe) -> Option<Cycle> {
+        let (generation, fingerprint, rule) = (
+            universe.generation(),
+            universe.fingerprint(),
+            universe.rule(),
+        );
+        match self.last {
+            // Seen already, as when a paused session carries on.
+            Some(last) if last == (generation, fingerprint, rule) => return self.found,
+            Some((last, _, last_rule)) if last + 1 == generation && last_rule == rule => {}
+            _ => self.clear(),
+        }
+        self.last = Some((generation, fingerprint, rule));
+        if self.found.is_some() {
+            return self.found;
+        }
+
+        if let Some(&start) = self.seen.get(&fingerprint) {
+            return self.find(start, generation, (0, 0));
+        }
+        let (grown_rows, grown_columns) = universe.grown();
+        let shape = universe
+            .shape()
+            .filter(|_| self.track_motion)
+            .map(|((top, left), hash)| {
+                let corner = (
+                    top as i64 - grown_rows as i64,
+                    left as i64 - grown_columns as i64,
+                );
+                (corner, hash)
+            });
+        if let Some((corner, hash)) = shape {
+            if let Some(&(start, (top, left))) = self.shapes.get(&hash) {
+                let shift = (corner.0 - top, corner.1 - left);
+                return self.find(start, generation, shift);
+            }
+        }
+
+        if self.order.len() == WINDOW {
+            if let Some((oldest, oldest_shape)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+                if let Some(oldest_shape) = oldest_shape {
+                    self.shapes.remove(&oldest_shape);
+                }
+            }
+        }
+        self.seen.insert(fingerprint, generation);
+        if let Some((corner, hash)) = shape {
+            self.shapes.insert(hash, (generation, corner));
+        }
+        self.order
+            .push_back((fingerprint, shape.map(|(_, hash)| hash)));
+        None
+    }
+
+    fn find(&mut self, start: u64, generation: u64, shift: (i64, i64)) -> Option<Cycle> {
+        self.found = Some(Cycle {
+            start,
+            period: generation - start,
+            shift,
+        });
+        self.found
+    }
+
+    /// The cycle found, if any.
+    pub fn found(&self) -> Option<Cycle> {
+        self.found
+    }
+
+    /// Forget every generation observed.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.shapes.clear();
+        self.order.clear();
+        self.last = None;
+        self.found = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Cell, Edge};
+
+    /// A universe of `edge` edges holding the live cells given as rows of
+    /// `O` and `.`, with its top left corner at `top` and `left`.
+    fn universe(edge: Edge, cells: &[&str], top: u32, left: u32) -> Universe {
+        let mut universe = Universe::empty(16, 16);
+        universe.set_boundary(Boundary { x: edge, y: edge });
+        for (row, line) in (top..).zip(cells) {
+            for (column, cell) in (left..).zip(line.chars()) {
+                if cell == 'O' {
+                    universe.set_cell(row, column, Cell::Alive);
+                }
+            }
+        }
+        universe
+    }
+
+    /// The cycle found by ticking `universe` until one turns up.
+    fn cycle_of(mut universe: Universe) -> Cycle {
+        let mut detector = CycleDetector::new();
+        detector.set_track_motion(true);
+        for _ in 0..100 {
+            if let Some(cycle) = detector.observe(&universe) {
+                return cycle;
+            }
+            universe.tick();
+        }
+        panic!("no cycle in 100 generations");
+    }
+
+    const BLOCK: &[&str] = &["OO", "OO"];
+    const BLINKER: &[&str] = &["OOO"];
+    const GLIDER: &[&str] = &[".O.", "..O", "OOO"];
+
+    #[test]
+    fn still_lifes_have_period_one() {
+        for edge in [Edge::Torus, Edge::Grow] {
+            let cycle = cycle_of(universe(edge, BLOCK, 7, 7));
+            assert_eq!((cycle.start, cycle.period, cycle.shift), (0, 1, (0, 0)));
+            // On the edge, where a growing universe grows around it once.
+            let cycle = cycle_of(universe(edge, BLOCK, 0, 0));
+            assert_eq!((cycle.period, cycle.shift), (1, (0, 0)), "{:?}", edge);
+        }
+    }
+
+    #[test]
+    fn blinkers_have_period_two() {
+        for edge in [Edge::Torus, Edge::Grow] {
+            for (top, left) in [(7, 6), (0, 0)] {
+                let cycle = cycle_of(universe(edge, BLINKER, top, left));
+                assert_eq!((cycle.period, cycle.shift), (2, (0, 0)), "{:?}", edge);
+                assert!(!cycle.moves());
+            }
+        }
+    }
+
+    #[test]
+    fn gliders_move_a_cell_diagonally_every_four_generations() {
+        for edge in [Edge::Torus, Edge::Grow] {
+            // Away from the edges, and heading straight into them.
+            for (top, left) in [(2, 2), (12, 12)] {
+                let cycle = cycle_of(universe(edge, GLIDER, top, left));
+                assert_eq!(
+                    (cycle.start, cycle.period, cycle.shift),
+                    (0, 4, (1, 1)),
+                    "{:?} at {},{}",
+                    edge,
+                    top,
+                    left
+                );
+                assert_eq!(cycle.speed(), "c/4");
+                assert_eq!(cycle.direction(), "southeast");
+            }
+        }
+    }
+
+    #[test]
+    fn gliders_growing_a_universe_up_and_left_move_back() {
+        // Heading northwest, which grows margins in front of it.
+        let glider = &["OOO", "O..", ".O."];
+        let cycle = cycle_of(universe(Edge::Grow, glider, 1, 1));
+        assert_eq!((cycle.period, cycle.shift), (4, (-1, -1)));
+        assert_eq!(cycle.direction(), "northwest");
+    }
+
+    #[test]
+    fn without_motion_tracking_gliders_are_not_cycles() {
+        let mut universe = universe(Edge::Grow, GLIDER, 2, 2);
+        let mut detector = CycleDetector::new();
+        for _ in 0..40 {
+            assert_eq!(detector.observe(&universe), None);
+            universe.tick();
+        }
+    }
+}
@@ -0,0 +1,111 @@
+/// The rows of a universe that changed, with their new cells, for bringing
+/// a copy of the universe kept elsewhere up to date without sending all of
+/// it. Taken with `Universe::take_delta` and applied with
+/// `Universe::apply_delta`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Delta {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// The generation the universe reached.
+    pub(crate) generation: u64,
+    /// The cells born and the cells that died in the last tick.
+    pub(crate) births: u64,
+    pub(crate) deaths: u64,
+    /// The packed cells of each changed row, in row order.
+    pub(crate) rows: Vec<(u32, Vec<u64>)>,
+}
+
+impl Delta {
+    /// Whether no row changed.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// How many rows changed.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The delta as a JSON object, each changed row listed with the
+    /// columns of its live cells:
+    ///
+    /// ```json
+    /// {"generation":12,"width":64,"height":32,"births":3,"deaths":4,
+    ///  "rows":[[5,[10,11,12]],[6,[]]]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|(row, words)| {
+                let columns: Vec<String> = (0..)
+                    .zip(words)
+                    .flat_map(|(index, &word)| {
+                        (0..64)
+                            .filter(move |bit| word >> bit & 1 == 1)
+                            .map(move |bit| (index * 64 + bit).to_string())
+                    })
+                    .collect();
+                format!("[{},[{}]]", row, columns.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"generation\":{},\"width\":{},\"height\":{},\"births\":{},\"deaths\":{},\"rows\":[{}]}}",
+            self.generation,
+            self.width,
+            self.height,
+            self.births,
+            self.deaths,
+            rows.join(",")
+        )
+    }
+
+    /// The delta packed into bytes, every number little-endian: the width
+    /// and height as 32 bits, the generation as 64, the number of changed
+    /// rows as 32, then for each row its index as 32 bits followed by its
+    /// cells as `(width + 63) / 64` words of 64, bit `c % 64` of word
+    /// `c / 64` holding column `c`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        bytes.extend_from_slice(&(self.rows.len() as u32).to_le_bytes());
+        for (row, words) in &self.rows {
+            bytes.extend_from_slice(&row.to_le_bytes());
+            for word in words {
+                bytes.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Fold in the changes taken after this delta, so applying the result
+    /// has the same effect as applying both in turn.
+    pub fn merge(&mut self, later: Delta) {
+        self.generation = later.generation;
+        (self.births, self.deaths) = (later.births, later.deaths);
+        if (later.width, later.height) != (self.width, self.height) {
+            // A universe that changed size lists every row.
+            *self = later;
+            return;
+        }
+
+        let earlier = std::mem::take(&mut self.rows);
+        let mut earlier = earlier.into_iter().peekable();
+        let mut later = later.rows.into_iter().peekable();
+        loop {
+            let next = match (earlier.peek(), later.peek()) {
+                (Some((a, _)), Some((b, _))) if a < b => earlier.next(),
+                (Some((a, _)), Some((b, _))) if a == b => {
+                    earlier.next();
+                    later.next()
+                }
+                (_, Some(_)) => later.next(),
+                (Some(_), None) => earlier.next(),
+                (None, None) => break,
+            };
+            self.rows.extend(next);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::step::WORD_BITS;
+use crate::Universe;
+
+/// How two universes differ cell by cell, for checking engines against each
+/// other or following two runs that started out alike. Taken with
+/// `Universe::diff`.
+///
+/// Universes of different sizes are compared over the larger of the two,
+/// with the cells past the edges of the smaller one taken to be dead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// The cells alive in just one of the two universes, row by row.
+    pub cells: Vec<(u32, u32)>,
+    /// How many of `cells` are alive in the first universe only, and how
+    /// many in the second only.
+    pub only_first: u64,
+    pub only_second: u64,
+}
+
+impl DiffReport {
+    pub(crate) fn between(first: &Universe, second: &Universe) -> DiffReport {
+        let mut report = DiffReport::default();
+        let words = |universe: &Universe, row: u32, word: usize| {
+            if row < universe.height() {
+                universe.row_words(row).get(word).copied().unwrap_or(0)
+            } else {
+                0
+            }
+        };
+        let stride = first.stride().max(second.stride());
+        for row in 0..first.height().max(second.height()) {
+            for word in 0..stride {
+                let (a, b) = (words(first, row, word), words(second, row, word));
+                report.only_first += (a & !b).count_ones() as u64;
+                report.only_second += (b & !a).count_ones() as u64;
+                let mut differing = a ^ b;
+                while differing != 0 {
+                    let bit = differing.trailing_zeros();
+                    report.cells.push((row, word as u32 * WORD_BITS + bit));
+                    differing &= differing - 1;
+                }
+            }
+        }
+        report
+    }
+
+    /// How many cells differ, which is the Hamming distance between the
+    /// two boards.
+    pub fn hamming(&self) -> u64 {
+        self.cells.len() as u64
+    }
+
+    /// Whether every cell is the same in both universes.
+    pub fn is_identical(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// How many more cells are alive in the second universe than in the
+    /// first, negative if fewer.
+    pub fn population_change(&self) -> i64 {
+        self.only_second as i64 - self.only_first as i64
+    }
+}
+
+impl fmt::Display for DiffReport {
+    /// Such as `12 cells differ (7 only in the first, 5 only in the second)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.hamming() {
+            0 => f.write_str("identical"),
+            cells => write!(
+                f,
+                "{} {} ({} only in the first, {} only in the second)",
+                cells,
+                if cells == 1 {
+                    "cell differs"
+                } else {
+                    "cells differ"
+                },
+                self.only_first,
+                self.only_second
+            ),
+        }
+    }
+}
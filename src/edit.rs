@@ -0,0 +1,121 @@
+use crate::pattern::Pattern;
+use crate::{Cell, Universe};
+
+/// One cell changed by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Change {
+    row: u32,
+    column: u32,
+    before: Cell,
+    after: Cell,
+}
+
+/// Cells changed by hand, grouped into the operations that changed them,
+/// so a toggle or a whole brush stroke can be undone and redone at once.
+///
+/// Changes are replayed cell by cell on the current board, so undoing an
+/// edit leaves the generations simulated since alone.
+#[derive(Debug, Default)]
+pub struct EditLog {
+    done: Vec<Vec<Change>>,
+    undone: Vec<Vec<Change>>,
+}
+
+impl EditLog {
+    pub fn new() -> EditLog {
+        EditLog::default()
+    }
+
+    /// Start a new operation. Whatever was undone can no longer be redone.
+    pub fn begin(&mut self) {
+        self.undone.clear();
+        if self.done.last().is_none_or(|changes| !changes.is_empty()) {
+            self.done.push(Vec::new());
+        }
+    }
+
+    /// Set a cell as part of the current operation, starting one if needed.
+    pub fn set_cell(&mut self, universe: &mut Universe, row: u32, column: u32, cell: Cell) {
+        let before = universe.get_cell(row, column);
+        if before == cell {
+            return;
+        }
+        if self.done.is_empty() {
+            self.begin();
+        }
+        universe.set_cell(row, column, cell);
+        if let Some(changes) = self.done.last_mut() {
+            changes.push(Change {
+                row,
+                column,
+                before,
+                after: cell,
+            });
+        }
+    }
+
+    /// Toggle a cell as part of the current operation.
+    pub fn toggle_cell(&mut self, universe: &mut Universe, row: u32, column: u32) {
+        let cell = universe.get_cell(row, column).toggled();
+        self.set_cell(universe, row, column, cell);
+    }
+
+    /// Bring the live cells of `pattern` to life with its top left corner
+    /// at `row` and `column`, as `Universe::insert_pattern` does, as part of
+    /// the current operation.
+    pub fn insert_pattern(
+        &mut self,
+        universe: &mut Universe,
+        pattern: &Pattern,
+        row: u32,
+        column: u32,
+    ) {
+        for (r, c) in pattern.cells_at(row, column) {
+            if r < universe.height() && c < universe.width() {
+                self.set_cell(universe, r, c, Cell::Alive);
+            }
+        }
+    }
+
+    /// Revert the last operation, returning whether there was one.
+    pub fn undo(&mut self, universe: &mut Universe) -> bool {
+        while let Some(changes) = self.done.pop() {
+            if changes.is_empty() {
+                continue;
+            }
+            for change in changes.iter().rev() {
+                apply(universe, change.row, change.column, change.before);
+            }
+            self.undone.push(changes);
+            return true;
+        }
+        false
+    }
+
+    /// Apply the last undone operation again, returning whether there was one.
+    pub fn redo(&mut self, universe: &mut Universe) -> bool {
+        match self.undone.pop() {
+            Some(changes) => {
+                for change in &changes {
+                    apply(universe, change.row, change.column, change.after);
+                }
+                self.done.push(changes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget every operation, as when the board is replaced.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+/// Set a cell the log remembers, unless the board has since shrunk past it.
+fn apply(universe: &mut Universe, row: u32, column: u32, cell: Cell) {
+    if row < universe.height() && column < universe.width() {
+        universe.set_cell(row, column, cell);
+    }
+}
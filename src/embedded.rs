@@ -0,0 +1,125 @@
+//! Universes drawn on `embedded-graphics` displays, such as SSD1306 OLEDs
+//! and LED matrices.
+//!
+//! The crate builds on `std`, so this is for boards running an operating
+//! system, such as a Raspberry Pi driving a display over SPI or I2C, rather
+//! than `no_std` firmware.
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+use crate::{Cell, Universe};
+
+/// Every cell of a universe as a square of pixels, live cells in one color
+/// and dead ones in another.
+///
+/// Buffered drivers, such as that of the SSD1306 in its graphics mode, take
+/// the whole frame before it is flushed to the display.
+#[derive(Clone, Copy)]
+pub struct UniverseDrawable<'a, C> {
+    universe: &'a Universe,
+    top_left: Point,
+    /// Pixels on a side of each cell.
+    scale: u32,
+    alive: C,
+    dead: C,
+}
+
+impl<'a> UniverseDrawable<'a, BinaryColor> {
+    /// Draw `universe` a pixel per cell from the top left corner of the
+    /// display, with lit pixels for live cells.
+    pub fn new(universe: &'a Universe) -> UniverseDrawable<'a, BinaryColor> {
+        UniverseDrawable::with_colors(universe, BinaryColor::On, BinaryColor::Off)
+    }
+}
+
+impl<'a, C: PixelColor> UniverseDrawable<'a, C> {
+    /// Draw `universe` with the given colors for live and dead cells, for
+    /// color and grayscale displays.
+    pub fn with_colors(universe: &'a Universe, alive: C, dead: C) -> UniverseDrawable<'a, C> {
+        UniverseDrawable {
+            universe,
+            top_left: Point::zero(),
+            scale: 1,
+            alive,
+            dead,
+        }
+    }
+
+    /// Draw each cell `scale` pixels on a side, at least one.
+    pub fn scaled(self, scale: u32) -> UniverseDrawable<'a, C> {
+        UniverseDrawable {
+            scale: scale.max(1),
+            ..self
+        }
+    }
+
+    /// Draw the top left cell at `top_left` instead of the corner of the
+    /// display.
+    pub fn at(self, top_left: Point) -> UniverseDrawable<'a, C> {
+        UniverseDrawable { top_left, ..self }
+    }
+}
+
+impl<C: PixelColor> Dimensions for UniverseDrawable<'_, C> {
+    fn bounding_box(&self) -> Rectangle {
+        let size = Size::new(self.universe.width(), self.universe.height());
+        Rectangle::new(self.top_left, size * self.scale)
+    }
+}
+
+impl<C: PixelColor> Drawable for UniverseDrawable<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        // Every pixel of the area in turn, which drivers can send in one
+        // go and clip to the display.
+        let area = self.bounding_box();
+        let colors = area.points().map(|point| {
+            let offset = point - self.top_left;
+            let row = offset.y as u32 / self.scale;
+            let column = offset.x as u32 / self.scale;
+            match self.universe.get_cell(row, column) {
+                Cell::Alive => self.alive,
+                Cell::Dead => self.dead,
+            }
+        });
+        target.fill_contiguous(&area, colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Gray4;
+
+    #[test]
+    fn cells_become_pixels() {
+        let mut universe = Universe::empty(3, 2);
+        universe.set_cells(&[(0, 1), (1, 2)]);
+        let mut display = MockDisplay::new();
+        UniverseDrawable::new(&universe)
+            .at(Point::new(1, 1))
+            .draw(&mut display)
+            .unwrap();
+        display.assert_pattern(&["    ", " .#.", " ..#"]);
+    }
+
+    #[test]
+    fn scaled_cells_cover_squares() {
+        let mut universe = Universe::empty(2, 2);
+        universe.set_cells(&[(0, 0), (1, 1)]);
+        let mut display = MockDisplay::new();
+        UniverseDrawable::with_colors(&universe, Gray4::new(0xf), Gray4::new(0))
+            .scaled(2)
+            .draw(&mut display)
+            .unwrap();
+        display.assert_pattern(&["FF00", "FF00", "00FF", "00FF"]);
+    }
+}
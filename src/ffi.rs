@@ -0,0 +1,320 @@
+//! A C interface to the engine, for visualizers and game engines written
+//! in C or C++.
+//!
+//! A universe is handed out as an opaque pointer and must be given back to
+//! `gol_universe_free`. Every function accepts a null universe and does
+//! nothing with it. The declarations are in `include/game_of_life.h`,
+//! generated from this file with
+//! `cbindgen --config cbindgen.toml --output include/game_of_life.h`.
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use crate::{Cell, Rule, Universe};
+
+/// A new universe of `width` by `height` dead cells.
+#[no_mangle]
+pub extern "C" fn gol_universe_new(width: u32, height: u32) -> *mut Universe {
+    Box::into_raw(Box::new(Universe::empty(width, height)))
+}
+
+/// A new universe with each cell alive with probability `density`, drawn
+/// from `seed` the same way `--seed` does on the command line.
+#[no_mangle]
+pub extern "C" fn gol_universe_new_random(
+    width: u32,
+    height: u32,
+    density: f64,
+    seed: u64,
+) -> *mut Universe {
+    Box::into_raw(Box::new(Universe::random(width, height, density, seed)))
+}
+
+/// Destroy a universe.
+///
+/// # Safety
+///
+/// `universe` must be null or come from one of the constructors above, and
+/// must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_free(universe: *mut Universe) {
+    if !universe.is_null() {
+        drop(Box::from_raw(universe));
+    }
+}
+
+/// Advance the universe by `generations` generations.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe not in use elsewhere.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_tick(universe: *mut Universe, generations: u32) {
+    if let Some(universe) = universe.as_mut() {
+        for _ in 0..generations {
+            universe.tick();
+        }
+    }
+}
+
+/// Width in cells, or 0 for a null universe.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_width(universe: *const Universe) -> u32 {
+    universe.as_ref().map_or(0, Universe::width)
+}
+
+/// Height in cells, or 0 for a null universe.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_height(universe: *const Universe) -> u32 {
+    universe.as_ref().map_or(0, Universe::height)
+}
+
+/// Generations ticked since the universe was made.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_generation(universe: *const Universe) -> u64 {
+    universe.as_ref().map_or(0, Universe::generation)
+}
+
+/// Number of live cells.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_population(universe: *const Universe) -> u64 {
+    universe.as_ref().map_or(0, Universe::population)
+}
+
+/// Words of 64 cells holding each row in the buffer of
+/// `gol_universe_cells`.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_stride(universe: *const Universe) -> usize {
+    universe.as_ref().map_or(0, Universe::stride)
+}
+
+/// The packed cells, `stride` words per row from the top, with bit
+/// `c % 64` of word `c / 64` of a row holding column `c`. The number of
+/// words is stored in `len` unless it is null.
+///
+/// The buffer belongs to the universe and is only valid until it is next
+/// ticked, changed or freed.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe, and `len` null or valid to
+/// write.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_cells(
+    universe: *const Universe,
+    len: *mut usize,
+) -> *const u64 {
+    let words = universe.as_ref().map_or(&[][..], Universe::words);
+    if let Some(len) = len.as_mut() {
+        *len = words.len();
+    }
+    if words.is_empty() {
+        ptr::null()
+    } else {
+        words.as_ptr()
+    }
+}
+
+/// Whether the cell at `row` and `column` is alive, false outside the
+/// universe.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_get_cell(
+    universe: *const Universe,
+    row: u32,
+    column: u32,
+) -> bool {
+    match universe.as_ref() {
+        Some(universe) if row < universe.height() && column < universe.width() => {
+            universe.get_cell(row, column) == Cell::Alive
+        }
+        _ => false,
+    }
+}
+
+/// Bring the cell at `row` and `column` to life, or kill it. Returns false
+/// and changes nothing if the cell is outside the universe.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe not in use elsewhere.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_set_cell(
+    universe: *mut Universe,
+    row: u32,
+    column: u32,
+    alive: bool,
+) -> bool {
+    match universe.as_mut() {
+        Some(universe) if row < universe.height() && column < universe.width() => {
+            let cell = if alive { Cell::Alive } else { Cell::Dead };
+            universe.set_cell(row, column, cell);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Switch to the rule written in `rule`, as `B3/S23` or any other form
+/// `--rule` takes. Returns false and keeps the current rule if it cannot
+/// be read.
+///
+/// # Safety
+///
+/// `universe` must be null or a live universe not in use elsewhere, and
+/// `rule` null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gol_universe_set_rule(
+    universe: *mut Universe,
+    rule: *const c_char,
+) -> bool {
+    let (universe, rule) = match (universe.as_mut(), rule.is_null()) {
+        (Some(universe), false) => (universe, CStr::from_ptr(rule)),
+        _ => return false,
+    };
+    match rule.to_str().map(Rule::parse) {
+        Ok(Ok(rule)) => {
+            universe.set_rule(rule);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn universes_are_made_stepped_and_freed() {
+        unsafe {
+            let universe = gol_universe_new(5, 4);
+            assert_eq!(gol_universe_width(universe), 5);
+            assert_eq!(gol_universe_height(universe), 4);
+            for column in 1..4 {
+                assert!(gol_universe_set_cell(universe, 2, column, true));
+            }
+            assert_eq!(gol_universe_population(universe), 3);
+            gol_universe_tick(universe, 3);
+            assert_eq!(gol_universe_generation(universe), 3);
+            // The blinker stands upright after an odd number of ticks.
+            assert!(gol_universe_get_cell(universe, 1, 2));
+            assert!(!gol_universe_get_cell(universe, 2, 1));
+            gol_universe_free(universe);
+        }
+    }
+
+    #[test]
+    fn random_universes_match_the_seeded_soups() {
+        unsafe {
+            let universe = gol_universe_new_random(70, 20, 0.3, 9);
+            assert_eq!(
+                (*universe).get_cells(),
+                Universe::random(70, 20, 0.3, 9).get_cells()
+            );
+            gol_universe_free(universe);
+        }
+    }
+
+    #[test]
+    fn cells_are_handed_out_packed() {
+        unsafe {
+            let universe = gol_universe_new(70, 2);
+            gol_universe_set_cell(universe, 0, 0, true);
+            gol_universe_set_cell(universe, 1, 65, true);
+            let stride = gol_universe_stride(universe);
+            assert_eq!(stride, 2);
+            let mut len = 0;
+            let words = gol_universe_cells(universe, &mut len);
+            assert_eq!(len, 2 * stride);
+            let words = std::slice::from_raw_parts(words, len);
+            assert_eq!(words[0], 1);
+            assert_eq!(words[stride + 1], 1 << 1);
+            assert!(!gol_universe_cells(universe, ptr::null_mut()).is_null());
+            gol_universe_free(universe);
+        }
+    }
+
+    #[test]
+    fn cells_outside_the_universe_are_left_alone() {
+        unsafe {
+            let universe = gol_universe_new(3, 3);
+            assert!(!gol_universe_set_cell(universe, 3, 0, true));
+            assert!(!gol_universe_set_cell(universe, 0, 3, true));
+            assert!(!gol_universe_get_cell(universe, 5, 5));
+            assert_eq!(gol_universe_population(universe), 0);
+            gol_universe_free(universe);
+        }
+    }
+
+    #[test]
+    fn rules_are_read_from_c_strings() {
+        unsafe {
+            let universe = gol_universe_new(3, 3);
+            assert!(gol_universe_set_rule(universe, c"B36/S23".as_ptr()));
+            assert_eq!((*universe).rule().to_string(), "B36/S23");
+            assert!(!gol_universe_set_rule(universe, c"not a rule".as_ptr()));
+            assert!(!gol_universe_set_rule(universe, ptr::null()));
+            assert_eq!((*universe).rule().to_string(), "B36/S23");
+            gol_universe_free(universe);
+        }
+    }
+
+    #[test]
+    fn null_universes_are_ignored() {
+        let null = ptr::null_mut();
+        unsafe {
+            gol_universe_tick(null, 5);
+            assert_eq!(gol_universe_width(null), 0);
+            assert_eq!(gol_universe_height(null), 0);
+            assert_eq!(gol_universe_generation(null), 0);
+            assert_eq!(gol_universe_population(null), 0);
+            assert_eq!(gol_universe_stride(null), 0);
+            let mut len = 1;
+            assert!(gol_universe_cells(null, &mut len).is_null());
+            assert_eq!(len, 0);
+            assert!(!gol_universe_get_cell(null, 0, 0));
+            assert!(!gol_universe_set_cell(null, 0, 0, true));
+            assert!(!gol_universe_set_rule(null, c"B3/S23".as_ptr()));
+            gol_universe_free(null);
+        }
+    }
+
+    #[test]
+    fn the_header_declares_every_function() {
+        let header = include_str!("../include/game_of_life.h");
+        let source = include_str!("ffi.rs");
+        let defined: Vec<&str> = source
+            .split("extern \"C\" fn ")
+            .skip(1)
+            .filter_map(|rest| rest.split('(').next())
+            .collect();
+        assert_eq!(defined.len(), 13);
+        for name in defined {
+            assert!(header.contains(&format!("{}(", name)), "{}", name);
+        }
+    }
+}
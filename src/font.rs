@@ -0,0 +1,474 @@
+//! Bitmap fonts for writing text in live cells.
+//!
+//! Each glyph is a fixed number of rows, each row a number whose bits are
+//! its cells with the leftmost cell highest. Letters have no lower case, so
+//! text is written in capitals.
+
+use crate::pattern::Pattern;
+
+/// The built-in bitmap fonts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Font {
+    /// Glyphs 3 cells wide and 5 high.
+    Small,
+    /// Glyphs 5 cells wide and 7 high.
+    #[default]
+    Standard,
+}
+
+/// Glyphs 3 cells wide and 5 high.
+const SMALL: &[(char, [u8; 5])] = &[
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b110, 0b001, 0b010, 0b100, 0b111]),
+    ('3', [0b110, 0b001, 0b010, 0b001, 0b110]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b110, 0b001, 0b110]),
+    ('6', [0b011, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b110]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('?', [0b110, 0b001, 0b010, 0b000, 0b010]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+];
+
+/// Glyphs 5 cells wide and 7 high.
+const STANDARD: &[(char, [u8; 7])] = &[
+    (
+        ' ',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        'A',
+        [
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'B',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'C',
+        [
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'D',
+        [
+            0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'E',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'F',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'G',
+        [
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+    ),
+    (
+        'H',
+        [
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'I',
+        [
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        'J',
+        [
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+    ),
+    (
+        'K',
+        [
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'L',
+        [
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'M',
+        [
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'N',
+        [
+            0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'O',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'P',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'Q',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+    ),
+    (
+        'R',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'S',
+        [
+            0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+        ],
+    ),
+    (
+        'T',
+        [
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'U',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'V',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+    ),
+    (
+        'W',
+        [
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+        ],
+    ),
+    (
+        'X',
+        [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'Y',
+        [
+            0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'Z',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        '0',
+        [
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '1',
+        [
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        '2',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+        ],
+    ),
+    (
+        '3',
+        [
+            0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '4',
+        [
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+    ),
+    (
+        '5',
+        [
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '6',
+        [
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '7',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+    ),
+    (
+        '8',
+        [
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '9',
+        [
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+    ),
+    (
+        '.',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+        ],
+    ),
+    (
+        ',',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '!',
+        [
+            0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '?',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '-',
+        [
+            0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        ':',
+        [
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+        ],
+    ),
+    (
+        '\'',
+        [
+            0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+];
+
+impl Font {
+    pub const NAMES: [&'static str; 2] = ["small", "standard"];
+
+    pub fn from_name(name: &str) -> Option<Font> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "small" => Some(Font::Small),
+            "standard" => Some(Font::Standard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Font::Small => "small",
+            Font::Standard => "standard",
+        }
+    }
+
+    /// The width and height of every glyph, in cells.
+    pub fn glyph_size(self) -> (u32, u32) {
+        match self {
+            Font::Small => (3, 5),
+            Font::Standard => (5, 7),
+        }
+    }
+
+    /// The rows of the glyph for `ch`, in either case.
+    fn glyph(self, ch: char) -> Option<&'static [u8]> {
+        let ch = ch.to_ascii_uppercase();
+        match self {
+            Font::Small => SMALL
+                .iter()
+                .find(|&&(glyph, _)| glyph == ch)
+                .map(|(_, rows)| &rows[..]),
+            Font::Standard => STANDARD
+                .iter()
+                .find(|&&(glyph, _)| glyph == ch)
+                .map(|(_, rows)| &rows[..]),
+        }
+    }
+
+    /// The live cells spelling out `text`, a column apart between glyphs
+    /// and a row apart between lines, each line starting at the left.
+    ///
+    /// Characters the font has no glyph for are drawn as `?`.
+    pub(crate) fn banner(self, text: &str) -> Pattern {
+        let (width, height) = self.glyph_size();
+        let mut cells = Vec::new();
+        let mut size = (0, 0);
+        for (line, chars) in text.lines().enumerate() {
+            let top = line as u32 * (height + 1);
+            for (index, ch) in chars.chars().enumerate() {
+                let left = index as u32 * (width + 1);
+                let rows = self.glyph(ch).or_else(|| self.glyph('?')).unwrap_or(&[]);
+                for (row, bits) in rows.iter().enumerate() {
+                    for column in 0..width {
+                        if bits >> (width - 1 - column) & 1 == 1 {
+                            cells.push((top + row as u32, left + column));
+                        }
+                    }
+                }
+                size.0 = size.0.max(left + width);
+            }
+            size.1 = top + height;
+        }
+        Pattern::new(size.0, size.1, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cells of `pattern` as lines of `#` and `.`.
+    fn picture(pattern: &Pattern) -> Vec<String> {
+        let mut lines = vec![".".repeat(pattern.width() as usize); pattern.height() as usize];
+        for &(row, column) in pattern.cells() {
+            lines[row as usize].replace_range(column as usize..column as usize + 1, "#");
+        }
+        lines
+    }
+
+    #[test]
+    fn fonts_are_found_by_name() {
+        for name in Font::NAMES {
+            assert_eq!(Font::from_name(name).unwrap().name(), name);
+        }
+        assert_eq!(Font::from_name(" Small "), Some(Font::Small));
+        assert_eq!(Font::from_name("tiny"), None);
+    }
+
+    #[test]
+    fn glyphs_fit_their_font() {
+        let small: Vec<char> = SMALL.iter().map(|&(ch, _)| ch).collect();
+        let standard: Vec<char> = STANDARD.iter().map(|&(ch, _)| ch).collect();
+        assert_eq!(small, standard);
+        for font in [Font::Small, Font::Standard] {
+            let (width, height) = font.glyph_size();
+            for &ch in &small {
+                let rows = font.glyph(ch).unwrap();
+                assert_eq!(rows.len(), height as usize, "{:?}", ch);
+                assert!(rows.iter().all(|&bits| bits >> width == 0), "{:?}", ch);
+            }
+        }
+    }
+
+    #[test]
+    fn banners_spell_out_the_text() {
+        assert_eq!(
+            picture(&Font::Small.banner("Hi!")),
+            [
+                "#.#.###..#.",
+                "#.#..#...#.",
+                "###..#...#.",
+                "#.#..#.....",
+                "#.#.###..#.",
+            ]
+        );
+    }
+
+    #[test]
+    fn banners_put_each_line_below_the_last() {
+        let banner = Font::Small.banner("AB\nC");
+        assert_eq!((banner.width(), banner.height()), (7, 11));
+        let picture = picture(&banner);
+        assert_eq!(picture[5], ".......");
+        assert_eq!(&picture[6][..3], ".##");
+        assert_eq!(&picture[6][3..], "....");
+
+        let standard = Font::Standard.banner("GO");
+        assert_eq!((standard.width(), standard.height()), (11, 7));
+    }
+
+    #[test]
+    fn characters_without_glyphs_are_question_marks() {
+        assert_eq!(Font::Small.banner("é"), Font::Small.banner("?"));
+        assert_eq!(Font::Standard.banner("~"), Font::Standard.banner("?"));
+        assert_eq!(Font::Small.banner(""), Pattern::new(0, 0, Vec::new()));
+    }
+}
@@ -0,0 +1,398 @@
+//! Stepping a universe on the GPU with a wgpu compute shader, for boards of
+//! many millions of cells.
+//!
+//! The cells stay on the GPU in two buffers, each generation read from one
+//! and written to the other, and only come back when gathered. Every word
+//! of every row is stepped each generation, so unlike `Universe` the time
+//! taken does not shrink when the board settles down.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::channel;
+
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+use crate::{Edge, Universe};
+
+/// Cells each invocation of the shader steps, in one word.
+const WORD_BITS: usize = 32;
+
+/// Invocations in a workgroup along a row and down a column, as
+/// `step.wgsl` declares.
+const WORKGROUP_WIDTH: u32 = 16;
+const WORKGROUP_HEIGHT: u32 = 4;
+
+/// Generations sent to the GPU in each submission, to keep command buffers
+/// small on long runs.
+const SUBMISSION: u64 = 256;
+
+/// A universe held and stepped on the GPU.
+pub struct GpuUniverse {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    /// Stepping reads from `buffers[i]` and writes to the other one.
+    bind_groups: [wgpu::BindGroup; 2],
+    buffers: [wgpu::Buffer; 2],
+    /// Which of `buffers` holds the current generation.
+    current: usize,
+    /// Where the cells are copied to be read back.
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    /// 32-bit words in each row.
+    stride: u32,
+    generation: u64,
+    adapter: String,
+}
+
+impl GpuUniverse {
+    /// Copy `universe` to the first GPU wgpu finds, or a software one if
+    /// that is all there is.
+    pub fn new(universe: &Universe) -> Result<GpuUniverse, GpuError> {
+        let boundary = universe.boundary();
+        let edge = |edge: Edge| match edge {
+            Edge::Torus => Ok(0u32),
+            Edge::Dead => Ok(1),
+            Edge::Mirror => Ok(2),
+            Edge::Grow => Err(GpuError::Grow),
+        };
+        let (edge_x, edge_y) = (edge(boundary.x)?, edge(boundary.y)?);
+        let (width, height) = (universe.width(), universe.height());
+        let stride = (universe.stride() * 2) as u32;
+
+        let instance =
+            wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..wgpu::RequestAdapterOptions::default()
+        }))
+        .map_err(|error| GpuError::Unavailable(error.to_string()))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("game_of_life"),
+            required_limits: adapter.limits(),
+            ..wgpu::DeviceDescriptor::default()
+        }))
+        .map_err(|error| GpuError::Unavailable(error.to_string()))?;
+
+        let size = stride as u64 * height as u64 * 4;
+        let limits = device.limits();
+        let groups = limits.max_compute_workgroups_per_dimension;
+        if size > limits.max_storage_buffer_binding_size
+            || stride.div_ceil(WORKGROUP_WIDTH) > groups
+            || height.div_ceil(WORKGROUP_HEIGHT) > groups
+        {
+            return Err(GpuError::TooLarge);
+        }
+
+        let rule = universe.rule();
+        let mask = |counts: fn(&crate::Rule, u8) -> bool| {
+            (0..=8)
+                .filter(|&count| counts(&rule, count))
+                .fold(0u32, |mask, count| mask | 1 << count)
+        };
+        let params: [u32; 8] = [
+            width,
+            height,
+            stride,
+            mask(crate::Rule::is_born),
+            mask(crate::Rule::survives),
+            edge_x,
+            edge_y,
+            0,
+        ];
+        let params = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("params"),
+            contents: &words_to_bytes(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let cells = to_gpu_words(universe.words());
+        let buffer = |label| {
+            device.create_buffer_init(&BufferInitDescriptor {
+                label: Some(label),
+                contents: &words_to_bytes(&cells),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            })
+        };
+        let buffers = [buffer("cells"), buffer("next")];
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("step"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("step.wgsl"))),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("step"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        let layout = pipeline.get_bind_group_layout(0);
+        let bind_group = |from: &wgpu::Buffer, to: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: from.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: to.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let bind_groups = [
+            bind_group(&buffers[0], &buffers[1]),
+            bind_group(&buffers[1], &buffers[0]),
+        ];
+
+        Ok(GpuUniverse {
+            adapter: adapter.get_info().name,
+            device,
+            queue,
+            pipeline,
+            bind_groups,
+            buffers,
+            current: 0,
+            readback,
+            width,
+            height,
+            stride,
+            generation: universe.generation(),
+        })
+    }
+
+    /// The name of the GPU the universe runs on.
+    pub fn adapter(&self) -> &str {
+        &self.adapter
+    }
+
+    /// Simulate `generations` generations, returning once the GPU is done.
+    pub fn step(&mut self, generations: u64) {
+        let mut left = generations;
+        while left > 0 {
+            let batch = left.min(SUBMISSION);
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&self.pipeline);
+                for _ in 0..batch {
+                    pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+                    pass.dispatch_workgroups(
+                        self.stride.div_ceil(WORKGROUP_WIDTH),
+                        self.height.div_ceil(WORKGROUP_HEIGHT),
+                        1,
+                    );
+                    self.current = 1 - self.current;
+                }
+            }
+            self.queue.submit([encoder.finish()]);
+            left -= batch;
+        }
+        self.wait();
+        self.generation += generations;
+    }
+
+    /// Copy the current cells and generation into `universe`, which must
+    /// have the size of the universe this one was made from.
+    pub fn gather(&self, universe: &mut Universe) {
+        assert_eq!(
+            (universe.width(), universe.height()),
+            (self.width, self.height),
+            "gathering a GPU universe into a universe of another size"
+        );
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(
+            &self.buffers[self.current],
+            0,
+            &self.readback,
+            0,
+            self.readback.size(),
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = channel();
+        self.readback
+            .map_async(wgpu::MapMode::Read, .., move |result| {
+                let _ = sender.send(result);
+            });
+        self.wait();
+        receiver
+            .recv()
+            .expect("the mapping finishes while waiting")
+            .expect("the readback buffer can be mapped");
+        {
+            let bytes = self
+                .readback
+                .get_mapped_range(..)
+                .expect("the readback buffer is mapped");
+            let words: Vec<u64> = bytes
+                .chunks_exact(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunks of 8 bytes")))
+                .collect();
+            let stride = universe.stride().max(1);
+            for (row, line) in (0..self.height).zip(words.chunks(stride)) {
+                universe.set_row_words(row, line);
+            }
+        }
+        self.readback.unmap();
+        universe.set_generation(self.generation);
+    }
+
+    fn wait(&self) {
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .expect("the GPU finishes its work");
+    }
+}
+
+/// The words of a universe split in two, low half first, the way they
+/// are laid out in memory on little-endian machines.
+fn to_gpu_words(words: &[u64]) -> Vec<u32> {
+    words
+        .iter()
+        .flat_map(|&word| [word as u32, (word >> WORD_BITS) as u32])
+        .collect()
+}
+
+fn words_to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Why a universe could not be put on the GPU.
+#[derive(Debug)]
+pub enum GpuError {
+    /// No GPU could be found or opened.
+    Unavailable(String),
+    /// Growing universes change size, which the buffers cannot.
+    Grow,
+    /// The universe does not fit in the buffers the GPU allows.
+    TooLarge,
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GpuError::Unavailable(message) => write!(f, "no usable GPU: {}", message),
+            GpuError::Grow => f.write_str("growing universes cannot run on the GPU"),
+            GpuError::TooLarge => f.write_str("the universe is too large for the GPU"),
+        }
+    }
+}
+
+impl Error for GpuError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Rng, Rule};
+
+    /// A random soup, a cell in every three or so alive.
+    fn soup(width: u32, height: u32, seed: u64) -> Universe {
+        let mut rng = Rng::new(seed);
+        let cells: Vec<(u32, u32)> = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .filter(|_| rng.chance(0.35))
+            .collect();
+        let mut universe = Universe::empty(width, height);
+        universe.set_cells(&cells);
+        universe
+    }
+
+    #[test]
+    fn gpu_ticks_match_the_cpu() {
+        let edges = [Edge::Torus, Edge::Dead, Edge::Mirror];
+        let sizes = [(1, 1), (3, 2), (31, 3), (33, 5), (64, 4), (65, 6), (100, 7)];
+        for (seed, &(width, height)) in sizes.iter().enumerate() {
+            for x in edges {
+                for y in edges {
+                    for rule in ["B3/S23", "B36/S23", "B2/S"] {
+                        let mut expected = soup(width, height, seed as u64);
+                        expected.set_rule(Rule::parse(rule).unwrap());
+                        expected.set_boundary(Boundary { x, y });
+                        let mut gpu = match GpuUniverse::new(&expected) {
+                            Ok(gpu) => gpu,
+                            // Nothing to check on machines without a GPU.
+                            Err(GpuError::Unavailable(_)) => return,
+                            Err(error) => panic!("{}", error),
+                        };
+                        let mut actual = Universe::empty(width, height);
+                        for generations in [1, 3] {
+                            gpu.step(generations);
+                            for _ in 0..generations {
+                                expected.tick();
+                            }
+                            gpu.gather(&mut actual);
+                            assert_eq!(actual.generation(), expected.generation());
+                            assert_eq!(
+                                actual.get_cells(),
+                                expected.get_cells(),
+                                "{}x{} {:?} {}",
+                                width,
+                                height,
+                                Boundary { x, y },
+                                rule
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn gliders_cross_the_halves_of_words() {
+        // 100 columns end a row half way through its second u64 word, and
+        // the gliders start across columns 31/32, 63/64 and the wrap.
+        let mut expected = Universe::empty(100, 20);
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        let cells: Vec<(u32, u32)> = [30, 62, 98]
+            .iter()
+            .flat_map(|&column| {
+                glider
+                    .iter()
+                    .map(move |&(row, offset)| (row + 4, (column + offset) % 100))
+            })
+            .collect();
+        expected.set_cells(&cells);
+        let mut gpu = match GpuUniverse::new(&expected) {
+            Ok(gpu) => gpu,
+            Err(GpuError::Unavailable(_)) => return,
+            Err(error) => panic!("{}", error),
+        };
+        let mut actual = Universe::empty(100, 20);
+        for _ in 0..40 {
+            gpu.step(1);
+            expected.tick();
+            gpu.gather(&mut actual);
+            assert_eq!(actual.get_cells(), expected.get_cells());
+        }
+    }
+
+    #[test]
+    fn growing_universes_stay_on_the_cpu() {
+        let mut universe = Universe::empty(8, 8);
+        universe.set_boundary(Boundary::uniform(Edge::Grow));
+        assert!(matches!(GpuUniverse::new(&universe), Err(GpuError::Grow)));
+    }
+}
@@ -0,0 +1,152 @@
+// One generation of a bit-packed universe, an invocation for each 32-bit
+// word of cells. Bit `c % 32` of word `c / 32` of a row holds column `c`,
+// the layout of `Universe` with each of its words split in two.
+//
+// The eight neighbors of all 32 cells of a word are added up at once with
+// the bitwise adders of the CPU step, from the word and the words either
+// side of it in the row itself and the rows above and below.
+
+struct Params {
+    width: u32,
+    height: u32,
+    // Words in each row.
+    stride: u32,
+    // Bit `n` is set if a dead cell with `n` live neighbors is born.
+    born: u32,
+    // Bit `n` is set if a live cell with `n` live neighbors survives.
+    survive: u32,
+    edge_x: u32,
+    edge_y: u32,
+    padding: u32,
+}
+
+const TORUS: u32 = 0u;
+const MIRROR: u32 = 2u;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> cells: array<u32>;
+@group(0) @binding(2) var<storage, read_write> next: array<u32>;
+
+// The coordinate `delta` cells away from `coordinate` on an axis of `size`
+// cells, as `Edge::resolve` finds it, or -1 if that neighbor is always dead.
+fn resolve(edge: u32, coordinate: u32, delta: i32, size: u32) -> i32 {
+    let moved = i32(coordinate) + delta;
+    if moved >= 0 && moved < i32(size) {
+        return moved;
+    }
+    if edge == TORUS {
+        return (moved + i32(size)) % i32(size);
+    }
+    if edge == MIRROR {
+        return i32(coordinate);
+    }
+    return -1;
+}
+
+fn alive(row: u32, column: u32) -> u32 {
+    return (cells[row * params.stride + column / 32u] >> (column % 32u)) & 1u;
+}
+
+// A word of a row shifted so that bit `c` holds the cell to the left of
+// column `c`, the cell itself and the cell to its right, all zeros past a
+// dead edge.
+struct Row {
+    left: u32,
+    cells: u32,
+    right: u32,
+}
+
+fn load_row(row: i32, word: u32) -> Row {
+    if row < 0 {
+        return Row(0u, 0u, 0u);
+    }
+    let r = u32(row);
+    let start = r * params.stride;
+    let own = cells[start + word];
+    var before = 0u;
+    if word > 0u {
+        before = cells[start + word - 1u] >> 31u;
+    } else {
+        let column = resolve(params.edge_x, 0u, -1, params.width);
+        if column >= 0 {
+            before = alive(r, u32(column));
+        }
+    }
+    var after = 0u;
+    if word + 1u < params.stride {
+        after = cells[start + word + 1u] << 31u;
+    }
+    var right = (own >> 1u) | after;
+    // The cell past the last column is not the padding next to it.
+    let last = params.width - 1u;
+    if word == last / 32u {
+        let column = resolve(params.edge_x, last, 1, params.width);
+        var edge = 0u;
+        if column >= 0 {
+            edge = alive(r, u32(column));
+        }
+        let bit = last % 32u;
+        right = (right & ~(1u << bit)) | (edge << bit);
+    }
+    return Row((own << 1u) | before, own, right);
+}
+
+struct Sum {
+    ones: u32,
+    twos: u32,
+}
+
+fn full_add(a: u32, b: u32, c: u32) -> Sum {
+    let partial = a ^ b;
+    return Sum(partial ^ c, (a & b) | (partial & c));
+}
+
+@compute @workgroup_size(16, 4)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let word = id.x;
+    let row = id.y;
+    if word >= params.stride || row >= params.height {
+        return;
+    }
+    let above = load_row(resolve(params.edge_y, row, -1, params.height), word);
+    let middle = load_row(i32(row), word);
+    let below = load_row(resolve(params.edge_y, row, 1, params.height), word);
+
+    let sum_above = full_add(above.left, above.cells, above.right);
+    let sum_below = full_add(below.left, below.cells, below.right);
+    let ones_side = middle.left ^ middle.right;
+    let twos_side = middle.left & middle.right;
+
+    let low = full_add(sum_above.ones, sum_below.ones, ones_side);
+    let high = full_add(sum_above.twos, sum_below.twos, twos_side);
+    let bit0 = low.ones;
+    let bit1 = high.ones ^ low.twos;
+    let more_fours = high.ones & low.twos;
+    let bit2 = high.twos ^ more_fours;
+    let bit3 = high.twos & more_fours;
+
+    var born = 0u;
+    var survive = 0u;
+    for (var count = 0u; count <= 8u; count++) {
+        var matches = select(~bit0, bit0, (count & 1u) != 0u);
+        matches &= select(~bit1, bit1, (count & 2u) != 0u);
+        matches &= select(~bit2, bit2, (count & 4u) != 0u);
+        matches &= select(~bit3, bit3, (count & 8u) != 0u);
+        if ((params.born >> count) & 1u) != 0u {
+            born |= matches;
+        }
+        if ((params.survive >> count) & 1u) != 0u {
+            survive |= matches;
+        }
+    }
+    var result = (middle.cells & survive) | (~middle.cells & born);
+
+    // Columns past the width stay dead.
+    let first = word * 32u;
+    if first >= params.width {
+        result = 0u;
+    } else if params.width - first < 32u {
+        result &= (1u << (params.width - first)) - 1u;
+    }
+    next[row * params.stride + word] = result;
+}
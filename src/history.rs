@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use crate::{Snapshot, Universe};
+
+/// The most recent generations of a universe, oldest first, so a session
+/// can step backwards through them.
+///
+/// The cells of every generation are bit-packed into one shared arena used
+/// as a ring buffer, so remembering a generation is a copy into space freed
+/// by older ones rather than an allocation of its own.
+pub struct History {
+    capacity: usize,
+    /// The cells of the states, each in one contiguous run of words. New
+    /// states go after the newest one, or wrap around to the start of the
+    /// arena when they don't fit at its end.
+    arena: Vec<u64>,
+    states: VecDeque<State>,
+}
+
+/// A generation remembered, with where its cells are in the arena.
+struct State {
+    generation: u64,
+    width: u32,
+    height: u32,
+    start: usize,
+    len: usize,
+}
+
+impl State {
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+impl History {
+    /// Keep up to `capacity` generations, forgetting the oldest ones first.
+    pub fn new(capacity: usize) -> History {
+        History {
+            capacity,
+            arena: Vec::new(),
+            states: VecDeque::with_capacity(capacity.min(1024)),
+        }
+    }
+
+    /// Remember the current generation of `universe`.
+    pub fn push(&mut self, universe: &Universe) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.states.len() == self.capacity {
+            self.states.pop_front();
+        }
+        let words = universe.words();
+        let start = self.place(words.len());
+        self.arena[start..start + words.len()].copy_from_slice(words);
+        self.states.push_back(State {
+            generation: universe.generation(),
+            width: universe.width(),
+            height: universe.height(),
+            start,
+            len: words.len(),
+        });
+    }
+
+    /// Where the next state of `len` words goes, making room if there is
+    /// none.
+    fn place(&mut self, len: usize) -> usize {
+        let (front, back) = match (self.states.front(), self.states.back()) {
+            (Some(front), Some(back)) => (front, back),
+            _ if len <= self.arena.len() => return 0,
+            _ => return self.grow(len),
+        };
+        let wrapped = back.start < front.start;
+        if !wrapped && back.end() + len <= self.arena.len() {
+            back.end()
+        } else if !wrapped && len <= front.start {
+            0
+        } else if wrapped && back.end() + len <= front.start {
+            back.end()
+        } else {
+            self.grow(len)
+        }
+    }
+
+    /// Move the states into a larger arena, packed from its start, with
+    /// room for `len` more words after them, returning where that room is.
+    fn grow(&mut self, len: usize) -> usize {
+        let used: usize = self.states.iter().map(|state| state.len).sum();
+        // Double as the history fills up, but no further than what a full
+        // history of states this size takes.
+        let size = (used + len).max((self.arena.len() * 2).min(self.capacity.saturating_mul(len)));
+        let mut arena = vec![0; size];
+        let mut start = 0;
+        for state in &mut self.states {
+            arena[start..start + state.len].copy_from_slice(&self.arena[state.start..state.end()]);
+            state.start = start;
+            start += state.len;
+        }
+        self.arena = arena;
+        start
+    }
+
+    /// Take back the most recent generation remembered.
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        let state = self.states.pop_back()?;
+        let words = &self.arena[state.start..state.end()];
+        Some(Snapshot::pack(
+            state.width,
+            state.height,
+            state.generation,
+            words,
+        ))
+    }
+
+    /// Take back the most recent generation remembered up to `generation`,
+    /// forgetting the ones after it, or the oldest one if none goes back
+    /// that far.
+    pub fn pop_to(&mut self, generation: u64) -> Option<Snapshot> {
+        while self.states.len() > 1
+            && self
+                .states
+                .back()
+                .is_some_and(|state| state.generation > generation)
+        {
+            self.states.pop_back();
+        }
+        self.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Forget every generation, keeping the arena for the ones to come.
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
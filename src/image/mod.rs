@@ -0,0 +1,385 @@
+//! Grayscale images to seed boards from, read from PNG, PGM or PPM files,
+//! resampled to any number of cells and dithered down to live and dead.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+mod inflate;
+mod png;
+mod pnm;
+
+/// The 8 by 8 Bayer matrix, the order in which ordered dithering turns on
+/// the cells of each tile as the image gets darker.
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// A grayscale image, one brightness level from 0 for black to 255 for
+/// white for each pixel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    levels: Vec<u8>,
+}
+
+impl Image {
+    /// An image of the given size with the level of each pixel, row by row
+    /// from the top left.
+    ///
+    /// # Panics
+    ///
+    /// If there is not one level for each pixel.
+    pub fn new(width: u32, height: u32, levels: Vec<u8>) -> Image {
+        assert_eq!(
+            levels.len(),
+            width as usize * height as usize,
+            "one level for each pixel"
+        );
+        Image {
+            width,
+            height,
+            levels,
+        }
+    }
+
+    /// Read a PNG, PGM or PPM image, in color or not. Color images are
+    /// turned to gray by their perceived brightness.
+    pub fn load(path: impl AsRef<Path>) -> Result<Image, ImageError> {
+        Image::decode(&fs::read(path)?)
+    }
+
+    /// Decode the bytes of a PNG, PGM or PPM image.
+    pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+        let (width, height, levels) = if png::sniff(bytes) {
+            png::decode(bytes)
+        } else if pnm::sniff(bytes) {
+            pnm::decode(bytes)
+        } else {
+            Err(String::from("not a PNG, PGM or PPM image"))
+        }
+        .map_err(ImageError::Decode)?;
+        Ok(Image::new(width, height, levels))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The level of the pixel at `row` and `column`.
+    pub fn level(&self, row: u32, column: u32) -> u8 {
+        self.levels[row as usize * self.width as usize + column as usize]
+    }
+
+    /// The largest size the image can be resized to that fits in `width`
+    /// by `height` while keeping its shape, at least a pixel each way.
+    pub fn fit(&self, width: u32, height: u32) -> (u32, u32) {
+        let (columns, rows) = (self.width.max(1) as f64, self.height.max(1) as f64);
+        let scale = (width as f64 / columns).min(height as f64 / rows);
+        let pixels = |side: f64| ((side * scale).round() as u32).max(1);
+        (pixels(columns), pixels(rows))
+    }
+
+    /// Swap light and dark, for images drawn light on a dark background.
+    pub fn invert(&mut self) {
+        self.levels.iter_mut().for_each(|level| *level = !*level);
+    }
+
+    /// The image resampled to `width` by `height` pixels, one axis at a
+    /// time: pixels shrunk together are averaged over the area they cover,
+    /// and pixels stretched apart are blended linearly.
+    pub fn resize(&self, width: u32, height: u32) -> Image {
+        if self.width == 0 || self.height == 0 || width == 0 || height == 0 {
+            return Image::new(width, height, vec![255; width as usize * height as usize]);
+        }
+        let (from, to) = (self.width as usize, width as usize);
+        let rows: Vec<f64> = self
+            .levels
+            .chunks(from)
+            .flat_map(|row| {
+                let row: Vec<f64> = row.iter().map(|&level| level as f64).collect();
+                resample(&row, to)
+            })
+            .collect();
+        let mut levels = vec![0; to * height as usize];
+        let mut column = Vec::with_capacity(self.height as usize);
+        for x in 0..to {
+            column.clear();
+            column.extend(rows.iter().skip(x).step_by(to));
+            for (y, level) in resample(&column, height as usize).into_iter().enumerate() {
+                levels[y * to + x] = level.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+        Image::new(width, height, levels)
+    }
+
+    /// Which pixels are dark enough to be live cells, row by row, picking
+    /// between black and white the way `dither` says.
+    pub fn dither(&self, dither: Dither) -> Vec<bool> {
+        let width = self.width as usize;
+        match dither {
+            Dither::Threshold => self.levels.iter().map(|&level| level < 128).collect(),
+            Dither::Ordered => self
+                .levels
+                .iter()
+                .enumerate()
+                .map(|(index, &level)| {
+                    let (x, y) = (index % width, index / width);
+                    let threshold = (BAYER[y % 8][x % 8] as f64 + 0.5) * 4.0;
+                    (level as f64) < threshold
+                })
+                .collect(),
+            Dither::FloydSteinberg => {
+                // Each pixel passes the error of rounding it to black or
+                // white on to the ones right of it and below it.
+                let mut levels: Vec<f64> = self.levels.iter().map(|&level| level as f64).collect();
+                let mut dark = vec![false; levels.len()];
+                let height = self.height as usize;
+                for y in 0..height {
+                    for x in 0..width {
+                        let index = y * width + x;
+                        let level = levels[index];
+                        dark[index] = level < 128.0;
+                        let error = level - if dark[index] { 0.0 } else { 255.0 };
+                        let mut spread = |dx: isize, dy: usize, share: f64| {
+                            let x = x as isize + dx;
+                            if (0..width as isize).contains(&x) && y + dy < height {
+                                levels[(y + dy) * width + x as usize] += error * share;
+                            }
+                        };
+                        spread(1, 0, 7.0 / 16.0);
+                        spread(-1, 1, 3.0 / 16.0);
+                        spread(0, 1, 5.0 / 16.0);
+                        spread(1, 1, 1.0 / 16.0);
+                    }
+                }
+                dark
+            }
+        }
+    }
+}
+
+/// `values` resampled to `size` values spread over the same span.
+fn resample(values: &[f64], size: usize) -> Vec<f64> {
+    let scale = values.len() as f64 / size as f64;
+    (0..size)
+        .map(|index| {
+            if scale <= 1.0 {
+                let at = ((index as f64 + 0.5) * scale - 0.5).max(0.0);
+                let below = at.floor() as usize;
+                let above = (below + 1).min(values.len() - 1);
+                let t = at - below as f64;
+                values[below] * (1.0 - t) + values[above] * t
+            } else {
+                let (start, end) = (index as f64 * scale, (index + 1) as f64 * scale);
+                let mut sum = 0.0;
+                for (pixel, &value) in values
+                    .iter()
+                    .enumerate()
+                    .take(end.ceil() as usize)
+                    .skip(start.floor() as usize)
+                {
+                    let covered = end.min(pixel as f64 + 1.0) - start.max(pixel as f64);
+                    sum += value * covered;
+                }
+                sum / scale
+            }
+        })
+        .collect()
+}
+
+/// The perceived brightness of a color, as ITU-R BT.601 weighs it.
+fn luma(red: f64, green: f64, blue: f64) -> f64 {
+    0.299 * red + 0.587 * green + 0.114 * blue
+}
+
+/// How the shades of gray of an image are turned into live and dead cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dither {
+    /// Pixels darker than mid gray are alive, which keeps edges crisp but
+    /// loses every shade in between.
+    Threshold,
+    /// Shades become regular patterns of cells, the darker the denser.
+    Ordered,
+    /// The rounding error of each pixel is spread over its neighbors, which
+    /// keeps the most detail in photos.
+    #[default]
+    FloydSteinberg,
+}
+
+impl Dither {
+    pub const NAMES: [&'static str; 3] = ["threshold", "ordered", "floyd-steinberg"];
+
+    pub fn from_name(name: &str) -> Option<Dither> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "threshold" | "none" => Some(Dither::Threshold),
+            "ordered" | "bayer" => Some(Dither::Ordered),
+            "floyd-steinberg" | "diffusion" => Some(Dither::FloydSteinberg),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dither::Threshold => "threshold",
+            Dither::Ordered => "ordered",
+            Dither::FloydSteinberg => "floyd-steinberg",
+        }
+    }
+}
+
+/// Why an image could not be read.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    /// The file was read but is not an image this crate can decode.
+    Decode(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "{}", error),
+            ImageError::Decode(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Io(error) => Some(error),
+            ImageError::Decode(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> ImageError {
+        ImageError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+
+    fn decoded(bytes: &[u8]) -> Image {
+        match Image::decode(bytes) {
+            Ok(image) => image,
+            Err(error) => panic!(
+                "{:?} did not decode: {}",
+                String::from_utf8_lossy(bytes),
+                error
+            ),
+        }
+    }
+
+    #[test]
+    fn netpbm_images_decode() {
+        let gray = Image::new(3, 2, vec![0, 128, 255, 255, 64, 0]);
+        assert_eq!(
+            decoded(b"P2\n# gray\n3 2\n255\n0 128 255\n255 64 0\n"),
+            gray
+        );
+        assert_eq!(decoded(b"P5 3 2 255\n\x00\x80\xff\xff\x40\x00"), gray);
+        let wide = b"P5 3 2 65535\n\x00\x00\x80\x80\xff\xff\xff\xff\x40\x40\x00\x00";
+        assert_eq!(decoded(wide), gray);
+        let color = Image::new(2, 1, vec![76, 255]);
+        assert_eq!(decoded(b"P3 2 1 1\n1 0 0  1 1 1\n"), color);
+        assert_eq!(decoded(b"P6 2 1 255\n\xff\x00\x00\xff\xff\xff"), color);
+    }
+
+    #[test]
+    fn written_pngs_decode_back() {
+        let cells = vec![(0, 0), (1, 2), (2, 1), (2, 4)];
+        let pattern = Pattern::new(5, 3, cells.clone());
+        let image = decoded(&pattern.to_png(2));
+        assert_eq!((image.width(), image.height()), (10, 6));
+        for row in 0..6 {
+            for column in 0..10 {
+                let alive = cells.contains(&(row / 2, column / 2));
+                assert_eq!(image.level(row, column), if alive { 0 } else { 255 });
+            }
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_levels() {
+        let image = Image::new(4, 3, (0..12).map(|level| level * 20).collect());
+        assert_eq!(image.resize(4, 3), image);
+        let flat = Image::new(7, 5, vec![90; 35]);
+        for (width, height) in [(3, 2), (14, 10), (1, 1), (20, 3)] {
+            let resized = flat.resize(width, height);
+            assert_eq!(
+                resized,
+                Image::new(width, height, vec![90; (width * height) as usize])
+            );
+        }
+        assert_eq!(flat.fit(70, 100), (70, 50));
+        assert_eq!(flat.fit(1, 1), (1, 1));
+    }
+
+    #[test]
+    fn dithering_keeps_the_shade() {
+        assert_eq!(
+            Image::new(4, 1, vec![0, 127, 128, 255]).dither(Dither::Threshold),
+            [true, true, false, false]
+        );
+        for level in [0, 32, 128, 200, 255] {
+            let image = Image::new(64, 64, vec![level; 64 * 64]);
+            for dither in [Dither::Ordered, Dither::FloydSteinberg] {
+                let alive = image.dither(dither).iter().filter(|&&alive| alive).count();
+                let density = alive as f64 / (64 * 64) as f64;
+                let expected = 1.0 - level as f64 / 255.0;
+                assert!(
+                    (density - expected).abs() < 0.02,
+                    "{:?} of {} gave {}",
+                    dither,
+                    level,
+                    density
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_images_are_refused() {
+        let png = Pattern::new(5, 3, vec![(1, 1)]).to_png(1);
+        let mut corrupt = png.clone();
+        corrupt[20] ^= 0xff;
+        let cases: [&[u8]; 10] = [
+            b"",
+            b"GIF89a",
+            &png[..png.len() / 2],
+            &png[..20],
+            &corrupt,
+            b"P2 0 2 255\n",
+            b"P2 2 2 0\n0 0 0 0",
+            b"P2 2 2 255\n0 0 0",
+            b"P5 2 2 255\n\x00\x00",
+            b"P3 1 1 255\n0 x 0",
+        ];
+        for bytes in cases {
+            match Image::decode(bytes) {
+                Err(ImageError::Decode(_)) => {}
+                other => panic!("{:?} gave {:?}", String::from_utf8_lossy(bytes), other),
+            }
+        }
+    }
+}
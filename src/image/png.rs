@@ -0,0 +1,211 @@
+//! Reading PNG images down to the brightness of each pixel.
+//!
+//! Every bit depth and color type is read, images with transparency are
+//! shown over white, and interlaced images are turned away.
+
+use super::{inflate, luma};
+
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Whether `bytes` start like a PNG image.
+pub(crate) fn sniff(bytes: &[u8]) -> bool {
+    bytes.starts_with(SIGNATURE)
+}
+
+/// The width, height and brightness of each pixel of a PNG image, row by
+/// row from the top left.
+pub(crate) fn decode(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let mut rest = bytes.strip_prefix(SIGNATURE).ok_or("not a PNG image")?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut data = Vec::new();
+    while !rest.is_empty() {
+        if rest.len() < 12 {
+            return Err(String::from("truncated PNG chunk"));
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + length).ok_or("truncated PNG chunk")?;
+        // The CRC is left unchecked: a damaged image only shows as noise.
+        rest = rest.get(8 + length + 4..).ok_or("truncated PNG chunk")?;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(body)?),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let header = header.ok_or("PNG image without a header")?;
+    if header.interlaced {
+        return Err(String::from("interlaced PNG images are not supported"));
+    }
+    let pixels = inflate::decompress(&data)?;
+    let rows = unfilter(&header, &pixels)?;
+    let levels = brightness(&header, &rows, palette, transparency)?;
+    Ok((header.width, header.height, levels))
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    depth: u8,
+    color: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Result<Header, String> {
+        if body.len() != 13 {
+            return Err(String::from("malformed PNG header"));
+        }
+        let header = Header {
+            width: u32::from_be_bytes([body[0], body[1], body[2], body[3]]),
+            height: u32::from_be_bytes([body[4], body[5], body[6], body[7]]),
+            depth: body[8],
+            color: body[9],
+            interlaced: body[12] != 0,
+        };
+        let depths: &[u8] = match header.color {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => return Err(format!("unknown PNG color type {}", header.color)),
+        };
+        if !depths.contains(&header.depth) {
+            return Err(format!(
+                "bit depth {} is not valid for PNG color type {}",
+                header.depth, header.color
+            ));
+        }
+        if header.width == 0 || header.height == 0 {
+            return Err(String::from("empty PNG image"));
+        }
+        Ok(header)
+    }
+
+    /// Samples per pixel.
+    fn channels(&self) -> usize {
+        match self.color {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    /// Bytes per row of pixels, not counting the filter type.
+    fn stride(&self) -> usize {
+        (self.width as usize * self.channels() * self.depth as usize).div_ceil(8)
+    }
+}
+
+/// The rows of pixel bytes with the filter of each undone.
+fn unfilter(header: &Header, pixels: &[u8]) -> Result<Vec<u8>, String> {
+    let stride = header.stride();
+    // Filters reach back to the same byte of the pixel before.
+    let back = (header.channels() * header.depth as usize).div_ceil(8);
+    let height = header.height as usize;
+    if pixels.len() < (stride + 1) * height {
+        return Err(String::from("truncated PNG image data"));
+    }
+    let mut rows = vec![0u8; stride * height];
+    for row in 0..height {
+        let line = &pixels[row * (stride + 1)..(row + 1) * (stride + 1)];
+        let (filter, line) = (line[0], &line[1..]);
+        let (done, current) = rows.split_at_mut(row * stride);
+        let above = done
+            .get(done.len().saturating_sub(stride)..)
+            .filter(|_| row > 0);
+        let current = &mut current[..stride];
+        for i in 0..stride {
+            let left = if i >= back { current[i - back] } else { 0 };
+            let up = above.map_or(0, |above| above[i]);
+            let corner = match above {
+                Some(above) if i >= back => above[i - back],
+                _ => 0,
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, corner),
+                _ => return Err(format!("unknown PNG filter type {}", filter)),
+            };
+            current[i] = line[i].wrapping_add(predicted);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(left: u8, up: u8, corner: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - corner as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(corner) {
+        left
+    } else if distance(up) <= distance(corner) {
+        up
+    } else {
+        corner
+    }
+}
+
+/// The brightness of every pixel, with transparent ones blended towards
+/// white.
+fn brightness(
+    header: &Header,
+    rows: &[u8],
+    palette: &[u8],
+    transparency: &[u8],
+) -> Result<Vec<u8>, String> {
+    let (width, depth) = (header.width as usize, header.depth as usize);
+    let channels = header.channels();
+    let stride = header.stride();
+    let max = ((1u32 << depth) - 1) as f64;
+    let mut levels = Vec::with_capacity(width * header.height as usize);
+    for row in rows.chunks(stride) {
+        // The samples of the row as stored, which for a palette image are
+        // indices into it.
+        let sample = |index: usize| -> u32 {
+            match depth {
+                16 => u16::from_be_bytes([row[2 * index], row[2 * index + 1]]) as u32,
+                8 => row[index] as u32,
+                _ => {
+                    let bit = index * depth;
+                    (row[bit / 8] >> (8 - depth - bit % 8)) as u32 & ((1 << depth) - 1)
+                }
+            }
+        };
+        for x in 0..width {
+            let base = x * channels;
+            let unit = |index: usize| sample(index) as f64 / max;
+            let (level, alpha) = match header.color {
+                0 => (unit(base), 1.0),
+                2 => (luma(unit(base), unit(base + 1), unit(base + 2)), 1.0),
+                3 => {
+                    let index = sample(base) as usize;
+                    let color = palette
+                        .get(3 * index..3 * index + 3)
+                        .ok_or("PNG palette index out of range")?;
+                    let alpha = transparency.get(index).map_or(1.0, |&a| a as f64 / 255.0);
+                    let channel = |c: u8| c as f64 / 255.0;
+                    (
+                        luma(channel(color[0]), channel(color[1]), channel(color[2])),
+                        alpha,
+                    )
+                }
+                4 => (unit(base), unit(base + 1)),
+                _ => (
+                    luma(unit(base), unit(base + 1), unit(base + 2)),
+                    unit(base + 3),
+                ),
+            };
+            let level = level * alpha + (1.0 - alpha);
+            levels.push((level * 255.0).round() as u8);
+        }
+    }
+    Ok(levels)
+}
@@ -0,0 +1,92 @@
+//! Reading the grayscale and color images of the netpbm tools, PGM and PPM,
+//! in their plain and raw forms.
+
+use super::luma;
+
+/// Whether `bytes` start like a PGM or PPM image.
+pub(crate) fn sniff(bytes: &[u8]) -> bool {
+    matches!(bytes, [b'P', b'2' | b'3' | b'5' | b'6', ..])
+}
+
+/// The width, height and brightness of each pixel of a PGM or PPM image,
+/// row by row from the top left.
+pub(crate) fn decode(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let (channels, plain) = match bytes.get(..2) {
+        Some(b"P2") => (1, true),
+        Some(b"P3") => (3, true),
+        Some(b"P5") => (1, false),
+        Some(b"P6") => (3, false),
+        _ => return Err(String::from("not a PGM or PPM image")),
+    };
+    let mut position = 2;
+    let mut header = [0u32; 3];
+    for value in &mut header {
+        *value = number(bytes, &mut position)?;
+    }
+    let [width, height, max] = header;
+    if width == 0 || height == 0 {
+        return Err(String::from("empty netpbm image"));
+    }
+    if max == 0 || max > 65535 {
+        return Err(format!("invalid netpbm maximum value {}", max));
+    }
+    let samples = width as usize * height as usize * channels;
+    let mut values = Vec::with_capacity(samples);
+    if plain {
+        for _ in 0..samples {
+            values.push(number(bytes, &mut position)?);
+        }
+    } else {
+        // A single whitespace character separates the header from the
+        // samples, which take two bytes each past a maximum of 255.
+        let size = if max > 255 { 2 } else { 1 };
+        let data = bytes
+            .get(position + 1..position + 1 + samples * size)
+            .ok_or("truncated netpbm image")?;
+        values.extend(data.chunks(size).map(|sample| match sample {
+            [high, low] => u16::from_be_bytes([*high, *low]) as u32,
+            [value] => *value as u32,
+            _ => 0,
+        }));
+    }
+    let unit = |value: u32| value.min(max) as f64 / max as f64;
+    let levels = values
+        .chunks(channels)
+        .map(|pixel| {
+            let level = match pixel {
+                [red, green, blue] => luma(unit(*red), unit(*green), unit(*blue)),
+                [gray] => unit(*gray),
+                _ => 1.0,
+            };
+            (level * 255.0).round() as u8
+        })
+        .collect();
+    Ok((width, height, levels))
+}
+
+/// The next decimal number from `position` on, skipping whitespace and
+/// comments, leaving `position` just past it.
+fn number(bytes: &[u8], position: &mut usize) -> Result<u32, String> {
+    loop {
+        match bytes.get(*position) {
+            Some(byte) if byte.is_ascii_whitespace() => *position += 1,
+            Some(b'#') => {
+                while bytes.get(*position).is_some_and(|&byte| byte != b'\n') {
+                    *position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_digit() => break,
+            Some(&byte) => return Err(format!("unexpected '{}' in netpbm image", byte as char)),
+            None => return Err(String::from("truncated netpbm image")),
+        }
+    }
+    let mut value = 0u32;
+    while let Some(&byte) = bytes.get(*position).filter(|byte| byte.is_ascii_digit()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add((byte - b'0') as u32))
+            .ok_or("number too large in netpbm image")?;
+        *position += 1;
+    }
+    Ok(value)
+}
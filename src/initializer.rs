@@ -0,0 +1,35 @@
+use crate::pattern::Pattern;
+use crate::{Dither, Font, Image};
+
+/// What a new universe starts out holding, for `Universe::from_initializer`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Initializer {
+    /// Every cell dead.
+    #[default]
+    Empty,
+    /// Cells whose index in row-major order is a multiple of `a` or of `b`.
+    ///
+    /// The original way boards were seeded, kept so the boards given by
+    /// dividers on the command line come out the same. It makes stripes
+    /// and lattices that say little about the rule they run under.
+    Divisors { a: u32, b: u32 },
+    /// Each cell alive with probability `density`, as `Universe::random`.
+    Random { density: f64, seed: u64 },
+    /// Blobs of gradient noise around `scale` cells across, as
+    /// `Universe::noise`.
+    Noise {
+        scale: f64,
+        threshold: f64,
+        seed: u64,
+    },
+    /// Separate soups at random places, as `Universe::clusters`.
+    Clusters { count: u32, radius: u32, seed: u64 },
+    /// A pattern in the middle of a dead board.
+    Pattern(Pattern),
+    /// An image as large as fits the board, in the middle of it, its dark
+    /// pixels alive.
+    Image { image: Image, dither: Dither },
+    /// Text written in one of the built-in fonts, in the middle of the
+    /// board.
+    Text { text: String, font: Font },
+}
@@ -0,0 +1,426 @@
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    MouseButton, MouseEventKind,
+};
+use crossterm::execute;
+use crossterm::terminal;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A key press or mouse event read from the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Backspace,
+    /// Ctrl+C, delivered as a key because signals are disabled in raw mode,
+    /// or caught by `catch_interrupts`.
+    Interrupt,
+    /// Another letter typed with Ctrl held, such as `Ctrl('r')`.
+    Ctrl(char),
+    /// Available while a `MouseCapture` is active.
+    Mouse(Mouse),
+}
+
+impl Key {
+    /// Read a key back from the form it is displayed in, as written to key
+    /// recordings.
+    pub fn parse(text: &str) -> Option<Key> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let key = match words[..] {
+            ["space"] => Key::Char(' '),
+            ["up"] => Key::Up,
+            ["down"] => Key::Down,
+            ["left"] => Key::Left,
+            ["right"] => Key::Right,
+            ["enter"] => Key::Enter,
+            ["escape"] => Key::Escape,
+            ["backspace"] => Key::Backspace,
+            ["interrupt"] => Key::Interrupt,
+            [action, row, column] => Key::Mouse(Mouse {
+                action: match action {
+                    "press" => MouseAction::Press,
+                    "drag" => MouseAction::Drag,
+                    "release" => MouseAction::Release,
+                    _ => return None,
+                },
+                row: row.parse().ok()?,
+                column: column.parse().ok()?,
+            }),
+            [word] => match word.strip_prefix("ctrl+") {
+                Some(letter) => Key::Ctrl(single_char(letter)?),
+                None => Key::Char(single_char(word)?),
+            },
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+/// The only character of `text`, if it has exactly one.
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Char(' ') => f.write_str("space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Up => f.write_str("up"),
+            Key::Down => f.write_str("down"),
+            Key::Left => f.write_str("left"),
+            Key::Right => f.write_str("right"),
+            Key::Enter => f.write_str("enter"),
+            Key::Escape => f.write_str("escape"),
+            Key::Backspace => f.write_str("backspace"),
+            Key::Interrupt => f.write_str("interrupt"),
+            Key::Ctrl(c) => write!(f, "ctrl+{}", c),
+            Key::Mouse(mouse) => {
+                let action = match mouse.action {
+                    MouseAction::Press => "press",
+                    MouseAction::Drag => "drag",
+                    MouseAction::Release => "release",
+                };
+                write!(f, "{} {} {}", action, mouse.row, mouse.column)
+            }
+        }
+    }
+}
+
+/// Something done with the left mouse button at a terminal position, with
+/// rows and columns counted from zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mouse {
+    pub action: MouseAction,
+    pub row: u32,
+    pub column: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseAction {
+    Press,
+    /// Moving with the button held down.
+    Drag,
+    Release,
+}
+
+/// Puts the terminal in raw mode, unbuffered, without echo and with
+/// Ctrl+C read as a key, for as long as it is alive.
+///
+/// The previous settings are restored when the guard is dropped, and mouse
+/// capture is turned off again if it was turned on, panics included.
+pub struct RawMode {
+    mouse: bool,
+}
+
+/// Set while a `RawMode` is alive and stdout is the terminal it applies
+/// to, which then no longer turns a line feed into a new line on its own.
+static RAW_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+impl RawMode {
+    /// Switch the terminal to raw mode through crossterm.
+    ///
+    /// Returns `None` when stdin is not a terminal or its mode cannot be
+    /// changed, in which case the session simply runs without keyboard
+    /// input.
+    pub fn enable() -> Option<RawMode> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        terminal::enable_raw_mode().ok()?;
+        RAW_OUTPUT.store(io::stdout().is_terminal(), Ordering::Relaxed);
+        Some(RawMode { mouse: false })
+    }
+
+    /// Ask the terminal to report mouse clicks and drags as input until the
+    /// guard is dropped. Does nothing when stdout is not a terminal.
+    pub fn capture_mouse(&mut self) {
+        let mut stdout = io::stdout();
+        if stdout.is_terminal() && execute!(stdout, EnableMouseCapture).is_ok() {
+            self.mouse = true;
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if self.mouse {
+            let _ = execute!(io::stdout(), DisableMouseCapture);
+        }
+        RAW_OUTPUT.store(false, Ordering::Relaxed);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// What ends a line printed to stdout: a carriage return and line feed in
+/// raw mode, a line feed otherwise.
+pub fn line_end() -> &'static str {
+    if RAW_OUTPUT.load(Ordering::Relaxed) {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// The size of the terminal as `(columns, rows)`.
+pub fn terminal_size() -> Option<(u32, u32)> {
+    let (columns, rows) = terminal::size().ok()?;
+    Some((columns as u32, rows as u32))
+}
+
+/// Read key presses from stdin on a background thread.
+pub fn spawn_reader() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    read_stdin(sender);
+    receiver
+}
+
+/// How often the key reader checks whether Ctrl+C was caught.
+const INTERRUPT_POLL: Duration = Duration::from_millis(50);
+
+/// How many times Ctrl+C was caught since `catch_interrupts`.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Take Ctrl+C, SIGINT on Unix and the console control event on Windows,
+/// as a request to stop instead of dying from it, so a run interrupted
+/// without raw mode, where Ctrl+C is not a key, still restores the
+/// terminal, prints its summary and saves. The key readers turn each one
+/// into `Key::Interrupt` and anything else can check `interrupted`.
+pub fn catch_interrupts() {
+    // Only the first handler set is kept, which is all any command needs.
+    let _ = ctrlc::set_handler(|| {
+        INTERRUPTS.fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+/// Whether Ctrl+C was caught since `catch_interrupts` was called.
+pub fn interrupted() -> bool {
+    INTERRUPTS.load(Ordering::Relaxed) > 0
+}
+
+/// Press the recorded `keys` at the times they were recorded, counted from
+/// now, while still reading key presses from stdin.
+pub fn spawn_replay(keys: Vec<(Duration, Key)>) -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    read_stdin(sender.clone());
+    let started = Instant::now();
+    thread::spawn(move || {
+        for (time, key) in keys {
+            if let Some(remaining) = time.checked_sub(started.elapsed()) {
+                thread::sleep(remaining);
+            }
+            if sender.send(key).is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// Load keys recorded by `KeyRecorder`.
+pub fn load_keys(path: &str) -> io::Result<Vec<(Duration, Key)>> {
+    let text = fs::read_to_string(path)?;
+    let mut keys = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = line.split_once(' ').and_then(|(millis, key)| {
+            Some((
+                Duration::from_millis(millis.parse().ok()?),
+                Key::parse(key)?,
+            ))
+        });
+        match parsed {
+            Some(key) => keys.push(key),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected '<milliseconds> <key>'", number + 1),
+                ))
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Writes every key pressed to a file along with when it was pressed, one
+/// `<milliseconds> <key>` line each, so a session can be replayed.
+pub struct KeyRecorder {
+    out: BufWriter<File>,
+    started: Instant,
+}
+
+impl KeyRecorder {
+    pub fn create(path: &str) -> io::Result<KeyRecorder> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "# game_of_life key recording")?;
+        Ok(KeyRecorder {
+            out,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, key: Key) -> io::Result<()> {
+        writeln!(self.out, "{} {}", self.started.elapsed().as_millis(), key)
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Send the keys pressed on stdin to `sender` from a background thread,
+/// along with a `Key::Interrupt` for every Ctrl+C caught.
+fn read_stdin(sender: Sender<Key>) {
+    forward_interrupts(sender.clone());
+    if io::stdin().is_terminal() {
+        // Crossterm decodes what the terminal sends, mouse reports included.
+        thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                if let Some(key) = from_event(event) {
+                    if sender.send(key).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        return;
+    }
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0u8; 64];
+        while let Ok(read) = stdin.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            for key in parse_keys(&buffer[..read]) {
+                if sender.send(key).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Send a `Key::Interrupt` to `sender` for every Ctrl+C caught, from a
+/// background thread, so pressing it again still gets through while the
+/// first one is being handled.
+fn forward_interrupts(sender: Sender<Key>) {
+    thread::spawn(move || {
+        let mut sent = 0;
+        loop {
+            while sent < INTERRUPTS.load(Ordering::Relaxed) {
+                if sender.send(Key::Interrupt).is_err() {
+                    return;
+                }
+                sent += 1;
+            }
+            thread::sleep(INTERRUPT_POLL);
+        }
+    });
+}
+
+/// The key or left button mouse event a crossterm event stands for, if any.
+fn from_event(event: Event) -> Option<Key> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('c') if ctrl => Some(Key::Interrupt),
+                KeyCode::Char(c) if ctrl => Some(Key::Ctrl(c.to_ascii_lowercase())),
+                KeyCode::Char(c) => Some(Key::Char(c)),
+                KeyCode::Up => Some(Key::Up),
+                KeyCode::Down => Some(Key::Down),
+                KeyCode::Left => Some(Key::Left),
+                KeyCode::Right => Some(Key::Right),
+                KeyCode::Enter => Some(Key::Enter),
+                KeyCode::Esc => Some(Key::Escape),
+                KeyCode::Backspace => Some(Key::Backspace),
+                KeyCode::Tab => Some(Key::Ctrl('i')),
+                _ => None,
+            }
+        }
+        Event::Mouse(mouse) => {
+            let action = match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => MouseAction::Press,
+                MouseEventKind::Drag(MouseButton::Left) => MouseAction::Drag,
+                MouseEventKind::Up(MouseButton::Left) => MouseAction::Release,
+                _ => return None,
+            };
+            Some(Key::Mouse(Mouse {
+                action,
+                row: mouse.row as u32,
+                column: mouse.column as u32,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Decode the raw bytes of one read of stdin, when it is not a terminal,
+/// into keys.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            0x1B if bytes.get(i + 1) == Some(&b'[') && i + 2 < bytes.len() => {
+                let key = match bytes[i + 2] {
+                    b'A' => Some(Key::Up),
+                    b'B' => Some(Key::Down),
+                    b'C' => Some(Key::Right),
+                    b'D' => Some(Key::Left),
+                    _ => None,
+                };
+                keys.extend(key);
+                i += 3;
+                continue;
+            }
+            0x1B => keys.push(Key::Escape),
+            0x03 => keys.push(Key::Interrupt),
+            b'\r' | b'\n' => keys.push(Key::Enter),
+            0x7F | 0x08 => keys.push(Key::Backspace),
+            byte @ 0x01..=0x1A => keys.push(Key::Ctrl((b'a' + byte - 1) as char)),
+            byte if byte.is_ascii() && !byte.is_ascii_control() => {
+                keys.push(Key::Char(byte as char))
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_interrupt_becomes_a_key() {
+        let (sender, keys) = mpsc::channel();
+        forward_interrupts(sender);
+        for _ in 0..2 {
+            INTERRUPTS.fetch_add(1, Ordering::Relaxed);
+            let key = keys.recv_timeout(Duration::from_secs(5));
+            assert_eq!(key, Ok(Key::Interrupt));
+        }
+        assert!(interrupted());
+    }
+}
@@ -0,0 +1,58 @@
+mod bits;
+pub mod boundary;
+mod census;
+mod cycle;
+mod delta;
+mod diff;
+mod edit;
+#[cfg(feature = "embedded-graphics")]
+pub mod embedded;
+mod ffi;
+mod font;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod history;
+mod image;
+mod initializer;
+pub mod input;
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod mapped;
+mod noise;
+pub mod pattern;
+mod records;
+#[cfg(test)]
+mod reference;
+pub mod render;
+mod rng;
+pub mod rule;
+mod snapshot;
+mod sparse;
+mod step;
+mod strips;
+mod symmetry;
+mod universe;
+
+pub use bits::{BitEncoding, BitStringError};
+pub use boundary::{Boundary, Edge};
+pub use census::Census;
+pub use cycle::{Cycle, CycleDetector};
+pub use delta::Delta;
+pub use diff::DiffReport;
+pub use edit::EditLog;
+pub use font::Font;
+#[cfg(feature = "gpu")]
+pub use gpu::{GpuError, GpuUniverse};
+pub use history::History;
+pub use image::{Dither, Image, ImageError};
+pub use initializer::Initializer;
+#[cfg(all(unix, target_pointer_width = "64"))]
+pub use mapped::MappedUniverse;
+pub use records::Records;
+pub use rng::Rng;
+pub use rule::Rule;
+pub use snapshot::Snapshot;
+pub use sparse::{Run, SparseUniverse};
+pub use step::Engine;
+pub use strips::Strips;
+pub use symmetry::Symmetry;
+pub use universe::{Cell, DoesNotFit, InitializerError, Universe, ZeroDivisor};
@@ -1,205 +1,48 @@
-use futures::executor::block_on;
-use settimeout::set_timeout;
 use std::env;
-use std::fmt;
-use std::time::Duration;
+use std::process;
 
-#[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Cell {
-    Dead = 0,
-    Alive = 1,
-}
-
-impl Cell {
-    fn toggle(&mut self) {
-        *self = match *self {
-            Cell::Dead => Cell::Alive,
-            Cell::Alive => Cell::Dead,
-        };
-    }
-}
-
-pub struct Universe {
-    width: u32,
-    height: u32,
-    cells: Vec<Cell>,
-}
-
-impl Universe {
-    fn get_index(&self, row: u32, column: u32) -> usize {
-        (row * self.width + column) as usize
-    }
-
-    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
-        let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
-                if delta_row == 0 && delta_col == 0 {
-                    continue;
-                }
-
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
-                count += self.cells[idx] as u8;
-            }
-        }
-        count
-    }
-
-    /// Get the dead and alive values of the entire universe.
-    pub fn get_cells(&self) -> &[Cell] {
-        &self.cells
-    }
+mod cli;
 
-    /// Set cells to be alive in a universe by passing the row and column
-    /// of each cell as an array.
-    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
-        for (row, col) in cells.iter().cloned() {
-            let idx = self.get_index(row, col);
-            self.cells[idx] = Cell::Alive;
-        }
-    }
-}
-
-impl Universe {
-    pub fn tick(&mut self) {
-        let mut next = self.cells.clone();
+use cli::Args;
 
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let idx = self.get_index(row, col);
-                let cell = self.cells[idx];
-                let live_neighbors = self.live_neighbor_count(row, col);
-
-                let next_cell = match (cell, live_neighbors) {
-                    // Rule 1: Any live cell with fewer than two live neighbours
-                    // dies, as if caused by underpopulation.
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
-                    // Rule 2: Any live cell with two or three live neighbours
-                    // lives on to the next generation.
-                    (Cell::Alive, 2) | (Cell::Alive, 3) => Cell::Alive,
-                    // Rule 3: Any live cell with more than three live
-                    // neighbours dies, as if by overpopulation.
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
-                    // Rule 4: Any dead cell with exactly three live neighbours
-                    // becomes a live cell, as if by reproduction.
-                    (Cell::Dead, 3) => Cell::Alive,
-                    // All other cells remain in the same state.
-                    (otherwise, _) => otherwise,
-                };
-
-                next[idx] = next_cell;
-            }
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let command = if args.is_empty() {
+        String::new()
+    } else {
+        args.remove(0)
+    };
+
+    if let Err(message) = cli::config::load() {
+        eprintln!("error: {}", message);
+        process::exit(2);
+    }
+
+    let result = match command.as_str() {
+        "run" => cli::run::main(Args::new(args)),
+        "convert" => cli::convert::main(Args::new(args)),
+        "bench" => cli::bench::main(Args::new(args)),
+        "diverge" => cli::diverge::main(Args::new(args)),
+        "soupsearch" => cli::soupsearch::main(Args::new(args)),
+        "serve" => cli::serve::main(Args::new(args)),
+        "info" => cli::info::main(Args::new(args)),
+        "completions" => cli::completions::main(Args::new(args)),
+        "man" => cli::man::main(Args::new(args)),
+        "help" | "--help" | "-h" => {
+            println!("{}", cli::USAGE);
+            Ok(())
         }
-
-        self.cells = next;
-    }
-
-    pub fn new(initial_width: u32, initial_height: u32, div_a: u32, div_b: u32) -> Universe {
-        let width = initial_width;
-        let height = initial_height;
-
-        let cells = (0..width * height)
-            .map(|i| {
-                if i % div_a == 0 || i % div_b == 0 {
-                    Cell::Alive
-                } else {
-                    Cell::Dead
-                }
-                // if js_sys::Math::random() < 0.5 {
-                //     Cell::Alive
-                // } else {
-                //     Cell::Dead
-                // }
-            })
-            .collect();
-
-        Universe {
-            width,
-            height,
-            cells,
+        // Earlier versions took the run arguments directly.
+        width if width.parse::<u32>().is_ok() => {
+            args.insert(0, command.clone());
+            cli::run::main(Args::new(args))
         }
-    }
+        "" => cli::wizard::main(),
+        other => Err(format!("unknown command '{}'\n\n{}", other, cli::USAGE)),
+    };
 
-    pub fn render(&self) -> String {
-        self.to_string()
-    }
-
-    pub fn width(&self) -> u32 {
-        self.width
-    }
-
-    pub fn height(&self) -> u32 {
-        self.height
-    }
-
-    pub fn cells(&self) -> *const Cell {
-        self.cells.as_ptr()
-    }
-
-    pub fn reset(&mut self) {
-        self.cells = (0..self.width * self.height).map(|_i| Cell::Dead).collect();
-    }
-
-    /// Set the width of the universe.
-    ///
-    /// Resets all cells to the dead state.
-    pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        self.cells = (0..width * self.height).map(|_i| Cell::Dead).collect();
-    }
-
-    /// Set the height of the universe.
-    ///
-    /// Resets all cells to the dead state.
-    pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        self.cells = (0..self.width * height).map(|_i| Cell::Dead).collect();
-    }
-
-    pub fn toggle_cell(&mut self, row: u32, column: u32) {
-        let idx = self.get_index(row, column);
-        self.cells[idx].toggle();
-    }
-}
-
-impl fmt::Display for Universe {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for line in self.cells.as_slice().chunks(self.width as usize) {
-            for &cell in line {
-                let symbol = if cell == Cell::Dead { "   " } else { " ◼ " };
-                write!(f, "{}", symbol)?;
-            }
-            write!(f, "\n")?;
-        }
-
-        Ok(())
-    }
-}
-
-async fn play(width: u32, height: u32, a: u32, b: u32) {
-    let mut universe = Universe::new(width, height, a, b);
-    loop {
-        print!("\x1B[2J\x1B[1;1H");
-        universe.tick();
-        set_timeout(Duration::from_millis(100)).await;
-        println!("{}", universe);
-    }
-}
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() == 5 {
-        let width = args[1].to_string().parse::<u32>().unwrap();
-        let height = args[2].to_string().parse::<u32>().unwrap();
-        let a = args[3].to_string().parse::<u32>().unwrap();
-        let b = args[4].to_string().parse::<u32>().unwrap();
-        block_on(play(width, height, a, b));
-    } else {
-        println!(
-            "You must enter [width], [height] [first integer divider] [second integer divider]"
-        )
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(2);
     }
 }
@@ -0,0 +1,422 @@
+//! Universes kept in a memory-mapped file instead of in memory, for boards
+//! far larger than the RAM of the machine.
+//!
+//! The file holds the cells bit-packed in the same row-padded layout as
+//! `Universe`. It starts out sparse, so dead regions take no disk space,
+//! and a tick only visits the rows that hold live cells or sit next to
+//! them, updating them in place top to bottom with a few rows kept aside.
+
+use std::ffi::c_void;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::ops::Range;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+
+use crate::pattern::Pattern;
+use crate::step::{self, last_word_mask, stride_for, Engine, Kernel, Neighborhood, WORD_BITS};
+use crate::{Boundary, Cell, Edge, Rule};
+
+const PROT_READ: i32 = 1;
+const PROT_WRITE: i32 = 2;
+const MAP_SHARED: i32 = 1;
+
+extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: i32,
+        flags: i32,
+        fd: i32,
+        offset: i64,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> i32;
+}
+
+/// A file mapped into memory as words, unmapped when dropped.
+struct Map {
+    words: *mut u64,
+    len: usize,
+    /// Kept open for as long as it is mapped.
+    _file: File,
+}
+
+impl Map {
+    fn new(file: File, len: usize) -> io::Result<Map> {
+        if len == 0 {
+            return Ok(Map {
+                words: ptr::NonNull::dangling().as_ptr(),
+                len,
+                _file: file,
+            });
+        }
+        let bytes = len * size_of::<u64>();
+        // SAFETY: a fresh shared mapping of the whole file, which is at
+        // least `bytes` long, checked for failure below.
+        let address = unsafe {
+            mmap(
+                ptr::null_mut(),
+                bytes,
+                PROT_READ | PROT_WRITE,
+                MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if address as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Map {
+            words: address.cast(),
+            len,
+            _file: file,
+        })
+    }
+
+    fn words(&self) -> &[u64] {
+        // SAFETY: the mapping is page aligned, `len` words long and only
+        // reachable through this `Map`.
+        unsafe { slice::from_raw_parts(self.words, self.len) }
+    }
+
+    fn words_mut(&mut self) -> &mut [u64] {
+        // SAFETY: as in `words`, with `&mut self` making the access unique.
+        unsafe { slice::from_raw_parts_mut(self.words, self.len) }
+    }
+}
+
+impl Drop for Map {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: unmaps exactly what `new` mapped, once.
+            unsafe { munmap(self.words.cast(), self.len * size_of::<u64>()) };
+        }
+    }
+}
+
+/// A universe stored in a memory-mapped file.
+///
+/// Only boundaries that keep the size fixed are supported, so a growing
+/// edge acts as a dead one.
+pub struct MappedUniverse {
+    width: u32,
+    height: u32,
+    stride: usize,
+    map: Map,
+    rule: Rule,
+    engine: Engine,
+    boundary: Boundary,
+    /// The words of each row that may hold live cells, empty for dead rows,
+    /// kept in memory so the dead parts of the file are never read.
+    spans: Vec<Range<usize>>,
+}
+
+/// The old words of a row from `start` on, saved before stepping it.
+type Saved = (u32, usize, Vec<u64>);
+
+impl MappedUniverse {
+    /// Create `path`, or empty it if it exists, and map a dead universe of
+    /// the given size onto it.
+    pub fn create(path: impl AsRef<Path>, width: u32, height: u32) -> io::Result<MappedUniverse> {
+        let stride = stride_for(width);
+        let len = stride * height as usize;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((len * size_of::<u64>()) as u64)?;
+        Ok(MappedUniverse {
+            width,
+            height,
+            stride,
+            map: Map::new(file, len)?,
+            rule: Rule::default(),
+            engine: Engine::default(),
+            boundary: Boundary::default(),
+            spans: vec![0..0; height as usize],
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    fn row_words(&self, row: u32) -> &[u64] {
+        let start = row as usize * self.stride;
+        &self.map.words()[start..start + self.stride]
+    }
+
+    /// Overwrite `range` of the words of `row`, the only ones of the row
+    /// that may hold live cells.
+    fn set_row_words(&mut self, row: u32, range: Range<usize>, words: &[u64]) {
+        let start = row as usize * self.stride;
+        self.map.words_mut()[start + range.start..start + range.end].copy_from_slice(words);
+        self.spans[row as usize] = match words.iter().position(|&word| word != 0) {
+            Some(first) => {
+                let last = words.iter().rposition(|&word| word != 0).unwrap_or(first);
+                range.start + first..range.start + last + 1
+            }
+            None => 0..0,
+        };
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        let word = self.row_words(row)[(column / WORD_BITS) as usize];
+        match (word >> (column % WORD_BITS)) & 1 {
+            0 => Cell::Dead,
+            _ => Cell::Alive,
+        }
+    }
+
+    /// Bring the cells of `pattern` to life with its top left corner at
+    /// `row` and `column`. Cells falling outside the universe are dropped.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: u32, column: u32) {
+        for (r, c) in pattern.cells_at(row, column) {
+            if r < self.height && c < self.width {
+                let word = (c / WORD_BITS) as usize;
+                self.map.words_mut()[r as usize * self.stride + word] |= 1 << (c % WORD_BITS);
+                let span = self.spans[r as usize].clone();
+                self.spans[r as usize] = if span.is_empty() {
+                    word..word + 1
+                } else {
+                    span.start.min(word)..span.end.max(word + 1)
+                };
+            }
+        }
+    }
+
+    /// How many cells are alive.
+    pub fn population(&self) -> u64 {
+        self.occupied_rows()
+            .map(|row| {
+                self.row_words(row)[self.spans[row as usize].clone()]
+                    .iter()
+                    .map(|word| word.count_ones() as u64)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+
+    /// The live cells, with the size of the universe.
+    pub fn to_pattern(&self) -> Pattern {
+        let mut cells = Vec::new();
+        for row in self.occupied_rows() {
+            let span = self.spans[row as usize].clone();
+            for (idx, &word) in span.clone().zip(&self.row_words(row)[span]) {
+                let mut bits = word;
+                while bits != 0 {
+                    cells.push((row, idx as u32 * WORD_BITS + bits.trailing_zeros()));
+                    bits &= bits - 1;
+                }
+            }
+        }
+        let mut pattern = Pattern::new(self.width, self.height, cells);
+        pattern.rule = Some(self.rule.to_string());
+        pattern
+    }
+
+    fn occupied_rows(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.height).filter(|&row| !self.spans[row as usize].is_empty())
+    }
+
+    /// The rows above and below `row`, or `None` past a dead edge.
+    fn neighbor_rows(&self, row: u32) -> (Option<u32>, Option<u32>) {
+        let edge = self.boundary.y.fixed();
+        (
+            edge.resolve(row, -1, self.height),
+            edge.resolve(row, 1, self.height),
+        )
+    }
+
+    /// The words that can change in the next tick, row by row in order:
+    /// those in or next to the live words of the row and the rows around
+    /// it, or everything if dead neighborhoods come to life.
+    fn windows(&self) -> Vec<(u32, Range<usize>)> {
+        if self.rule.is_born(0) {
+            return (0..self.height).map(|row| (row, 0..self.stride)).collect();
+        }
+        let mut rows = Vec::new();
+        for row in self.occupied_rows() {
+            let (above, below) = self.neighbor_rows(row);
+            rows.extend([above, Some(row), below].into_iter().flatten());
+        }
+        rows.sort_unstable();
+        rows.dedup();
+
+        let torus = self.boundary.x.fixed() == Edge::Torus;
+        rows.into_iter()
+            .map(|row| {
+                let (above, below) = self.neighbor_rows(row);
+                let (start, end) = [above, Some(row), below]
+                    .into_iter()
+                    .flatten()
+                    .map(|row| &self.spans[row as usize])
+                    .filter(|span| !span.is_empty())
+                    .fold((usize::MAX, 0), |(start, end), span| {
+                        (start.min(span.start), end.max(span.end))
+                    });
+                let (start, end) = (start.saturating_sub(1), (end + 1).min(self.stride));
+                // Cells at one edge of a torus are next to the other edge.
+                if torus && (start == 0 || end == self.stride) {
+                    (row, 0..self.stride)
+                } else {
+                    (row, start..end)
+                }
+            })
+            .collect()
+    }
+
+    /// Copy `range` of the words of `target` as they were before this
+    /// tick into `out`, or dead words if there is no such row.
+    fn old_words(
+        &self,
+        target: Option<u32>,
+        range: Range<usize>,
+        saved: &[Saved],
+        out: &mut Vec<u64>,
+    ) {
+        out.clear();
+        let target = match target {
+            Some(target) => target,
+            None => {
+                out.resize(range.len(), 0);
+                return;
+            }
+        };
+        out.extend_from_slice(&self.row_words(target)[range.clone()]);
+        for (_, start, words) in saved.iter().filter(|&&(row, _, _)| row == target) {
+            for idx in range.start.max(*start)..range.end.min(start + words.len()) {
+                out[idx - range.start] = words[idx - start];
+            }
+        }
+    }
+
+    /// Advance one generation.
+    pub fn tick(&mut self) {
+        if self.width == 0 {
+            return;
+        }
+        let kernel = Kernel::new(self.rule, self.engine);
+        let edge = self.boundary.x.fixed();
+        let mask = last_word_mask(self.width);
+        let mut window: [Vec<u64>; 3] = Default::default();
+        let mut left: [Vec<u64>; 3] = Default::default();
+        let mut right: [Vec<u64>; 3] = Default::default();
+        let mut next = Vec::new();
+
+        // Rows are overwritten in order, so the old words of the row just
+        // stepped and of the first row, which the last one may wrap around
+        // to, are kept aside.
+        let mut saved: Vec<Saved> = Vec::new();
+        for (row, words) in self.windows() {
+            // One more word on each side for the neighbors of the edge
+            // cells of the window.
+            let start = words.start.saturating_sub(1);
+            let end = (words.end + 1).min(self.stride);
+            let width =
+                (self.width as usize).min(end * WORD_BITS as usize) - start * WORD_BITS as usize;
+            let (above, below) = self.neighbor_rows(row);
+            for (idx, target) in [above, Some(row), below].into_iter().enumerate() {
+                self.old_words(target, start..end, &saved, &mut window[idx]);
+                left[idx].resize(end - start, 0);
+                right[idx].resize(end - start, 0);
+                step::shift(
+                    &window[idx],
+                    width as u32,
+                    edge,
+                    &mut left[idx],
+                    &mut right[idx],
+                );
+            }
+
+            let rows = Neighborhood {
+                above: [&left[0], &window[0], &right[0]],
+                left: &left[1],
+                cells: &window[1],
+                right: &right[1],
+                below: [&left[2], &window[2], &right[2]],
+            };
+            let local = words.start - start..words.end - start;
+            next.resize(end - start, 0);
+            step::step_row(&rows, &kernel, &mut next, local.clone());
+            if words.end == self.stride {
+                next[local.end - 1] &= mask;
+            }
+
+            saved.retain(|&(saved, _, _)| saved == 0);
+            saved.push((row, words.start, window[1][local.clone()].to_vec()));
+            self.set_row_words(row, words, &next[local]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES};
+    use crate::Universe;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn mapped_ticks_match_the_reference() {
+        let path = env::temp_dir().join(format!("game_of_life-mapped-{}", std::process::id()));
+        let mut seed = 0;
+        for boundary in boundaries() {
+            // Growing edges act as dead ones here.
+            let fixed = Boundary {
+                x: boundary.x.fixed(),
+                y: boundary.y.fixed(),
+            };
+            for rule in RULES {
+                for engine in [Engine::Adder, Engine::Table] {
+                    seed += 1;
+                    // Clusters leave most words dead, so only some of each
+                    // row is stepped.
+                    let mut expected = match seed % 2 {
+                        0 => reference::soup(130, 11, seed, rule, fixed),
+                        _ => Universe::clusters(400, 30, 3, 5, seed),
+                    };
+                    expected.set_rule(Rule::parse(rule).unwrap());
+                    expected.set_boundary(fixed);
+                    let (width, height) = (expected.width(), expected.height());
+                    let mut mapped = MappedUniverse::create(&path, width, height).unwrap();
+                    mapped.set_rule(expected.rule());
+                    mapped.set_engine(engine);
+                    mapped.set_boundary(boundary);
+                    mapped.insert_pattern(&Pattern::from_universe(&expected), 0, 0);
+                    for generation in 1..=10 {
+                        expected = reference::tick(&expected);
+                        mapped.tick();
+                        let mut actual = Universe::empty(width, height);
+                        actual.insert_pattern(&mapped.to_pattern(), 0, 0);
+                        let case = (rule, boundary, engine, width, generation);
+                        assert_same(&actual, &expected, case);
+                        assert_eq!(mapped.population(), expected.population(), "{:?}", case);
+                    }
+                }
+            }
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
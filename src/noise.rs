@@ -0,0 +1,99 @@
+//! Gradient noise, as Perlin described it, for starting patterns made of
+//! blobs rather than scattered cells.
+//!
+//! Every point of the integer lattice gets a gradient of its own, drawn
+//! from its coordinates and a seed, and the noise between them blends the
+//! slopes of the four corners around it. Points a unit apart are unrelated,
+//! points much closer than that are alike.
+
+use std::f64::consts::{SQRT_2, TAU};
+
+use crate::rng::mix;
+
+/// The noise at `(x, y)`, between -1 and 1, and 0 on every lattice point.
+pub(crate) fn gradient_noise(x: f64, y: f64, seed: u64) -> f64 {
+    let (left, top) = (x.floor(), y.floor());
+    let (dx, dy) = (x - left, y - top);
+    let (column, row) = (left as i64, top as i64);
+    let slope = |c: i64, r: i64, dx: f64, dy: f64| {
+        let (gx, gy) = gradient(c, r, seed);
+        gx * dx + gy * dy
+    };
+    let (u, v) = (fade(dx), fade(dy));
+    let upper = lerp(
+        slope(column, row, dx, dy),
+        slope(column + 1, row, dx - 1.0, dy),
+        u,
+    );
+    let lower = lerp(
+        slope(column, row + 1, dx, dy - 1.0),
+        slope(column + 1, row + 1, dx - 1.0, dy - 1.0),
+        u,
+    );
+    // Unit gradients keep the blend within half the diagonal of a square.
+    (lerp(upper, lower, v) * SQRT_2).clamp(-1.0, 1.0)
+}
+
+/// The unit gradient at a lattice point.
+fn gradient(column: i64, row: i64, seed: u64) -> (f64, f64) {
+    let hash = mix(seed ^ mix(column as u64 ^ mix(row as u64)));
+    let angle = (hash >> 11) as f64 / (1u64 << 53) as f64 * TAU;
+    (angle.cos(), angle.sin())
+}
+
+/// Ease `t` in and out so the noise is smooth across the lattice lines.
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_vanishes_on_the_lattice() {
+        for (x, y) in [(0.0, 0.0), (3.0, -2.0), (-7.0, 11.0)] {
+            assert_eq!(gradient_noise(x, y, 42), 0.0);
+        }
+    }
+
+    #[test]
+    fn noise_stays_between_minus_one_and_one() {
+        let mut extremes = (0.0_f64, 0.0_f64);
+        for i in 0..10_000 {
+            let (x, y) = (i as f64 * 0.137, i as f64 * 0.071 - 300.0);
+            let noise = gradient_noise(x, y, 7);
+            assert!((-1.0..=1.0).contains(&noise), "{} at {:?}", noise, (x, y));
+            extremes = (extremes.0.min(noise), extremes.1.max(noise));
+        }
+        // And uses most of that range.
+        assert!(extremes.0 < -0.5 && extremes.1 > 0.5, "{:?}", extremes);
+    }
+
+    #[test]
+    fn noise_changes_smoothly() {
+        for i in 0..1_000 {
+            let (x, y) = (i as f64 * 0.29, i as f64 * 0.13);
+            let here = gradient_noise(x, y, 3);
+            assert!((gradient_noise(x + 0.01, y, 3) - here).abs() < 0.05);
+            assert!((gradient_noise(x, y + 0.01, 3) - here).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn noise_follows_the_seed() {
+        let at = |seed| gradient_noise(1.3, 2.7, seed);
+        assert_eq!(at(5), at(5));
+        assert_ne!(at(5), at(6));
+    }
+
+    #[test]
+    fn fading_eases_in_and_out() {
+        assert_eq!((fade(0.0), fade(0.5), fade(1.0)), (0.0, 0.5, 1.0));
+        assert!(fade(0.01) < 0.01 && fade(0.99) > 0.99);
+    }
+}
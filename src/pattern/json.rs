@@ -0,0 +1,285 @@
+//! A JSON representation of patterns:
+//!
+//! ```json
+//! {"name": "Glider", "comments": [], "rule": "B3/S23",
+//!  "width": 3, "height": 3, "cells": [[0, 1], [1, 2], [2, 0], [2, 1], [2, 2]]}
+//! ```
+//!
+//! Cells are `[row, column]` pairs. Only `cells` is required.
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::{Pattern, PatternError};
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut parser = Parser {
+        chars: text.chars().peekable(),
+        line: 1,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err(parser.error("unexpected text after the pattern"));
+    }
+
+    let mut fields = match value {
+        Value::Object(fields) => fields,
+        _ => return Err(PatternError::parse(1, "expected an object")),
+    };
+    let invalid = |field: &str| PatternError::parse(1, format!("invalid '{}' field", field));
+
+    let number = |value: Value, field: &str| match value {
+        Value::Number(n) if n >= 0.0 && n <= u32::MAX as f64 && n.fract() == 0.0 => Ok(n as u32),
+        _ => Err(invalid(field)),
+    };
+    let string = |value: Value, field: &str| match value {
+        Value::String(s) => Ok(s),
+        _ => Err(invalid(field)),
+    };
+
+    let mut cells = Vec::new();
+    match fields.remove("cells") {
+        Some(Value::Array(items)) => {
+            for item in items {
+                match item {
+                    Value::Array(pair) if pair.len() == 2 => {
+                        let mut pair = pair.into_iter();
+                        let row = number(pair.next().unwrap(), "cells")?;
+                        let col = number(pair.next().unwrap(), "cells")?;
+                        cells.push((row, col));
+                    }
+                    _ => return Err(invalid("cells")),
+                }
+            }
+        }
+        Some(_) => return Err(invalid("cells")),
+        None => return Err(PatternError::parse(1, "missing 'cells' field")),
+    }
+
+    let width = fields
+        .remove("width")
+        .map(|v| number(v, "width"))
+        .transpose()?;
+    let height = fields
+        .remove("height")
+        .map(|v| number(v, "height"))
+        .transpose()?;
+    let mut pattern = Pattern::new(width.unwrap_or(0), height.unwrap_or(0), cells);
+    pattern.name = fields
+        .remove("name")
+        .map(|v| string(v, "name"))
+        .transpose()?;
+    pattern.rule = fields
+        .remove("rule")
+        .map(|v| string(v, "rule"))
+        .transpose()?;
+    if let Some(comments) = fields.remove("comments") {
+        match comments {
+            Value::Array(items) => {
+                for item in items {
+                    pattern.comments.push(string(item, "comments")?);
+                }
+            }
+            _ => return Err(invalid("comments")),
+        }
+    }
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::from("{\n");
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("  \"name\": {},\n", quote(name)));
+    }
+    if !pattern.comments.is_empty() {
+        let comments: Vec<String> = pattern.comments.iter().map(|c| quote(c)).collect();
+        out.push_str(&format!("  \"comments\": [{}],\n", comments.join(", ")));
+    }
+    if let Some(rule) = &pattern.rule {
+        out.push_str(&format!("  \"rule\": {},\n", quote(rule)));
+    }
+    out.push_str(&format!("  \"width\": {},\n", pattern.width()));
+    out.push_str(&format!("  \"height\": {},\n", pattern.height()));
+    let cells: Vec<String> = pattern
+        .cells()
+        .iter()
+        .map(|&(row, col)| format!("[{}, {}]", row, col))
+        .collect();
+    out.push_str(&format!("  \"cells\": [{}]\n}}\n", cells.join(", ")));
+    out
+}
+
+/// Quote and escape a string for JSON output.
+pub(crate) fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+enum Value {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> PatternError {
+        PatternError::parse(self.line, message)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PatternError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}'", expected))),
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> Result<(), PatternError> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(format!("expected '{}'", word)));
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value, PatternError> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Value::String),
+            Some('t') => self.keyword("true").map(|_| Value::Bool),
+            Some('f') => self.keyword("false").map(|_| Value::Bool),
+            Some('n') => self.keyword("null").map(|_| Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(format!("unexpected character '{}'", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, PatternError> {
+        self.expect('{')?;
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.insert(key, self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(fields)),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, PatternError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, PatternError> {
+        if self.next() != Some('"') {
+            return Err(self.error("expected a string"));
+        }
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.next() {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let code: String = (0..4).filter_map(|_| self.next()).collect();
+                        let c = u32::from_str_radix(&code, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid unicode escape"))?;
+                        out.push(c);
+                    }
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    _ => return Err(self.error("invalid escape")),
+                },
+                Some(c) => out.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, PatternError> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        text.parse::<f64>()
+            .map(Value::Number)
+            .map_err(|_| self.error(format!("invalid number '{}'", text)))
+    }
+}
@@ -0,0 +1,52 @@
+use super::{Pattern, PatternError};
+
+/// First line of every Life 1.06 file.
+const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut coordinates = Vec::new();
+
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace().map(|field| field.parse::<i64>());
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => coordinates.push((y, x)),
+            _ => {
+                return Err(PatternError::parse(
+                    number,
+                    format!("expected 'x y' coordinates, found '{}'", line),
+                ))
+            }
+        }
+    }
+
+    // Coordinates are relative to an arbitrary origin and may be negative.
+    let top = coordinates.iter().map(|&(row, _)| row).min().unwrap_or(0);
+    let left = coordinates.iter().map(|&(_, col)| col).min().unwrap_or(0);
+    let mut cells = Vec::with_capacity(coordinates.len());
+    for (row, col) in coordinates {
+        let row = u32::try_from(row - top);
+        let col = u32::try_from(col - left);
+        match (row, col) {
+            (Ok(row), Ok(col)) => cells.push((row, col)),
+            _ => return Err(PatternError::parse(1, "pattern is too large")),
+        }
+    }
+    Ok(Pattern::new(0, 0, cells))
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::from(HEADER);
+    out.push('\n');
+    for &(row, col) in pattern.cells() {
+        out.push_str(&format!("{} {}\n", col, row));
+    }
+    out
+}
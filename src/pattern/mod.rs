@@ -0,0 +1,406 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{Cell, Universe};
+
+pub(crate) mod json;
+mod life106;
+mod plaintext;
+pub(crate) mod png;
+mod presets;
+mod rle;
+
+pub use presets::{preset, Preset, PRESETS};
+
+/// Pixels per cell in images written by `Pattern::save`.
+pub const PNG_SCALE: u32 = 4;
+
+/// A finite arrangement of live cells, as stored in pattern files.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub comments: Vec<String>,
+    /// The rulestring recorded in the file, if any.
+    pub rule: Option<String>,
+    width: u32,
+    height: u32,
+    cells: Vec<(u32, u32)>,
+}
+
+impl Pattern {
+    /// Create a pattern from the row and column of each live cell.
+    ///
+    /// The pattern is at least as large as needed to hold every cell.
+    pub fn new(width: u32, height: u32, cells: Vec<(u32, u32)>) -> Pattern {
+        let width = cells.iter().map(|&(_, col)| col + 1).fold(width, u32::max);
+        let height = cells.iter().map(|&(row, _)| row + 1).fold(height, u32::max);
+        let mut cells = cells;
+        cells.sort_unstable();
+        cells.dedup();
+
+        Pattern {
+            width,
+            height,
+            cells,
+            ..Pattern::default()
+        }
+    }
+
+    /// Capture the live cells of a universe, keeping its dimensions.
+    pub fn from_universe(universe: &Universe) -> Pattern {
+        let width = universe.width();
+        let cells = universe
+            .get_cells()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &cell)| cell == Cell::Alive)
+            .map(|(idx, _)| (idx as u32 / width, idx as u32 % width))
+            .collect();
+
+        let mut pattern = Pattern::new(width, universe.height(), cells);
+        pattern.rule = Some(universe.rule().to_string());
+        pattern
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The row and column of every live cell, sorted row by row.
+    pub fn cells(&self) -> &[(u32, u32)] {
+        &self.cells
+    }
+
+    /// The live cells with the top left corner of the pattern at `row` and
+    /// `column`, leaving out any that would lie past the largest
+    /// coordinate.
+    pub(crate) fn cells_at(&self, row: u32, column: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.cells
+            .iter()
+            .filter_map(move |&(r, c)| Some((r.checked_add(row)?, c.checked_add(column)?)))
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The pattern turned a quarter turn clockwise.
+    pub fn rotated(&self) -> Pattern {
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(row, col)| (col, self.height - 1 - row))
+            .collect();
+        self.with_cells(self.height, self.width, cells)
+    }
+
+    /// The pattern mirrored left to right.
+    pub fn flipped(&self) -> Pattern {
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(row, col)| (row, self.width - 1 - col))
+            .collect();
+        self.with_cells(self.width, self.height, cells)
+    }
+
+    /// A copy of the pattern and its metadata holding other cells.
+    fn with_cells(&self, width: u32, height: u32, cells: Vec<(u32, u32)>) -> Pattern {
+        Pattern {
+            name: self.name.clone(),
+            comments: self.comments.clone(),
+            rule: self.rule.clone(),
+            ..Pattern::new(width, height, cells)
+        }
+    }
+
+    /// Parse a pattern from text in the given format.
+    pub fn parse(text: &str, format: Format) -> Result<Pattern, PatternError> {
+        match format {
+            Format::Rle => rle::parse(text),
+            Format::Plaintext => plaintext::parse(text),
+            Format::Life106 => life106::parse(text),
+            Format::Json => json::parse(text),
+        }
+    }
+
+    /// Read a pattern file, picking the format from its extension and
+    /// falling back to sniffing the contents.
+    pub fn load(path: impl AsRef<Path>) -> Result<Pattern, PatternError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let format = Format::from_path(path).unwrap_or_else(|| Format::sniff(&text));
+        Pattern::parse(&text, format)
+    }
+
+    /// Write the pattern to a file, picking the format from its extension
+    /// and defaulting to RLE. A `.png` extension saves an image instead.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+        {
+            return fs::write(path, self.to_png(PNG_SCALE));
+        }
+        let format = Format::from_path(path).unwrap_or(Format::Rle);
+        fs::write(path, self.write(format))
+    }
+
+    /// Serialize the pattern in the given format.
+    pub fn write(&self, format: Format) -> String {
+        match format {
+            Format::Rle => rle::write(self),
+            Format::Plaintext => plaintext::write(self),
+            Format::Life106 => life106::write(self),
+            Format::Json => json::write(self),
+        }
+    }
+
+    /// Render the pattern as a grayscale PNG image with black live cells,
+    /// each `scale` pixels wide.
+    pub fn to_png(&self, scale: u32) -> Vec<u8> {
+        png::encode(self, scale)
+    }
+}
+
+/// The pattern file formats that can be read and written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Run Length Encoded, the de facto standard `.rle` format.
+    Rle,
+    /// The `.cells` format using `.` and `O` characters.
+    Plaintext,
+    /// Life 1.06, one `x y` coordinate pair per live cell.
+    Life106,
+    /// A JSON object listing the live cells, see `json.rs` for the layout.
+    Json,
+}
+
+impl Format {
+    /// Look up a format by name, as written on the command line.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_ascii_lowercase().as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" | "plaintext" | "txt" => Some(Format::Plaintext),
+            "life106" | "lif" | "life" => Some(Format::Life106),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        Format::from_name(path.extension()?.to_str()?)
+    }
+
+    /// Guess the format from the contents of a file.
+    pub fn sniff(text: &str) -> Format {
+        let header = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.starts_with('#'));
+        if text.trim_start().starts_with("#Life 1.06") {
+            return Format::Life106;
+        }
+        match header {
+            Some(line) if line.starts_with('{') => Format::Json,
+            Some(line) if line.starts_with('x') => Format::Rle,
+            _ => Format::Plaintext,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Rle => "rle",
+            Format::Plaintext => "cells",
+            Format::Life106 => "life106",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Why a pattern could not be read.
+#[derive(Debug)]
+pub enum PatternError {
+    Io(io::Error),
+    /// The file was read but its contents are malformed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl PatternError {
+    pub(crate) fn parse(line: usize, message: impl Into<String>) -> PatternError {
+        PatternError::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::Io(error) => write!(f, "{}", error),
+            PatternError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for PatternError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PatternError::Io(error) => Some(error),
+            PatternError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatternError {
+    fn from(error: io::Error) -> PatternError {
+        PatternError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [Format; 4] = [
+        Format::Rle,
+        Format::Plaintext,
+        Format::Life106,
+        Format::Json,
+    ];
+
+    /// Patterns from empty to wide and dense, some with live cells along
+    /// their right and bottom edges and some with dead rows and columns
+    /// there.
+    fn patterns() -> Vec<Pattern> {
+        let mut patterns = vec![
+            Pattern::new(0, 0, Vec::new()),
+            Pattern::new(1, 1, vec![(0, 0)]),
+            Pattern::new(5, 4, vec![(1, 2)]),
+        ];
+        for (seed, &(width, height)) in [(3, 2), (70, 9), (200, 3), (64, 64)].iter().enumerate() {
+            let universe = Universe::random(width, height, 0.45, seed as u64);
+            patterns.push(Pattern::from_universe(&universe));
+        }
+        let mut named = Pattern::from_universe(&Universe::random(40, 12, 0.3, 9));
+        named.name = Some(String::from("soup \"nine\" \\ \u{e9}"));
+        named.comments = vec![String::from("first"), String::from("second line")];
+        named.rule = Some(String::from("B36/S23"));
+        patterns.push(named);
+        patterns
+    }
+
+    /// What of `pattern` survives being written as `format`.
+    fn kept(pattern: &Pattern, format: Format) -> Pattern {
+        match format {
+            Format::Rle | Format::Json => pattern.clone(),
+            Format::Plaintext => {
+                // Rows are kept, dead or not, but each row ends at its last
+                // live cell, and there is no rule.
+                let width = pattern.cells().iter().map(|&(_, col)| col + 1).max();
+                let mut kept = Pattern::new(
+                    width.unwrap_or(0),
+                    pattern.height(),
+                    pattern.cells().to_vec(),
+                );
+                kept.name = pattern.name.clone();
+                kept.comments = pattern.comments.clone();
+                kept
+            }
+            Format::Life106 => {
+                // Only the cells, moved to the top left corner.
+                let top = pattern
+                    .cells()
+                    .iter()
+                    .map(|&(row, _)| row)
+                    .min()
+                    .unwrap_or(0);
+                let left = pattern
+                    .cells()
+                    .iter()
+                    .map(|&(_, col)| col)
+                    .min()
+                    .unwrap_or(0);
+                let cells = pattern
+                    .cells()
+                    .iter()
+                    .map(|&(row, col)| (row - top, col - left));
+                Pattern::new(0, 0, cells.collect())
+            }
+        }
+    }
+
+    #[test]
+    fn written_patterns_parse_back() {
+        for format in FORMATS {
+            for pattern in patterns() {
+                let text = pattern.write(format);
+                let parsed = match Pattern::parse(&text, format) {
+                    Ok(parsed) => parsed,
+                    Err(error) => panic!("{:?} did not parse back: {}\n{}", format, error, text),
+                };
+                assert_eq!(parsed, kept(&pattern, format), "{:?}\n{}", format, text);
+                assert_eq!(Format::sniff(&text), format, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn images_decode_back_to_the_pattern() {
+        for pattern in patterns().into_iter().filter(|pattern| pattern.width() > 0) {
+            for scale in [1, 3] {
+                let image = crate::image::Image::decode(&pattern.to_png(scale)).unwrap();
+                let (width, height) = (pattern.width() * scale, pattern.height() * scale);
+                assert_eq!((image.width(), image.height()), (width, height));
+                let image = image.resize(pattern.width(), pattern.height());
+                let alive = image.dither(crate::image::Dither::Threshold);
+                let cells: Vec<(u32, u32)> = (0..alive.len() as u32)
+                    .filter(|&idx| alive[idx as usize])
+                    .map(|idx| (idx / pattern.width(), idx % pattern.width()))
+                    .collect();
+                assert_eq!(cells, pattern.cells(), "scale {}", scale);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_patterns_say_where() {
+        let cases = [
+            (Format::Rle, "#N glider\nbo$2bo$3o!", 2),
+            (Format::Rle, "x 3, y = 3\nbo$2bo$3o!", 1),
+            (Format::Rle, "x = 3, y = 3\nbo$2bo$3q!", 2),
+            (Format::Rle, "x = 3, y = 3\n99999999999o!", 2),
+            (Format::Plaintext, "!Name: glider\n.O.\n..O\nOOX", 4),
+            (Format::Life106, "#Life 1.06\n0 0\n1\n", 3),
+            (Format::Life106, "#Life 1.06\n0 0\n1 2 3\n", 3),
+            (Format::Life106, "#Life 1.06\nx y\n", 2),
+            (Format::Json, "[]", 1),
+            (Format::Json, "{\"width\":3}", 1),
+            (Format::Json, "{\"cells\":[[0,0]]} x", 1),
+            (Format::Json, "{\n\"cells\":[[0,0]],\n\"name\":\"open", 3),
+            (Format::Json, "{\"cells\":[[0]]}", 1),
+            (Format::Json, "{\"cells\":[[0,-1]]}", 1),
+        ];
+        for (format, text, expected) in cases {
+            match Pattern::parse(text, format) {
+                Err(PatternError::Parse { line, .. }) => {
+                    assert_eq!(line, expected, "{:?} {:?}", format, text)
+                }
+                other => panic!("{:?} {:?} gave {:?}", format, text, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Pattern, PatternError};
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut name = None;
+    let mut comments = Vec::new();
+    let mut cells = Vec::new();
+    let mut width = 0;
+    let mut row = 0;
+
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+    {
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(value) => name = Some(value.trim().to_string()),
+                None => comments.push(comment.trim().to_string()),
+            }
+            continue;
+        }
+
+        for (col, symbol) in line.chars().enumerate() {
+            match symbol {
+                'O' | 'o' | '*' => cells.push((row, col as u32)),
+                '.' | ' ' => {}
+                other => {
+                    return Err(PatternError::parse(
+                        number,
+                        format!("unexpected character '{}'", other),
+                    ))
+                }
+            }
+        }
+        width = width.max(line.chars().count() as u32);
+        row += 1;
+    }
+
+    let mut pattern = Pattern::new(width, row, cells);
+    pattern.name = name;
+    pattern.comments = comments;
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+    for comment in &pattern.comments {
+        out.push_str(&format!("!{}\n", comment));
+    }
+
+    let mut cells = pattern.cells().iter().peekable();
+    for row in 0..pattern.height() {
+        let mut line = String::new();
+        while let Some(&&(_, col)) = cells.peek().filter(|&&&(r, _)| r == row) {
+            line.extend((line.len() as u32..col).map(|_| '.'));
+            line.push('O');
+            cells.next();
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,100 @@
+//! A minimal PNG encoder for exporting patterns and activity maps as images. The image data
+//! is stored uncompressed, which keeps the encoder small at the cost of
+//! larger files.
+
+/// Largest block of uncompressed data a single deflate block can hold.
+const STORED_BLOCK: usize = 65535;
+
+/// Grayscale levels of live and dead cells.
+const ALIVE: u8 = 0;
+const DEAD: u8 = 255;
+
+/// Encode the pattern as an 8-bit grayscale PNG, drawing each cell as a
+/// `scale` by `scale` square.
+pub fn encode(pattern: &super::Pattern, scale: u32) -> Vec<u8> {
+    // PNG images cannot be empty, so an empty pattern becomes one dead cell.
+    let (columns, rows) = (pattern.width().max(1), pattern.height().max(1));
+    let mut levels = vec![DEAD; (columns * rows) as usize];
+    for &(row, col) in pattern.cells() {
+        levels[(row * columns + col) as usize] = ALIVE;
+    }
+    encode_gray(&levels, columns, scale)
+}
+
+/// Encode an 8-bit grayscale PNG of `columns` cells per row with the
+/// given level for each cell, row by row, drawing each as a `scale` by
+/// `scale` square.
+pub(crate) fn encode_gray(levels: &[u8], columns: u32, scale: u32) -> Vec<u8> {
+    let scale = scale.max(1);
+    let rows = levels.len() as u32 / columns;
+    let (width, height) = (columns * scale, rows * scale);
+
+    // Every scanline starts with a filter type byte, 0 meaning no filter.
+    let mut pixels = Vec::with_capacity(((width + 1) * height) as usize);
+    for y in 0..height {
+        pixels.push(0);
+        let row = y / scale;
+        pixels.extend((0..width).map(|x| levels[(row * columns + x / scale) as usize]));
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, grayscale, deflate, adaptive filtering, no interlace.
+    header.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib_stored(&pixels));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wrap `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(STORED_BLOCK).peekable();
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let length = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&(!length).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,80 @@
+use super::{Format, Pattern};
+
+/// A classic pattern embedded in the binary.
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    rle: &'static str,
+}
+
+impl Preset {
+    pub fn pattern(&self) -> Pattern {
+        let mut pattern =
+            Pattern::parse(self.rle, Format::Rle).expect("embedded presets are valid RLE");
+        pattern.name = Some(self.name.to_string());
+        pattern
+    }
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "glider",
+        description: "The smallest spaceship, moving diagonally every 4 generations",
+        rle: "x = 3, y = 3\nbo$2bo$3o!",
+    },
+    Preset {
+        name: "lwss",
+        description: "Lightweight spaceship, moving orthogonally every 4 generations",
+        rle: "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!",
+    },
+    Preset {
+        name: "blinker",
+        description: "The smallest oscillator, period 2",
+        rle: "x = 3, y = 1\n3o!",
+    },
+    Preset {
+        name: "beacon",
+        description: "Two blocks blinking at their corners, period 2",
+        rle: "x = 4, y = 4\n2o2b$2o2b$2b2o$2b2o!",
+    },
+    Preset {
+        name: "pulsar",
+        description: "A large symmetric oscillator, period 3",
+        rle: "x = 13, y = 13\n2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$\
+              2b3o3b3o2b$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    },
+    Preset {
+        name: "pentadecathlon",
+        description: "A row of cells oscillating with period 15",
+        rle: "x = 10, y = 3\n2bo4bo2b$2ob4ob2o$2bo4bo!",
+    },
+    Preset {
+        name: "glider-gun",
+        description: "Gosper's glider gun, emitting a glider every 30 generations",
+        rle: "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+              2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    },
+    Preset {
+        name: "r-pentomino",
+        description: "Five cells that take 1103 generations to stabilize",
+        rle: "x = 3, y = 3\nb2o$2ob$bo!",
+    },
+    Preset {
+        name: "acorn",
+        description: "A methuselah growing for 5206 generations",
+        rle: "x = 7, y = 3\nbo5b$3bo3b$2o2b3o!",
+    },
+    Preset {
+        name: "diehard",
+        description: "Vanishes completely after 130 generations",
+        rle: "x = 8, y = 3\n6bob$2o6b$bo3b3o!",
+    },
+];
+
+/// Look up an embedded pattern by name.
+pub fn preset(name: &str) -> Option<Pattern> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .map(Preset::pattern)
+}
@@ -0,0 +1,178 @@
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+/// Longest line written in the body of an RLE file, as the format suggests.
+const LINE_LENGTH: usize = 70;
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut name = None;
+    let mut comments = Vec::new();
+    let mut header = None;
+    let mut cells = Vec::new();
+    let (mut row, mut col) = (0u32, 0u32);
+    let mut count = String::new();
+
+    'lines: for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let (kind, content) = comment.split_at(comment.len().min(1));
+            match kind {
+                "N" => name = Some(content.trim().to_string()),
+                "C" | "c" | "O" => comments.push(content.trim().to_string()),
+                _ => {}
+            }
+            continue;
+        }
+        if header.is_none() {
+            header = Some(parse_header(number, line)?);
+            continue;
+        }
+
+        for symbol in line.chars() {
+            match symbol {
+                '0'..='9' => {
+                    count.push(symbol);
+                    continue;
+                }
+                _ if symbol.is_whitespace() => continue,
+                '!' => break 'lines,
+                _ => {}
+            }
+
+            let run = if count.is_empty() {
+                1
+            } else {
+                count
+                    .parse::<u32>()
+                    .map_err(|_| PatternError::parse(number, "run count is too large"))?
+            };
+            count.clear();
+
+            match symbol {
+                'b' | '.' => col += run,
+                '$' => {
+                    row += run;
+                    col = 0;
+                }
+                'o' | 'A'..='Z' => {
+                    cells.extend((col..col + run).map(|c| (row, c)));
+                    col += run;
+                }
+                other => {
+                    return Err(PatternError::parse(
+                        number,
+                        format!("unexpected character '{}'", other),
+                    ))
+                }
+            }
+        }
+    }
+
+    let (width, height, rule) =
+        header.ok_or_else(|| PatternError::parse(1, "missing 'x = .., y = ..' header"))?;
+    let mut pattern = Pattern::new(width, height, cells);
+    pattern.name = name;
+    pattern.comments = comments;
+    pattern.rule = rule;
+    Ok(pattern)
+}
+
+/// Parse a header line such as `x = 3, y = 3, rule = B3/S23`.
+fn parse_header(number: usize, line: &str) -> Result<(u32, u32, Option<String>), PatternError> {
+    let (mut width, mut height, mut rule) = (None, None, None);
+    for part in line.split(',') {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| PatternError::parse(number, format!("malformed header '{}'", line)))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = value.parse::<u32>().ok(),
+            "y" => height = value.parse::<u32>().ok(),
+            "rule" => rule = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(PatternError::parse(
+            number,
+            "header must give numeric 'x' and 'y' sizes",
+        )),
+    }
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        writeln!(out, "#N {}", name).unwrap();
+    }
+    for comment in &pattern.comments {
+        writeln!(out, "#C {}", comment).unwrap();
+    }
+    write!(out, "x = {}, y = {}", pattern.width(), pattern.height()).unwrap();
+    if let Some(rule) = &pattern.rule {
+        write!(out, ", rule = {}", rule).unwrap();
+    }
+    out.push('\n');
+
+    let mut tokens = Vec::new();
+    let mut cells = pattern.cells().iter().peekable();
+    let mut row = 0;
+    while let Some(&(cell_row, cell_col)) = cells.next() {
+        if cell_row > row {
+            tokens.push(run(cell_row - row, '$'));
+            row = cell_row;
+            tokens.push(run(cell_col, 'b'));
+        } else if tokens.is_empty() {
+            tokens.push(run(cell_col, 'b'));
+        }
+
+        let mut length = 1;
+        let mut end = cell_col;
+        while let Some(&&(next_row, next_col)) = cells.peek() {
+            if next_row != row || next_col != end + 1 {
+                break;
+            }
+            length += 1;
+            end = next_col;
+            cells.next();
+        }
+        tokens.push(run(length, 'o'));
+
+        if let Some(&&(next_row, next_col)) = cells.peek() {
+            if next_row == row {
+                tokens.push(run(next_col - end - 1, 'b'));
+            }
+        }
+    }
+    tokens.push(String::from("!"));
+
+    let mut line_length = 0;
+    for token in tokens.iter().filter(|token| !token.is_empty()) {
+        if line_length + token.len() > LINE_LENGTH {
+            out.push('\n');
+            line_length = 0;
+        }
+        out.push_str(token);
+        line_length += token.len();
+    }
+    out.push('\n');
+    out
+}
+
+/// Encode a run of `length` copies of `tag`, omitting a count of one.
+fn run(length: u32, tag: char) -> String {
+    match length {
+        0 => String::new(),
+        1 => tag.to_string(),
+        _ => format!("{}{}", length, tag),
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::Universe;
+
+/// The most extreme moments of a run, for keeping track of long
+/// exploratory ones. Each record comes with the generation it was first set
+/// in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Records {
+    /// The most cells alive at once.
+    pub max_population: (u64, u64),
+    /// The widest and highest bounds of the live cells, by area.
+    pub largest_bounds: ((u32, u32), u64),
+    /// The most cells born in one generation.
+    pub most_births: (u64, u64),
+    /// The most generations in a row the population stayed the same for.
+    pub longest_steady: (u64, u64),
+    /// The generation observed last, along with the population and the
+    /// generation it has been steady since.
+    last: Option<(u64, u64, u64)>,
+}
+
+impl Records {
+    pub fn new() -> Records {
+        Records::default()
+    }
+
+    /// Take the current generation of `universe` into account. Goes over
+    /// every cell to find the bounds of the live ones.
+    ///
+    /// Stretches of steady population only carry on over generations that
+    /// follow one another, so a rewind or a jump starts a new one.
+    pub fn observe(&mut self, universe: &Universe) {
+        let (generation, population) = (universe.generation(), universe.population());
+        if population > self.max_population.0 {
+            self.max_population = (population, generation);
+        }
+        if universe.births() > self.most_births.0 {
+            self.most_births = (universe.births(), generation);
+        }
+        if let Some((top, left, bottom, right)) = universe.live_bounds() {
+            let size = (right - left + 1, bottom - top + 1);
+            let area = |(width, height): (u32, u32)| width as u64 * height as u64;
+            if area(size) > area(self.largest_bounds.0) {
+                self.largest_bounds = (size, generation);
+            }
+        }
+        let since = match self.last {
+            Some((last, steady, since)) if last + 1 == generation && steady == population => since,
+            Some((last, _, _)) if last == generation => return,
+            _ => generation,
+        };
+        let steady = generation - since;
+        if steady > self.longest_steady.0 {
+            self.longest_steady = (steady, since);
+        }
+        self.last = Some((generation, population, since));
+    }
+}
+
+impl fmt::Display for Records {
+    /// Such as `most alive 612 at generation 48, largest bounds 90x71 at
+    /// generation 310, most births 97 at generation 3, steady for 25
+    /// generations from generation 120`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ((width, height), bounds_at) = self.largest_bounds;
+        write!(
+            f,
+            "most alive {} at generation {}, largest bounds {}x{} at generation {}, \
+             most births {} at generation {}, steady for {} generation{} from generation {}",
+            self.max_population.0,
+            self.max_population.1,
+            width,
+            height,
+            bounds_at,
+            self.most_births.0,
+            self.most_births.1,
+            self.longest_steady.0,
+            if self.longest_steady.0 == 1 { "" } else { "s" },
+            self.longest_steady.1
+        )
+    }
+}
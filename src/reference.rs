@@ -0,0 +1,153 @@
+//! Universes stepped the slow and obvious way, a cell at a time, for the
+//! tests of the faster backends to be checked against.
+
+use std::fmt::Debug;
+
+use crate::universe::{GROW_MARGIN, MAX_GROWN_SIZE};
+use crate::{Boundary, Cell, Edge, Rule, Universe};
+
+/// Rules the backends are checked under, born and surviving on counts far
+/// enough apart to catch a miscounted neighbor.
+pub(crate) const RULES: [&str; 5] = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B1357/S02468"];
+
+/// Sizes from a single cell to several words a row, around the ends of
+/// words.
+pub(crate) const SIZES: [(u32, u32); 7] = [
+    (1, 1),
+    (3, 2),
+    (63, 5),
+    (64, 9),
+    (65, 4),
+    (130, 7),
+    (300, 12),
+];
+
+pub(crate) const EDGES: [Edge; 4] = [Edge::Torus, Edge::Dead, Edge::Mirror, Edge::Grow];
+
+/// Every edge on every axis.
+pub(crate) fn boundaries() -> impl Iterator<Item = Boundary> {
+    EDGES
+        .into_iter()
+        .flat_map(|x| EDGES.into_iter().map(move |y| Boundary { x, y }))
+}
+
+/// A random soup of the given size running `rule` within `boundary`.
+pub(crate) fn soup(width: u32, height: u32, seed: u64, rule: &str, boundary: Boundary) -> Universe {
+    let mut universe = Universe::random(width, height, 0.4, seed);
+    universe.set_rule(Rule::parse(rule).unwrap());
+    universe.set_boundary(boundary);
+    universe
+}
+
+/// The generation after `universe`, grown first if its edges grow, and
+/// each cell worked out from its neighbors as `Edge::resolve` finds them.
+pub(crate) fn tick(universe: &Universe) -> Universe {
+    let universe = grown(universe);
+    let (width, height) = (universe.width(), universe.height());
+    let (boundary, rule) = (universe.boundary(), universe.rule());
+    let cells = universe.get_cells();
+    let alive = |row: Option<u32>, column: Option<u32>| match (row, column) {
+        (Some(row), Some(column)) => cells[(row * width + column) as usize] == Cell::Alive,
+        _ => false,
+    };
+    let mut born = Vec::new();
+    for row in 0..height {
+        for column in 0..width {
+            let neighbors = OFFSETS
+                .iter()
+                .filter(|&&(dr, dc)| {
+                    alive(
+                        boundary.y.resolve(row, dr, height),
+                        boundary.x.resolve(column, dc, width),
+                    )
+                })
+                .count() as u8;
+            let cell = cells[(row * width + column) as usize];
+            if rule.next(cell, neighbors) == Cell::Alive {
+                born.push((row, column));
+            }
+        }
+    }
+    let mut next = Universe::empty(width, height);
+    next.set_rule(rule);
+    next.set_boundary(boundary);
+    next.set_cells(&born);
+    next
+}
+
+const OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// `universe` with `GROW_MARGIN` dead cells added past each growing edge
+/// that a live cell touches, unless the axis is already as long as it gets.
+pub(crate) fn grown(universe: &Universe) -> Universe {
+    let (width, height) = (universe.width(), universe.height());
+    let boundary = universe.boundary();
+    let live: Vec<(u32, u32)> = (0..height)
+        .flat_map(|row| (0..width).map(move |column| (row, column)))
+        .filter(|&(row, column)| universe.get_cell(row, column) == Cell::Alive)
+        .collect();
+    let grows = |edge: Edge, size: u32, touched: bool| {
+        if edge == Edge::Grow && size < MAX_GROWN_SIZE && touched {
+            GROW_MARGIN
+        } else {
+            0
+        }
+    };
+    let top = grows(boundary.y, height, live.iter().any(|&(r, _)| r == 0));
+    let bottom = grows(
+        boundary.y,
+        height,
+        live.iter().any(|&(r, _)| r == height - 1),
+    );
+    let left = grows(boundary.x, width, live.iter().any(|&(_, c)| c == 0));
+    let right = grows(boundary.x, width, live.iter().any(|&(_, c)| c == width - 1));
+
+    let mut grown = Universe::empty(width + left + right, height + top + bottom);
+    grown.set_rule(universe.rule());
+    grown.set_boundary(boundary);
+    grown.set_cells(
+        &live
+            .iter()
+            .map(|&(r, c)| (r + top, c + left))
+            .collect::<Vec<_>>(),
+    );
+    grown
+}
+
+/// Check that `actual` holds the same cells as `expected`, and knows how
+/// many of them are alive, saying which `case` failed otherwise.
+pub(crate) fn assert_same(actual: &Universe, expected: &Universe, case: impl Debug) {
+    let size = |universe: &Universe| (universe.width(), universe.height());
+    assert_eq!(size(actual), size(expected), "size of {:?}", case);
+    let (cells, expected_cells) = (actual.get_cells(), expected.get_cells());
+    if let Some(idx) = (0..cells.len()).find(|&idx| cells[idx] != expected_cells[idx]) {
+        let width = actual.width() as usize;
+        panic!(
+            "cell {},{} of {:?} is {:?}, expected {:?}",
+            idx / width,
+            idx % width,
+            case,
+            cells[idx],
+            expected_cells[idx]
+        );
+    }
+    let population = expected_cells
+        .iter()
+        .filter(|&&cell| cell == Cell::Alive)
+        .count();
+    assert_eq!(
+        actual.population(),
+        population as u64,
+        "population of {:?}",
+        case
+    );
+}
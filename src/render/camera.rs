@@ -0,0 +1,185 @@
+use std::io;
+
+use super::{
+    enter_terminal, leave_terminal, locate, present, start_frame, Charset, Renderer, Status,
+};
+use crate::Universe;
+
+/// A window onto part of the universe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Camera {
+    pub top: u32,
+    pub left: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Camera {
+    pub fn new(width: u32, height: u32) -> Camera {
+        Camera {
+            top: 0,
+            left: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Whether the cell at `row` and `column` is in view.
+    pub fn contains(&self, row: u32, column: u32) -> bool {
+        (self.top..self.top + self.height).contains(&row)
+            && (self.left..self.left + self.width).contains(&column)
+    }
+
+    /// Move the camera towards the center of the live cells' bounding box.
+    ///
+    /// The camera covers half the remaining distance on every call, which
+    /// keeps fast movers on screen without making the view jitter.
+    pub fn follow(&mut self, universe: &Universe) {
+        self.width = self.width.min(universe.width());
+        self.height = self.height.min(universe.height());
+
+        if let Some((top, left, bottom, right)) = universe.live_bounds() {
+            let target_top = ((top + bottom) / 2).saturating_sub(self.height / 2);
+            let target_left = ((left + right) / 2).saturating_sub(self.width / 2);
+            self.top = approach(self.top, target_top);
+            self.left = approach(self.left, target_left);
+        }
+
+        self.top = self.top.min(universe.height() - self.height);
+        self.left = self.left.min(universe.width() - self.width);
+    }
+}
+
+/// Halfway between `from` and `to`, rounding towards `to`.
+fn approach(from: u32, to: u32) -> u32 {
+    if to > from {
+        from + (to - from).div_ceil(2)
+    } else {
+        from - (from - to).div_ceil(2)
+    }
+}
+
+/// Draws only the part of the universe seen by a camera that tracks the
+/// live cells, so escaping gliders stay on screen in large universes.
+pub struct FollowRenderer {
+    charset: Charset,
+    camera: Camera,
+    /// Reused from frame to frame.
+    frame: String,
+}
+
+impl FollowRenderer {
+    pub fn new(charset: Charset, camera: Camera) -> FollowRenderer {
+        FollowRenderer {
+            charset,
+            camera,
+            frame: String::new(),
+        }
+    }
+}
+
+impl Renderer for FollowRenderer {
+    fn init(&mut self, _universe: &Universe) -> io::Result<()> {
+        enter_terminal()
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        self.camera.follow(universe);
+
+        let camera = self.camera;
+        start_frame(&mut self.frame);
+        for row in camera.top..camera.top + camera.height {
+            for column in camera.left..camera.left + camera.width {
+                let cell = universe.get_cell(row, column);
+                self.frame.push_str(self.charset.glyph(cell));
+            }
+            self.frame.push('\n');
+        }
+        present(&mut self.frame, status, &self.charset, camera)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        leave_terminal()
+    }
+
+    fn cell_at(&self, _universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        locate(self.camera, &self.charset, row, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cameras_see_their_window_only() {
+        let camera = Camera {
+            top: 2,
+            left: 3,
+            width: 4,
+            height: 2,
+        };
+        assert!(camera.contains(2, 3));
+        assert!(camera.contains(3, 6));
+        assert!(!camera.contains(4, 3));
+        assert!(!camera.contains(2, 7));
+        assert!(!camera.contains(1, 3));
+    }
+
+    #[test]
+    fn approaching_covers_half_the_distance() {
+        assert_eq!(approach(0, 45), 23);
+        assert_eq!(approach(23, 45), 34);
+        assert_eq!(approach(45, 44), 44);
+        assert_eq!(approach(10, 0), 5);
+        assert_eq!(approach(7, 7), 7);
+    }
+
+    #[test]
+    fn the_camera_closes_in_on_the_live_cells() {
+        let mut universe = Universe::empty(100, 100);
+        universe.set_cells(&[(50, 50)]);
+        let mut camera = Camera::new(10, 10);
+        camera.follow(&universe);
+        assert_eq!((camera.top, camera.left), (23, 23));
+        for _ in 0..10 {
+            camera.follow(&universe);
+        }
+        assert_eq!((camera.top, camera.left), (45, 45));
+        assert!(camera.contains(50, 50));
+
+        // An empty universe leaves the camera where it was.
+        universe.reset();
+        camera.follow(&universe);
+        assert_eq!((camera.top, camera.left), (45, 45));
+    }
+
+    #[test]
+    fn the_camera_stays_inside_the_universe() {
+        let mut universe = Universe::empty(100, 50);
+        universe.set_cells(&[(49, 99)]);
+        let mut camera = Camera::new(10, 10);
+        for _ in 0..10 {
+            camera.follow(&universe);
+        }
+        assert_eq!((camera.top, camera.left), (40, 90));
+
+        // Nor does it grow past a smaller universe.
+        let small = Universe::empty(6, 4);
+        camera.follow(&small);
+        assert_eq!(camera, Camera::new(6, 4));
+    }
+
+    #[test]
+    fn clicks_land_on_the_cells_in_view() {
+        let mut universe = Universe::empty(100, 100);
+        universe.set_cells(&[(50, 50)]);
+        let mut camera = Camera::new(10, 10);
+        for _ in 0..10 {
+            camera.follow(&universe);
+        }
+        let renderer = FollowRenderer::new(Charset::ASCII, camera);
+        assert_eq!(renderer.cell_at(&universe, 5, 10), Some((50, 50)));
+        assert_eq!(renderer.cell_at(&universe, 10, 0), None);
+    }
+}
@@ -0,0 +1,82 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Whether renderers may use ANSI colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Use colors when stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub const NAMES: [&'static str; 3] = ["auto", "always", "never"];
+
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+
+    /// Decide whether to color the output, following the `NO_COLOR`
+    /// convention (https://no-color.org) in auto mode.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                !no_color && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// The colors cells are drawn in, for boards that stand out from the rest
+/// of the terminal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    /// The terminal's own colors.
+    #[default]
+    Plain,
+    /// Phosphor green on black.
+    Green,
+    /// Amber on black, like old monochrome monitors.
+    Amber,
+    /// Cyan on navy.
+    Ocean,
+    /// Black on white, for light terminals set to a dark background.
+    Paper,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 5] = ["plain", "green", "amber", "ocean", "paper"];
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "plain" | "none" => Some(Theme::Plain),
+            "green" => Some(Theme::Green),
+            "amber" => Some(Theme::Amber),
+            "ocean" => Some(Theme::Ocean),
+            "paper" => Some(Theme::Paper),
+            _ => None,
+        }
+    }
+
+    /// The 256-color palette indices of the cells and the background, or
+    /// `None` to leave the terminal's colors alone.
+    pub fn colors(self) -> Option<(u8, u8)> {
+        match self {
+            Theme::Plain => None,
+            Theme::Green => Some((46, 16)),
+            Theme::Amber => Some((214, 16)),
+            Theme::Ocean => Some((51, 17)),
+            Theme::Paper => Some((16, 231)),
+        }
+    }
+}
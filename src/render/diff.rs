@@ -0,0 +1,188 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use super::{
+    enter_terminal, leave_terminal, locate, start_frame, write_cursor, write_frame, write_hud,
+    Camera, Charset, Renderer, Status,
+};
+use crate::Universe;
+
+/// Redraws only the rows that changed since the previous frame, moving the
+/// cursor to each of them instead of reprinting the whole grid.
+///
+/// This keeps terminal I/O proportional to activity, which matters a lot
+/// for wide universes where most rows are static.
+pub struct DiffRenderer {
+    charset: Charset,
+    /// Dimensions of the last frame, `None` until something was drawn.
+    size: Option<(u32, u32)>,
+    /// Rows the edit tool preview was drawn over, which need redrawing once it
+    /// moves away.
+    previewed: Vec<u32>,
+    /// Reused from frame to frame.
+    frame: String,
+}
+
+impl DiffRenderer {
+    pub fn new(charset: Charset) -> DiffRenderer {
+        DiffRenderer {
+            charset,
+            size: None,
+            previewed: Vec::new(),
+            frame: String::new(),
+        }
+    }
+
+    /// Write the next frame into `frame`, leaving out the rows that stayed
+    /// as they were drawn last.
+    fn compose(&mut self, universe: &Universe, status: &Status) {
+        let size = (universe.width(), universe.height());
+        let full = self.size != Some(size);
+        self.size = Some(size);
+
+        let frame = &mut self.frame;
+        if full {
+            start_frame(frame);
+        } else {
+            frame.clear();
+        }
+
+        for (row, &changed) in (0..).zip(universe.changed_rows()) {
+            if !full && !changed && !self.previewed.contains(&row) {
+                continue;
+            }
+            write!(frame, "\x1B[{};1H", row + 1).expect("writing to a String cannot fail");
+            for column in 0..universe.width() {
+                frame.push_str(self.charset.glyph(universe.get_cell(row, column)));
+            }
+        }
+
+        write!(frame, "\x1B[{};1H\x1B[2K", universe.height() + 1)
+            .expect("writing to a String cannot fail");
+        if status.hud_visible() {
+            write_hud(frame, status).expect("writing to a String cannot fail");
+        }
+        let view = Camera::new(universe.width(), universe.height());
+        write_cursor(frame, status, &self.charset, view);
+        self.previewed = status.preview.iter().map(|&(row, _)| row).collect();
+        self.previewed.dedup();
+    }
+}
+
+impl Renderer for DiffRenderer {
+    fn init(&mut self, _universe: &Universe) -> io::Result<()> {
+        self.size = None;
+        enter_terminal()
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        self.compose(universe, status);
+        write_frame(&self.frame)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        if let Some((_, height)) = self.size {
+            writeln!(stdout, "\x1B[{};1H", height + 2)?;
+        }
+        leave_terminal()
+    }
+
+    fn invalidate(&mut self) {
+        self.size = None;
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        let view = Camera::new(universe.width(), universe.height());
+        locate(view, &self.charset, row, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::CLEAR_SCREEN;
+    use crate::Cell;
+
+    /// The rows a frame moves the cursor to before drawing, leaving out the
+    /// line of the HUD.
+    fn drawn_rows(renderer: &DiffRenderer, height: u32) -> Vec<u32> {
+        renderer
+            .frame
+            .trim_start_matches(CLEAR_SCREEN)
+            .split("\x1B[")
+            .filter_map(|sequence| sequence.split_once(";1H"))
+            .map(|(row, _)| row)
+            .filter_map(|row| row.parse::<u32>().ok())
+            .map(|row| row - 1)
+            .filter(|&row| row < height)
+            .collect()
+    }
+
+    fn blinker() -> Universe {
+        let mut universe = Universe::empty(6, 6);
+        universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+        universe.clear_changed_rows();
+        universe
+    }
+
+    #[test]
+    fn the_first_frame_is_drawn_whole() {
+        let universe = blinker();
+        let mut renderer = DiffRenderer::new(Charset::ASCII);
+        renderer.compose(&universe, &Status::default());
+        assert!(renderer.frame.starts_with(CLEAR_SCREEN));
+        assert_eq!(drawn_rows(&renderer, 6), [0, 1, 2, 3, 4, 5]);
+        assert!(renderer.frame.contains("\x1B[3;1H. # # # . . "));
+    }
+
+    #[test]
+    fn only_changed_rows_are_redrawn() {
+        let mut universe = blinker();
+        let mut renderer = DiffRenderer::new(Charset::ASCII);
+        renderer.compose(&universe, &Status::default());
+        universe.clear_changed_rows();
+        universe.tick();
+        renderer.compose(&universe, &Status::default());
+        assert!(!renderer.frame.contains(CLEAR_SCREEN));
+        assert_eq!(drawn_rows(&renderer, 6), [1, 2, 3]);
+
+        universe.clear_changed_rows();
+        renderer.compose(&universe, &Status::default());
+        assert_eq!(drawn_rows(&renderer, 6), []);
+        universe.set_cell(5, 5, Cell::Alive);
+        renderer.compose(&universe, &Status::default());
+        assert_eq!(drawn_rows(&renderer, 6), [5]);
+    }
+
+    #[test]
+    fn previewed_rows_are_redrawn_once_the_preview_moves() {
+        let mut universe = blinker();
+        let mut renderer = DiffRenderer::new(Charset::ASCII);
+        renderer.compose(&universe, &Status::default());
+        let previewing = Status {
+            preview: vec![(4, 1), (4, 2)],
+            ..Status::default()
+        };
+        renderer.compose(&universe, &previewing);
+        assert_eq!(drawn_rows(&renderer, 6), []);
+        universe.clear_changed_rows();
+        renderer.compose(&universe, &Status::default());
+        assert_eq!(drawn_rows(&renderer, 6), [4]);
+    }
+
+    #[test]
+    fn frames_are_drawn_whole_after_invalidating_or_resizing() {
+        let mut universe = blinker();
+        let mut renderer = DiffRenderer::new(Charset::ASCII);
+        renderer.compose(&universe, &Status::default());
+        renderer.invalidate();
+        renderer.compose(&universe, &Status::default());
+        assert_eq!(drawn_rows(&renderer, 6).len(), 6);
+
+        universe.resize(8, 4);
+        universe.clear_changed_rows();
+        renderer.compose(&universe, &Status::default());
+        assert_eq!(drawn_rows(&renderer, 4), [0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,128 @@
+//! A small animated GIF encoder for recordings, limited to the two colors
+//! a universe needs.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Colors of dead and live cells, as indices 0 and 1 of the palette.
+const PALETTE: [u8; 6] = [255, 255, 255, 0, 0, 0];
+
+/// Smallest LZW code size the format allows, enough for two colors.
+const MIN_CODE_SIZE: u8 = 2;
+
+/// Codes are at most 12 bits long, so the dictionary holds 4096 entries.
+const MAX_CODES: u16 = 1 << 12;
+
+/// Write the header of a looping animation of `width` by `height` pixels.
+pub(crate) fn write_header(out: &mut impl Write, width: u16, height: u16) -> io::Result<()> {
+    out.write_all(b"GIF89a")?;
+    out.write_all(&width.to_le_bytes())?;
+    out.write_all(&height.to_le_bytes())?;
+    // A global color table of two entries, background color 0.
+    out.write_all(&[0x80, 0, 0])?;
+    out.write_all(&PALETTE)?;
+    // Loop forever.
+    out.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")
+}
+
+/// Write one frame of palette indices, shown for `delay` hundredths of a
+/// second.
+pub(crate) fn write_frame(
+    out: &mut impl Write,
+    width: u16,
+    height: u16,
+    pixels: &[u8],
+    delay: u16,
+) -> io::Result<()> {
+    out.write_all(&[0x21, 0xF9, 4, 0])?;
+    out.write_all(&delay.to_le_bytes())?;
+    out.write_all(&[0, 0])?;
+
+    out.write_all(&[0x2C, 0, 0, 0, 0])?;
+    out.write_all(&width.to_le_bytes())?;
+    out.write_all(&height.to_le_bytes())?;
+    out.write_all(&[0, MIN_CODE_SIZE])?;
+    for block in compress(pixels).chunks(255) {
+        out.write_all(&[block.len() as u8])?;
+        out.write_all(block)?;
+    }
+    out.write_all(&[0])
+}
+
+pub(crate) fn write_trailer(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(&[0x3B])
+}
+
+/// LZW-compress palette indices the way GIF expects, packing codes least
+/// significant bit first.
+fn compress(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+
+    let mut bits = BitWriter::default();
+    let mut dictionary: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+    bits.write(clear, code_size);
+
+    let mut pixels = pixels.iter();
+    let mut prefix = match pixels.next() {
+        Some(&first) => first as u16,
+        None => {
+            bits.write(end, code_size);
+            return bits.finish();
+        }
+    };
+    for &pixel in pixels {
+        if let Some(&code) = dictionary.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+
+        bits.write(prefix, code_size);
+        if next == MAX_CODES {
+            bits.write(clear, code_size);
+            dictionary.clear();
+            next = end + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        } else {
+            dictionary.insert((prefix, pixel), next);
+            // The decoder adds its entries one code later, so it widens
+            // its codes once this entry is the first that doesn't fit.
+            if next == 1 << code_size {
+                code_size += 1;
+            }
+            next += 1;
+        }
+        prefix = pixel as u16;
+    }
+    bits.write(prefix, code_size);
+    bits.write(end, code_size);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    length: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.length;
+        self.length += size;
+        while self.length >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.length -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.length > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
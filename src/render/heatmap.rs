@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use super::{
+    enter_terminal, leave_terminal, locate, present, start_frame, Camera, Charset, Renderer, Status,
+};
+use crate::pattern::{png, PNG_SCALE};
+use crate::{Cell, Universe};
+
+/// 256-color palette used by the heatmap, from cold to hot.
+const HEAT_PALETTE: [u8; 12] = [17, 19, 21, 27, 33, 39, 45, 50, 82, 226, 208, 196];
+
+/// Counts how many of the last `window` generations drawn each cell spent
+/// alive, or how many of all of them.
+pub struct ActivityMap {
+    /// `None` to never forget a generation.
+    window: Option<usize>,
+    width: u32,
+    history: VecDeque<Vec<Cell>>,
+    counts: Vec<u32>,
+}
+
+impl ActivityMap {
+    pub fn new(window: Option<usize>, universe: &Universe) -> ActivityMap {
+        ActivityMap {
+            window: window.map(|window| window.max(1)),
+            width: universe.width(),
+            history: VecDeque::new(),
+            counts: vec![0; universe.width() as usize * universe.height() as usize],
+        }
+    }
+
+    /// Add the current generation of the universe to the map, forgetting
+    /// the oldest one once the window is full. Resizing the universe starts
+    /// the map afresh.
+    pub fn record(&mut self, universe: &Universe) {
+        let cells = universe.get_cells();
+        if cells.len() != self.counts.len() || universe.width() != self.width {
+            // The universe was resized, so the old history no longer lines up.
+            *self = ActivityMap::new(self.window, universe);
+        }
+        for (count, &cell) in self.counts.iter_mut().zip(&cells) {
+            *count += cell as u32;
+        }
+        let window = match self.window {
+            Some(window) => window,
+            None => return,
+        };
+        self.history.push_back(cells);
+
+        if self.history.len() > window {
+            if let Some(oldest) = self.history.pop_front() {
+                for (count, cell) in self.counts.iter_mut().zip(oldest) {
+                    *count -= cell as u32;
+                }
+            }
+        }
+    }
+
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// The counts row by row, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[u32]> + '_ {
+        self.counts.chunks(self.width.max(1) as usize)
+    }
+
+    /// The count standing for the most activity: the window if there is
+    /// one, or else the highest count so far.
+    fn peak(&self) -> usize {
+        match self.window {
+            Some(window) => window,
+            None => self
+                .counts
+                .iter()
+                .max()
+                .map_or(1, |&peak| peak.max(1) as usize),
+        }
+    }
+
+    /// Render the map as a grayscale PNG image, darker the more active a
+    /// cell was, each cell `scale` pixels wide.
+    pub fn to_png(&self, scale: u32) -> Vec<u8> {
+        let peak = self.peak();
+        let levels: Vec<u8> = self
+            .counts
+            .iter()
+            .map(|&count| 255 - (count as usize * 255 / peak).min(255) as u8)
+            .collect();
+        match self.width {
+            // PNG images cannot be empty.
+            0 => png::encode_gray(&[255], 1, scale),
+            width => png::encode_gray(&levels, width, scale),
+        }
+    }
+
+    /// Write the map row by row, coloring the alive glyph by activity.
+    ///
+    /// Without colors the activity is shown with the live, fading and faded
+    /// glyphs, from most to least active.
+    pub fn write_grid(
+        &self,
+        f: &mut impl fmt::Write,
+        charset: &Charset,
+        color: bool,
+    ) -> fmt::Result {
+        let top = HEAT_PALETTE.len() - 1;
+        let peak = self.peak();
+        for line in self.rows() {
+            for &count in line {
+                if count == 0 {
+                    f.write_str(&charset.dead)?;
+                } else if color {
+                    let heat = (count as usize * top).div_ceil(peak);
+                    let color = HEAT_PALETTE[heat.min(top)];
+                    write!(f, "\x1B[38;5;{}m{}\x1B[0m", color, charset.alive)?;
+                } else {
+                    let glyph = match count as usize * 3 / peak {
+                        0 => &charset.faded,
+                        1 => &charset.fading,
+                        _ => &charset.alive,
+                    };
+                    f.write_str(glyph)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Colors each cell by how often it was alive in the last generations, or
+/// in all of them.
+pub struct HeatmapRenderer {
+    window: Option<usize>,
+    charset: Charset,
+    color: bool,
+    activity: Option<ActivityMap>,
+    /// Where to save the map as an image when the session ends.
+    image: Option<String>,
+    /// Reused from frame to frame.
+    frame: String,
+}
+
+impl HeatmapRenderer {
+    /// Count the last `window` generations drawn, or all of them if
+    /// `None`.
+    pub fn new(window: Option<usize>, charset: Charset, color: bool) -> HeatmapRenderer {
+        HeatmapRenderer {
+            window,
+            charset,
+            color,
+            activity: None,
+            image: None,
+            frame: String::new(),
+        }
+    }
+
+    /// Save the map as a PNG image at `path` once the session ends.
+    pub fn save_image(&mut self, path: String) {
+        self.image = Some(path);
+    }
+}
+
+impl Renderer for HeatmapRenderer {
+    fn init(&mut self, universe: &Universe) -> io::Result<()> {
+        self.activity = Some(ActivityMap::new(self.window, universe));
+        enter_terminal()
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        let window = self.window;
+        let activity = self
+            .activity
+            .get_or_insert_with(|| ActivityMap::new(window, universe));
+        activity.record(universe);
+
+        start_frame(&mut self.frame);
+        activity
+            .write_grid(&mut self.frame, &self.charset, self.color)
+            .expect("writing to a String cannot fail");
+        let view = Camera::new(universe.width(), universe.height());
+        present(&mut self.frame, status, &self.charset, view)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        leave_terminal()?;
+        match (&self.image, &self.activity) {
+            (Some(path), Some(activity)) => fs::write(path, activity.to_png(PNG_SCALE))
+                .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path, error))),
+            _ => Ok(()),
+        }
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        let view = Camera::new(universe.width(), universe.height());
+        locate(view, &self.charset, row, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A blinker standing upright in the middle of a 5x5 universe, which
+    /// lies down next generation.
+    fn blinker() -> Universe {
+        let mut universe = Universe::empty(5, 5);
+        universe.set_cells(&[(1, 2), (2, 2), (3, 2)]);
+        universe
+    }
+
+    #[test]
+    fn counts_add_up_the_generations_each_cell_was_alive() {
+        let mut universe = blinker();
+        let mut activity = ActivityMap::new(None, &universe);
+        for _ in 0..4 {
+            activity.record(&universe);
+            universe.tick();
+        }
+        let rows: Vec<&[u32]> = activity.rows().collect();
+        assert_eq!(rows[1], [0, 0, 2, 0, 0]);
+        // The center is alive in every phase, its sides in every other one.
+        assert_eq!(rows[2], [0, 2, 4, 2, 0]);
+        assert_eq!(rows[0], [0; 5]);
+        assert_eq!(activity.peak(), 4);
+    }
+
+    #[test]
+    fn windows_forget_the_oldest_generation() {
+        let mut universe = blinker();
+        let mut activity = ActivityMap::new(Some(1), &universe);
+        activity.record(&universe);
+        universe.tick();
+        activity.record(&universe);
+        // Only the lying blinker is left.
+        assert_eq!(activity.counts().iter().sum::<u32>(), 3);
+        assert_eq!(activity.rows().nth(2).unwrap(), [0, 1, 1, 1, 0]);
+        assert_eq!(activity.peak(), 1);
+
+        // A window of none still remembers the last generation.
+        assert_eq!(ActivityMap::new(Some(0), &universe).peak(), 1);
+    }
+
+    #[test]
+    fn resizing_starts_the_map_over() {
+        let mut universe = blinker();
+        let mut activity = ActivityMap::new(Some(8), &universe);
+        activity.record(&universe);
+        universe.resize(7, 5);
+        activity.record(&universe);
+        assert_eq!(activity.counts().len(), 35);
+        assert_eq!(activity.counts().iter().sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn heat_is_drawn_with_fainter_glyphs_or_colder_colors() {
+        let mut universe = blinker();
+        let mut activity = ActivityMap::new(None, &universe);
+        for _ in 0..4 {
+            activity.record(&universe);
+            universe.tick();
+        }
+        let mut plain = String::new();
+        activity
+            .write_grid(&mut plain, &Charset::ASCII, false)
+            .unwrap();
+        let lines: Vec<&str> = plain.lines().collect();
+        assert_eq!(lines[1], ". . + . . ");
+        assert_eq!(lines[2], ". + # + . ");
+
+        let mut colored = String::new();
+        activity
+            .write_grid(&mut colored, &Charset::ASCII, true)
+            .unwrap();
+        let row = colored.lines().nth(2).unwrap();
+        assert!(row.contains("\x1B[38;5;196m# \x1B[0m"), "{:?}", row);
+        assert!(row.contains("\x1B[38;5;45m# \x1B[0m"), "{:?}", row);
+    }
+
+    #[test]
+    fn images_are_darker_where_cells_were_busier() {
+        let mut activity = ActivityMap::new(None, &blinker());
+        activity.record(&blinker());
+        let image = activity.to_png(1);
+        assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let empty = ActivityMap::new(None, &Universe::empty(0, 0));
+        assert!(empty.to_png(1).starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::time::Duration;
+
+use crate::{Cycle, Records, Universe};
+
+/// How many generations the population sparkline spans.
+pub const SPARKLINE: usize = 40;
+
+/// Information about the running session shown alongside the grid.
+#[derive(Clone, Debug, Default)]
+pub struct Status {
+    pub generation: u64,
+    pub population: u64,
+    /// Cells born and cells that died in the last generation.
+    pub births: u64,
+    pub deaths: u64,
+    /// The cycle the universe was found to be in, if any.
+    pub cycle: Option<Cycle>,
+    /// The records set so far, when they are kept.
+    pub records: Option<Records>,
+    /// The population at the last `SPARKLINE` generations caught up with,
+    /// oldest first.
+    pub populations: VecDeque<u64>,
+    /// Whether the HUD draws `populations` as a sparkline.
+    pub show_sparkline: bool,
+    /// Measured simulation speed in generations per second.
+    pub rate: f64,
+    /// Cells updated per second, counting only the time spent simulating
+    /// and leaving out drawing and waiting for the next frame.
+    pub cell_rate: f64,
+    /// Generations simulated over the whole session and the time spent
+    /// simulating them.
+    pub simulated: (u64, Duration),
+    pub rule: String,
+    pub paused: bool,
+    /// Requested time between frames, or `None` to run without delay.
+    pub interval: Option<Duration>,
+    /// Whether frames are skipped to simulate as fast as possible.
+    pub max_speed: bool,
+    /// Cell under the edit cursor, or `None` when not editing.
+    pub cursor: Option<(u32, u32)>,
+    /// The edit tool in use other than toggling cells, such as the pattern
+    /// stamped at the cursor.
+    pub tool: Option<String>,
+    /// Cells the tool would affect, previewed over the grid.
+    pub preview: Vec<(u32, u32)>,
+    /// A note about the last thing done, such as where a file was saved.
+    pub message: Option<String>,
+    /// A question being answered on the HUD line, along with the answer
+    /// typed so far.
+    pub prompt: Option<String>,
+    /// Whether the one-line HUD should be drawn at all.
+    pub show_hud: bool,
+}
+
+impl Status {
+    /// The status of a session about to start on `universe`.
+    pub fn new(universe: &Universe, show_hud: bool) -> Status {
+        Status {
+            generation: universe.generation(),
+            rule: universe.rule().to_string(),
+            population: universe.population(),
+            populations: VecDeque::from([universe.population()]),
+            show_hud,
+            ..Status::default()
+        }
+    }
+
+    /// Whether the HUD line is drawn, which it always is while prompting.
+    pub fn hud_visible(&self) -> bool {
+        self.show_hud || self.prompt.is_some()
+    }
+
+    /// Account for one more generation of `universe`, which took `elapsed`
+    /// seconds of wall time to produce.
+    pub fn advance(&mut self, universe: &Universe, elapsed: f64) {
+        self.advance_by(universe, 1, elapsed);
+    }
+
+    /// Catch up with `universe` after it was stepped through by hand or
+    /// otherwise changed, which says nothing about the simulation speed.
+    pub fn step(&mut self, universe: &Universe) {
+        if universe.generation() != self.generation {
+            if self.populations.len() == SPARKLINE {
+                self.populations.pop_front();
+            }
+            self.populations.push_back(universe.population());
+        }
+        self.generation = universe.generation();
+        self.rule = universe.rule().to_string();
+        self.population = universe.population();
+        self.births = universe.births();
+        self.deaths = universe.deaths();
+        if let Some(records) = &mut self.records {
+            records.observe(universe);
+        }
+    }
+
+    /// Account for `generations` more generations produced in `elapsed`
+    /// seconds, as when several are simulated per frame.
+    pub fn advance_by(&mut self, universe: &Universe, generations: u64, elapsed: f64) {
+        self.step(universe);
+
+        let rate = generations as f64 / elapsed.max(f64::EPSILON);
+        self.rate = if self.rate == 0.0 {
+            rate
+        } else {
+            self.rate * 0.9 + rate * 0.1
+        };
+    }
+
+    /// Account for `busy` time spent simulating `generations` generations
+    /// of `universe`, feeding the throughput figures.
+    pub fn simulated(&mut self, universe: &Universe, generations: u64, busy: Duration) {
+        self.simulated.0 += generations;
+        self.simulated.1 += busy;
+
+        let cells = universe.width() as f64 * universe.height() as f64 * generations as f64;
+        let rate = cells / busy.as_secs_f64().max(f64::EPSILON);
+        self.cell_rate = if self.cell_rate == 0.0 {
+            rate
+        } else {
+            self.cell_rate * 0.9 + rate * 0.1
+        };
+    }
+
+    /// Lines summing up where the session ended and the simulation speed
+    /// over all of it, or `None` if nothing was simulated.
+    pub fn summary(&self, universe: &Universe) -> Option<String> {
+        let (generations, busy) = self.simulated;
+        if generations == 0 {
+            return None;
+        }
+        let mut summary = format!(
+            "{}\n{}",
+            ending(universe, self.cycle),
+            throughput((universe.width(), universe.height()), generations, busy)
+        );
+        if let Some(records) = &self.records {
+            write!(summary, "\nrecords: {}", records).expect("writing to a String cannot fail");
+        }
+        Some(summary)
+    }
+}
+
+/// `values` as a line of block glyphs, one per value, each as tall as
+/// the value between the least and the greatest of them.
+pub fn sparkline(values: &VecDeque<u64>) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let least = values.iter().copied().min().unwrap_or(0);
+    let range = values.iter().copied().max().unwrap_or(0) - least;
+    values
+        .iter()
+        .map(|&value| match range {
+            0 => BARS[0],
+            _ => BARS[((value - least) * 7 / range) as usize],
+        })
+        .collect()
+}
+
+/// Where a run on `universe` ended, along with the cycle it was found to be
+/// in, such as `generation 120, population 57, cycle of period 2 since
+/// generation 98`.
+pub fn ending(universe: &Universe, cycle: Option<Cycle>) -> String {
+    let mut ending = format!(
+        "generation {}, population {}",
+        universe.generation(),
+        universe.population()
+    );
+    if let Some(cycle) = cycle {
+        write!(ending, ", {}", cycle).expect("writing to a String cannot fail");
+    }
+    ending
+}
+
+/// How fast `generations` generations of a universe of `size` cells wide
+/// and high were simulated in `busy` time, such as `500 generations in
+/// 0.123s (4065.0 generations/s, 1.1G cells/s)`.
+pub fn throughput(size: (u32, u32), generations: u64, busy: Duration) -> String {
+    let seconds = busy.as_secs_f64().max(f64::EPSILON);
+    let cells = size.0 as f64 * size.1 as f64 * generations as f64;
+    format!(
+        "{} generation{} in {:.3}s ({:.1} generations/s, {} cells/s)",
+        generations,
+        if generations == 1 { "" } else { "s" },
+        busy.as_secs_f64(),
+        generations as f64 / seconds,
+        abbreviate(cells / seconds)
+    )
+}
+
+/// A large count in three significant figures or so, such as `12.3M`.
+fn abbreviate(count: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    match UNITS.iter().find(|&&(size, _)| count >= size) {
+        Some(&(size, unit)) => format!("{:.1}{}", count / size, unit),
+        None => format!("{:.0}", count),
+    }
+}
+
+/// Write the one-line HUD summarizing `status`.
+pub fn write_hud(f: &mut impl Write, status: &Status) -> fmt::Result {
+    write!(f, "gen {} | pop {}", status.generation, status.population)?;
+    if status.show_sparkline && status.populations.len() > 1 {
+        write!(f, " {}", sparkline(&status.populations))?;
+    }
+    write!(f, " | {:.1} gen/s (", status.rate)?;
+    match status.interval {
+        _ if status.max_speed => f.write_str("max speed")?,
+        Some(interval) => write!(f, "{:.0} fps", 1.0 / interval.as_secs_f64())?,
+        None => f.write_str("no delay")?,
+    }
+    let state = if status.cursor.is_some() {
+        "editing"
+    } else if status.paused {
+        "paused"
+    } else {
+        "running"
+    };
+    f.write_str(")")?;
+    if status.cell_rate > 0.0 {
+        write!(f, " | {} cells/s", abbreviate(status.cell_rate))?;
+    }
+    write!(f, " | {} | {}", status.rule, state)?;
+    if let Some(tool) = &status.tool {
+        write!(f, " | {}", tool)?;
+    }
+    if let Some(message) = &status.message {
+        write!(f, " | {}", message)?;
+    }
+    match &status.prompt {
+        Some(prompt) => write!(f, " | {}", prompt),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hud(status: &Status) -> String {
+        let mut line = String::new();
+        write_hud(&mut line, status).unwrap();
+        line
+    }
+
+    #[test]
+    fn the_hud_sums_up_the_session() {
+        let mut universe = Universe::empty(4, 4);
+        universe.set_cells(&[(1, 1), (1, 2), (2, 1)]);
+        let mut status = Status::new(&universe, true);
+        status.interval = Some(Duration::from_millis(100));
+        assert_eq!(
+            hud(&status),
+            "gen 0 | pop 3 | 0.0 gen/s (10 fps) | B3/S23 | running"
+        );
+
+        status.paused = true;
+        status.interval = None;
+        status.cell_rate = 2.5e6;
+        assert_eq!(
+            hud(&status),
+            "gen 0 | pop 3 | 0.0 gen/s (no delay) | 2.5M cells/s | B3/S23 | paused"
+        );
+
+        status.max_speed = true;
+        status.cursor = Some((0, 0));
+        status.tool = Some(String::from("stamp glider"));
+        status.message = Some(String::from("saved"));
+        status.prompt = Some(String::from("save as: gl"));
+        assert_eq!(
+            hud(&status),
+            "gen 0 | pop 3 | 0.0 gen/s (max speed) | 2.5M cells/s | B3/S23 | editing \
+             | stamp glider | saved | save as: gl"
+        );
+    }
+
+    #[test]
+    fn prompts_show_the_hud_even_when_hidden() {
+        let mut status = Status::new(&Universe::empty(2, 2), false);
+        assert!(!status.hud_visible());
+        status.prompt = Some(String::new());
+        assert!(status.hud_visible());
+    }
+
+    #[test]
+    fn advancing_follows_the_universe_and_smooths_the_rate() {
+        let mut universe = Universe::empty(4, 4);
+        universe.set_cells(&[(1, 1), (1, 2), (2, 1)]);
+        let mut status = Status::new(&universe, true);
+        universe.tick();
+        status.advance(&universe, 0.5);
+        assert_eq!((status.generation, status.population), (1, 4));
+        assert_eq!((status.births, status.deaths), (1, 0));
+        assert_eq!(status.rate, 2.0);
+        universe.tick();
+        status.advance_by(&universe, 1, 0.1);
+        assert!((status.rate - 2.8).abs() < 1e-9);
+        assert_eq!(status.populations, [3, 4, 4]);
+
+        // Stepping to the same generation again records nothing new.
+        status.step(&universe);
+        assert_eq!(status.populations.len(), 3);
+    }
+
+    #[test]
+    fn the_sparkline_keeps_only_the_latest_populations() {
+        let mut universe = Universe::empty(4, 4);
+        let mut status = Status::new(&universe, true);
+        for generation in 1..=SPARKLINE as u64 + 5 {
+            universe.set_generation(generation);
+            status.step(&universe);
+        }
+        assert_eq!(status.populations.len(), SPARKLINE);
+
+        status.show_sparkline = true;
+        status.populations = VecDeque::from([2, 4, 9, 16]);
+        assert!(hud(&status).starts_with("gen 45 | pop 0 ▁▂▄█ | "));
+    }
+
+    #[test]
+    fn sparklines_span_the_values() {
+        assert_eq!(sparkline(&VecDeque::from([0, 7, 14])), "▁▄█");
+        assert_eq!(sparkline(&VecDeque::from([5, 5])), "▁▁");
+        assert_eq!(sparkline(&VecDeque::new()), "");
+    }
+
+    #[test]
+    fn endings_and_throughput_are_spelled_out() {
+        let mut universe = Universe::empty(10, 10);
+        universe.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+        universe.set_generation(12);
+        assert_eq!(ending(&universe, None), "generation 12, population 3");
+        let cycle = Cycle {
+            start: 10,
+            period: 2,
+            shift: (0, 0),
+        };
+        assert_eq!(
+            ending(&universe, Some(cycle)),
+            "generation 12, population 3, cycle of period 2 since generation 10"
+        );
+
+        assert_eq!(
+            throughput((1000, 1000), 500, Duration::from_millis(250)),
+            "500 generations in 0.250s (2000.0 generations/s, 2.0G cells/s)"
+        );
+        assert_eq!(
+            throughput((10, 10), 1, Duration::from_secs(1)),
+            "1 generation in 1.000s (1.0 generations/s, 100 cells/s)"
+        );
+    }
+
+    #[test]
+    fn summaries_need_something_simulated() {
+        let universe = Universe::empty(10, 10);
+        let mut status = Status::new(&universe, true);
+        assert_eq!(status.summary(&universe), None);
+        status.simulated(&universe, 100, Duration::from_secs(1));
+        assert_eq!(status.cell_rate, 10_000.0);
+        assert_eq!(
+            status.summary(&universe).unwrap(),
+            "generation 0, population 0\n\
+             100 generations in 1.000s (100.0 generations/s, 10.0k cells/s)"
+        );
+    }
+}
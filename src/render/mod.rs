@@ -0,0 +1,448 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::input::line_end;
+use crate::{Cell, Universe};
+
+mod camera;
+mod color;
+mod diff;
+mod gif;
+mod heatmap;
+mod hud;
+mod record;
+mod split;
+mod stats;
+mod terminal;
+mod trails;
+
+pub use camera::{Camera, FollowRenderer};
+pub use color::{ColorMode, Theme};
+pub use diff::DiffRenderer;
+pub use heatmap::{ActivityMap, HeatmapRenderer};
+pub use hud::{ending, sparkline, throughput, write_hud, Status, SPARKLINE};
+pub use record::{RecordFormat, Recorder, RecordingRenderer};
+pub use split::SplitRenderer;
+pub use stats::{SeriesRenderer, StatsRenderer};
+pub use terminal::TerminalRenderer;
+pub use trails::{Trails, TrailsRenderer};
+
+/// A frontend able to display successive generations of a universe.
+///
+/// The play loop only talks to this trait, so terminal, image, GUI and
+/// network outputs can be swapped without touching the simulation.
+pub trait Renderer {
+    /// Prepare the output before the first frame is drawn.
+    fn init(&mut self, _universe: &Universe) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Draw the current generation of the universe.
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()>;
+
+    /// Restore the output once the simulation ends.
+    fn shutdown(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Forget what is on screen so the next frame is drawn in full, after
+    /// something else has drawn over the output.
+    fn invalidate(&mut self) {}
+
+    /// The cell drawn at a terminal position counted from zero, used to
+    /// turn mouse clicks into cells. `None` if no cell is drawn there.
+    fn cell_at(&self, _universe: &Universe, _row: u32, _column: u32) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// Clear the terminal and move the cursor to the top left corner.
+pub(crate) const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+
+/// Hide the cursor so it doesn't flicker over the grid.
+pub(crate) fn enter_terminal() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1B[?25l")?;
+    stdout.flush()
+}
+
+/// Show the cursor again.
+pub(crate) fn leave_terminal() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1B[?25h")?;
+    stdout.flush()
+}
+
+/// Empty the frame buffer kept by a renderer and start it by clearing the
+/// screen, ready for the grid.
+pub(crate) fn start_frame(frame: &mut String) {
+    frame.clear();
+    frame.push_str(CLEAR_SCREEN);
+}
+
+/// Print a whole frame in one write to a locked stdout, so it neither gets
+/// flushed line by line nor interleaved with other output. In raw mode each
+/// line ends in a carriage return as well, which the terminal no longer
+/// adds itself.
+pub fn write_frame(frame: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    match line_end() {
+        "\n" => stdout.write_all(frame.as_bytes())?,
+        end => {
+            let mut lines = frame.split('\n');
+            if let Some(first) = lines.next() {
+                stdout.write_all(first.as_bytes())?;
+            }
+            for line in lines {
+                stdout.write_all(end.as_bytes())?;
+                stdout.write_all(line.as_bytes())?;
+            }
+        }
+    }
+    stdout.flush()
+}
+
+/// Finish a frame begun with `start_frame` and holding a rendered grid by
+/// adding the HUD and the edit cursor placed on the grid showing `view` of
+/// the universe, then print it.
+pub(crate) fn present(
+    frame: &mut String,
+    status: &Status,
+    charset: &Charset,
+    view: Camera,
+) -> io::Result<()> {
+    if status.hud_visible() {
+        write_hud(frame, status).expect("writing to a String cannot fail");
+        frame.push('\n');
+    }
+    write_cursor(frame, status, charset, view);
+    write_frame(frame)
+}
+
+/// Draw the edit tool preview over the grid, then show the terminal cursor on
+/// the cell under the edit cursor, or hide it when not editing or when that
+/// cell is outside `view`.
+pub(crate) fn write_cursor(frame: &mut String, status: &Status, charset: &Charset, view: Camera) {
+    for &(row, col) in &status.preview {
+        if view.contains(row, col) {
+            let column = (col - view.left) as usize * charset.columns() + 1;
+            write!(
+                frame,
+                "\x1B[{};{}H{}",
+                row - view.top + 1,
+                column,
+                charset.fading
+            )
+            .expect("writing to a String cannot fail");
+        }
+    }
+
+    let visible = status.cursor.filter(|&(row, col)| view.contains(row, col));
+    match visible {
+        Some((row, col)) => {
+            let column = (col - view.left) as usize * charset.columns() + 1;
+            write!(frame, "\x1B[{};{}H\x1B[?25h", row - view.top + 1, column)
+                .expect("writing to a String cannot fail");
+        }
+        None => frame.push_str("\x1B[?25l"),
+    }
+}
+
+/// The cell of `view` drawn at a terminal position, for grids drawn from
+/// the top left corner of the screen.
+pub(crate) fn locate(view: Camera, charset: &Charset, row: u32, column: u32) -> Option<(u32, u32)> {
+    let col = column / charset.columns().max(1) as u32;
+    if row < view.height && col < view.width {
+        Some((view.top + row, view.left + col))
+    } else {
+        None
+    }
+}
+
+/// The strings used to draw cells.
+///
+/// Every glyph occupies exactly two terminal columns so that cells come out
+/// roughly square whichever charset is in use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Charset {
+    pub alive: Cow<'static, str>,
+    pub dead: Cow<'static, str>,
+    /// A cell that died a few generations ago.
+    pub fading: Cow<'static, str>,
+    /// A cell that died a while ago and has almost faded out.
+    pub faded: Cow<'static, str>,
+}
+
+impl Charset {
+    pub const UNICODE: Charset = Charset {
+        alive: Cow::Borrowed("◼ "),
+        dead: Cow::Borrowed("  "),
+        fading: Cow::Borrowed("▪ "),
+        faded: Cow::Borrowed("· "),
+    };
+
+    pub const ASCII: Charset = Charset {
+        alive: Cow::Borrowed("# "),
+        dead: Cow::Borrowed(". "),
+        fading: Cow::Borrowed("+ "),
+        faded: Cow::Borrowed("- "),
+    };
+
+    /// Pick the Unicode charset when the locale advertises UTF-8 support and
+    /// fall back to plain ASCII otherwise.
+    pub fn detect() -> Charset {
+        Charset::for_locale(&locale(|name| env::var(name).ok()))
+    }
+
+    /// The charset for a locale such as `en_US.UTF-8`.
+    fn for_locale(locale: &str) -> Charset {
+        let locale = locale.to_lowercase();
+        if locale.contains("utf-8") || locale.contains("utf8") {
+            Charset::UNICODE
+        } else {
+            Charset::ASCII
+        }
+    }
+
+    /// A charset drawing live and dead cells with the given glyphs.
+    ///
+    /// The narrower glyph is padded with spaces so both take the same number
+    /// of terminal columns, and trails reuse the live glyph in dimmer colors.
+    pub fn custom(alive: &str, dead: &str) -> Charset {
+        let columns = display_width(alive).max(display_width(dead));
+        let pad = |glyph: &str| format!("{}{}", glyph, " ".repeat(columns - display_width(glyph)));
+        let alive = pad(alive);
+        Charset {
+            fading: Cow::Owned(alive.clone()),
+            faded: Cow::Owned(alive.clone()),
+            alive: Cow::Owned(alive),
+            dead: Cow::Owned(pad(dead)),
+        }
+    }
+
+    /// The charset with every glyph drawn in the colors of `theme`.
+    pub fn themed(self, theme: Theme) -> Charset {
+        let (foreground, background) = match theme.colors() {
+            Some(colors) => colors,
+            None => return self,
+        };
+        let paint = |glyph: Cow<'static, str>| {
+            Cow::Owned(format!(
+                "\x1B[38;5;{};48;5;{}m{}\x1B[0m",
+                foreground, background, glyph
+            ))
+        };
+        Charset {
+            alive: paint(self.alive),
+            dead: paint(self.dead),
+            fading: paint(self.fading),
+            faded: paint(self.faded),
+        }
+    }
+
+    pub fn glyph(&self, cell: Cell) -> &str {
+        match cell {
+            Cell::Alive => &self.alive,
+            Cell::Dead => &self.dead,
+        }
+    }
+
+    /// How many terminal columns a single cell takes, not counting the
+    /// escape sequences a theme colors it with.
+    pub fn columns(&self) -> usize {
+        display_width(&self.dead)
+    }
+
+    /// Write every row of the universe, one line per row.
+    pub fn write_grid(&self, f: &mut impl fmt::Write, universe: &Universe) -> fmt::Result {
+        for row in 0..universe.height() {
+            for column in 0..universe.width() {
+                f.write_str(self.glyph(universe.get_cell(row, column)))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The locale text is shown in, from the first of the variables that set
+/// it which `var` finds not empty.
+fn locale(var: impl Fn(&str) -> Option<String>) -> String {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/// How many terminal columns `text` takes, wide characters counting twice
+/// and the escape sequences coloring it not at all.
+pub fn display_width(text: &str) -> usize {
+    let mut plain = String::with_capacity(text.len());
+    let mut escaped = false;
+    for ch in text.chars() {
+        match ch {
+            '\x1B' => escaped = true,
+            'm' if escaped => escaped = false,
+            _ if escaped => {}
+            _ => plain.push(ch),
+        }
+    }
+    plain.width()
+}
+
+impl Default for Charset {
+    fn default() -> Charset {
+        Charset::UNICODE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_take_two_columns_in_the_built_in_charsets() {
+        assert_eq!(Charset::UNICODE.columns(), 2);
+        assert_eq!(Charset::ASCII.columns(), 2);
+        let themed = Charset::UNICODE.themed(Theme::from_name("green").unwrap());
+        assert_ne!(themed.dead, Charset::UNICODE.dead);
+        assert_eq!(themed.columns(), 2);
+    }
+
+    #[test]
+    fn custom_glyphs_are_padded_to_the_same_width() {
+        let narrow = Charset::custom("#", "..");
+        assert_eq!((&*narrow.alive, &*narrow.dead), ("# ", ".."));
+        assert_eq!(narrow.columns(), 2);
+
+        // Wide characters take two columns as one char.
+        let wide = Charset::custom("🟩", ".");
+        assert_eq!((&*wide.alive, &*wide.dead), ("🟩", ". "));
+        assert_eq!(wide.columns(), 2);
+        let both = Charset::custom("🟩", "⬛");
+        assert_eq!((&*both.alive, &*both.dead), ("🟩", "⬛"));
+        assert_eq!(both.columns(), 2);
+
+        // A combining accent takes no column of its own.
+        let accented = Charset::custom("e\u{301}", "..");
+        assert_eq!(&*accented.alive, "e\u{301} ");
+        assert_eq!(accented.columns(), 2);
+    }
+
+    #[test]
+    fn escape_sequences_take_no_columns() {
+        assert_eq!(display_width("\x1B[38;5;46;48;5;16m🟩\x1B[0m"), 2);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width(""), 0);
+    }
+
+    #[test]
+    fn ascii_is_drawn_unless_the_locale_is_utf8() {
+        assert_eq!(Charset::for_locale("en_US.UTF-8"), Charset::UNICODE);
+        assert_eq!(Charset::for_locale("de_DE.utf8"), Charset::UNICODE);
+        assert_eq!(Charset::for_locale("C"), Charset::ASCII);
+        assert_eq!(Charset::for_locale("en_US.ISO-8859-1"), Charset::ASCII);
+        assert_eq!(Charset::for_locale(""), Charset::ASCII);
+        for glyph in [&Charset::ASCII.alive, &Charset::ASCII.dead] {
+            assert!(glyph.is_ascii());
+        }
+    }
+
+    #[test]
+    fn the_first_locale_variable_set_wins() {
+        let vars = |set: &[(&str, &str)], name: &str| {
+            set.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        };
+        let all = [("LC_ALL", "C"), ("LC_CTYPE", "en_US.UTF-8"), ("LANG", "fr")];
+        assert_eq!(locale(|name| vars(&all, name)), "C");
+        // Empty variables count as not set.
+        let empty = [("LC_ALL", ""), ("LANG", "en_US.UTF-8")];
+        assert_eq!(locale(|name| vars(&empty, name)), "en_US.UTF-8");
+        assert_eq!(locale(|_| None), "");
+    }
+
+    /// A renderer that only keeps the generations it was asked to draw.
+    #[derive(Default)]
+    struct Drawn(Vec<u64>);
+
+    impl Renderer for Drawn {
+        fn draw(&mut self, universe: &Universe, _status: &Status) -> io::Result<()> {
+            self.0.push(universe.generation());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn renderers_only_need_to_draw() {
+        let mut universe = Universe::empty(4, 3);
+        let mut drawn = Drawn::default();
+        let renderer: &mut dyn Renderer = &mut drawn;
+        renderer.init(&universe).unwrap();
+        for _ in 0..3 {
+            renderer.draw(&universe, &Status::default()).unwrap();
+            universe.tick();
+        }
+        renderer.invalidate();
+        assert_eq!(renderer.cell_at(&universe, 0, 0), None);
+        renderer.shutdown().unwrap();
+        assert_eq!(drawn.0, [0, 1, 2]);
+    }
+
+    #[test]
+    fn grids_are_written_a_line_per_row() {
+        let mut universe = Universe::empty(3, 2);
+        universe.set_cells(&[(0, 1), (1, 2)]);
+        let mut grid = String::new();
+        Charset::ASCII.write_grid(&mut grid, &universe).unwrap();
+        assert_eq!(grid, ". # . \n. . # \n");
+    }
+
+    #[test]
+    fn positions_are_located_in_the_view() {
+        let view = Camera {
+            top: 2,
+            left: 3,
+            width: 4,
+            height: 2,
+        };
+        let charset = Charset::ASCII;
+        assert_eq!(locate(view, &charset, 0, 0), Some((2, 3)));
+        // Both columns of a cell belong to it.
+        assert_eq!(locate(view, &charset, 1, 7), Some((3, 6)));
+        assert_eq!(locate(view, &charset, 1, 8), None);
+        assert_eq!(locate(view, &charset, 2, 0), None);
+    }
+
+    #[test]
+    fn the_cursor_is_shown_only_inside_the_view() {
+        let view = Camera {
+            top: 2,
+            left: 3,
+            width: 4,
+            height: 2,
+        };
+        let charset = Charset::ASCII;
+        let mut status = Status {
+            cursor: Some((3, 4)),
+            preview: vec![(2, 3), (9, 9)],
+            ..Status::default()
+        };
+        let mut frame = String::new();
+        write_cursor(&mut frame, &status, &charset, view);
+        assert_eq!(frame, "\x1B[1;1H+ \x1B[2;3H\x1B[?25h");
+
+        frame.clear();
+        status.cursor = Some((0, 0));
+        status.preview.clear();
+        write_cursor(&mut frame, &status, &charset, view);
+        assert_eq!(frame, "\x1B[?25l");
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use super::{gif, write_hud, Charset, Renderer, Status, CLEAR_SCREEN};
+use crate::pattern::json::quote;
+use crate::{Cell, Universe};
+
+/// Pixels per cell in recorded GIFs.
+const GIF_SCALE: u32 = 4;
+
+/// The file formats a session can be recorded in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// An animated image with one frame per generation.
+    Gif,
+    /// An asciinema v2 recording, replayed with `asciinema play`.
+    Cast,
+    /// One JSON object per generation listing the live cells.
+    Jsonl,
+}
+
+impl RecordFormat {
+    pub fn from_path(path: &Path) -> Option<RecordFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gif" => Some(RecordFormat::Gif),
+            "cast" => Some(RecordFormat::Cast),
+            "jsonl" => Some(RecordFormat::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// Writes every frame of a session to a file as it is drawn.
+pub struct Recorder {
+    format: RecordFormat,
+    out: BufWriter<File>,
+    charset: Charset,
+    started: Instant,
+    /// Whether the header has been written, which waits for the first
+    /// frame to know the size of the universe.
+    started_output: bool,
+    /// The last GIF frame and when it was captured. It is only written once
+    /// the next one arrives and the time it stayed on screen is known.
+    pending: Option<(Vec<u8>, Instant)>,
+    /// Size of the GIF in cells, fixed by the first frame.
+    gif_size: (u32, u32),
+}
+
+impl Recorder {
+    /// Create `path` and record into it, in the format given by its
+    /// extension. Terminal recordings draw cells with `charset`.
+    pub fn create(path: impl AsRef<Path>, charset: Charset) -> io::Result<Recorder> {
+        let path = path.as_ref();
+        let format = RecordFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "recordings must end in .gif, .cast or .jsonl",
+            )
+        })?;
+        Ok(Recorder {
+            format,
+            out: BufWriter::new(File::create(path)?),
+            charset,
+            started: Instant::now(),
+            started_output: false,
+            pending: None,
+            gif_size: (0, 0),
+        })
+    }
+
+    /// Append the current generation.
+    pub fn record(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        let first = !self.started_output;
+        self.started_output = true;
+        match self.format {
+            RecordFormat::Gif => self.record_gif(universe, first),
+            RecordFormat::Cast => self.record_cast(universe, status, first),
+            RecordFormat::Jsonl => self.record_jsonl(universe, status),
+        }
+    }
+
+    /// Write out anything still buffered and close the recording.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.format == RecordFormat::Gif && self.started_output {
+            if let Some((pixels, captured)) = self.pending.take() {
+                self.write_gif_frame(&pixels, captured)?;
+            }
+            gif::write_trailer(&mut self.out)?;
+        }
+        self.out.flush()
+    }
+
+    fn record_gif(&mut self, universe: &Universe, first: bool) -> io::Result<()> {
+        if first {
+            // GIF sizes are 16 bits, so huge universes are cropped.
+            let limit = u16::MAX as u32 / GIF_SCALE;
+            self.gif_size = (universe.width().min(limit), universe.height().min(limit));
+            let (width, height) = self.gif_pixels();
+            gif::write_header(&mut self.out, width, height)?;
+        }
+
+        // A universe that grew since the first frame is cropped to the
+        // original size.
+        let (columns, rows) = self.gif_size;
+        let (width, height) = self.gif_pixels();
+        let cells = universe.get_cells();
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..rows * GIF_SCALE {
+            let row = y / GIF_SCALE;
+            pixels.extend((0..columns * GIF_SCALE).map(|x| {
+                let col = x / GIF_SCALE;
+                let inside = row < universe.height() && col < universe.width();
+                let idx = (row * universe.width() + col) as usize;
+                (inside && cells[idx] == Cell::Alive) as u8
+            }));
+        }
+
+        if let Some((previous, captured)) = self.pending.take() {
+            self.write_gif_frame(&previous, captured)?;
+        }
+        self.pending = Some((pixels, Instant::now()));
+        Ok(())
+    }
+
+    fn write_gif_frame(&mut self, pixels: &[u8], captured: Instant) -> io::Result<()> {
+        let (width, height) = self.gif_pixels();
+        let delay = (captured.elapsed().as_millis() / 10).clamp(2, u16::MAX as u128) as u16;
+        gif::write_frame(&mut self.out, width, height, pixels, delay)
+    }
+
+    fn gif_pixels(&self) -> (u16, u16) {
+        let (columns, rows) = self.gif_size;
+        ((columns * GIF_SCALE) as u16, (rows * GIF_SCALE) as u16)
+    }
+
+    fn record_cast(&mut self, universe: &Universe, status: &Status, first: bool) -> io::Result<()> {
+        if first {
+            let columns = universe.width() as usize * self.charset.columns();
+            writeln!(
+                self.out,
+                "{{\"version\": 2, \"width\": {}, \"height\": {}}}",
+                columns,
+                universe.height() + 1
+            )?;
+        }
+
+        let mut frame = String::from(CLEAR_SCREEN);
+        self.charset
+            .write_grid(&mut frame, universe)
+            .expect("writing to a String cannot fail");
+        if status.show_hud {
+            write_hud(&mut frame, status).expect("writing to a String cannot fail");
+        }
+        // The terminal is in raw mode when replayed, so lines need a
+        // carriage return too.
+        let frame = frame.replace('\n', "\r\n");
+        let elapsed = self.started.elapsed().as_secs_f64();
+        writeln!(self.out, "[{:.6}, \"o\", {}]", elapsed, quote(&frame))
+    }
+
+    fn record_jsonl(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        let width = universe.width();
+        let mut cells = String::new();
+        let all = universe.get_cells();
+        let alive = all
+            .iter()
+            .enumerate()
+            .filter(|&(_, &cell)| cell == Cell::Alive);
+        for (n, (idx, _)) in alive.enumerate() {
+            let separator = if n == 0 { "" } else { ", " };
+            let (row, col) = (idx as u32 / width, idx as u32 % width);
+            write!(cells, "{}[{}, {}]", separator, row, col)
+                .expect("writing to a String cannot fail");
+        }
+        writeln!(
+            self.out,
+            "{{\"generation\": {}, \"time\": {:.6}, \"width\": {}, \"height\": {}, \
+             \"population\": {}, \"cells\": [{}]}}",
+            status.generation,
+            self.started.elapsed().as_secs_f64(),
+            width,
+            universe.height(),
+            status.population,
+            cells
+        )
+    }
+}
+
+/// Draws with another renderer while recording every frame.
+pub struct RecordingRenderer {
+    renderer: Box<dyn Renderer>,
+    recorder: Recorder,
+}
+
+impl RecordingRenderer {
+    pub fn new(renderer: Box<dyn Renderer>, recorder: Recorder) -> RecordingRenderer {
+        RecordingRenderer { renderer, recorder }
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn init(&mut self, universe: &Universe) -> io::Result<()> {
+        self.renderer.init(universe)
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        self.renderer.draw(universe, status)?;
+        self.recorder.record(universe, status)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.renderer.shutdown()?;
+        self.recorder.finish()
+    }
+
+    fn invalidate(&mut self) {
+        self.renderer.invalidate()
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        self.renderer.cell_at(universe, row, column)
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Write as _;
+use std::io;
+
+use super::{enter_terminal, leave_terminal, start_frame, write_frame, write_hud, Charset, Status};
+use crate::Universe;
+
+/// Columns between the two halves of the screen.
+const SEPARATOR: &str = " │ ";
+
+/// Draws two universes next to each other, each with its own HUD line, so
+/// different seeds or settings can be compared generation by generation.
+/// The HUD of the right one also tells how many cells differ between them.
+pub struct SplitRenderer {
+    charset: Charset,
+    /// Reused from frame to frame.
+    frame: String,
+    /// The HUD of the left universe, padded to the width of its grid.
+    hud: String,
+}
+
+impl SplitRenderer {
+    pub fn new(charset: Charset) -> SplitRenderer {
+        SplitRenderer {
+            charset,
+            frame: String::new(),
+            hud: String::new(),
+        }
+    }
+
+    pub fn init(&mut self) -> io::Result<()> {
+        enter_terminal()
+    }
+
+    pub fn draw(
+        &mut self,
+        left: &Universe,
+        right: &Universe,
+        statuses: &[Status; 2],
+    ) -> io::Result<()> {
+        self.compose(left, right, statuses);
+        write_frame(&self.frame)
+    }
+
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        leave_terminal()
+    }
+
+    /// Write the frame showing both universes into `frame`.
+    fn compose(&mut self, left: &Universe, right: &Universe, statuses: &[Status; 2]) {
+        let left_columns = left.width() as usize * self.charset.columns();
+        let rows = left.height().max(right.height());
+
+        let mut frame = std::mem::take(&mut self.frame);
+        start_frame(&mut frame);
+        for row in 0..rows {
+            self.write_row(&mut frame, left, row, left_columns);
+            frame.push_str(SEPARATOR);
+            self.write_row(&mut frame, right, row, 0);
+            frame.push('\n');
+        }
+
+        if statuses.iter().any(|status| status.show_hud) {
+            self.hud.clear();
+            write_hud(&mut self.hud, &statuses[0]).expect("writing to a String cannot fail");
+            write!(frame, "{:<width$}", self.hud, width = left_columns)
+                .expect("writing to a String cannot fail");
+            frame.push_str(SEPARATOR);
+            write_hud(&mut frame, &statuses[1])
+                .and_then(|()| write!(frame, " | {}", left.diff(right)))
+                .expect("writing to a String cannot fail");
+            frame.push('\n');
+        }
+
+        self.frame = frame;
+    }
+
+    /// Append one row of `universe`, padded to `columns` when it has fewer
+    /// rows than the other side.
+    fn write_row(&self, frame: &mut String, universe: &Universe, row: u32, columns: usize) {
+        if row >= universe.height() {
+            frame.extend(std::iter::repeat_n(' ', columns));
+            return;
+        }
+
+        for column in 0..universe.width() {
+            frame.push_str(self.charset.glyph(universe.get_cell(row, column)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::CLEAR_SCREEN;
+
+    fn compose(left: &Universe, right: &Universe, statuses: &[Status; 2]) -> String {
+        let mut renderer = SplitRenderer::new(Charset::ASCII);
+        renderer.compose(left, right, statuses);
+        renderer.frame.trim_start_matches(CLEAR_SCREEN).to_string()
+    }
+
+    #[test]
+    fn universes_are_drawn_side_by_side() {
+        let mut left = Universe::empty(2, 2);
+        left.set_cells(&[(0, 0)]);
+        let mut right = Universe::empty(3, 2);
+        right.set_cells(&[(1, 2)]);
+        let frame = compose(&left, &right, &Default::default());
+        assert_eq!(frame, "# .  │ . . . \n. .  │ . . # \n");
+    }
+
+    #[test]
+    fn the_shorter_universe_is_padded() {
+        let left = Universe::empty(2, 1);
+        let right = Universe::empty(1, 3);
+        let frame = compose(&left, &right, &Default::default());
+        assert_eq!(frame, ". .  │ . \n     │ . \n     │ . \n");
+        let frame = compose(&right, &left, &Default::default());
+        assert_eq!(frame, ".  │ . . \n.  │ \n.  │ \n");
+    }
+
+    #[test]
+    fn the_right_hud_tells_the_cells_that_differ() {
+        let mut left = Universe::empty(40, 1);
+        left.set_cells(&[(0, 0), (0, 1)]);
+        let right = Universe::empty(40, 1);
+        let statuses = [Status::new(&left, true), Status::new(&right, true)];
+        let frame = compose(&left, &right, &statuses);
+        let hud = frame.lines().nth(1).unwrap();
+        let (left_hud, right_hud) = hud.split_once(SEPARATOR).unwrap();
+        // The left HUD is padded to the width of its grid.
+        assert_eq!(left_hud.chars().count(), 80);
+        assert!(left_hud.starts_with("gen 0 | pop 2 |"));
+        assert!(right_hud.starts_with("gen 0 | pop 0 |"));
+        assert!(
+            right_hud.ends_with(" | 2 cells differ (2 only in the first, 0 only in the second)")
+        );
+
+        let hidden = [Status::new(&left, false), Status::new(&right, false)];
+        assert_eq!(compose(&left, &right, &hidden).lines().count(), 1);
+    }
+}
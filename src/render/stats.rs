@@ -0,0 +1,120 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::{write_frame, write_hud, Renderer, Status};
+use crate::Universe;
+
+/// Prints a statistics line every few generations instead of the grid,
+/// which keeps logs and slow remote sessions readable. Besides the HUD it
+/// counts the cells born and the cells that died in the last generation,
+/// and gives the density and block entropy of the board, for following
+/// how a rule orders or scatters a soup, along with the records set so far
+/// when they are kept.
+pub struct StatsRenderer {
+    every: u64,
+    /// Generation of the last frame drawn, so reports aren't skipped when
+    /// several generations pass per frame.
+    last: u64,
+    /// Reused from report to report.
+    line: String,
+}
+
+impl StatsRenderer {
+    /// Report once every `every` generations.
+    pub fn new(every: u64) -> StatsRenderer {
+        StatsRenderer {
+            every: every.max(1),
+            last: 0,
+            line: String::new(),
+        }
+    }
+}
+
+impl Renderer for StatsRenderer {
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        let last = std::mem::replace(&mut self.last, status.generation);
+        if status.generation / self.every == last / self.every {
+            return Ok(());
+        }
+
+        self.line.clear();
+        write_hud(&mut self.line, status)
+            .and_then(|()| {
+                write!(
+                    self.line,
+                    " | {} born, {} died | density {:.3} | entropy {:.3}",
+                    status.births,
+                    status.deaths,
+                    universe.density(),
+                    universe.entropy()
+                )
+            })
+            .and_then(|()| match &status.records {
+                Some(records) => write!(self.line, " | {}", records),
+                None => Ok(()),
+            })
+            .expect("writing to a String cannot fail");
+        self.line.push('\n');
+        write_frame(&self.line)
+    }
+}
+
+/// Draws with another renderer while writing the generation, population,
+/// births and deaths of every frame to a CSV file, for plotting the whole
+/// run afterwards.
+pub struct SeriesRenderer {
+    renderer: Box<dyn Renderer>,
+    out: BufWriter<File>,
+    /// Generation of the last row written, so a frame drawn again without
+    /// simulating doesn't repeat it.
+    last: Option<u64>,
+}
+
+impl SeriesRenderer {
+    /// Create `path` and write the series into it.
+    pub fn create(
+        renderer: Box<dyn Renderer>,
+        path: impl AsRef<Path>,
+    ) -> io::Result<SeriesRenderer> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "generation,population,births,deaths")?;
+        Ok(SeriesRenderer {
+            renderer,
+            out,
+            last: None,
+        })
+    }
+}
+
+impl Renderer for SeriesRenderer {
+    fn init(&mut self, universe: &Universe) -> io::Result<()> {
+        self.renderer.init(universe)
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        self.renderer.draw(universe, status)?;
+        if self.last.replace(status.generation) == Some(status.generation) {
+            return Ok(());
+        }
+        writeln!(
+            self.out,
+            "{},{},{},{}",
+            status.generation, status.population, status.births, status.deaths
+        )
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.renderer.shutdown()?;
+        self.out.flush()
+    }
+
+    fn invalidate(&mut self) {
+        self.renderer.invalidate()
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        self.renderer.cell_at(universe, row, column)
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+
+use super::{
+    enter_terminal, leave_terminal, locate, present, start_frame, Camera, Charset, Renderer, Status,
+};
+use crate::Universe;
+
+/// Prints every cell of the universe to stdout.
+#[derive(Default)]
+pub struct TerminalRenderer {
+    charset: Charset,
+    /// Reused from frame to frame.
+    frame: String,
+}
+
+impl TerminalRenderer {
+    pub fn new(charset: Charset) -> TerminalRenderer {
+        TerminalRenderer {
+            charset,
+            frame: String::new(),
+        }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn init(&mut self, _universe: &Universe) -> io::Result<()> {
+        enter_terminal()
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        start_frame(&mut self.frame);
+        self.charset
+            .write_grid(&mut self.frame, universe)
+            .expect("writing to a String cannot fail");
+        let view = Camera::new(universe.width(), universe.height());
+        present(&mut self.frame, status, &self.charset, view)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        leave_terminal()
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        let view = Camera::new(universe.width(), universe.height());
+        locate(view, &self.charset, row, column)
+    }
+}
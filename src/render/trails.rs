@@ -0,0 +1,223 @@
+use std::fmt;
+use std::io;
+
+use super::{
+    enter_terminal, leave_terminal, locate, present, start_frame, Camera, Charset, Renderer, Status,
+};
+use crate::{Cell, Universe};
+
+/// Remembers how many generations ago each cell died, up to `length`
+/// generations, so recently vacated cells can be drawn as a fading trail.
+pub struct Trails {
+    length: u32,
+    width: u32,
+    previous: Vec<Cell>,
+    since_death: Vec<u32>,
+}
+
+impl Trails {
+    pub fn new(length: u32, universe: &Universe) -> Trails {
+        Trails {
+            length: length.max(1),
+            width: universe.width(),
+            previous: universe.get_cells(),
+            since_death: vec![0; universe.width() as usize * universe.height() as usize],
+        }
+    }
+
+    /// Age every trail by one generation and start new trails for the cells
+    /// that died since the last call.
+    pub fn record(&mut self, universe: &Universe) {
+        let cells = universe.get_cells();
+        if cells.len() != self.previous.len() || universe.width() != self.width {
+            // The universe was resized, so existing trails no longer line up.
+            *self = Trails::new(self.length, universe);
+        }
+        for ((age, previous), &cell) in self
+            .since_death
+            .iter_mut()
+            .zip(self.previous.iter_mut())
+            .zip(&cells)
+        {
+            *age = match (*previous, cell) {
+                (_, Cell::Alive) => 0,
+                (Cell::Alive, Cell::Dead) => 1,
+                (Cell::Dead, Cell::Dead) if *age > 0 && *age < self.length => *age + 1,
+                (Cell::Dead, Cell::Dead) => 0,
+            };
+            *previous = cell;
+        }
+    }
+
+    /// Write the trails row by row, dimming cells the longer ago they died.
+    /// Without colors only the fading and faded glyphs tell ages apart.
+    pub fn write_grid(
+        &self,
+        f: &mut impl fmt::Write,
+        charset: &Charset,
+        color: bool,
+    ) -> fmt::Result {
+        // Chunks cannot be empty, even for a universe without columns.
+        let width = self.width.max(1) as usize;
+        let rows = self.previous.chunks(width);
+        for (line, ages) in rows.zip(self.since_death.chunks(width)) {
+            for (&cell, &age) in line.iter().zip(ages) {
+                if cell == Cell::Alive {
+                    f.write_str(&charset.alive)?;
+                } else if age == 0 {
+                    f.write_str(&charset.dead)?;
+                } else {
+                    // Fade from light grey towards the darkest grey step.
+                    let shade = 250 - (age * 16 / self.length).min(16);
+                    let symbol = if age * 2 <= self.length {
+                        &charset.fading
+                    } else {
+                        &charset.faded
+                    };
+                    if color {
+                        write!(f, "\x1B[38;5;{}m{}\x1B[0m", shade, symbol)?;
+                    } else {
+                        f.write_str(symbol)?;
+                    }
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws recently dead cells with dimmer glyphs that fade out over time.
+pub struct TrailsRenderer {
+    length: u32,
+    charset: Charset,
+    color: bool,
+    trails: Option<Trails>,
+    /// Reused from frame to frame.
+    frame: String,
+}
+
+impl TrailsRenderer {
+    pub fn new(length: u32, charset: Charset, color: bool) -> TrailsRenderer {
+        TrailsRenderer {
+            length,
+            charset,
+            color,
+            trails: None,
+            frame: String::new(),
+        }
+    }
+}
+
+impl Renderer for TrailsRenderer {
+    fn init(&mut self, universe: &Universe) -> io::Result<()> {
+        self.trails = Some(Trails::new(self.length, universe));
+        enter_terminal()
+    }
+
+    fn draw(&mut self, universe: &Universe, status: &Status) -> io::Result<()> {
+        let length = self.length;
+        let trails = self
+            .trails
+            .get_or_insert_with(|| Trails::new(length, universe));
+        trails.record(universe);
+
+        start_frame(&mut self.frame);
+        trails
+            .write_grid(&mut self.frame, &self.charset, self.color)
+            .expect("writing to a String cannot fail");
+        let view = Camera::new(universe.width(), universe.height());
+        present(&mut self.frame, status, &self.charset, view)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        leave_terminal()
+    }
+
+    fn cell_at(&self, universe: &Universe, row: u32, column: u32) -> Option<(u32, u32)> {
+        let view = Camera::new(universe.width(), universe.height());
+        locate(view, &self.charset, row, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Edge};
+
+    /// The trails of a lone cell that died, `generations` generations on,
+    /// in the middle of the one row of a universe with dead edges.
+    fn after(generations: u32, length: u32) -> Trails {
+        let mut universe = Universe::empty(3, 1);
+        universe.set_boundary(Boundary {
+            x: Edge::Dead,
+            y: Edge::Dead,
+        });
+        universe.set_cell(0, 1, Cell::Alive);
+        let mut trails = Trails::new(length, &universe);
+        for _ in 0..generations {
+            universe.tick();
+            trails.record(&universe);
+        }
+        trails
+    }
+
+    fn grid(trails: &Trails, color: bool) -> String {
+        let mut grid = String::new();
+        trails
+            .write_grid(&mut grid, &Charset::ASCII, color)
+            .unwrap();
+        grid
+    }
+
+    #[test]
+    fn dead_cells_leave_a_trail_that_ages() {
+        assert_eq!(after(0, 4).since_death, [0, 0, 0]);
+        for generations in 1..=4 {
+            assert_eq!(after(generations, 4).since_death, [0, generations, 0]);
+        }
+        // Gone once the trail is as long as it can be.
+        assert_eq!(after(5, 4).since_death, [0, 0, 0]);
+        assert_eq!(after(9, 4).since_death, [0, 0, 0]);
+    }
+
+    #[test]
+    fn trails_fade_from_the_fading_to_the_faded_glyph() {
+        assert_eq!(grid(&after(0, 4), false), ". # . \n");
+        assert_eq!(grid(&after(1, 4), false), ". + . \n");
+        assert_eq!(grid(&after(2, 4), false), ". + . \n");
+        assert_eq!(grid(&after(3, 4), false), ". - . \n");
+        assert_eq!(grid(&after(5, 4), false), ". . . \n");
+        // Colors darken from light grey.
+        assert_eq!(grid(&after(1, 4), true), ". \x1B[38;5;246m+ \x1B[0m. \n");
+        assert_eq!(grid(&after(4, 4), true), ". \x1B[38;5;234m- \x1B[0m. \n");
+    }
+
+    #[test]
+    fn cells_coming_back_to_life_end_their_trail() {
+        // A blinker's ends die and come back every other generation.
+        let mut universe = Universe::empty(5, 5);
+        universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+        let mut trails = Trails::new(8, &universe);
+        for _ in 0..2 {
+            universe.tick();
+            trails.record(&universe);
+        }
+        assert_eq!(trails.since_death[2 * 5 + 1], 0);
+        assert_eq!(trails.since_death[5 + 2], 1);
+    }
+
+    #[test]
+    fn resizing_starts_the_trails_over() {
+        let mut universe = Universe::empty(3, 1);
+        universe.set_cell(0, 1, Cell::Alive);
+        let mut trails = Trails::new(4, &universe);
+        universe.tick();
+        universe.resize(4, 2);
+        trails.record(&universe);
+        assert_eq!(trails.since_death, [0; 8]);
+        let empty = Trails::new(4, &Universe::empty(0, 3));
+        assert_eq!(grid(&empty, false), "");
+    }
+}
@@ -0,0 +1,267 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Cell;
+
+/// Well known rules that can be referred to by name.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("conway", "B3/S23"),
+    ("highlife", "B36/S23"),
+    ("seeds", "B2/S"),
+    ("daynight", "B3678/S34678"),
+    ("maze", "B3/S12345"),
+    ("replicator", "B1357/S1357"),
+    ("diamoeba", "B35678/S5678"),
+    ("morley", "B368/S245"),
+    ("2x2", "B36/S125"),
+    ("life-without-death", "B3/S012345678"),
+];
+
+/// An outer totalistic rule: whether a cell is alive in the next generation
+/// depends only on its state and the number of live neighbours it has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    /// Bit `n` is set when a dead cell with `n` live neighbours is born.
+    birth: u16,
+    /// Bit `n` is set when a live cell with `n` live neighbours survives.
+    survival: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life.
+    ///
+    /// Live cells with two or three live neighbours live on, dead cells with
+    /// exactly three become alive, and every other cell dies or stays dead.
+    pub const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+    };
+
+    /// Build a rule from the neighbour counts causing birth and survival.
+    ///
+    /// Counts above 8 are ignored.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        let mask = |counts: &[u8]| {
+            counts
+                .iter()
+                .filter(|&&count| count <= 8)
+                .fold(0u16, |mask, &count| mask | 1 << count)
+        };
+        Rule {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    /// Parse a preset name, a `B3/S23` rulestring or the older `23/3`
+    /// survival/birth notation.
+    pub fn parse(input: &str) -> Result<Rule, RuleError> {
+        let trimmed = input.trim();
+        if let Some((_, rulestring)) = PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(trimmed))
+        {
+            return Rule::parse(rulestring);
+        }
+
+        let error = || RuleError {
+            input: input.to_string(),
+        };
+        let (first, second) = trimmed.split_once('/').ok_or_else(error)?;
+        let first = first.trim();
+        let second = second.trim();
+
+        let (birth, survival) = match (first.chars().next(), second.chars().next()) {
+            (Some('B' | 'b'), _) => (
+                &first[1..],
+                second.strip_prefix(['S', 's']).ok_or_else(error)?,
+            ),
+            (Some('S' | 's'), _) => (
+                second.strip_prefix(['B', 'b']).ok_or_else(error)?,
+                &first[1..],
+            ),
+            _ => (second, first),
+        };
+
+        Ok(Rule::new(
+            &digits(birth).ok_or_else(error)?,
+            &digits(survival).ok_or_else(error)?,
+        ))
+    }
+
+    /// Whether a dead cell with `live_neighbors` live neighbours is born.
+    /// Never for more than 8.
+    pub fn is_born(&self, live_neighbors: u8) -> bool {
+        has_count(self.birth, live_neighbors)
+    }
+
+    /// Whether a live cell with `live_neighbors` live neighbours survives.
+    /// Never for more than 8.
+    pub fn survives(&self, live_neighbors: u8) -> bool {
+        has_count(self.survival, live_neighbors)
+    }
+
+    /// The state of a cell in the next generation.
+    pub fn next(&self, cell: Cell, live_neighbors: u8) -> Cell {
+        let alive = match cell {
+            Cell::Alive => self.survives(live_neighbors),
+            Cell::Dead => self.is_born(live_neighbors),
+        };
+        if alive {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+}
+
+/// Whether bit `count` of `mask` is set, taking the bits past the mask as
+/// clear.
+fn has_count(mask: u16, count: u8) -> bool {
+    1u16.checked_shl(count as u32)
+        .is_some_and(|bit| mask & bit != 0)
+}
+
+/// Parse a string of neighbour counts such as `"236"`.
+fn digits(counts: &str) -> Option<Vec<u8>> {
+    counts
+        .chars()
+        .map(|c| c.to_digit(9).map(|digit| digit as u8))
+        .collect()
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::CONWAY
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        for count in (0..=8).filter(|&count| self.is_born(count)) {
+            write!(f, "{}", count)?;
+        }
+        write!(f, "/S")?;
+        for count in (0..=8).filter(|&count| self.survives(count)) {
+            write!(f, "{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(input: &str) -> Result<Rule, RuleError> {
+        Rule::parse(input)
+    }
+}
+
+/// A rule that is neither a valid rulestring nor a known preset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleError {
+    input: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a rulestring like B3/S23 or a preset (",
+            self.input
+        )?;
+        for (i, (name, _)) in PRESETS.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", name)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Error for RuleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Rule {
+        Rule::parse(input).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[test]
+    fn rulestrings_name_birth_and_survival() {
+        for input in ["B3/S23", "b3/s23", " B3 / S23 ", "S23/B3", "23/3"] {
+            assert_eq!(parse(input), Rule::CONWAY, "{}", input);
+        }
+        let highlife = parse("B36/S23");
+        assert_eq!(highlife, Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!(parse("23/36"), highlife);
+        assert_eq!(parse("HighLife"), highlife);
+        assert_eq!(highlife.to_string(), "B36/S23");
+        assert_eq!(
+            parse("B012345678/S012345678").to_string(),
+            "B012345678/S012345678"
+        );
+    }
+
+    #[test]
+    fn sides_may_be_empty() {
+        assert_eq!(parse("B2/S"), Rule::new(&[2], &[]));
+        assert_eq!(parse("B/S23"), Rule::new(&[], &[2, 3]));
+        assert_eq!(parse("B/S").to_string(), "B/S");
+        // In the older notation survival comes first.
+        assert_eq!(parse("/2"), Rule::new(&[2], &[]));
+        assert_eq!(parse("23/"), Rule::new(&[], &[2, 3]));
+    }
+
+    #[test]
+    fn repeated_counts_count_once() {
+        assert_eq!(parse("B33/S2323"), Rule::CONWAY);
+        assert_eq!(parse("B33/S2323").to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn invalid_rulestrings_are_refused() {
+        for input in [
+            "",
+            "B3",
+            "B3S23",
+            "B3/S23/S4",
+            "B9/S23",
+            "B3/S29",
+            "B3/Sx",
+            "Bx/S23",
+            "B-1/S23",
+            "B3/B23",
+            "S3/S23",
+            "X3/S23",
+            "B3/23",
+            "23/3x",
+            "conway2",
+        ] {
+            assert!(Rule::parse(input).is_err(), "{}", input);
+        }
+        let error = Rule::parse("B9/S23").unwrap_err().to_string();
+        assert!(
+            error.starts_with("'B9/S23' is not a rulestring"),
+            "{}",
+            error
+        );
+        assert!(error.contains("conway, highlife"), "{}", error);
+    }
+
+    #[test]
+    fn counts_past_eight_never_match() {
+        for count in [9, 15, 16, 64, u8::MAX] {
+            let everything = Rule::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8], &[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+            assert!(!everything.is_born(count), "{}", count);
+            assert!(!everything.survives(count), "{}", count);
+            assert_eq!(everything.next(Cell::Alive, count), Cell::Dead);
+        }
+        assert_eq!(Rule::new(&[3, 9, 200], &[2, 3]), Rule::CONWAY);
+    }
+}
@@ -0,0 +1,42 @@
+/// A compact copy of a universe's cells, one bit per cell in the same
+/// row-padded layout as the universe, for keeping many past states around
+/// cheaply.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    generation: u64,
+    bits: Vec<u64>,
+}
+
+impl Snapshot {
+    pub(crate) fn pack(width: u32, height: u32, generation: u64, words: &[u64]) -> Snapshot {
+        Snapshot {
+            width,
+            height,
+            generation,
+            bits: words.to_vec(),
+        }
+    }
+
+    pub(crate) fn unpack(&self) -> Vec<u64> {
+        self.bits.clone()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The generation the universe was at.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.bits.iter().map(|word| word.count_ones() as u64).sum()
+    }
+}
@@ -0,0 +1,336 @@
+//! Universes stored as runs of live cells, for huge boards that are mostly
+//! dead.
+//!
+//! Each row that holds live cells keeps them as sorted intervals, and the
+//! next state of a row is worked out interval by interval rather than cell
+//! by cell, so a tick costs time in proportion to the live structure no
+//! matter how large the board around it is.
+
+use std::collections::BTreeMap;
+
+use crate::pattern::Pattern;
+use crate::{Boundary, Cell, Edge, Rule};
+
+/// The live cells `start..end` of a row.
+pub type Run = (u32, u32);
+
+/// A universe stored as the runs of live cells of each row.
+///
+/// Like `MappedUniverse` it keeps its size, so a growing edge acts as a
+/// dead one.
+pub struct SparseUniverse {
+    width: u32,
+    height: u32,
+    rule: Rule,
+    boundary: Boundary,
+    /// The runs of every row with live cells, sorted and neither touching
+    /// nor overlapping.
+    rows: BTreeMap<u32, Vec<Run>>,
+}
+
+impl SparseUniverse {
+    /// A dead universe of the given size.
+    pub fn new(width: u32, height: u32) -> SparseUniverse {
+        SparseUniverse {
+            width,
+            height,
+            rule: Rule::default(),
+            boundary: Boundary::default(),
+            rows: BTreeMap::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        let runs = match self.rows.get(&row) {
+            Some(runs) => runs,
+            None => return Cell::Dead,
+        };
+        let idx = runs.partition_point(|&(_, end)| end <= column);
+        match runs.get(idx) {
+            Some(&(start, _)) if start <= column => Cell::Alive,
+            _ => Cell::Dead,
+        }
+    }
+
+    /// The runs of live cells of every row that has any, top to bottom,
+    /// for drawing only what is alive.
+    pub fn rows(&self) -> impl Iterator<Item = (u32, &[Run])> + '_ {
+        self.rows.iter().map(|(&row, runs)| (row, runs.as_slice()))
+    }
+
+    /// Bring the cells of `pattern` to life with its top left corner at
+    /// `row` and `column`. Cells falling outside the universe are dropped.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: u32, column: u32) {
+        for (r, c) in pattern.cells_at(row, column) {
+            if r < self.height && c < self.width {
+                insert(self.rows.entry(r).or_default(), c);
+            }
+        }
+    }
+
+    /// How many cells are alive.
+    pub fn population(&self) -> u64 {
+        self.rows
+            .values()
+            .flatten()
+            .map(|&(start, end)| (end - start) as u64)
+            .sum()
+    }
+
+    /// The live cells, with the size of the universe.
+    pub fn to_pattern(&self) -> Pattern {
+        let cells = self
+            .rows()
+            .flat_map(|(row, runs)| {
+                runs.iter()
+                    .flat_map(move |&(start, end)| (start..end).map(move |col| (row, col)))
+            })
+            .collect();
+        let mut pattern = Pattern::new(self.width, self.height, cells);
+        pattern.rule = Some(self.rule.to_string());
+        pattern
+    }
+
+    /// The rows above and below `row`, or `None` past a dead edge.
+    fn neighbor_rows(&self, row: u32) -> (Option<u32>, Option<u32>) {
+        let edge = self.boundary.y.fixed();
+        (
+            edge.resolve(row, -1, self.height),
+            edge.resolve(row, 1, self.height),
+        )
+    }
+
+    /// Advance one generation.
+    pub fn tick(&mut self) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        // Only rows with live cells or next to them can change, unless dead
+        // neighborhoods come to life.
+        let candidates: Vec<u32> = if self.rule.is_born(0) {
+            (0..self.height).collect()
+        } else {
+            let mut rows = Vec::new();
+            for &row in self.rows.keys() {
+                let (above, below) = self.neighbor_rows(row);
+                rows.extend([above, Some(row), below].into_iter().flatten());
+            }
+            rows.sort_unstable();
+            rows.dedup();
+            rows
+        };
+
+        let mut events = Vec::new();
+        let mut next = BTreeMap::new();
+        for row in candidates {
+            let (above, below) = self.neighbor_rows(row);
+            events.clear();
+            for (target, own) in [(above, false), (Some(row), true), (below, false)] {
+                let runs = target.and_then(|target| self.rows.get(&target));
+                for (start, end) in self.extend(runs.map_or(&[], Vec::as_slice)) {
+                    add_neighbors(&mut events, (start, end), own);
+                }
+            }
+            let runs = self.step_row(&mut events);
+            if !runs.is_empty() {
+                next.insert(row, runs);
+            }
+        }
+        self.rows = next;
+    }
+
+    /// The runs of a row with the cells just past its ends that the edge
+    /// makes them neighbors of, in coordinates that may fall outside it.
+    fn extend(&self, runs: &[Run]) -> Vec<(i64, i64)> {
+        let width = self.width as i64;
+        let mut extended: Vec<(i64, i64)> = runs
+            .iter()
+            .map(|&(start, end)| (start as i64, end as i64))
+            .collect();
+        let (first, last) = match (extended.first(), extended.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return extended,
+        };
+        match self.boundary.x.fixed() {
+            Edge::Torus => {
+                extended.push((first.0 + width, first.1 + width));
+                extended.push((last.0 - width, last.1 - width));
+            }
+            Edge::Mirror => {
+                if first.0 == 0 {
+                    extended.push((-1, 0));
+                }
+                if last.1 == width {
+                    extended.push((width, width + 1));
+                }
+            }
+            Edge::Dead | Edge::Grow => {}
+        }
+        extended
+    }
+
+    /// Sweep the neighbor counts and live cells of a row left to right,
+    /// applying the rule to each stretch over which neither changes.
+    fn step_row(&self, events: &mut [Event]) -> Vec<Run> {
+        events.sort_unstable_by_key(|event| event.at);
+        let width = self.width as i64;
+        let mut runs: Vec<Run> = Vec::new();
+        let (mut neighbors, mut alive) = (0i32, 0i32);
+        let mut events = events.iter().peekable();
+        let mut at = 0;
+        while at < width {
+            while let Some(event) = events.next_if(|event| event.at <= at) {
+                neighbors += event.neighbors;
+                alive += event.alive;
+            }
+            let until = events.peek().map_or(width, |event| event.at.min(width));
+            let lives = match alive {
+                0 => self.rule.is_born(neighbors as u8),
+                _ => self.rule.survives(neighbors as u8),
+            };
+            if lives {
+                let (start, end) = (at as u32, until as u32);
+                match runs.last_mut() {
+                    Some(last) if last.1 == start => last.1 = end,
+                    _ => runs.push((start, end)),
+                }
+            }
+            at = until;
+        }
+        runs
+    }
+}
+
+/// A change in the neighbor count or liveness of the cells of a row from
+/// column `at` on.
+struct Event {
+    at: i64,
+    neighbors: i32,
+    alive: i32,
+}
+
+/// Count the run `start..end` of the row itself, if `own`, or of a row next
+/// to it as the neighbors of the cells it touches.
+fn add_neighbors(events: &mut Vec<Event>, (start, end): (i64, i64), own: bool) {
+    // The run is the left neighbor of the cells one to its right, the right
+    // neighbor of those one to its left, and above or below those in line.
+    let shifts: &[i64] = if own { &[-1, 1] } else { &[-1, 0, 1] };
+    for shift in shifts {
+        events.push(Event {
+            at: start + shift,
+            neighbors: 1,
+            alive: 0,
+        });
+        events.push(Event {
+            at: end + shift,
+            neighbors: -1,
+            alive: 0,
+        });
+    }
+    if own {
+        events.push(Event {
+            at: start,
+            neighbors: 0,
+            alive: 1,
+        });
+        events.push(Event {
+            at: end,
+            neighbors: 0,
+            alive: -1,
+        });
+    }
+}
+
+/// Bring `column` to life in the runs of a row.
+fn insert(runs: &mut Vec<Run>, column: u32) {
+    let idx = runs.partition_point(|&(_, end)| end < column);
+    match runs.get_mut(idx) {
+        Some(run) if run.0 <= column && column < run.1 => {}
+        Some(run) if run.1 == column => {
+            run.1 += 1;
+            if runs.get(idx + 1).is_some_and(|next| next.0 == column + 1) {
+                runs[idx].1 = runs.remove(idx + 1).1;
+            }
+        }
+        Some(run) if run.0 == column + 1 => run.0 = column,
+        _ => runs.insert(idx, (column, column + 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES, SIZES};
+    use crate::Universe;
+
+    #[test]
+    fn runs_step_like_the_reference() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            // Growing edges act as dead ones here.
+            let fixed = Boundary {
+                x: boundary.x.fixed(),
+                y: boundary.y.fixed(),
+            };
+            for rule in RULES {
+                for (width, height) in SIZES {
+                    seed += 1;
+                    let mut expected = reference::soup(width, height, seed, rule, fixed);
+                    let mut sparse = SparseUniverse::new(width, height);
+                    sparse.set_rule(expected.rule());
+                    sparse.set_boundary(boundary);
+                    sparse.insert_pattern(&Pattern::from_universe(&expected), 0, 0);
+                    for generation in 1..=8 {
+                        expected = reference::tick(&expected);
+                        sparse.tick();
+                        let mut actual = Universe::empty(width, height);
+                        actual.insert_pattern(&sparse.to_pattern(), 0, 0);
+                        let case = (rule, boundary, width, height, generation);
+                        assert_same(&actual, &expected, case);
+                        assert_eq!(sparse.population(), expected.population(), "{:?}", case);
+                        let runs_in_order = sparse.rows().all(|(_, runs)| {
+                            runs.iter().all(|&(start, end)| start < end)
+                                && runs.windows(2).all(|pair| pair[0].1 < pair[1].0)
+                        });
+                        assert!(runs_in_order, "{:?}", case);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inserted_cells_merge_into_runs() {
+        let mut runs = Vec::new();
+        for column in [5, 7, 6, 1, 2, 0, 9, 6, 3] {
+            insert(&mut runs, column);
+        }
+        assert_eq!(runs, [(0, 4), (5, 8), (9, 10)]);
+    }
+
+    #[test]
+    fn patterns_past_the_far_corner_are_cut_off() {
+        let block = Pattern::new(2, 2, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let mut sparse = SparseUniverse::new(70, 3);
+        sparse.insert_pattern(&block, 2, 69);
+        sparse.insert_pattern(&block, u32::MAX, u32::MAX);
+        assert_eq!(sparse.population(), 1);
+        assert_eq!(sparse.get_cell(2, 69), Cell::Alive);
+    }
+}
@@ -0,0 +1,163 @@
+//! Four words at a time with AVX2, chosen at runtime.
+
+use std::arch::x86_64::*;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Range};
+
+use super::{Counts, Lanes, Neighborhood};
+
+/// Whether the processor running us supports AVX2. The answer is cached by
+/// the standard library after the first call.
+pub(super) fn available() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+/// 256 cells in one register.
+///
+/// Values only exist inside `step_words_avx2`, which is only entered once
+/// `available` said yes, so the intrinsics below are always supported.
+#[derive(Clone, Copy)]
+pub(super) struct Avx2(__m256i);
+
+impl BitAnd for Avx2 {
+    type Output = Avx2;
+
+    #[inline(always)]
+    fn bitand(self, other: Avx2) -> Avx2 {
+        // SAFETY: see `Avx2`.
+        Avx2(unsafe { _mm256_and_si256(self.0, other.0) })
+    }
+}
+
+impl BitOr for Avx2 {
+    type Output = Avx2;
+
+    #[inline(always)]
+    fn bitor(self, other: Avx2) -> Avx2 {
+        // SAFETY: see `Avx2`.
+        Avx2(unsafe { _mm256_or_si256(self.0, other.0) })
+    }
+}
+
+impl BitXor for Avx2 {
+    type Output = Avx2;
+
+    #[inline(always)]
+    fn bitxor(self, other: Avx2) -> Avx2 {
+        // SAFETY: see `Avx2`.
+        Avx2(unsafe { _mm256_xor_si256(self.0, other.0) })
+    }
+}
+
+impl Not for Avx2 {
+    type Output = Avx2;
+
+    #[inline(always)]
+    fn not(self) -> Avx2 {
+        // SAFETY: see `Avx2`.
+        Avx2(unsafe { _mm256_xor_si256(self.0, _mm256_set1_epi64x(-1)) })
+    }
+}
+
+impl Lanes for Avx2 {
+    const WORDS: usize = 4;
+
+    #[inline(always)]
+    fn zero() -> Avx2 {
+        // SAFETY: see `Avx2`.
+        Avx2(unsafe { _mm256_setzero_si256() })
+    }
+
+    #[inline(always)]
+    fn load(words: &[u64], idx: usize) -> Avx2 {
+        let words = &words[idx..idx + Self::WORDS];
+        // SAFETY: the slice holds the four words read, and unaligned loads
+        // are allowed.
+        Avx2(unsafe { _mm256_loadu_si256(words.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, words: &mut [u64], idx: usize) {
+        let words = &mut words[idx..idx + Self::WORDS];
+        // SAFETY: as in `load`.
+        unsafe { _mm256_storeu_si256(words.as_mut_ptr().cast(), self.0) }
+    }
+}
+
+/// Step as many of `words` as fill whole registers, returning the index of
+/// the first word left over.
+pub(super) fn step_words(
+    rows: &Neighborhood,
+    counts: &Counts,
+    out: &mut [u64],
+    words: Range<usize>,
+) -> usize {
+    assert!(available());
+    // SAFETY: AVX2 is supported, as just checked.
+    unsafe { step_words_avx2(rows, counts, out, words) }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn step_words_avx2(
+    rows: &Neighborhood,
+    counts: &Counts,
+    out: &mut [u64],
+    words: Range<usize>,
+) -> usize {
+    super::step_words::<Avx2>(rows, counts, out, words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES};
+    use crate::step::step_words as step_lanes;
+    use crate::{Rng, Rule};
+
+    #[test]
+    fn registers_step_like_single_words() {
+        if !available() {
+            return;
+        }
+        let mut rng = Rng::new(7);
+        let mut random_row = || (0..13).map(|_| rng.next_u64()).collect::<Vec<u64>>();
+        for rule in RULES {
+            let counts = Counts::new(Rule::parse(rule).unwrap());
+            let rows: Vec<Vec<u64>> = (0..9).map(|_| random_row()).collect();
+            let rows = Neighborhood {
+                above: [&rows[0], &rows[1], &rows[2]],
+                left: &rows[3],
+                cells: &rows[4],
+                right: &rows[5],
+                below: [&rows[6], &rows[7], &rows[8]],
+            };
+            for words in [0..13, 1..9, 3..7, 5..6] {
+                let (mut vector, mut scalar) = (vec![0; 13], vec![0; 13]);
+                let rest = step_words(&rows, &counts, &mut vector, words.clone());
+                assert_eq!(rest, words.end - words.len() % Avx2::WORDS);
+                step_lanes::<u64>(&rows, &counts, &mut vector, rest..words.end);
+                step_lanes::<u64>(&rows, &counts, &mut scalar, words.clone());
+                assert_eq!(vector, scalar, "{} over {:?}", rule, words);
+            }
+        }
+    }
+
+    #[test]
+    fn wide_universes_match_the_reference() {
+        if !available() {
+            return;
+        }
+        // Rows of several registers and some words left over.
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                seed += 1;
+                let mut universe = reference::soup(900, 6, seed, rule, boundary);
+                for generation in 1..=4 {
+                    let expected = reference::tick(&universe);
+                    universe.tick();
+                    assert_same(&universe, &expected, (rule, boundary, generation));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! The kernel behind `Universe::tick`. Cells are packed one per bit, so the
+//! eight neighbors of a whole word of cells are added up in parallel with
+//! bitwise adders, giving each count as four bit planes that are matched
+//! against the rule. With the `simd` feature the same adders run on 256-bit
+//! registers when the processor supports AVX2.
+//!
+//! The table engine looks the next state of each pair of cells up instead,
+//! see `table`.
+
+use std::ops::{BitAnd, BitOr, BitXor, Not, Range};
+
+use crate::{Edge, Rule};
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2;
+mod table;
+
+use table::Table;
+
+/// Bits in a storage word.
+pub(crate) const WORD_BITS: u32 = u64::BITS;
+
+/// Words needed for a row of `width` cells.
+pub(crate) fn stride_for(width: u32) -> usize {
+    width.div_ceil(WORD_BITS) as usize
+}
+
+/// The bits of the last word of a row that hold cells.
+pub(crate) fn last_word_mask(width: u32) -> u64 {
+    match width % WORD_BITS {
+        0 => !0,
+        used => (1 << used) - 1,
+    }
+}
+
+/// A bundle of cells stepped together, one per bit.
+pub(crate) trait Lanes:
+    Copy + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    /// Storage words covered by one bundle.
+    const WORDS: usize;
+
+    fn zero() -> Self;
+
+    /// Read `WORDS` words starting at `idx`.
+    fn load(words: &[u64], idx: usize) -> Self;
+
+    /// Write `WORDS` words starting at `idx`.
+    fn store(self, words: &mut [u64], idx: usize);
+}
+
+impl Lanes for u64 {
+    const WORDS: usize = 1;
+
+    fn zero() -> u64 {
+        0
+    }
+
+    fn load(words: &[u64], idx: usize) -> u64 {
+        words[idx]
+    }
+
+    fn store(self, words: &mut [u64], idx: usize) {
+        words[idx] = self;
+    }
+}
+
+/// The rows around the one being stepped, all the same number of words.
+///
+/// `left` rows are shifted so that bit `c` holds the cell to the left of
+/// column `c`, and `right` rows the cell to its right. Rows past a dead
+/// edge are all zeros.
+pub(crate) struct Neighborhood<'a> {
+    pub above: [&'a [u64]; 3],
+    pub left: &'a [u64],
+    pub cells: &'a [u64],
+    pub right: &'a [u64],
+    pub below: [&'a [u64]; 3],
+}
+
+/// How `Universe::tick` works out the next generation. Both give the same
+/// result, they only differ in speed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Count the neighbors of a word of cells at once with bitwise adders.
+    #[default]
+    Adder,
+    /// Look the next state of two cells at a time up in a table made for
+    /// the rule.
+    Table,
+}
+
+impl Engine {
+    pub const NAMES: [&'static str; 2] = ["adder", "table"];
+
+    pub fn from_name(name: &str) -> Option<Engine> {
+        match name.to_ascii_lowercase().as_str() {
+            "adder" => Some(Engine::Adder),
+            "table" => Some(Engine::Table),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Adder => "adder",
+            Engine::Table => "table",
+        }
+    }
+}
+
+/// What an engine needs to know about the rule, prepared once per rule.
+#[derive(Clone)]
+pub(crate) enum Kernel {
+    Adder(Counts),
+    Table(Table),
+}
+
+impl Kernel {
+    pub fn new(rule: Rule, engine: Engine) -> Kernel {
+        match engine {
+            Engine::Adder => Kernel::Adder(Counts::new(rule)),
+            Engine::Table => Kernel::Table(Table::new(rule)),
+        }
+    }
+
+    pub fn engine(&self) -> Engine {
+        match self {
+            Kernel::Adder(_) => Engine::Adder,
+            Kernel::Table(_) => Engine::Table,
+        }
+    }
+}
+
+/// The neighbor counts at which a cell is born or survives.
+#[derive(Clone)]
+pub(crate) struct Counts {
+    births: Vec<u8>,
+    survivals: Vec<u8>,
+}
+
+impl Counts {
+    pub fn new(rule: Rule) -> Counts {
+        Counts {
+            births: (0..=8).filter(|&n| rule.is_born(n)).collect(),
+            survivals: (0..=8).filter(|&n| rule.survives(n)).collect(),
+        }
+    }
+}
+
+/// Add three bit vectors lane by lane, returning the sum and carry bits.
+#[inline(always)]
+fn full_add<T: Lanes>(a: T, b: T, c: T) -> (T, T) {
+    let partial = a ^ b;
+    (partial ^ c, (a & b) | (partial & c))
+}
+
+/// The next state of the cells in `WORDS` words starting at `idx`.
+#[inline(always)]
+fn next_lanes<T: Lanes>(rows: &Neighborhood, counts: &Counts, idx: usize) -> T {
+    let load = |words: &[u64]| T::load(words, idx);
+    let [above_left, above, above_right] = rows.above.map(load);
+    let [below_left, below, below_right] = rows.below.map(load);
+    let (left, right) = (load(rows.left), load(rows.right));
+
+    let (ones_above, twos_above) = full_add(above_left, above, above_right);
+    let (ones_below, twos_below) = full_add(below_left, below, below_right);
+    let (ones_side, twos_side) = (left ^ right, left & right);
+
+    let (bit0, twos) = full_add(ones_above, ones_below, ones_side);
+    let (partial, fours) = full_add(twos_above, twos_below, twos_side);
+    let (bit1, more_fours) = (partial ^ twos, partial & twos);
+    let (bit2, bit3) = (fours ^ more_fours, fours & more_fours);
+
+    let count_is = |n: u8| {
+        let plane = |bit: T, set: bool| if set { bit } else { !bit };
+        plane(bit0, n & 1 != 0)
+            & plane(bit1, n & 2 != 0)
+            & plane(bit2, n & 4 != 0)
+            & plane(bit3, n & 8 != 0)
+    };
+    let born = counts
+        .births
+        .iter()
+        .fold(T::zero(), |acc, &n| acc | count_is(n));
+    let survive = counts
+        .survivals
+        .iter()
+        .fold(T::zero(), |acc, &n| acc | count_is(n));
+
+    let cells = load(rows.cells);
+    (cells & survive) | (!cells & born)
+}
+
+/// Step `words` of `out` in bundles of `T`, returning the index of the
+/// first word left over.
+#[inline(always)]
+fn step_words<T: Lanes>(
+    rows: &Neighborhood,
+    counts: &Counts,
+    out: &mut [u64],
+    words: Range<usize>,
+) -> usize {
+    let mut idx = words.start;
+    while idx + T::WORDS <= words.end {
+        next_lanes::<T>(rows, counts, idx).store(out, idx);
+        idx += T::WORDS;
+    }
+    idx
+}
+
+/// Write the next generation of `words` of the row described by `rows`
+/// into `out`.
+///
+/// The bits past the end of the row are left for the caller to clear.
+pub(crate) fn step_row(rows: &Neighborhood, kernel: &Kernel, out: &mut [u64], words: Range<usize>) {
+    let counts = match kernel {
+        Kernel::Adder(counts) => counts,
+        Kernel::Table(table) => return table::step_words(rows, table, out, words),
+    };
+    #[allow(unused_mut)]
+    let mut start = words.start;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if avx2::available() {
+        start = avx2::step_words(rows, counts, out, words.clone());
+    }
+    step_words::<u64>(rows, counts, out, start..words.end);
+}
+
+/// Shift a row of `width` cells one cell right and one cell left into
+/// `left` and `right`, so bit `c` of the results holds the neighbor to the
+/// left and to the right of column `c`.
+///
+/// The words are shifted independently of each other, with no thought for
+/// the edges, and then the cells beyond the first and last columns are
+/// filled in as `edge` says.
+pub(crate) fn shift(words: &[u64], width: u32, edge: Edge, left: &mut [u64], right: &mut [u64]) {
+    let last_word = match words.len().checked_sub(1) {
+        Some(last_word) => last_word,
+        None => return,
+    };
+    left[0] = words[0] << 1;
+    for ((shifted, &word), &previous) in left[1..].iter_mut().zip(&words[1..]).zip(words) {
+        *shifted = (word << 1) | (previous >> (WORD_BITS - 1));
+    }
+    for ((shifted, &word), &next) in right.iter_mut().zip(words).zip(&words[1..]) {
+        *shifted = (word >> 1) | (next << (WORD_BITS - 1));
+    }
+    right[last_word] = words[last_word] >> 1;
+
+    // The cells beyond the first and last columns.
+    let last_bit = (width - 1) % WORD_BITS;
+    let first = words[0] & 1;
+    let last = (words[last_word] >> last_bit) & 1;
+    let (beyond_first, beyond_last) = match edge {
+        Edge::Torus => (last, first),
+        Edge::Mirror => (first, last),
+        Edge::Dead | Edge::Grow => (0, 0),
+    };
+    left[0] |= beyond_first;
+    right[last_word] |= beyond_last << last_bit;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::EDGES;
+    use crate::Rng;
+
+    #[test]
+    fn shifted_rows_hold_the_neighbors_edges_resolve() {
+        let mut rng = Rng::new(1);
+        for edge in EDGES {
+            for width in [1, 2, 5, 63, 64, 65, 127, 128, 129, 200] {
+                let stride = stride_for(width);
+                let mut words: Vec<u64> = (0..stride).map(|_| rng.next_u64()).collect();
+                words[stride - 1] &= last_word_mask(width);
+                let (mut left, mut right) = (vec![0; stride], vec![0; stride]);
+                shift(&words, width, edge, &mut left, &mut right);
+
+                let bit = |words: &[u64], column: u32| {
+                    words[(column / WORD_BITS) as usize] >> (column % WORD_BITS) & 1
+                };
+                for column in 0..width {
+                    for (shifted, delta) in [(&left, -1), (&right, 1)] {
+                        let expected = edge
+                            .resolve(column, delta, width)
+                            .map_or(0, |neighbor| bit(&words, neighbor));
+                        let case = (edge, width, column, delta);
+                        assert_eq!(bit(shifted, column), expected, "{:?}", case);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Stepping by table lookup: the next state of two neighboring cells
+//! depends only on the 3×4 block of cells around them, so it is worked out
+//! once per rule for all 4096 blocks and a row is stepped two cells per
+//! lookup.
+
+use std::ops::Range;
+
+use super::{Neighborhood, WORD_BITS};
+use crate::Rule;
+
+/// Columns in the block looked up, one on each side of the pair.
+const COLUMNS: u32 = 4;
+
+/// The next state of the middle pair of every 3×4 block of cells.
+#[derive(Clone)]
+pub(crate) struct Table {
+    /// Indexed by the block, the rows above and below the pair in bits 0-3
+    /// and 8-11 and the pair's own row in bits 4-7, leftmost column lowest.
+    /// Bit 0 of each entry is the left cell of the pair, bit 1 the right.
+    next: Vec<u8>,
+}
+
+impl Table {
+    pub fn new(rule: Rule) -> Table {
+        let next = (0..1u32 << (3 * COLUMNS))
+            .map(|block| {
+                let alive = |row: u32, col: u32| (block >> (row * COLUMNS + col)) & 1 == 1;
+                (1..=2).fold(0, |pair, col| {
+                    let neighbors = (0..3)
+                        .flat_map(|row| (col - 1..=col + 1).map(move |col| (row, col)))
+                        .filter(|&(row, c)| (row, c) != (1, col) && alive(row, c))
+                        .count() as u8;
+                    let next = if alive(1, col) {
+                        rule.survives(neighbors)
+                    } else {
+                        rule.is_born(neighbors)
+                    };
+                    pair | (next as u8) << (col - 1)
+                })
+            })
+            .collect();
+        Table { next }
+    }
+}
+
+/// The columns -1 to 2 of a word of a row.
+fn first_block(left: &[u64], cells: &[u64], right: &[u64], idx: usize) -> u64 {
+    (left[idx] & 1) | (cells[idx] & 1) << 1 | (right[idx] & 0b11) << 2
+}
+
+/// Step `words` of `out` two cells at a time.
+///
+/// Bit `c` of a `right` row holds column `c + 1`, including the cell past
+/// the end of the row, so all but the first block of a word are read from
+/// there.
+pub(crate) fn step_words(rows: &Neighborhood, table: &Table, out: &mut [u64], words: Range<usize>) {
+    let [above_left, above, above_right] = rows.above;
+    let [below_left, below, below_right] = rows.below;
+    let block = (1 << COLUMNS) - 1;
+    for idx in words {
+        let first = first_block(above_left, above, above_right, idx)
+            | first_block(rows.left, rows.cells, rows.right, idx) << COLUMNS
+            | first_block(below_left, below, below_right, idx) << (2 * COLUMNS);
+        let mut next = table.next[first as usize] as u64;
+        let (above, cells, below) = (above_right[idx], rows.right[idx], below_right[idx]);
+        for col in (2..WORD_BITS).step_by(2) {
+            let shift = col - 2;
+            let index = ((above >> shift) & block)
+                | ((cells >> shift) & block) << COLUMNS
+                | ((below >> shift) & block) << (2 * COLUMNS);
+            next |= (table.next[index as usize] as u64) << col;
+        }
+        out[idx] = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES, SIZES};
+    use crate::step::{shift, step_row, Engine, Kernel};
+    use crate::Edge;
+    use crate::Rng;
+
+    #[test]
+    fn lookups_step_like_adders() {
+        let mut rng = Rng::new(3);
+        // The shifted rows have to agree with the cells, since lookups
+        // read most columns from them.
+        let mut shifted = || {
+            let cells: Vec<u64> = (0..6).map(|_| rng.next_u64()).collect();
+            let (mut left, mut right) = (vec![0; 6], vec![0; 6]);
+            shift(&cells, 6 * WORD_BITS, Edge::Torus, &mut left, &mut right);
+            [left, cells, right]
+        };
+        for rule in RULES {
+            let rule = Rule::parse(rule).unwrap();
+            let [above, row, below] = [shifted(), shifted(), shifted()];
+            let rows = Neighborhood {
+                above: [&above[0], &above[1], &above[2]],
+                left: &row[0],
+                cells: &row[1],
+                right: &row[2],
+                below: [&below[0], &below[1], &below[2]],
+            };
+            let (mut looked_up, mut added) = (vec![0; 6], vec![0; 6]);
+            step_words(&rows, &Table::new(rule), &mut looked_up, 0..6);
+            step_row(&rows, &Kernel::new(rule, Engine::Adder), &mut added, 0..6);
+            assert_eq!(looked_up, added, "{}", rule);
+        }
+    }
+
+    #[test]
+    fn table_ticks_match_the_reference() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                for (width, height) in SIZES {
+                    seed += 1;
+                    let mut universe = reference::soup(width, height, seed, rule, boundary);
+                    universe.set_engine(Engine::Table);
+                    for generation in 1..=6 {
+                        let expected = reference::tick(&universe);
+                        universe.tick();
+                        assert_same(
+                            &universe,
+                            &expected,
+                            (rule, boundary, width, height, generation),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Stepping a large universe on several threads at once.
+//!
+//! The universe is cut into horizontal strips, each owned by a thread that
+//! keeps a copy of the row just above and just below it. After every
+//! generation the threads send their first and last rows to their
+//! neighbors over channels, so the only thing shared between them is those
+//! two rows.
+
+use std::ops::Range;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::{Boundary, Edge, Universe};
+
+/// The work handed to a strip's thread.
+enum Command {
+    /// Simulate this many generations, then report back.
+    Step(u64),
+    /// Send back the rows of the strip.
+    Gather,
+}
+
+/// Where the row beyond one end of a strip comes from.
+enum Halo {
+    /// Past a dead edge of the universe, always empty.
+    Dead,
+    /// Past a mirrored edge, the same as the row at the end of the strip.
+    Mirror,
+    /// From the neighboring strip, which sends it every generation.
+    Neighbor(Receiver<Vec<u64>>),
+}
+
+/// The row a strip sends to the strip next to it.
+type Outbox = Option<Sender<Vec<u64>>>;
+
+/// One strip and the thread stepping it.
+struct Worker {
+    rows: Range<u32>,
+    commands: Sender<Command>,
+    /// Carries the rows of the strip back after `Command::Gather`, and an
+    /// empty vector once a `Command::Step` is done.
+    replies: Receiver<Vec<u64>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// A universe split into strips stepped in parallel, for universes large
+/// enough that a single thread can't keep up.
+pub struct Strips {
+    width: u32,
+    height: u32,
+    /// The generation every strip has reached.
+    generation: u64,
+    workers: Vec<Worker>,
+}
+
+impl Strips {
+    /// Split `universe` into up to `threads` strips, each on its own thread.
+    ///
+    /// Growing universes can't be split, since their size keeps changing,
+    /// and neither can empty ones, so they give `None`.
+    pub fn new(universe: &Universe, threads: usize) -> Option<Strips> {
+        let boundary = universe.boundary();
+        if boundary.x == Edge::Grow || boundary.y == Edge::Grow || universe.height() == 0 {
+            return None;
+        }
+        let (width, height) = (universe.width(), universe.height());
+        let count = threads.clamp(1, height as usize) as u32;
+        let ranges: Vec<Range<u32>> = (0..count)
+            .map(|strip| strip * height / count..(strip + 1) * height / count)
+            .collect();
+
+        // Strip `i` reads the rows above it from `from_above[i]`, sent by
+        // the strip above, and the rows below it from `from_below[i]`.
+        let (to_above, from_below): (Vec<_>, Vec<_>) = (0..count).map(|_| channel()).unzip();
+        let (to_below, from_above): (Vec<_>, Vec<_>) = (0..count).map(|_| channel()).unzip();
+        let torus = boundary.y == Edge::Torus;
+        let above = |strip: u32| match strip {
+            0 if torus => Some(count - 1),
+            0 => None,
+            strip => Some(strip - 1),
+        };
+        let below = |strip: u32| match strip + 1 {
+            next if next < count => Some(next),
+            _ if torus => Some(0),
+            _ => None,
+        };
+        let edge_halo = || match boundary.y {
+            Edge::Mirror => Halo::Mirror,
+            _ => Halo::Dead,
+        };
+
+        let mut workers = Vec::new();
+        for ((strip, (from_above, from_below)), rows) in (0..count)
+            .zip(from_above.into_iter().zip(from_below))
+            .zip(ranges)
+        {
+            let halos = (
+                match above(strip) {
+                    Some(_) => Halo::Neighbor(from_above),
+                    None => edge_halo(),
+                },
+                match below(strip) {
+                    Some(_) => Halo::Neighbor(from_below),
+                    None => edge_halo(),
+                },
+            );
+            // The first row goes to the strip above, as the row below it,
+            // and the last row to the strip below.
+            let outboxes = (
+                above(strip).map(|above| to_above[above as usize].clone()),
+                below(strip).map(|below| to_below[below as usize].clone()),
+            );
+            let strip = cut_strip(universe, rows.clone());
+
+            let (commands, inbox) = channel();
+            let (reply, replies) = channel();
+            let thread = thread::spawn(move || run_strip(strip, halos, outboxes, inbox, reply));
+            workers.push(Worker {
+                rows,
+                commands,
+                replies,
+                thread: Some(thread),
+            });
+        }
+
+        Some(Strips {
+            width,
+            height,
+            generation: universe.generation(),
+            workers,
+        })
+    }
+
+    /// How many strips, and threads, the universe was split into.
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Simulate `generations` generations on every strip, returning once
+    /// they are all done.
+    pub fn step(&mut self, generations: u64) {
+        for worker in &self.workers {
+            worker
+                .commands
+                .send(Command::Step(generations))
+                .expect("strip threads run until dropped");
+        }
+        for worker in &self.workers {
+            worker
+                .replies
+                .recv()
+                .expect("strip threads run until dropped");
+        }
+        self.generation += generations;
+    }
+
+    /// Copy the current cells and generation of every strip into
+    /// `universe`, which must have the size of the universe the strips
+    /// were cut from.
+    pub fn gather(&self, universe: &mut Universe) {
+        assert_eq!(
+            (universe.width(), universe.height()),
+            (self.width, self.height),
+            "gathering strips into a universe of another size"
+        );
+        for worker in &self.workers {
+            worker
+                .commands
+                .send(Command::Gather)
+                .expect("strip threads run until dropped");
+        }
+        for worker in &self.workers {
+            let words = worker
+                .replies
+                .recv()
+                .expect("strip threads run until dropped");
+            let stride = universe.row_words(0).len().max(1);
+            for (row, line) in worker.rows.clone().zip(words.chunks(stride)) {
+                universe.set_row_words(row, line);
+            }
+        }
+        universe.set_generation(self.generation);
+    }
+}
+
+impl Drop for Strips {
+    fn drop(&mut self) {
+        for mut worker in self.workers.drain(..) {
+            // Hanging up the command channel makes the thread finish.
+            drop(worker.commands);
+            if let Some(thread) = worker.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// A universe holding `rows` of `universe` plus a halo row above and below.
+fn cut_strip(universe: &Universe, rows: Range<u32>) -> Universe {
+    let height = rows.len() as u32;
+    let mut strip = Universe::empty(universe.width(), height + 2);
+    strip.set_rule(universe.rule());
+    strip.set_engine(universe.engine());
+    // The halo rows stand in for everything beyond the strip, so nothing
+    // past them counts.
+    strip.set_boundary(Boundary {
+        x: universe.boundary().x,
+        y: Edge::Dead,
+    });
+    for (target, row) in (1..).zip(rows.clone()) {
+        strip.set_row_words(target, universe.row_words(row));
+    }
+
+    // The halos start out as whatever lies beyond the strip in the whole
+    // universe.
+    let (above, _) = universe.neighbor_rows(rows.start);
+    let (_, below) = universe.neighbor_rows(rows.end - 1);
+    if let Some(row) = above {
+        strip.set_row_words(0, universe.row_words(row));
+    }
+    if let Some(row) = below {
+        strip.set_row_words(height + 1, universe.row_words(row));
+    }
+    strip
+}
+
+/// The loop of a strip's thread: step when told to, swapping edge rows
+/// with the neighbors after every generation.
+fn run_strip(
+    mut strip: Universe,
+    halos: (Halo, Halo),
+    outboxes: (Outbox, Outbox),
+    commands: Receiver<Command>,
+    reply: Sender<Vec<u64>>,
+) {
+    let last = strip.height() - 2;
+    let halo_below = last + 1;
+    let zeros = vec![0; strip.row_words(0).len()];
+    while let Ok(command) = commands.recv() {
+        match command {
+            Command::Step(generations) => {
+                for _ in 0..generations {
+                    strip.tick();
+                    if let Some(outbox) = &outboxes.0 {
+                        let _ = outbox.send(strip.row_words(1).to_vec());
+                    }
+                    if let Some(outbox) = &outboxes.1 {
+                        let _ = outbox.send(strip.row_words(last).to_vec());
+                    }
+                    for (halo, row, edge) in [(&halos.0, 0, 1), (&halos.1, halo_below, last)] {
+                        match halo {
+                            Halo::Dead => strip.set_row_words(row, &zeros),
+                            Halo::Mirror => {
+                                let words = strip.row_words(edge).to_vec();
+                                strip.set_row_words(row, &words);
+                            }
+                            Halo::Neighbor(inbox) => match inbox.recv() {
+                                Ok(words) => strip.set_row_words(row, &words),
+                                Err(_) => return,
+                            },
+                        }
+                    }
+                }
+                let _ = reply.send(Vec::new());
+            }
+            Command::Gather => {
+                let rows = (1..=last).flat_map(|row| strip.row_words(row).to_vec());
+                let _ = reply.send(rows.collect());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES};
+
+    #[test]
+    fn strips_step_like_the_reference() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                for (width, height, threads) in [(65, 13, 3), (130, 5, 7), (40, 9, 1), (200, 16, 4)]
+                {
+                    seed += 1;
+                    let mut expected = reference::soup(width, height, seed, rule, boundary);
+                    let strips = Strips::new(&expected, threads);
+                    let grows = boundary.x == Edge::Grow || boundary.y == Edge::Grow;
+                    let mut strips = match strips {
+                        Some(strips) => strips,
+                        None if grows => continue,
+                        None => panic!("{:?} was not split", boundary),
+                    };
+                    assert!(!grows, "growing {:?} was split", boundary);
+                    assert_eq!(strips.len(), threads.min(height as usize));
+                    let mut gathered = expected.clone();
+                    for generation in 1..=6 {
+                        expected = reference::tick(&expected);
+                        strips.step(1);
+                        strips.gather(&mut gathered);
+                        let case = (rule, boundary, width, height, threads, generation);
+                        assert_same(&gathered, &expected, case);
+                    }
+                    // Several generations at once exchange rows in between.
+                    for _ in 0..5 {
+                        expected = reference::tick(&expected);
+                    }
+                    strips.step(5);
+                    strips.gather(&mut gathered);
+                    assert_same(
+                        &gathered,
+                        &expected,
+                        (rule, boundary, width, height, threads),
+                    );
+                    assert_eq!(gathered.generation(), 11);
+                }
+            }
+        }
+    }
+}
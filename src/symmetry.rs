@@ -0,0 +1,269 @@
+use std::fmt;
+
+use crate::step::WORD_BITS;
+use crate::Universe;
+
+/// The ways a universe looks the same once turned or flipped about its
+/// center. Taken with `Universe::symmetry`.
+///
+/// Every rule treats all directions alike, so a universe keeps its
+/// symmetries as it evolves, as long as its edges keep them too.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Symmetry {
+    /// The same flipped left to right.
+    pub mirror_x: bool,
+    /// The same flipped top to bottom.
+    pub mirror_y: bool,
+    /// The same turned half way round.
+    pub half_turn: bool,
+    /// The same turned a quarter of the way round, which only a square
+    /// universe can be.
+    pub quarter_turn: bool,
+    /// The same flipped across the diagonal from the top left corner, or
+    /// the one from the top right corner, which only a square universe can
+    /// be.
+    pub diagonal: bool,
+    pub antidiagonal: bool,
+}
+
+impl Symmetry {
+    pub(crate) fn of(universe: &Universe) -> Symmetry {
+        let (width, height) = (universe.width(), universe.height());
+        let Symmetry {
+            mirror_x,
+            mirror_y,
+            half_turn,
+            ..
+        } = Symmetry::of_rows(universe);
+        let square = width == height;
+        let all = |map: fn(u32, u32, u32) -> (u32, u32)| {
+            square
+                && (0..height).all(|row| {
+                    (0..width).all(|col| {
+                        let (r, c) = map(row, col, width - 1);
+                        universe.get_cell(row, col) == universe.get_cell(r, c)
+                    })
+                })
+        };
+        Symmetry {
+            mirror_x,
+            mirror_y,
+            half_turn,
+            quarter_turn: half_turn && all(|row, col, last| (col, last - row)),
+            diagonal: all(|row, col, _| (col, row)),
+            antidiagonal: all(|row, col, last| (last - col, last - row)),
+        }
+    }
+
+    /// The symmetries that map rows onto rows, found a row at a time rather
+    /// than a cell at a time, leaving the others unset.
+    fn of_rows(universe: &Universe) -> Symmetry {
+        let (width, height) = (universe.width(), universe.height());
+        let mut reversed = vec![0; universe.stride()];
+        let mut symmetry = Symmetry {
+            mirror_x: true,
+            mirror_y: true,
+            half_turn: true,
+            ..Symmetry::default()
+        };
+        for row in 0..height {
+            let (cells, opposite) = (
+                universe.row_words(row),
+                universe.row_words(height - 1 - row),
+            );
+            symmetry.mirror_y &= cells == opposite;
+            reverse_row(opposite, width, &mut reversed);
+            symmetry.half_turn &= cells == reversed;
+            reverse_row(cells, width, &mut reversed);
+            symmetry.mirror_x &= cells == reversed;
+        }
+        symmetry
+    }
+
+    /// The name of the symmetry group, as soup searches write it: `C1` for
+    /// none, `C2` and `C4` for turning only, `D2` for one mirror, `D4` for
+    /// two and `D8` for all of them, with `-`, `|`, `+` or `x` telling
+    /// which mirrors.
+    pub fn name(&self) -> &'static str {
+        let diagonals = self.diagonal || self.antidiagonal;
+        match *self {
+            Symmetry {
+                quarter_turn: true,
+                mirror_x: true,
+                ..
+            } => "D8",
+            Symmetry {
+                quarter_turn: true, ..
+            } => "C4",
+            Symmetry {
+                mirror_x: true,
+                mirror_y: true,
+                ..
+            } => "D4+",
+            Symmetry {
+                diagonal: true,
+                antidiagonal: true,
+                ..
+            } => "D4x",
+            Symmetry { mirror_y: true, .. } => "D2-",
+            Symmetry { mirror_x: true, .. } => "D2|",
+            _ if diagonals => "D2x",
+            Symmetry {
+                half_turn: true, ..
+            } => "C2",
+            _ => "C1",
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    /// Such as `D2- (flipped top to bottom)` or `C1 (none)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut kinds = Vec::new();
+        if self.mirror_x {
+            kinds.push("flipped left to right");
+        }
+        if self.mirror_y {
+            kinds.push("flipped top to bottom");
+        }
+        if self.quarter_turn {
+            kinds.push("turned a quarter");
+        } else if self.half_turn {
+            kinds.push("turned half way");
+        }
+        if self.diagonal || self.antidiagonal {
+            kinds.push("flipped across a diagonal");
+        }
+        if kinds.is_empty() {
+            write!(f, "{} (none)", self.name())
+        } else {
+            write!(f, "{} (the same {})", self.name(), kinds.join(", "))
+        }
+    }
+}
+
+/// How `tick` can work out the bottom half of a universe from the top one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Fold {
+    /// Not looked into since the cells were last changed other than by a
+    /// tick.
+    Unknown,
+    /// It cannot.
+    Nothing,
+    /// The bottom half is the top one flipped upside down.
+    Mirror,
+    /// The bottom half is the top one turned half way round.
+    HalfTurn,
+}
+
+impl Fold {
+    /// How `universe` can be folded, given its symmetries.
+    pub(crate) fn of(universe: &Universe) -> Fold {
+        let symmetry = Symmetry::of_rows(universe);
+        if symmetry.mirror_y {
+            Fold::Mirror
+        } else if symmetry.half_turn {
+            Fold::HalfTurn
+        } else {
+            Fold::Nothing
+        }
+    }
+}
+
+/// Write the packed cells of a row `width` cells wide into `out` from right
+/// to left.
+pub(crate) fn reverse_row(words: &[u64], width: u32, out: &mut [u64]) {
+    // Reversing every bit of the row leaves the cells at the top end of the
+    // last word, `pad` bits too far left.
+    let pad = words.len() as u32 * WORD_BITS - width;
+    let reversed = |idx: usize| {
+        words
+            .len()
+            .checked_sub(idx + 1)
+            .map_or(0, |i| words[i].reverse_bits())
+    };
+    for (idx, word) in out.iter_mut().enumerate() {
+        *word = match pad {
+            0 => reversed(idx),
+            pad => reversed(idx) >> pad | reversed(idx + 1) << (WORD_BITS - pad),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Cell, Edge, Rng, Rule};
+
+    /// A universe the same turned half way round, or flipped top to
+    /// bottom, with a random soup down the left edge of its top half.
+    ///
+    /// The soup is kept narrow so that the two halves only change in
+    /// different words of the rows where they meet.
+    fn symmetric(width: u32, height: u32, half_turn: bool, seed: u64) -> Universe {
+        let mut rng = Rng::new(seed);
+        let mut universe = Universe::empty(width, height);
+        for row in 0..height.div_ceil(2) {
+            for col in 0..width.min(12) {
+                if rng.chance(0.45) {
+                    let (r, c) = match half_turn {
+                        true => (height - 1 - row, width - 1 - col),
+                        false => (height - 1 - row, col),
+                    };
+                    universe.set_cell(row, col, Cell::Alive);
+                    universe.set_cell(r, c, Cell::Alive);
+                }
+            }
+        }
+        universe
+    }
+
+    /// Tick `unfolded` side by side with a copy that folds, checking they
+    /// stay the same.
+    fn compare_folded(mut unfolded: Universe, expected: Fold, generations: u64) {
+        let mut folded = unfolded.clone();
+        folded.set_fold_symmetric(true);
+        assert_eq!(Fold::of(&folded), expected);
+        for generation in 1..=generations {
+            folded.tick();
+            unfolded.tick();
+            let case = (
+                unfolded.rule(),
+                unfolded.boundary(),
+                unfolded.width(),
+                unfolded.height(),
+            );
+            let case = (case, generation);
+            assert!(folded.get_cells() == unfolded.get_cells(), "{:?}", case);
+            assert_eq!(folded.population(), unfolded.population(), "{:?}", case);
+            assert_eq!(folded.state_hash(), unfolded.state_hash(), "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn folded_ticks_match_unfolded_ones() {
+        let edges = [Edge::Torus, Edge::Dead, Edge::Mirror];
+        let rules = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678"];
+        let sizes = [(37, 20), (100, 31), (300, 24), (450, 33)];
+        let mut seed = 0;
+        for (x, y) in edges.into_iter().flat_map(|x| edges.map(|y| (x, y))) {
+            for rule in rules {
+                for ((width, height), half_turn) in sizes
+                    .into_iter()
+                    .flat_map(|size| [(size, false), (size, true)])
+                {
+                    seed += 1;
+                    let mut universe = symmetric(width, height, half_turn, seed);
+                    universe.set_rule(Rule::parse(rule).unwrap());
+                    universe.set_boundary(Boundary { x, y });
+                    let expected = if half_turn {
+                        Fold::HalfTurn
+                    } else {
+                        Fold::Mirror
+                    };
+                    compare_folded(universe, expected, 40);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1880 @@
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use crate::bits::{self, BitEncoding, BitStringError};
+use crate::noise::gradient_noise;
+use crate::pattern::Pattern;
+use crate::render::Charset;
+use crate::rng::mix;
+use crate::step::{self, last_word_mask, stride_for, Engine, Kernel, Neighborhood, WORD_BITS};
+use crate::symmetry::{reverse_row, Fold};
+use crate::{
+    Boundary, Delta, DiffReport, Dither, Edge, Font, Image, Initializer, Rng, Rule, Snapshot,
+    Symmetry,
+};
+
+/// Dead cells added on a side when a growing universe runs out of room.
+pub(crate) const GROW_MARGIN: u32 = 8;
+
+/// A growing universe stops growing along an axis past this many cells and
+/// treats the edge as dead from then on.
+pub(crate) const MAX_GROWN_SIZE: u32 = 1 << 14;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Dead = 0,
+    Alive = 1,
+}
+
+impl Cell {
+    /// The other state.
+    pub fn toggled(self) -> Cell {
+        match self {
+            Cell::Dead => Cell::Alive,
+            Cell::Alive => Cell::Dead,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Universe {
+    width: u32,
+    height: u32,
+    /// One bit per cell, set for live ones. Each row starts on a fresh word
+    /// and the bits past the end of a row are always zero.
+    words: Vec<u64>,
+    /// Words holding each row.
+    stride: usize,
+    rule: Rule,
+    /// The engine stepping the universe, prepared for `rule`.
+    kernel: Kernel,
+    boundary: Boundary,
+    /// Rows that changed since `clear_changed_rows` was last called.
+    changed_rows: Vec<bool>,
+    /// The rows above and below each row, worked out once per size and
+    /// boundary so stepping never has to wrap coordinates.
+    row_links: Vec<(Option<u32>, Option<u32>)>,
+    /// Every word that changed in the last tick or was edited since, maybe
+    /// more than once. Only the words around them can change in the next
+    /// tick, so the rest of the universe is skipped.
+    changes: Vec<usize>,
+    /// Live cells, kept up to date as cells change rather than recounted.
+    population: u64,
+    /// The `word_hash` of every word folded together, kept up to date the
+    /// same way.
+    hash: u64,
+    /// Generations stepped through by `tick` since the universe was created
+    /// or reset.
+    generation: u64,
+    /// Rows and columns added above and to the left by growing edges since
+    /// the universe was created.
+    grown: (u64, u64),
+    /// Cells born and cells that died in the last tick.
+    births: u64,
+    deaths: u64,
+    /// The generation each cell last came to life or died in, row by row,
+    /// when ages are tracked. Only the cells that change are touched.
+    changed_at: Option<Vec<u64>>,
+    /// Whether `tick` steps only the top half of a universe whose bottom
+    /// half follows from it, and how it does.
+    fold_symmetric: bool,
+    fold: Fold,
+    scratch: Scratch,
+}
+
+/// Buffers kept from one tick to the next so stepping doesn't allocate.
+#[derive(Clone, Default)]
+struct Scratch {
+    /// Receives the next generation, then swaps places with the cells.
+    next: Vec<u64>,
+    from_left: Vec<u64>,
+    from_right: Vec<u64>,
+    /// A row of dead cells, standing in for the rows past a dead edge.
+    zeros: Vec<u64>,
+    /// The words next to a changed one, which have to be stepped, in order.
+    to_step: Vec<usize>,
+    /// Which words are in `to_step`, all false between ticks.
+    stepping: Vec<bool>,
+    /// The rows shifted into `from_left` and `from_right` this tick.
+    shifted_rows: Vec<u32>,
+    /// Which rows are in `shifted_rows`, all false between ticks.
+    shifted: Vec<bool>,
+    /// The rows of the top half whose bottom counterpart is filled in when
+    /// folding, and the cells of one of them.
+    unfolded_rows: Vec<usize>,
+    unfolded: Vec<u64>,
+}
+
+impl Scratch {
+    /// Size the buffers for `height` rows of `stride` words, keeping their
+    /// allocations.
+    fn fit(&mut self, height: usize, stride: usize) {
+        let len = height * stride;
+        for buffer in [&mut self.next, &mut self.from_left, &mut self.from_right] {
+            buffer.resize(len, 0);
+        }
+        self.zeros.resize(stride, 0);
+        self.stepping.resize(len, false);
+        self.shifted.resize(height, false);
+    }
+
+    /// Forget the words stepped and rows shifted this tick.
+    fn reset(&mut self) {
+        for &idx in &self.to_step {
+            self.stepping[idx] = false;
+        }
+        self.to_step.clear();
+        for &row in &self.shifted_rows {
+            self.shifted[row as usize] = false;
+        }
+        self.shifted_rows.clear();
+    }
+}
+
+/// Row `row` of `words`, or the row of dead cells in `zeros` past an edge.
+fn row_slice<'a>(words: &'a [u64], row: Option<u32>, stride: usize, zeros: &'a [u64]) -> &'a [u64] {
+    match row {
+        Some(row) => &words[row as usize * stride..(row as usize + 1) * stride],
+        None => zeros,
+    }
+}
+
+/// OR the bits of `source` into `target`, shifted `offset` cells to the
+/// right. Bits falling past the end of `target` are dropped.
+fn blit_row(target: &mut [u64], source: &[u64], offset: usize) {
+    let (start, shift) = (offset / WORD_BITS as usize, offset as u32 % WORD_BITS);
+    for (idx, &bits) in source.iter().enumerate() {
+        if let Some(word) = target.get_mut(start + idx) {
+            *word |= bits << shift;
+        }
+        if shift > 0 {
+            if let Some(word) = target.get_mut(start + idx + 1) {
+                *word |= bits >> (WORD_BITS - shift);
+            }
+        }
+    }
+}
+
+impl Universe {
+    /// The word holding a cell and the bit for it within that word.
+    ///
+    /// Panics if the cell is outside the universe, so that nothing reads or
+    /// writes the padding past the last column, which must stay dead.
+    fn locate(&self, row: u32, column: u32) -> (usize, u64) {
+        assert!(
+            row < self.height && column < self.width,
+            "cell {},{} is outside the {}x{} universe",
+            row,
+            column,
+            self.width,
+            self.height
+        );
+        let idx = row as usize * self.stride + (column / WORD_BITS) as usize;
+        (idx, 1 << (column % WORD_BITS))
+    }
+
+    pub(crate) fn row_words(&self, row: u32) -> &[u64] {
+        let start = row as usize * self.stride;
+        &self.words[start..start + self.stride]
+    }
+
+    /// Replace the packed cells of `row`, as returned by `row_words`.
+    pub(crate) fn set_row_words(&mut self, row: u32, words: &[u64]) {
+        let start = row as usize * self.stride;
+        for (idx, &word) in (start..).zip(words) {
+            if self.words[idx] != word {
+                self.population += word.count_ones() as u64;
+                self.population -= self.words[idx].count_ones() as u64;
+                self.hash ^= word_hash(idx, self.words[idx]) ^ word_hash(idx, word);
+                if let Some(changed_at) = &mut self.changed_at {
+                    let at = (self.width, self.stride, self.generation);
+                    note_changes(changed_at, at, idx, self.words[idx] ^ word);
+                }
+                self.words[idx] = word;
+                self.fold = Fold::Unknown;
+                self.changes.push(idx);
+                self.changed_rows[row as usize] = true;
+            }
+        }
+    }
+
+    /// Copy the cells into a universe of another size, with the old top
+    /// left corner landing at `top` and `left`.
+    fn copy_words(&self, width: u32, height: u32, top: u32, left: u32) -> Vec<u64> {
+        let stride = stride_for(width);
+        let mut words = vec![0; stride * height as usize];
+        for row in 0..self.height {
+            let target_row = (row + top) as usize;
+            if target_row >= height as usize {
+                break;
+            }
+            let target = &mut words[target_row * stride..(target_row + 1) * stride];
+            blit_row(target, self.row_words(row), left as usize);
+            if let Some(last) = target.last_mut() {
+                *last &= last_word_mask(width);
+            }
+        }
+        words
+    }
+
+    /// Add dead margins to every side of a growing universe that has live
+    /// cells on its edge, so patterns can keep expanding outwards.
+    fn grow_to_fit(&mut self) {
+        // Nothing can be alive on the edge of a universe without cells.
+        if self.height == 0 || self.width == 0 {
+            return;
+        }
+        let can_grow = |edge: Edge, size: u32| edge == Edge::Grow && size < MAX_GROWN_SIZE;
+
+        let grow_y = can_grow(self.boundary.y, self.height);
+        let grow_x = can_grow(self.boundary.x, self.width);
+        let any = |words: &[u64]| words.iter().any(|&word| word != 0);
+        let column_alive =
+            |column: u32| (0..self.height).any(|row| self.get_cell(row, column) == Cell::Alive);
+        let top = grow_y && any(self.row_words(0));
+        let bottom = grow_y && any(self.row_words(self.height - 1));
+        let left = grow_x && column_alive(0);
+        let right = grow_x && column_alive(self.width - 1);
+        if !(top || bottom || left || right) {
+            return;
+        }
+
+        let margin = |grow: bool| if grow { GROW_MARGIN } else { 0 };
+        let (top, bottom, left, right) = (margin(top), margin(bottom), margin(left), margin(right));
+        let new_width = self.width + left + right;
+        let new_height = self.height + top + bottom;
+
+        self.words = self.copy_words(new_width, new_height, top, left);
+        self.grown = (self.grown.0 + top as u64, self.grown.1 + left as u64);
+        self.width = new_width;
+        self.height = new_height;
+        self.stride = stride_for(new_width);
+        self.mark_all_changed();
+    }
+
+    /// The rows above and below `row`, or `None` past a dead edge.
+    pub(crate) fn neighbor_rows(&self, row: u32) -> (Option<u32>, Option<u32>) {
+        self.row_links[row as usize]
+    }
+
+    fn link_rows(&mut self) {
+        let (edge, height) = (self.boundary.y, self.height);
+        self.row_links = (0..height)
+            .map(|row| (edge.resolve(row, -1, height), edge.resolve(row, 1, height)))
+            .collect();
+    }
+
+    /// List the words of the first `rows` rows whose neighborhood holds a
+    /// changed word: the changed word itself and the ones around it,
+    /// wrapping around the ends of a row.
+    ///
+    /// Changes past `rows` count too, as they reach across into the rows
+    /// stepped where the halves of a folded universe meet and around edges
+    /// that wrap. Turned half way round they land elsewhere in their row
+    /// than the changes they mirror.
+    fn find_active(&self, scratch: &mut Scratch, rows: u32) {
+        let stride = self.stride;
+        // Changes mostly come in order, so the row is rarely recomputed.
+        let (mut row, mut row_start) = (0, 0);
+        for &idx in &self.changes {
+            if !(row_start..row_start + stride).contains(&idx) {
+                row = (idx / stride) as u32;
+                row_start = row as usize * stride;
+            }
+            let column = idx - row_start;
+            let left = if column == 0 { stride - 1 } else { column - 1 };
+            let right = if column + 1 == stride { 0 } else { column + 1 };
+            let (above, below) = self.neighbor_rows(row);
+            let targets = [above, Some(row), below].into_iter().flatten();
+            for target_row in targets.filter(|&target| target < rows) {
+                for target_column in [left, column, right] {
+                    let target = target_row as usize * stride + target_column;
+                    if !scratch.stepping[target] {
+                        scratch.stepping[target] = true;
+                        scratch.to_step.push(target);
+                    }
+                }
+            }
+        }
+        // Put the list in order, by scanning the flags when most of the
+        // universe is active and sorting would take longer.
+        if scratch.to_step.len() > scratch.stepping.len() / 16 {
+            scratch.to_step.clear();
+            scratch
+                .to_step
+                .extend((0..scratch.stepping.len()).filter(|&idx| scratch.stepping[idx]));
+        } else {
+            scratch.to_step.sort_unstable();
+        }
+    }
+
+    /// Shift `row` into `from_left` and `from_right`, see `step::shift`.
+    fn shift_row(&self, row: u32, from_left: &mut [u64], from_right: &mut [u64]) {
+        let start = row as usize * self.stride;
+        step::shift(
+            self.row_words(row),
+            self.width,
+            self.boundary.x,
+            &mut from_left[start..start + self.stride],
+            &mut from_right[start..start + self.stride],
+        );
+    }
+
+    /// Get the dead and alive values of the entire universe, row by row.
+    pub fn get_cells(&self) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(self.width as usize * self.height as usize);
+        for row in 0..self.height {
+            cells.extend((0..self.width).map(|column| self.get_cell(row, column)));
+        }
+        cells
+    }
+
+    /// Set cells to be alive in a universe by passing the row and column
+    /// of each cell as an array.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
+        for &(row, col) in cells {
+            self.set_cell(row, col, Cell::Alive);
+        }
+    }
+
+    /// How many cells are alive, kept up to date as cells change so it
+    /// costs nothing to ask.
+    pub fn population(&self) -> u64 {
+        self.population
+    }
+
+    /// Share of the cells that are alive.
+    pub fn density(&self) -> f64 {
+        match self.width as u64 * self.height as u64 {
+            0 => 0.0,
+            cells => self.population as f64 / cells as f64,
+        }
+    }
+
+    /// How disordered the board looks, as the Shannon entropy in bits of
+    /// the 2×2 blocks it divides into: 0 when every block looks the same,
+    /// such as on an empty board, up to 4 when all 16 arrangements of a
+    /// block are equally common. A row or column left over at an odd edge
+    /// is not counted.
+    pub fn entropy(&self) -> f64 {
+        let mut counts = [0u64; 16];
+        for row in (0..self.height.saturating_sub(1)).step_by(2) {
+            let (upper, lower) = (self.row_words(row), self.row_words(row + 1));
+            for col in (0..self.width.saturating_sub(1)).step_by(2) {
+                // Even columns never straddle two words.
+                let (idx, shift) = ((col / WORD_BITS) as usize, col % WORD_BITS);
+                let block = (upper[idx] >> shift) & 0b11 | ((lower[idx] >> shift) & 0b11) << 2;
+                counts[block as usize] += 1;
+            }
+        }
+        let blocks = counts.iter().sum::<u64>() as f64;
+        counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| count as f64 / blocks)
+            .fold(0.0, |entropy, share| entropy - share * share.log2())
+    }
+
+    /// The smallest rectangle containing every live cell, as
+    /// `(top, left, bottom, right)` inclusive, or `None` if nothing is alive.
+    pub fn live_bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for row in 0..self.height {
+            let words = self.row_words(row);
+            let first = match words.iter().position(|&word| word != 0) {
+                Some(first) => first,
+                None => continue,
+            };
+            let last = words.iter().rposition(|&word| word != 0).unwrap_or(first);
+            let left = first as u32 * WORD_BITS + words[first].trailing_zeros();
+            let right = last as u32 * WORD_BITS + (WORD_BITS - 1 - words[last].leading_zeros());
+            bounds = Some(match bounds {
+                None => (row, left, row, right),
+                Some((top, min_left, _, max_right)) => {
+                    (top, min_left.min(left), row, max_right.max(right))
+                }
+            });
+        }
+        bounds
+    }
+
+    /// Bring the cells of `pattern` to life with its top left corner at
+    /// `row` and `column`. Cells falling outside the universe are dropped.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, row: u32, column: u32) {
+        let cells: Vec<(u32, u32)> = pattern
+            .cells_at(row, column)
+            .filter(|&(r, c)| r < self.height && c < self.width)
+            .collect();
+        self.set_cells(&cells);
+    }
+
+    /// Bring the cells of `pattern` to life in the middle of the universe,
+    /// returning the row and column its top left corner went to.
+    ///
+    /// Fails, leaving the universe as it was, if the pattern is wider or
+    /// taller than the universe.
+    pub fn place_centered(&mut self, pattern: &Pattern) -> Result<(u32, u32), DoesNotFit> {
+        let (width, height) = (pattern.width(), pattern.height());
+        if width > self.width || height > self.height {
+            return Err(DoesNotFit {
+                pattern: (width, height),
+                universe: (self.width, self.height),
+            });
+        }
+        let (row, column) = ((self.height - height) / 2, (self.width - width) / 2);
+        self.insert_pattern(pattern, row, column);
+        Ok((row, column))
+    }
+
+    /// Which rows changed since the flags were last cleared, one flag per
+    /// row. Renderers use this to avoid redrawing rows that stayed the same.
+    pub fn changed_rows(&self) -> &[bool] {
+        &self.changed_rows
+    }
+
+    /// Mark every row as unchanged, typically once a frame has been drawn.
+    pub fn clear_changed_rows(&mut self) {
+        self.changed_rows
+            .iter_mut()
+            .for_each(|changed| *changed = false);
+    }
+
+    /// The rows that changed since the flags were last cleared, with their
+    /// cells, marking every row as unchanged.
+    pub fn take_delta(&mut self) -> Delta {
+        let mut rows = Vec::new();
+        for row in 0..self.height {
+            if mem::take(&mut self.changed_rows[row as usize]) {
+                rows.push((row, self.row_words(row).to_vec()));
+            }
+        }
+        Delta {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            births: self.births,
+            deaths: self.deaths,
+            rows,
+        }
+    }
+
+    /// Every row with its cells, leaving the changed rows as they are, to
+    /// bring a copy that holds nothing yet up to date.
+    pub fn full_delta(&self) -> Delta {
+        Delta {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            births: self.births,
+            deaths: self.deaths,
+            rows: (0..self.height)
+                .map(|row| (row, self.row_words(row).to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Bring a copy of another universe up to date with a delta taken from
+    /// it, marking the rows that differ as changed.
+    pub fn apply_delta(&mut self, delta: &Delta) {
+        if (delta.width, delta.height) != (self.width, self.height) {
+            self.resize(delta.width, delta.height);
+        }
+        self.generation = delta.generation;
+        for (row, words) in &delta.rows {
+            self.set_row_words(*row, words);
+        }
+        (self.births, self.deaths) = (delta.births, delta.deaths);
+        // A copy that is only ever brought up to date would otherwise list
+        // the same words over and over.
+        if self.changes.len() > self.words.len() {
+            self.mark_all_words_changed();
+        }
+    }
+
+    /// Mark every row and word as changed after the cells were replaced
+    /// wholesale, maybe with a new size.
+    fn mark_all_changed(&mut self) {
+        self.population = self.words.iter().map(|word| word.count_ones() as u64).sum();
+        self.hash = (0..)
+            .zip(&self.words)
+            .fold(0, |hash, (idx, &word)| hash ^ word_hash(idx, word));
+        (self.births, self.deaths) = (0, 0);
+        self.fold = Fold::Unknown;
+        if let Some(changed_at) = &mut self.changed_at {
+            // Cells that moved or were replaced start aging anew.
+            *changed_at = vec![self.generation; self.width as usize * self.height as usize];
+        }
+        self.changed_rows = vec![true; self.height as usize];
+        self.link_rows();
+        self.mark_all_words_changed();
+    }
+
+    /// Step every word in the next tick, once something other than the
+    /// cells changed.
+    fn mark_all_words_changed(&mut self) {
+        self.changes = (0..self.words.len()).collect();
+    }
+}
+
+impl Universe {
+    /// Advance one generation.
+    pub fn tick(&mut self) {
+        if self.boundary.x == Edge::Grow || self.boundary.y == Edge::Grow {
+            self.grow_to_fit();
+        }
+
+        let mut scratch = mem::take(&mut self.scratch);
+        scratch.fit(self.height as usize, self.stride);
+        // Only the top half of a symmetric universe is stepped, the bottom
+        // half being filled in from it afterwards.
+        let fold = self.fold();
+        let rows = match fold {
+            Fold::Mirror | Fold::HalfTurn => self.height.div_ceil(2),
+            Fold::Unknown | Fold::Nothing => self.height,
+        };
+        self.find_active(&mut scratch, rows);
+
+        let Scratch {
+            next,
+            from_left,
+            from_right,
+            zeros,
+            to_step,
+            shifted_rows,
+            shifted,
+            unfolded_rows,
+            unfolded,
+            ..
+        } = &mut scratch;
+        let mut row_end = 0;
+        for &idx in to_step.iter() {
+            if idx < row_end {
+                continue;
+            }
+            let row = (idx / self.stride) as u32;
+            row_end = (row as usize + 1) * self.stride;
+            let (above, below) = self.neighbor_rows(row);
+            for row in [above, Some(row), below].into_iter().flatten() {
+                if !shifted[row as usize] {
+                    shifted[row as usize] = true;
+                    shifted_rows.push(row);
+                    self.shift_row(row, from_left, from_right);
+                }
+            }
+        }
+
+        // Words that stayed the same last tick still hold the same value in
+        // the spare buffer, so only the listed ones need writing.
+        let mask = last_word_mask(self.width);
+        let mut changes = mem::take(&mut self.changes);
+        changes.clear();
+        (self.births, self.deaths) = (0, 0);
+        let mut position = 0;
+        while position < to_step.len() {
+            // Step each run of neighboring words in a row in one go.
+            let first = to_step[position];
+            let row = (first / self.stride) as u32;
+            let start = row as usize * self.stride;
+            let mut end = first + 1;
+            position += 1;
+            while position < to_step.len() && to_step[position] == end && end < start + self.stride
+            {
+                end += 1;
+                position += 1;
+            }
+
+            let (above, below) = self.neighbor_rows(row);
+            let row_of = |words, row| row_slice(words, row, self.stride, zeros);
+            let neighborhood = |row: Option<u32>| {
+                [
+                    row_of(from_left, row),
+                    row_of(&self.words, row),
+                    row_of(from_right, row),
+                ]
+            };
+            let rows = Neighborhood {
+                above: neighborhood(above),
+                left: row_of(from_left, Some(row)),
+                cells: self.row_words(row),
+                right: row_of(from_right, Some(row)),
+                below: neighborhood(below),
+            };
+            let out = &mut next[start..start + self.stride];
+            step::step_row(&rows, &self.kernel, out, first - start..end - start);
+            if end == start + self.stride {
+                out[self.stride - 1] &= mask;
+            }
+
+            let stepped = next[first..end].iter().zip(&self.words[first..end]);
+            for (idx, (new, old)) in (first..).zip(stepped) {
+                if new != old {
+                    self.births += (new & !old).count_ones() as u64;
+                    self.deaths += (old & !new).count_ones() as u64;
+                    self.hash ^= word_hash(idx, *old) ^ word_hash(idx, *new);
+                    if let Some(changed_at) = &mut self.changed_at {
+                        let at = (self.width, self.stride, self.generation + 1);
+                        note_changes(changed_at, at, idx, old ^ new);
+                    }
+                    changes.push(idx);
+                    self.changed_rows[row as usize] = true;
+                }
+            }
+        }
+
+        if rows < self.height {
+            unfolded_rows.clear();
+            unfolded_rows.extend(to_step.iter().map(|&idx| idx / self.stride));
+            self.unfold(fold, next, (unfolded_rows, unfolded), &mut changes);
+        }
+
+        scratch.reset();
+        mem::swap(&mut self.words, &mut scratch.next);
+        self.changes = changes;
+        self.scratch = scratch;
+        self.population = self.population + self.births - self.deaths;
+        self.generation += 1;
+    }
+
+    /// Fill in the rows of the bottom half of `next` across from the `rows`
+    /// of the top half just stepped, as `fold` says.
+    fn unfold(
+        &mut self,
+        fold: Fold,
+        next: &mut [u64],
+        (rows, cells): (&mut Vec<usize>, &mut Vec<u64>),
+        changes: &mut Vec<usize>,
+    ) {
+        let (height, stride) = (self.height as usize, self.stride);
+        rows.sort_unstable();
+        rows.dedup();
+        cells.resize(stride, 0);
+        for &top in rows.iter() {
+            let bottom = height - 1 - top;
+            if bottom == top {
+                continue;
+            }
+            let source = &next[top * stride..(top + 1) * stride];
+            match fold {
+                Fold::HalfTurn => reverse_row(source, self.width, cells),
+                _ => cells.copy_from_slice(source),
+            }
+            for (idx, &new) in (bottom * stride..).zip(cells.iter()) {
+                let old = self.words[idx];
+                next[idx] = new;
+                if new != old {
+                    self.births += (new & !old).count_ones() as u64;
+                    self.deaths += (old & !new).count_ones() as u64;
+                    self.hash ^= word_hash(idx, old) ^ word_hash(idx, new);
+                    if let Some(changed_at) = &mut self.changed_at {
+                        let at = (self.width, self.stride, self.generation + 1);
+                        note_changes(changed_at, at, idx, old ^ new);
+                    }
+                    changes.push(idx);
+                    self.changed_rows[bottom] = true;
+                }
+            }
+        }
+    }
+
+    /// How the next tick can fold the universe, looking into it again if
+    /// the cells were edited since.
+    fn fold(&mut self) -> Fold {
+        let grows = self.boundary.x == Edge::Grow || self.boundary.y == Edge::Grow;
+        if !self.fold_symmetric || grows {
+            return Fold::Nothing;
+        }
+        if self.fold == Fold::Unknown {
+            self.fold = Fold::of(self);
+            // Edits may have left the halves with changes that don't mirror
+            // each other, which the bottom one would miss if not spread.
+            if self.fold != Fold::Nothing {
+                self.mark_all_words_changed();
+            }
+        }
+        self.fold
+    }
+
+    /// A universe whose cells are alive where their index in row-major
+    /// order is a multiple of `div_a` or of `div_b`.
+    ///
+    /// Fails if either divisor is 0, of which nothing is a multiple but 0.
+    pub fn new(
+        initial_width: u32,
+        initial_height: u32,
+        div_a: u32,
+        div_b: u32,
+    ) -> Result<Universe, ZeroDivisor> {
+        if div_a == 0 || div_b == 0 {
+            return Err(ZeroDivisor);
+        }
+        let width = initial_width;
+        let height = initial_height;
+
+        let mut universe = Universe::empty(width, height);
+        for i in 0..width * height {
+            if i % div_a == 0 || i % div_b == 0 {
+                universe.set_cell(i / width, i % width, Cell::Alive);
+            }
+        }
+        Ok(universe)
+    }
+
+    /// A universe of the given size holding what `initializer` says.
+    ///
+    /// Fails if a pattern or text is too large for the universe, or if a
+    /// divisor is 0. Images are made to fit.
+    pub fn from_initializer(
+        width: u32,
+        height: u32,
+        initializer: &Initializer,
+    ) -> Result<Universe, InitializerError> {
+        let centered = |pattern: &Pattern| {
+            let mut universe = Universe::empty(width, height);
+            match universe.place_centered(pattern) {
+                Ok(_) => Ok(universe),
+                Err(error) => Err(InitializerError::DoesNotFit(error)),
+            }
+        };
+        match initializer {
+            Initializer::Empty => Ok(Universe::empty(width, height)),
+            &Initializer::Divisors { a, b } => {
+                Universe::new(width, height, a, b).map_err(|_| InitializerError::ZeroDivisor)
+            }
+            &Initializer::Random { density, seed } => {
+                Ok(Universe::random(width, height, density, seed))
+            }
+            &Initializer::Noise {
+                scale,
+                threshold,
+                seed,
+            } => Ok(Universe::noise(width, height, scale, threshold, seed)),
+            &Initializer::Clusters {
+                count,
+                radius,
+                seed,
+            } => Ok(Universe::clusters(width, height, count, radius, seed)),
+            Initializer::Pattern(pattern) => centered(pattern),
+            Initializer::Image { image, dither } => {
+                let (columns, rows) = image.fit(width, height);
+                let drawn = Universe::from_image(image, columns, rows, *dither);
+                centered(&Pattern::from_universe(&drawn))
+            }
+            Initializer::Text { text, font } => centered(&font.banner(text)),
+        }
+    }
+
+    /// A random soup: a universe in which every cell is alive with
+    /// probability `density`.
+    ///
+    /// The cells are drawn from `seed` with `Rng`, ChaCha8 from `rand`, row
+    /// by row from the top left corner, so the same seed gives the same soup
+    /// on every platform.
+    pub fn random(width: u32, height: u32, density: f64, seed: u64) -> Universe {
+        let mut universe = Universe::empty(width, height);
+        universe.scatter(density, seed);
+        universe
+    }
+
+    /// Bring cells to life at random the way `random` does, leaving the
+    /// ones alive already as they are.
+    pub fn scatter(&mut self, density: f64, seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut words = vec![0; self.stride];
+        for row in 0..self.height {
+            words.copy_from_slice(self.row_words(row));
+            for column in 0..self.width {
+                if rng.chance(density) {
+                    words[(column / WORD_BITS) as usize] |= 1 << (column % WORD_BITS);
+                }
+            }
+            self.set_row_words(row, &words);
+        }
+    }
+
+    /// Separate soups that collide as they grow: `count` discs of cells
+    /// `radius` across at random centers, each alive with a density of its
+    /// own between 0.2 and 0.6 drawn from `seed`.
+    ///
+    /// Discs may overlap, and the parts of them past the edges are left
+    /// out.
+    pub fn clusters(width: u32, height: u32, count: u32, radius: u32, seed: u64) -> Universe {
+        let mut universe = Universe::empty(width, height);
+        if width == 0 || height == 0 {
+            return universe;
+        }
+        let mut rng = Rng::new(seed);
+        let mut cells = Vec::new();
+        let reach = radius as i64;
+        for _ in 0..count {
+            let center = (
+                rng.below(height as u64) as i64,
+                rng.below(width as u64) as i64,
+            );
+            let density = 0.2 + 0.4 * rng.next_f64();
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    if dx * dx + dy * dy > reach * reach || !rng.chance(density) {
+                        continue;
+                    }
+                    let (row, column) = (center.0 + dy, center.1 + dx);
+                    if (0..height as i64).contains(&row) && (0..width as i64).contains(&column) {
+                        cells.push((row as u32, column as u32));
+                    }
+                }
+            }
+        }
+        universe.set_cells(&cells);
+        universe
+    }
+
+    /// A universe `width` by `height` cells drawing `image` resampled to
+    /// that size, its dark pixels alive and its light ones dead, with the
+    /// shades in between dithered the way `dither` says.
+    pub fn from_image(image: &Image, width: u32, height: u32, dither: Dither) -> Universe {
+        let dark = image.resize(width, height).dither(dither);
+        Universe::from_flags(width, height, &dark)
+    }
+
+    /// A universe with a flag for each cell, row by row, telling whether it
+    /// is alive.
+    fn from_flags(width: u32, height: u32, alive: &[bool]) -> Universe {
+        let mut universe = Universe::empty(width, height);
+        let mut words = vec![0; universe.stride];
+        for (row, cells) in alive.chunks(width.max(1) as usize).enumerate() {
+            words.fill(0);
+            for (column, _) in cells.iter().enumerate().filter(|&(_, &alive)| alive) {
+                words[column / WORD_BITS as usize] |= 1 << (column % WORD_BITS as usize);
+            }
+            universe.set_row_words(row as u32, &words);
+        }
+        universe
+    }
+
+    /// A universe of blobs: every cell is alive where gradient noise drawn
+    /// from `seed` rises above `threshold`.
+    ///
+    /// The blobs and the gaps between them are around `scale` cells across.
+    /// The noise runs from -1 to 1, so a threshold of 0 brings about half the
+    /// cells to life and higher ones leave fewer, smaller blobs.
+    pub fn noise(width: u32, height: u32, scale: f64, threshold: f64, seed: u64) -> Universe {
+        let mut universe = Universe::empty(width, height);
+        let mut words = vec![0; universe.stride];
+        for row in 0..height {
+            words.fill(0);
+            let y = (row as f64 + 0.5) / scale;
+            for column in 0..width {
+                let x = (column as f64 + 0.5) / scale;
+                if gradient_noise(x, y, seed) > threshold {
+                    words[(column / WORD_BITS) as usize] |= 1 << (column % WORD_BITS);
+                }
+            }
+            universe.set_row_words(row, &words);
+        }
+        universe
+    }
+
+    /// A universe just large enough for `text` written in live cells with
+    /// one of the built-in bitmap fonts, one line of glyphs for each line
+    /// of text.
+    ///
+    /// Letters are written in capitals, and characters the font has no
+    /// glyph for as `?`.
+    pub fn from_text_banner(text: &str, font: Font) -> Universe {
+        let banner = font.banner(text);
+        let mut universe = Universe::empty(banner.width(), banner.height());
+        universe.insert_pattern(&banner, 0, 0);
+        universe
+    }
+
+    /// A universe `width` cells wide laid out from a string of bits in hex
+    /// or base64, as `to_bit_string` writes them.
+    ///
+    /// The universe has as many rows as the bits fill. The bits left over
+    /// past the last of them, which only pad the string out, have to be 0.
+    /// On boards narrower than a hex digit or a byte, dead rows at the
+    /// bottom that fit in that padding are taken for it.
+    pub fn from_bit_string(
+        text: &str,
+        encoding: BitEncoding,
+        width: u32,
+    ) -> Result<Universe, BitStringError> {
+        if width == 0 {
+            return Err(BitStringError::new(
+                "the rows need to be at least a cell wide",
+            ));
+        }
+        let cells = bits::decode(text, encoding)?;
+        let width_cells = width as usize;
+        let mut height = cells.len() / width_cells;
+        // Rows narrower than a digit or byte can be filled by the padding
+        // alone, so dead rows that only pad the string out are not rows.
+        while height > 0
+            && cells.len() - (height - 1) * width_cells < bits::unit(encoding)
+            && !cells[(height - 1) * width_cells..height * width_cells].contains(&true)
+        {
+            height -= 1;
+        }
+        let full = height * width_cells;
+        if cells[full..].iter().any(|&alive| alive) {
+            return Err(BitStringError::new(format!(
+                "{} bits do not make whole rows of {} cells",
+                cells.len(),
+                width
+            )));
+        }
+        Ok(Universe::from_flags(width, height as u32, &cells[..full]))
+    }
+
+    /// Every cell as a string of bits in hex or base64, for sharing an exact
+    /// board along with its width.
+    pub fn to_bit_string(&self, encoding: BitEncoding) -> String {
+        let cells: Vec<bool> = (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |column| (row, column)))
+            .map(|(row, column)| self.get_cell(row, column) == Cell::Alive)
+            .collect();
+        bits::encode(&cells, encoding)
+    }
+
+    /// Create a universe where every cell is dead.
+    pub fn empty(width: u32, height: u32) -> Universe {
+        let stride = stride_for(width);
+        let mut universe = Universe {
+            width,
+            height,
+            words: vec![0; stride * height as usize],
+            stride,
+            rule: Rule::default(),
+            kernel: Kernel::new(Rule::default(), Engine::default()),
+            boundary: Boundary::default(),
+            changed_rows: Vec::new(),
+            row_links: Vec::new(),
+            changes: Vec::new(),
+            population: 0,
+            hash: 0,
+            generation: 0,
+            grown: (0, 0),
+            births: 0,
+            deaths: 0,
+            changed_at: None,
+            fold_symmetric: false,
+            fold: Fold::Unknown,
+            scratch: Scratch::default(),
+        };
+        universe.mark_all_changed();
+        universe
+    }
+
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// How many generations `tick` has stepped through.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Rows and columns added above and to the left of the universe by
+    /// growing edges since it was created, which moved every cell down and
+    /// to the right as far.
+    pub fn grown(&self) -> (u64, u64) {
+        self.grown
+    }
+
+    /// Number the current generation, as when loading a state saved
+    /// partway through a run.
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
+    /// The ways the universe looks the same turned or flipped about its
+    /// center. Goes over every cell.
+    pub fn symmetry(&self) -> Symmetry {
+        Symmetry::of(self)
+    }
+
+    /// Step only the top half of the universe while it stays the same
+    /// flipped top to bottom or turned half way round, and fill in the
+    /// bottom half from it, saving up to half the work for symmetric soups.
+    /// Growing edges are never folded.
+    pub fn set_fold_symmetric(&mut self, fold: bool) {
+        self.fold_symmetric = fold;
+        self.fold = Fold::Unknown;
+    }
+
+    /// The cells that differ between this universe and `other`.
+    pub fn diff(&self, other: &Universe) -> DiffReport {
+        DiffReport::between(self, other)
+    }
+
+    /// A hash of the size and live cells of the universe, equal for
+    /// universes in the same state and almost never for any others. Kept up
+    /// to date as cells change, so it costs nothing to ask.
+    pub fn fingerprint(&self) -> u64 {
+        mix(self.hash ^ mix((self.width as u64) << 32 | self.height as u64))
+    }
+
+    /// A hash of the size and live cells of the universe that stays the same
+    /// from one version of this crate and one machine to the next, for
+    /// checking a run is reproduced. Unlike `fingerprint` it goes over every
+    /// cell.
+    ///
+    /// It is the 64-bit FNV-1a hash of the width and then the height as
+    /// 4-byte little-endian integers, followed by every row from the top,
+    /// each as `width / 8` bytes rounded up holding 8 cells apiece, the
+    /// leftmost cell in the lowest bit, with a live cell as a set bit and
+    /// the bits past the right edge clear.
+    pub fn state_hash(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let fold = |hash: u64, byte: &u8| (hash ^ *byte as u64).wrapping_mul(PRIME);
+        let size = [self.width.to_le_bytes(), self.height.to_le_bytes()];
+        let mut hash = size.iter().flatten().fold(OFFSET, fold);
+        let bytes_per_row = self.width.div_ceil(8) as usize;
+        for row in 0..self.height {
+            let bytes = self
+                .row_words(row)
+                .iter()
+                .flat_map(|word| word.to_le_bytes());
+            hash = bytes
+                .take(bytes_per_row)
+                .fold(hash, |hash, byte| fold(hash, &byte));
+        }
+        hash
+    }
+
+    /// Where the live cells start, as the top left corner of `live_bounds`,
+    /// and a hash of how they are laid out from there, equal for the same
+    /// pattern anywhere on the board. `None` if nothing is alive.
+    ///
+    /// Unlike `fingerprint` this goes over every cell in the bounds.
+    pub fn shape(&self) -> Option<((u32, u32), u64)> {
+        let (top, left, bottom, right) = self.live_bounds()?;
+        let chunks = ((right - left) / WORD_BITS + 1) as usize;
+        let (first, shift) = ((left / WORD_BITS) as usize, left % WORD_BITS);
+        let mut hash = mix(((bottom - top) as u64) << 32 | (right - left) as u64);
+        for row in top..=bottom {
+            let words = self.row_words(row);
+            for idx in first..first + chunks {
+                // The 64 cells from column `left` on, using the next word to
+                // fill in the ones shifted out.
+                let mut word = words[idx] >> shift;
+                if shift > 0 {
+                    if let Some(next) = words.get(idx + 1) {
+                        word |= next << (WORD_BITS - shift);
+                    }
+                }
+                hash = mix(hash ^ word);
+            }
+        }
+        Some(((top, left), hash))
+    }
+
+    /// Keep track of how long each cell has been alive or dead, for `age`
+    /// and `ages`. Costs nothing while off, and in proportion to the cells
+    /// that change while on. Ages count from when tracking starts.
+    pub fn set_track_ages(&mut self, track: bool) {
+        let cells = self.width as usize * self.height as usize;
+        self.changed_at = track.then(|| vec![self.generation; cells]);
+    }
+
+    /// How many generations a cell has been in its current state, alive or
+    /// dead, or `None` unless ages are tracked.
+    pub fn age(&self, row: u32, column: u32) -> Option<u64> {
+        let changed_at = self.changed_at.as_ref()?;
+        let at = changed_at[row as usize * self.width as usize + column as usize];
+        Some(self.generation.saturating_sub(at))
+    }
+
+    /// The `age` of every cell, row by row like `get_cells`, or `None`
+    /// unless ages are tracked. Ages start over for every cell when the
+    /// universe is resized or restored.
+    pub fn ages(&self) -> Option<Vec<u64>> {
+        let changed_at = self.changed_at.as_ref()?;
+        Some(
+            changed_at
+                .iter()
+                .map(|&at| self.generation.saturating_sub(at))
+                .collect(),
+        )
+    }
+
+    /// How many cells came to life in the last tick.
+    pub fn births(&self) -> u64 {
+        self.births
+    }
+
+    /// How many cells died in the last tick.
+    pub fn deaths(&self) -> u64 {
+        self.deaths
+    }
+
+    /// Whether the last tick left every cell as it was, so the universe
+    /// will stay this way. Only meaningful right after a tick.
+    pub fn is_still(&self) -> bool {
+        self.births == 0 && self.deaths == 0
+    }
+
+    /// Whether every cell is dead under a rule that never brings dead
+    /// neighborhoods back to life.
+    pub fn is_extinct(&self) -> bool {
+        self.population == 0 && !self.rule.is_born(0)
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Change the rule used by `tick`, keeping the current cells.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.kernel = Kernel::new(rule, self.engine());
+        self.mark_all_words_changed();
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.kernel.engine()
+    }
+
+    /// Change how `tick` works out the next generation, which only affects
+    /// how fast it does so.
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.kernel = Kernel::new(self.rule, engine);
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Change what happens at the edges of the universe.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.link_rows();
+        self.mark_all_words_changed();
+    }
+
+    /// The packed cells: `stride` words per row, bit `c % 64` of word
+    /// `c / 64` holding column `c`.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Words holding each row in `words`.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Kill every cell and start counting generations over.
+    pub fn reset(&mut self) {
+        self.words.iter_mut().for_each(|word| *word = 0);
+        self.generation = 0;
+        self.mark_all_changed();
+    }
+
+    /// Set the width of the universe.
+    ///
+    /// Resets all cells to the dead state.
+    pub fn set_width(&mut self, width: u32) {
+        self.width = width;
+        self.stride = stride_for(width);
+        self.words = vec![0; self.stride * self.height as usize];
+        self.generation = 0;
+        self.mark_all_changed();
+    }
+
+    /// Set the height of the universe.
+    ///
+    /// Resets all cells to the dead state.
+    pub fn set_height(&mut self, height: u32) {
+        self.height = height;
+        self.words = vec![0; self.stride * height as usize];
+        self.generation = 0;
+        self.mark_all_changed();
+    }
+
+    /// Change the size of the universe, keeping the cells that still fit
+    /// where they are relative to the top left corner.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.words = self.copy_words(width, height, 0, 0);
+        self.width = width;
+        self.height = height;
+        self.stride = stride_for(width);
+        self.mark_all_changed();
+    }
+
+    /// The state of a single cell. Panics if it is outside the universe.
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        let (idx, bit) = self.locate(row, column);
+        if self.words[idx] & bit == 0 {
+            Cell::Dead
+        } else {
+            Cell::Alive
+        }
+    }
+
+    /// Set a single cell to be dead or alive. Panics if it is outside the
+    /// universe.
+    pub fn set_cell(&mut self, row: u32, column: u32, cell: Cell) {
+        let (idx, bit) = self.locate(row, column);
+        if (self.words[idx] & bit != 0) != (cell == Cell::Alive) {
+            self.note_edit(row, column);
+        }
+        self.hash ^= word_hash(idx, self.words[idx]);
+        match (cell, self.words[idx] & bit != 0) {
+            (Cell::Alive, false) => self.population += 1,
+            (Cell::Dead, true) => self.population -= 1,
+            _ => {}
+        }
+        match cell {
+            Cell::Alive => self.words[idx] |= bit,
+            Cell::Dead => self.words[idx] &= !bit,
+        }
+        self.hash ^= word_hash(idx, self.words[idx]);
+        self.fold = Fold::Unknown;
+        self.changes.push(idx);
+        self.changed_rows[row as usize] = true;
+    }
+
+    /// Start the age of a cell changed by hand over.
+    fn note_edit(&mut self, row: u32, column: u32) {
+        if let Some(changed_at) = &mut self.changed_at {
+            changed_at[row as usize * self.width as usize + column as usize] = self.generation;
+        }
+    }
+
+    /// Capture the current cells.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::pack(self.width, self.height, self.generation, &self.words)
+    }
+
+    /// Bring back the cells, size and generation captured in `snapshot`,
+    /// keeping the rule and boundary.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.width = snapshot.width();
+        self.height = snapshot.height();
+        self.generation = snapshot.generation();
+        self.stride = stride_for(self.width);
+        self.words = snapshot.unpack();
+        self.mark_all_changed();
+    }
+
+    /// Flip a single cell. Panics if it is outside the universe.
+    pub fn toggle_cell(&mut self, row: u32, column: u32) {
+        let (idx, bit) = self.locate(row, column);
+        self.note_edit(row, column);
+        self.hash ^= word_hash(idx, self.words[idx]) ^ word_hash(idx, self.words[idx] ^ bit);
+        self.words[idx] ^= bit;
+        self.fold = Fold::Unknown;
+        match self.words[idx] & bit {
+            0 => self.population -= 1,
+            _ => self.population += 1,
+        }
+        self.changes.push(idx);
+        self.changed_rows[row as usize] = true;
+    }
+}
+
+/// Note that the cells of the word at `idx` set in `changed` came to life
+/// or died in `generation`, given the width and stride of the universe.
+fn note_changes(
+    changed_at: &mut [u64],
+    (width, stride, generation): (u32, usize, u64),
+    idx: usize,
+    mut changed: u64,
+) {
+    let row = idx / stride;
+    let first = (idx % stride) as u32 * WORD_BITS;
+    while changed != 0 {
+        let column = first + changed.trailing_zeros();
+        changed &= changed - 1;
+        changed_at[row * width as usize + column as usize] = generation;
+    }
+}
+
+/// What the word at `idx` adds to the hash of a universe, nothing when all
+/// its cells are dead.
+fn word_hash(idx: usize, word: u64) -> u64 {
+    match word {
+        0 => 0,
+        _ => mix(mix(idx as u64) ^ word),
+    }
+}
+
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Charset::default().write_grid(f, self)
+    }
+}
+
+/// A pattern too large for the universe it was to be placed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DoesNotFit {
+    /// The width and height of the pattern.
+    pub pattern: (u32, u32),
+    /// The width and height of the universe.
+    pub universe: (u32, u32),
+}
+
+impl fmt::Display for DoesNotFit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a {}x{} pattern does not fit in a {}x{} universe",
+            self.pattern.0, self.pattern.1, self.universe.0, self.universe.1
+        )
+    }
+}
+
+impl Error for DoesNotFit {}
+
+/// The error of `Universe::new` when given a divisor of 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeroDivisor;
+
+impl fmt::Display for ZeroDivisor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "divisors must be at least 1")
+    }
+}
+
+impl Error for ZeroDivisor {}
+
+/// Why `Universe::from_initializer` could not fill a universe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitializerError {
+    /// The pattern or text is larger than the universe.
+    DoesNotFit(DoesNotFit),
+    /// `Initializer::Divisors` was given a divisor of 0.
+    ZeroDivisor,
+}
+
+impl fmt::Display for InitializerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InitializerError::DoesNotFit(error) => error.fmt(f),
+            InitializerError::ZeroDivisor => ZeroDivisor.fmt(f),
+        }
+    }
+}
+
+impl Error for InitializerError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{self, assert_same, boundaries, RULES, SIZES};
+
+    #[test]
+    fn ticks_match_the_reference() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                for (width, height) in SIZES {
+                    seed += 1;
+                    let mut universe = reference::soup(width, height, seed, rule, boundary);
+                    for generation in 1..=8 {
+                        let expected = reference::tick(&universe);
+                        universe.tick();
+                        assert_same(
+                            &universe,
+                            &expected,
+                            (rule, boundary, width, height, generation),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reused_buffers_hold_no_stale_cells() {
+        // Edits between ticks, and changes of size, leave the spare buffer
+        // behind the current cells, which the next tick must not mistake
+        // for their state.
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for (width, height) in SIZES {
+                seed += 1;
+                let mut universe = reference::soup(width, height, seed, "B3/S23", boundary);
+                let mut rng = Rng::new(seed);
+                for generation in 1..=12 {
+                    let (width, height) = (universe.width(), universe.height());
+                    for _ in 0..3 {
+                        let row = rng.below(height as u64) as u32;
+                        let column = rng.below(width as u64) as u32;
+                        universe.toggle_cell(row, column);
+                    }
+                    match generation {
+                        4 => universe.resize(width + 70, height + 3),
+                        8 => {
+                            let (width, height) = (width.div_ceil(3), height.div_ceil(2));
+                            let smaller = reference::soup(width, height, seed, "B3/S23", boundary);
+                            universe.restore(&smaller.snapshot());
+                        }
+                        _ => {}
+                    }
+                    let expected = reference::tick(&universe);
+                    universe.tick();
+                    assert_same(&universe, &expected, (boundary, width, height, generation));
+                }
+            }
+        }
+    }
+
+    /// A dead universe with a few small soups, near the corners to cross
+    /// the edges, and a glider.
+    fn patches(width: u32, height: u32, seed: u64, rule: &str, boundary: Boundary) -> Universe {
+        let mut rng = Rng::new(seed);
+        let mut universe = reference::soup(width, height, seed, rule, boundary);
+        universe.reset();
+        for (top, left) in [(0, 0), (height - 6, width - 6), (height / 2, width / 3)] {
+            for (row, column) in (0..6).flat_map(|row| (0..6).map(move |column| (row, column))) {
+                if rng.chance(0.5) {
+                    universe.set_cell(top + row, left + column, Cell::Alive);
+                }
+            }
+        }
+        universe.set_cells(&[(10, 41), (11, 42), (12, 40), (12, 41), (12, 42)]);
+        universe
+    }
+
+    #[test]
+    fn sparse_activity_matches_the_reference() {
+        // Most words are left alone each tick, so any the changes don't
+        // reach would show up here.
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                seed += 1;
+                let mut universe = patches(200, 40, seed, rule, boundary);
+                for generation in 1..=30 {
+                    let expected = reference::tick(&universe);
+                    universe.tick();
+                    assert_same(&universe, &expected, (rule, boundary, generation));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn change_lists_count_births_and_deaths() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for rule in RULES {
+                seed += 1;
+                let mut universe = patches(150, 30, seed, rule, boundary);
+                let mut rng = Rng::new(seed);
+                for generation in 1..=40 {
+                    // Poke the board now and then, which must wake up the
+                    // words around the cell even once the rest settled.
+                    if generation % 10 == 0 {
+                        let row = rng.below(universe.height() as u64) as u32;
+                        let column = rng.below(universe.width() as u64) as u32;
+                        universe.toggle_cell(row, column);
+                    }
+                    let before = reference::grown(&universe).get_cells();
+                    let expected = reference::tick(&universe);
+                    universe.tick();
+                    assert_same(&universe, &expected, (rule, boundary, generation));
+                    let after = expected.get_cells();
+                    let count = |from: Cell, to: Cell| {
+                        let changed = before
+                            .iter()
+                            .zip(&after)
+                            .filter(|&(&a, &b)| (a, b) == (from, to));
+                        changed.count() as u64
+                    };
+                    let case = (rule, boundary, generation);
+                    assert_eq!(
+                        universe.births(),
+                        count(Cell::Dead, Cell::Alive),
+                        "{:?}",
+                        case
+                    );
+                    assert_eq!(
+                        universe.deaths(),
+                        count(Cell::Alive, Cell::Dead),
+                        "{:?}",
+                        case
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn row_links_follow_boundary_and_size_changes() {
+        // The rows around each row are looked up in a table, which has to
+        // be rebuilt whenever the boundary or the height changes.
+        let boundaries: Vec<Boundary> = boundaries().collect();
+        let mut universe = reference::soup(70, 9, 1, "B3/S23", boundaries[0]);
+        for (step, &boundary) in boundaries.iter().cycle().take(48).enumerate() {
+            universe.set_boundary(boundary);
+            if step % 5 == 4 {
+                let height = universe.height() % 7 + 2;
+                universe.resize(universe.width(), height);
+            }
+            if universe.population() < 20 {
+                universe.scatter(0.4, step as u64);
+            }
+            let expected = reference::tick(&universe);
+            universe.tick();
+            assert_same(&universe, &expected, (boundary, step));
+        }
+    }
+
+    /// Change `universe` in each of the ways it can be changed, in turn,
+    /// calling `check` after each.
+    fn edit_every_way(mut universe: Universe, seed: u64, check: impl Fn(&Universe, &str)) {
+        let mut rng = Rng::new(seed);
+        let glider = Pattern::new(3, 3, vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        for round in 0..6 {
+            let (width, height) = (universe.width(), universe.height());
+            let (row, column) = (
+                rng.below(height as u64) as u32,
+                rng.below(width as u64) as u32,
+            );
+            universe.tick();
+            check(&universe, "tick");
+            universe.toggle_cell(row, column);
+            check(&universe, "toggle_cell");
+            universe.set_cell(row, column, Cell::Alive);
+            universe.set_cell(height - 1 - row, column, Cell::Dead);
+            check(&universe, "set_cell");
+            universe.insert_pattern(&glider, row, column);
+            check(&universe, "insert_pattern");
+            let mut other = universe.clone();
+            other.tick();
+            universe.apply_delta(&other.take_delta());
+            check(&universe, "apply_delta");
+            match round {
+                1 => universe.resize(width + 3, height + 1),
+                2 => {
+                    universe.restore(&Universe::random(width / 2 + 1, height, 0.3, seed).snapshot())
+                }
+                3 => universe.scatter(0.2, seed),
+                4 => universe.reset(),
+                _ => continue,
+            }
+            check(&universe, "wholesale");
+        }
+    }
+
+    #[test]
+    fn population_is_kept_up_to_date() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for (width, height) in SIZES {
+                seed += 1;
+                let universe = reference::soup(width, height, seed, "B36/S23", boundary);
+                edit_every_way(universe, seed, |universe, change| {
+                    let counted = universe
+                        .get_cells()
+                        .iter()
+                        .filter(|&&cell| cell == Cell::Alive)
+                        .count();
+                    let case = (boundary, width, height, change);
+                    assert_eq!(universe.population(), counted as u64, "{:?}", case);
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_kept_up_to_date() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for (width, height) in SIZES {
+                seed += 1;
+                let universe = reference::soup(width, height, seed, "B36/S23", boundary);
+                edit_every_way(universe, seed, |universe, change| {
+                    // Restoring a snapshot hashes all the words afresh.
+                    let mut fresh = Universe::empty(1, 1);
+                    fresh.restore(&universe.snapshot());
+                    let case = (boundary, width, height, change);
+                    assert_eq!(universe.fingerprint(), fresh.fingerprint(), "{:?}", case);
+                });
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the 70x3 universe")]
+    fn cells_past_the_width_are_rejected() {
+        // Column 70 is still inside the second word of the row, in its
+        // padding.
+        let mut universe = Universe::empty(70, 3);
+        universe.set_cell(0, 70, Cell::Alive);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the 70x3 universe")]
+    fn cells_past_the_height_are_rejected() {
+        let mut universe = Universe::empty(70, 3);
+        universe.toggle_cell(3, 0);
+    }
+
+    #[test]
+    fn cells_at_the_far_corner_are_kept() {
+        let mut universe = Universe::empty(70, 3);
+        universe.set_cell(2, 69, Cell::Alive);
+        assert_eq!(universe.get_cell(2, 69), Cell::Alive);
+        assert_eq!(universe.population(), 1);
+    }
+
+    #[test]
+    fn universes_without_cells_do_not_grow() {
+        let grow = Boundary {
+            x: Edge::Grow,
+            y: Edge::Grow,
+        };
+        for (width, height) in [(0, 0), (0, 5), (5, 0)] {
+            let mut universe = Universe::empty(width, height);
+            universe.set_boundary(grow);
+            universe.tick();
+            assert_eq!((universe.width(), universe.height()), (width, height));
+            assert_eq!((universe.generation(), universe.grown()), (1, (0, 0)));
+        }
+    }
+
+    #[test]
+    fn patterns_past_the_far_corner_are_cut_off() {
+        // A block with its top left corner in the bottom right cell.
+        let block = Pattern::new(2, 2, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let mut universe = Universe::empty(70, 3);
+        universe.insert_pattern(&block, 2, 69);
+        assert_eq!(universe.get_cell(2, 69), Cell::Alive);
+        assert_eq!(universe.population(), 1);
+
+        // Corners so far out that the cells would overflow their row and
+        // column are left out, not wrapped around.
+        for (row, column) in [(u32::MAX, 0), (0, u32::MAX), (u32::MAX, u32::MAX)] {
+            universe.insert_pattern(&block, row, column);
+            assert_eq!(universe.population(), 1);
+        }
+    }
+
+    /// `state_hash` worked out from the cells one at a time, as its
+    /// documentation lays it out.
+    fn documented_state_hash(universe: &Universe) -> u64 {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&universe.width().to_le_bytes());
+        bytes.extend_from_slice(&universe.height().to_le_bytes());
+        for row in 0..universe.height() {
+            let mut row_bytes = vec![0u8; universe.width().div_ceil(8) as usize];
+            for column in 0..universe.width() {
+                if universe.get_cell(row, column) == Cell::Alive {
+                    row_bytes[column as usize / 8] |= 1 << (column % 8);
+                }
+            }
+            bytes.extend(row_bytes);
+        }
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    #[test]
+    fn state_hash_follows_the_cells() {
+        let mut seed = 0;
+        for boundary in boundaries() {
+            for (width, height) in SIZES {
+                seed += 1;
+                let universe = reference::soup(width, height, seed, "B3/S23", boundary);
+                edit_every_way(universe, seed, |universe, change| {
+                    let mut fresh = Universe::empty(1, 1);
+                    fresh.restore(&universe.snapshot());
+                    let case = (boundary, width, height, change);
+                    assert_eq!(
+                        universe.state_hash(),
+                        documented_state_hash(universe),
+                        "{:?}",
+                        case
+                    );
+                    assert_eq!(universe.state_hash(), fresh.state_hash(), "{:?}", case);
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn state_hash_stays_the_same_across_versions() {
+        // Worked out apart from this crate, to catch any change to the format.
+        let mut universe = Universe::empty(70, 3);
+        assert_eq!(universe.state_hash(), 0xad42_c544_7235_74b0);
+        universe.set_cells(&[(0, 0), (1, 69), (2, 8)]);
+        assert_eq!(universe.state_hash(), 0xef0e_5bdd_9b14_ae3a);
+    }
+    #[test]
+    fn patterns_are_placed_in_the_middle() {
+        let glider = Pattern::new(3, 3, vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        let mut universe = Universe::empty(10, 7);
+        assert_eq!(universe.place_centered(&glider), Ok((2, 3)));
+        assert_eq!(universe.live_bounds(), Some((2, 3, 4, 5)));
+        assert_eq!(universe.population(), 5);
+
+        // Odd leftovers go to the bottom and right, and a pattern as large
+        // as the universe fills it from the corner.
+        let mut snug = Universe::empty(3, 4);
+        assert_eq!(snug.place_centered(&glider), Ok((0, 0)));
+        let mut exact = Universe::empty(3, 3);
+        assert_eq!(exact.place_centered(&glider), Ok((0, 0)));
+        assert_eq!(exact.population(), 5);
+    }
+
+    #[test]
+    fn patterns_too_large_are_refused() {
+        let wide = Pattern::new(5, 1, vec![(0, 0), (0, 4)]);
+        let mut universe = Universe::empty(4, 4);
+        universe.set_cells(&[(1, 1)]);
+        let before = universe.get_cells();
+        let error = universe.place_centered(&wide).unwrap_err();
+        assert_eq!(
+            error,
+            DoesNotFit {
+                pattern: (5, 1),
+                universe: (4, 4),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "a 5x1 pattern does not fit in a 4x4 universe"
+        );
+        // The universe is left as it was.
+        assert_eq!(universe.get_cells(), before);
+
+        let tall = Pattern::new(1, 5, vec![(0, 0)]);
+        assert!(universe.place_centered(&tall).is_err());
+        let pattern = Initializer::Pattern(tall);
+        assert!(matches!(
+            Universe::from_initializer(4, 4, &pattern),
+            Err(InitializerError::DoesNotFit(_))
+        ));
+    }
+
+    #[test]
+    fn text_banners_are_just_large_enough() {
+        let banner = Universe::from_text_banner("Life", Font::Standard);
+        assert_eq!((banner.width(), banner.height()), (23, 7));
+        assert_eq!(banner.live_bounds(), Some((0, 0, 6, 22)));
+        let pattern = Font::Standard.banner("LIFE");
+        assert_eq!(banner.population(), pattern.population() as u64);
+
+        let initializer = Initializer::Text {
+            text: String::from("Life"),
+            font: Font::Standard,
+        };
+        let centered = Universe::from_initializer(33, 17, &initializer).unwrap();
+        assert_eq!(centered.live_bounds(), Some((5, 5, 11, 27)));
+        let cramped = Universe::from_initializer(20, 17, &initializer);
+        assert!(matches!(cramped, Err(InitializerError::DoesNotFit(_))));
+    }
+
+    #[test]
+    fn clusters_follow_the_seed() {
+        let clusters = Universe::clusters(60, 40, 4, 5, 11);
+        assert_eq!(
+            clusters.get_cells(),
+            Universe::clusters(60, 40, 4, 5, 11).get_cells()
+        );
+        assert_ne!(
+            clusters.get_cells(),
+            Universe::clusters(60, 40, 4, 5, 12).get_cells()
+        );
+        assert!(clusters.population() > 0);
+
+        let initializer = Initializer::Clusters {
+            count: 4,
+            radius: 5,
+            seed: 11,
+        };
+        let universe = Universe::from_initializer(60, 40, &initializer).unwrap();
+        assert_eq!(universe.get_cells(), clusters.get_cells());
+    }
+
+    #[test]
+    fn a_cluster_stays_within_its_radius() {
+        for seed in 0..20 {
+            let cluster = Universe::clusters(80, 80, 1, 6, seed);
+            if let Some((top, left, bottom, right)) = cluster.live_bounds() {
+                assert!(bottom - top <= 12 && right - left <= 12, "seed {}", seed);
+            }
+            // No more cells than the disc holds.
+            assert!(cluster.population() <= 113, "seed {}", seed);
+        }
+        // A cluster without a radius is its center at most.
+        for seed in 0..20 {
+            assert!(Universe::clusters(10, 10, 1, 0, seed).population() <= 1);
+        }
+    }
+
+    #[test]
+    fn no_clusters_leave_the_universe_empty() {
+        assert_eq!(Universe::clusters(30, 30, 0, 5, 1).population(), 0);
+        let flat = Universe::clusters(0, 30, 5, 5, 1);
+        assert_eq!((flat.width(), flat.height(), flat.population()), (0, 30, 0));
+        // Clusters on the edges are cut off rather than wrapped around.
+        let corner = Universe::clusters(1, 1, 50, 3, 2);
+        assert!(corner.population() <= 1);
+    }
+
+    #[test]
+    fn noise_comes_out_in_blobs() {
+        let noise = Universe::noise(64, 64, 8.0, 0.0, 9);
+        assert_eq!(
+            noise.get_cells(),
+            Universe::noise(64, 64, 8.0, 0.0, 9).get_cells()
+        );
+        assert_ne!(
+            noise.get_cells(),
+            Universe::noise(64, 64, 8.0, 0.0, 10).get_cells()
+        );
+        // Neighbors are mostly alike, where in a soup they would be as
+        // often different as not.
+        let alike = (0..64)
+            .flat_map(|row| (1..64).map(move |column| (row, column)))
+            .filter(|&(row, column)| noise.get_cell(row, column) == noise.get_cell(row, column - 1))
+            .count();
+        assert!(alike > 64 * 63 * 8 / 10, "{}", alike);
+        let share = noise.density();
+        assert!((0.25..0.75).contains(&share), "{}", share);
+    }
+
+    #[test]
+    fn higher_noise_thresholds_leave_fewer_cells() {
+        let populations: Vec<u64> = [-1.0, -0.3, 0.0, 0.3, 1.0]
+            .iter()
+            .map(|&threshold| Universe::noise(48, 32, 6.0, threshold, 4).population())
+            .collect();
+        assert!(
+            populations.windows(2).all(|pair| pair[0] >= pair[1]),
+            "{:?}",
+            populations
+        );
+        assert!(populations[0] > 48 * 32 * 9 / 10);
+        assert_eq!(populations[4], 0);
+    }
+
+    #[test]
+    fn noise_initializers_match_the_noise() {
+        let initializer = Initializer::Noise {
+            scale: 5.0,
+            threshold: 0.2,
+            seed: 17,
+        };
+        let universe = Universe::from_initializer(40, 30, &initializer).unwrap();
+        assert_eq!(
+            universe.get_cells(),
+            Universe::noise(40, 30, 5.0, 0.2, 17).get_cells()
+        );
+    }
+}